@@ -0,0 +1,179 @@
+//! `--bench`: time each stage of the rendering pipeline over a file
+//!
+//! Meant for performance reports: the numbers cover loading, decoding,
+//! building the document, syntax highlighting, soft wrapping and search.
+
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::cli::{Args, WrapMode};
+use crate::display::Document;
+use crate::error::MatError;
+use crate::filter::build_regex;
+use crate::highlight::{apply_syntax_highlight, SearchState};
+use crate::input::{decode_bytes, detect_encoding, expand_tabs, strip_ansi};
+use crate::pager::App;
+use crate::theme::{get_theme, ThemeColors};
+
+/// Viewport used for the wrapping stage
+const BENCH_VIEWPORT: (u16, u16) = (120, 40);
+
+/// Pattern searched when no `-s` is given
+const DEFAULT_BENCH_PATTERN: &str = "the";
+
+/// Timings of one benchmark run
+#[derive(Debug)]
+pub struct BenchReport {
+    /// File name
+    pub name: String,
+    /// Size of the raw file
+    pub bytes: usize,
+    /// Number of document lines
+    pub lines: usize,
+    /// Rows after soft wrapping at the bench viewport width
+    pub wrapped_rows: usize,
+    /// Search matches found
+    pub matches: usize,
+    /// Duration of each stage, in pipeline order
+    pub stages: Vec<(&'static str, Duration)>,
+}
+
+impl BenchReport {
+    /// Sum of all stage durations
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|(_, d)| *d).sum()
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} bytes, {} lines, {} wrapped rows, {} matches",
+            self.name, self.bytes, self.lines, self.wrapped_rows, self.matches
+        )?;
+        for (stage, duration) in &self.stages {
+            writeln!(f, "  {:<10} {:>10.2} ms", stage, duration.as_secs_f64() * 1000.0)?;
+        }
+        let total = self.total();
+        let throughput = self.bytes as f64 / total.as_secs_f64().max(f64::EPSILON) / (1024.0 * 1024.0);
+        write!(
+            f,
+            "  {:<10} {:>10.2} ms ({:.1} MiB/s)",
+            "total",
+            total.as_secs_f64() * 1000.0,
+            throughput
+        )
+    }
+}
+
+/// Benchmark every FILE argument and print the reports
+pub fn run_bench(args: &Args) -> Result<(), MatError> {
+    if args.files.is_empty() {
+        eprintln!("mat: --bench needs at least one file");
+        return Ok(());
+    }
+
+    let pattern = build_pattern(args)?;
+    for (i, path) in args.files.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", bench_file(path, args, &pattern)?);
+    }
+    Ok(())
+}
+
+/// Search pattern for the search stage
+fn build_pattern(args: &Args) -> Result<Regex, MatError> {
+    let pattern = args.search.as_deref().unwrap_or(DEFAULT_BENCH_PATTERN);
+    build_regex(pattern, args)
+}
+
+/// Run each pipeline stage over `path` once, timing it
+pub fn bench_file(path: &Path, args: &Args, pattern: &Regex) -> Result<BenchReport, MatError> {
+    let mut stages = Vec::new();
+    let mut time = |stage: &'static str, start: Instant| stages.push((stage, start.elapsed()));
+
+    let start = Instant::now();
+    let bytes = std::fs::read(path).map_err(|e| MatError::Io {
+        source: e,
+        path: path.to_path_buf(),
+    })?;
+    let size = bytes.len();
+    time("load", start);
+
+    let start = Instant::now();
+    let encoding = detect_encoding(&bytes);
+    let text = expand_tabs(&strip_ansi(&decode_bytes(bytes, encoding)?), 4);
+    time("decode", start);
+
+    let start = Instant::now();
+    let mut document = Document::from_text(&text, path.display().to_string(), encoding.to_string());
+    drop(text);
+    time("document", start);
+
+    let start = Instant::now();
+    let theme = get_theme(args.theme.as_deref());
+    apply_syntax_highlight(&mut document, args.language.as_deref(), theme);
+    time("highlight", start);
+
+    let lines = document.line_count();
+
+    let start = Instant::now();
+    let mut state = SearchState::new(pattern.clone());
+    state.find_matches(&document);
+    let matches = state.match_count();
+    time("search", start);
+
+    let start = Instant::now();
+    let mut app = App::new(
+        document,
+        false,
+        None,
+        ThemeColors::for_theme(theme),
+        false,
+        None,
+        WrapMode::Wrap,
+        args.max_width,
+    );
+    app.set_terminal_size(BENCH_VIEWPORT.0, BENCH_VIEWPORT.1);
+    app.build_wrapped_lines();
+    let wrapped_rows = app.total_wrapped_lines();
+    time("wrap", start);
+
+    Ok(BenchReport {
+        name: path.display().to_string(),
+        bytes: size,
+        lines,
+        wrapped_rows,
+        matches,
+        stages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::io::Write;
+
+    #[test]
+    fn test_bench_file() {
+        let mut file = tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+        writeln!(file, "fn main() {{\n    println!(\"the answer\");\n}}").unwrap();
+
+        let args = Args::parse_from(["mat", "--bench"]);
+        let pattern = Regex::new("the").unwrap();
+        let report = bench_file(file.path(), &args, &pattern).unwrap();
+
+        assert_eq!(report.lines, 3);
+        assert_eq!(report.matches, 1);
+        let stages: Vec<&str> = report.stages.iter().map(|(s, _)| *s).collect();
+        assert_eq!(stages, vec!["load", "decode", "document", "highlight", "search", "wrap"]);
+        assert!(report.to_string().contains("total"));
+    }
+}
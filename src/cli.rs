@@ -9,10 +9,99 @@ pub enum WrapMode {
     None,
     /// Soft wrap at terminal width
     Wrap,
+    /// Soft wrap at terminal width, breaking lines between words
+    Word,
     /// Hard truncate at max-width
     Truncate,
 }
 
+impl WrapMode {
+    /// Whether long lines continue on the next rows
+    pub fn wraps(self) -> bool {
+        matches!(self, WrapMode::Wrap | WrapMode::Word)
+    }
+}
+
+/// Line numbering across multiple files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum Numbering {
+    /// Keep counting across files, like `cat -n`
+    #[default]
+    Continuous,
+    /// Restart at 1 for each file
+    PerFile,
+}
+
+/// When to show the TUI pager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum Paging {
+    /// Only when stdout is a terminal and the content doesn't fit on one screen
+    #[default]
+    Auto,
+    /// Always use the pager
+    Always,
+    /// Never use the pager, print directly
+    Never,
+}
+
+/// When to page through an external pager (`$PAGER`) instead of the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum PagerFallback {
+    /// When the terminal can't run the TUI (`TERM=dumb`, no raw mode)
+    #[default]
+    Auto,
+    /// Whenever the pager would be shown
+    Always,
+    /// Never, always use the TUI
+    Never,
+}
+
+/// When to color printed output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum ColorMode {
+    /// When stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always emit ANSI colors
+    Always,
+    /// Plain text
+    Never,
+}
+
+/// Which lines pager searches look at when a grep filter is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum SearchScope {
+    /// Only the lines kept by the grep filter
+    #[default]
+    Filtered,
+    /// Every line of the input (the pager shows the unfiltered file)
+    All,
+}
+
+/// How errors are reported on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum ErrorFormat {
+    /// Human-readable message
+    #[default]
+    Text,
+    /// One JSON object with kind, path, message and exit code
+    Json,
+}
+
+/// How timestamps at the start of log lines are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum TimestampMode {
+    /// As written in the input
+    #[default]
+    Original,
+    /// As a distance from now, e.g. "2m13s ago"
+    Relative,
+    /// Converted to local time
+    Local,
+    /// Converted to UTC
+    Utc,
+}
+
 /// mat - A CLI tool combining cat, less, grep functionality with markdown rendering and syntax highlighting
 #[derive(Parser, Debug, Default)]
 #[command(name = "mat")]
@@ -20,14 +109,18 @@ pub enum WrapMode {
 #[command(about = "A CLI tool combining cat, less, grep with markdown rendering and syntax highlighting")]
 #[command(long_about = None)]
 pub struct Args {
-    /// Input file (use - for stdin)
+    /// Input files, concatenated in order (use - for stdin)
     #[arg(value_name = "FILE")]
-    pub file: Option<PathBuf>,
+    pub files: Vec<PathBuf>,
 
     /// Show line numbers
     #[arg(short = 'n', long = "line-numbers")]
     pub line_numbers: bool,
 
+    /// Show line numbers relative to the top line
+    #[arg(long = "relative-numbers")]
+    pub relative_numbers: bool,
+
     /// Disable syntax highlighting
     #[arg(short = 'N', long = "no-highlight")]
     pub no_highlight: bool,
@@ -40,18 +133,80 @@ pub struct Args {
     #[arg(short = 'M', long = "no-markdown")]
     pub no_markdown: bool,
 
+    /// Mark markdown links with [N] and list their URLs at the end
+    #[arg(long = "link-references")]
+    pub link_references: bool,
+
+    /// Open markdown with a word count and reading time line
+    #[arg(long = "reading-time")]
+    pub reading_time: bool,
+
+    /// Underline misspelled words in markdown and plain text
+    #[cfg(feature = "spellcheck")]
+    #[arg(long = "spellcheck")]
+    pub spellcheck: bool,
+
+    /// Spelling dictionary: a hunspell .dic file or a word list (implies --spellcheck)
+    #[cfg(feature = "spellcheck")]
+    #[arg(long = "dictionary", value_name = "FILE")]
+    pub dictionary: Option<PathBuf>,
+
+    /// Re-indent XML, HTML and JSON (by extension, or by content for stdin)
+    #[arg(long = "pretty")]
+    pub pretty: bool,
+
+    /// Prefix each line with a short hash of its text (ignoring a leading timestamp)
+    #[arg(long = "line-hashes")]
+    pub line_hashes: bool,
+
+    /// Mark lines that don't occur in FILE with a +, e.g. today's log against yesterday's
+    #[arg(long = "compare", value_name = "FILE")]
+    pub compare: Option<PathBuf>,
+
     /// Follow mode (tail -f style)
     #[arg(short = 'f', long = "follow")]
     pub follow: bool,
 
+    /// In follow mode, append every line that arrives to FILE
+    #[arg(long = "log-output", value_name = "FILE", requires = "follow", conflicts_with = "no_exec")]
+    pub log_output: Option<PathBuf>,
+
+    /// Prefix the lines of --log-output with the time they arrived
+    #[arg(long = "log-timestamps", requires = "log_output")]
+    pub log_timestamps: bool,
+
+    /// Open scrolled to the end of the document, without following it
+    #[arg(long = "start-at-end")]
+    pub start_at_end: bool,
+
     /// Highlight pattern matches
     #[arg(short = 's', long = "search", value_name = "PAT")]
     pub search: Option<String>,
 
+    /// Print only the number of --search matches instead of the document
+    #[arg(long = "count-matches")]
+    pub count_matches: bool,
+
     /// Filter to matching lines
     #[arg(short = 'g', long = "grep", value_name = "PAT")]
     pub grep: Option<String>,
 
+    /// Hide lines matching PAT (repeatable), before any grep filtering
+    #[arg(long = "hide", value_name = "PAT")]
+    pub hide: Vec<String>,
+
+    /// Show lines matching PAT in a live pane below the document (repeatable)
+    #[arg(long = "pane", value_name = "PAT")]
+    pub panes: Vec<String>,
+
+    /// Read patterns from a file, one per line (grep -f); filters unless -s is given
+    #[arg(long = "pattern-file", value_name = "FILE")]
+    pub pattern_file: Option<PathBuf>,
+
+    /// Filter to the lines NOT matching the grep pattern (grep -v)
+    #[arg(short = 'v', long = "invert-match", conflicts_with = "extract")]
+    pub invert_match: bool,
+
     /// Case-insensitive for search/grep
     #[arg(short = 'i', long = "ignore-case")]
     pub ignore_case: bool,
@@ -68,6 +223,14 @@ pub struct Args {
     #[arg(short = 'x', long = "line-regexp")]
     pub line_regexp: bool,
 
+    /// Lines searched in the pager while grep filtering: filtered, all
+    #[arg(long = "search-scope", value_enum, default_value = "filtered", value_name = "SCOPE")]
+    pub search_scope: SearchScope,
+
+    /// Show a table of the grep pattern's named capture groups per matching line
+    #[arg(long = "extract")]
+    pub extract: bool,
+
     /// Lines after grep match
     #[arg(short = 'A', long = "after", value_name = "N")]
     pub after: Option<usize>,
@@ -80,10 +243,18 @@ pub struct Args {
     #[arg(short = 'C', long = "context", value_name = "N")]
     pub context: Option<usize>,
 
-    /// Line wrap mode: none, wrap, truncate
+    /// Line wrap mode: none, wrap, word (wrap between words), truncate
     #[arg(long = "wrap", value_enum, default_value = "none")]
     pub wrap: WrapMode,
 
+    /// Wrap at column N instead of the terminal width (implies --wrap wrap)
+    #[arg(
+        long = "wrap-width",
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub wrap_width: Option<usize>,
+
     /// Max line width before truncation
     #[arg(short = 'W', long = "max-width", value_name = "N", default_value = "200")]
     pub max_width: usize,
@@ -92,23 +263,203 @@ pub struct Args {
     #[arg(short = 'l', long = "language", value_name = "LANG")]
     pub language: Option<String>,
 
-    /// Select color theme
+    /// Color theme: light, dark, or any name --list-themes shows
     #[arg(short = 't', long = "theme", value_name = "NAME")]
     pub theme: Option<String>,
 
+    /// List the languages --language accepts, with their file extensions
+    #[arg(long = "list-languages")]
+    pub list_languages: bool,
+
+    /// List the color themes, each with a preview
+    #[arg(long = "list-themes")]
+    pub list_themes: bool,
+
     /// Show line range: 50:100, :100, 50:, or 50
     #[arg(short = 'L', long = "lines", value_name = "RANGE")]
     pub lines: Option<String>,
 
-    /// Direct output, skip TUI pager
+    /// When to use the TUI pager: auto, always, never
+    #[arg(long = "paging", value_enum, default_value = "auto", value_name = "WHEN")]
+    pub paging: Paging,
+
+    /// Page through $PAGER (or less) instead of the TUI: auto (TUI can't start), always, never
+    #[arg(long = "pager-fallback", value_enum, default_value = "auto", value_name = "WHEN")]
+    pub pager_fallback: PagerFallback,
+
+    /// Draw decorations with ASCII instead of Unicode box drawing and symbols
+    #[arg(long = "ascii")]
+    pub ascii: bool,
+
+    /// Direct output, skip TUI pager (same as --paging never)
     #[arg(short = 'P', long = "no-pager")]
     pub no_pager: bool,
 
-    /// Preserve ANSI escape codes in input
+    /// Draw the pager on the main screen and leave the last page there on exit
+    #[arg(long = "keep-screen")]
+    pub keep_screen: bool,
+
+    /// Time each pipeline stage over the given files and print a breakdown
+    #[arg(long = "bench")]
+    pub bench: bool,
+
+    /// Show the usage statistics counted with `usage_stats = true` in the config file
+    #[arg(long = "usage")]
+    pub usage: bool,
+
+    /// Print the line count, word count and reading time of each FILE instead of viewing it
+    #[arg(long = "stats")]
+    pub stats: bool,
+
+    /// Error report format on stderr: text, json
+    #[arg(long = "error-format", value_enum, default_value = "text", value_name = "FORMAT")]
+    pub error_format: ErrorFormat,
+
+    /// Color printed output: auto, always, never
+    #[arg(long = "color", value_enum, default_value = "auto", value_name = "WHEN")]
+    pub color: ColorMode,
+
+    /// Save piped input to FILE while viewing it
+    #[arg(long = "tee", value_name = "FILE", conflicts_with = "no_exec")]
+    pub tee: Option<PathBuf>,
+
+    /// Leave escape sequences (colors) out of the --tee copy
+    #[arg(long = "tee-strip-ansi", requires = "tee")]
+    pub tee_strip_ansi: bool,
+
+    /// Collapse runs of identical consecutive lines into one
+    #[arg(long = "squeeze-repeated")]
+    pub squeeze_repeated: bool,
+
+    /// Display line-leading timestamps: original, relative, local, utc
+    #[arg(long = "timestamps", value_enum, default_value = "original", value_name = "MODE")]
+    pub timestamps: TimestampMode,
+
+    /// Interval for the ]t / [t time jumps in the pager (e.g. 30s, 5m, 1h)
+    #[arg(long = "time-step", value_name = "DURATION", default_value = "1m")]
+    pub time_step: String,
+
+    /// On quitting the pager, write the lines bookmarked with m to FILE as path:line:text (- for stdout)
+    #[arg(long = "export-bookmarks", value_name = "FILE", conflicts_with = "no_exec")]
+    pub export_bookmarks: Option<PathBuf>,
+
+    /// Show the headings or code blocks the top line is in above the pager's content (toggle with C)
+    #[arg(long = "breadcrumbs")]
+    pub breadcrumbs: bool,
+
+    /// Capture the mouse in the pager: wheel scrolling, clicks and drag to copy lines (toggle with :mouse)
+    #[arg(long = "mouse")]
+    pub mouse: bool,
+
+    /// Exit with 3 after selecting a line with Enter and 130 after Ctrl+C, printing the selected and bookmarked lines
+    #[arg(long = "report-action")]
+    pub report_action: bool,
+
+    /// Decorations: comma-separated header, grid, numbers, snip (or full, plain)
+    #[arg(long = "style", value_name = "COMPONENTS")]
+    pub style: Option<String>,
+
+    /// Print a banner with the file name before each file
+    #[arg(long = "file-headers")]
+    pub file_headers: bool,
+
+    /// Line numbering across multiple files: continuous, per-file
+    #[arg(long = "numbering", value_enum, default_value = "continuous", value_name = "MODE")]
+    pub numbering: Numbering,
+
+    /// Keep the colors of ANSI escape codes in the input (`git diff --color | mat --ansi`)
     #[arg(long = "ansi")]
     pub ansi: bool,
 
-    /// Force display of binary files
+    /// Keep a byte order mark as a U+FEFF character instead of stripping it
+    #[arg(long = "keep-bom")]
+    pub keep_bom: bool,
+
+    /// Don't read files larger than SIZE (e.g. 512M, 2G) into memory; only --lines or --grep of them are loaded
+    #[arg(long = "max-memory", value_name = "SIZE", value_parser = parse_byte_size)]
+    pub max_memory: Option<u64>,
+
+    /// Only report errors, no warnings
+    #[arg(short = 'q', long = "quiet", conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Report how inputs were decoded and highlighted (again for follow events, a third time for every read)
+    #[arg(long = "verbose", action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Write the messages of --verbose to FILE instead of stderr
+    #[arg(long = "log-file", value_name = "FILE", conflicts_with = "no_exec")]
+    pub log_file: Option<PathBuf>,
+
+    /// Show binary files as a hex dump instead of refusing them
     #[arg(long = "force-binary")]
     pub force_binary: bool,
+
+    /// Show the input as a hex dump: offset, hex bytes and ASCII
+    #[arg(long = "hex")]
+    pub hex: bool,
+
+    /// On Ctrl+C during loading, open what was read instead of exiting
+    #[arg(long = "open-partial")]
+    pub open_partial: bool,
+
+    /// Delegate search match discovery to ripgrep for large files
+    #[arg(long = "ripgrep", conflicts_with = "no_exec")]
+    pub ripgrep: bool,
+
+    /// Print the first screen the pager would show on a WxH terminal (e.g. 80x24), without a terminal
+    #[arg(long = "render-screen", value_name = "WxH", value_parser = parse_screen_size)]
+    pub render_screen: Option<(u16, u16)>,
+
+    /// Type KEYS into the pager before reading the keyboard, e.g. "/error<Enter>nn" (<Esc>, <C-l>, <F1>...)
+    #[arg(long = "keys", value_name = "KEYS", conflicts_with = "keys_file")]
+    pub keys: Option<String>,
+
+    /// Type the keys in FILE into the pager (line breaks are Enter)
+    #[arg(long = "keys-file", value_name = "FILE")]
+    pub keys_file: Option<PathBuf>,
+
+    /// Read-only mode for untrusted files: never start programs (editor, pager,
+    /// preprocessors, decompressors) or write files
+    #[arg(long = "no-exec")]
+    pub no_exec: bool,
+}
+
+/// Parse a terminal size given as columns x rows: 80x24
+fn parse_screen_size(value: &str) -> Result<(u16, u16), String> {
+    let size = value.split_once(['x', 'X']).and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+    match size {
+        Some((width, height)) if width > 0 && height > 1 => Ok((width, height)),
+        _ => Err(format!("'{}' is not a screen size, e.g. 80x24", value)),
+    }
+}
+
+/// Parse a size in bytes with an optional binary unit: 4096, 100K, 512M, 1.5G
+fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = &value[digits.len()..];
+    let number = match digits.parse::<f64>() {
+        Ok(number) if number > 0.0 => number,
+        _ => return Err(format!("'{}' is not a size, e.g. 512M or 2G", value)),
+    };
+    let multiplier: u64 = match unit.to_ascii_uppercase().trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("unknown size unit '{}' (expected K, M, G or T)", unit)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+impl Args {
+    /// Wrap mode, with `--wrap-width` turning wrapping on when `--wrap` is left at none
+    pub fn wrap_mode(&self) -> WrapMode {
+        match (self.wrap, self.wrap_width) {
+            (WrapMode::None, Some(_)) => WrapMode::Wrap,
+            (mode, _) => mode,
+        }
+    }
 }
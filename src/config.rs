@@ -0,0 +1,199 @@
+//! User configuration file
+//!
+//! Read from `$MAT_CONFIG`, or `mat/config.toml` under `$XDG_CONFIG_HOME`
+//! (falling back to `~/.config`). A missing file means all defaults.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::MatError;
+use crate::highlight::Injection;
+use crate::input::Preprocessor;
+
+/// Keybinding profile for the pager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyProfile {
+    /// vim-style keys
+    #[default]
+    Default,
+    /// less keys (Space/b paging, `F` follow, `-i` toggles, `=` file info) on top of the defaults
+    Less,
+}
+
+/// Settings from the configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Columns moved by `h`/`l` and the arrow keys
+    pub hscroll_step: usize,
+    /// Keybinding profile
+    pub keys: KeyProfile,
+    /// Text width of the reading mode (`M`)
+    pub reading_width: usize,
+    /// File converters, tried in order (`[[preprocessor]]` tables)
+    #[serde(rename = "preprocessor")]
+    pub preprocessors: Vec<Preprocessor>,
+    /// Languages highlighted inside others, before the built-in rules (`[[injection]]` tables)
+    #[serde(rename = "injection")]
+    pub injections: Vec<Injection>,
+    /// Count the flags, keys and commands used in a local file (`mat --usage` shows them)
+    pub usage_stats: bool,
+    /// File extensions opened scrolled to the end, as with `--start-at-end` (case-insensitive)
+    pub start_at_end: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            hscroll_step: 4,
+            keys: KeyProfile::Default,
+            reading_width: 80,
+            preprocessors: Vec::new(),
+            injections: Vec::new(),
+            usage_stats: false,
+            start_at_end: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration from its default location
+    pub fn load() -> Result<Self, MatError> {
+        match config_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load the configuration from `path`
+    pub fn load_from(path: &Path) -> Result<Self, MatError> {
+        let content = std::fs::read_to_string(path).map_err(|e| MatError::Io {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+        Self::parse(&content).map_err(|message| MatError::InvalidConfig {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    /// Whether the file at `path` opens scrolled to the end
+    pub fn starts_at_end(&self, path: &Path) -> bool {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        extension.is_some_and(|ext| self.start_at_end.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+
+    /// Parse configuration file contents
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| e.message().to_string())?;
+        if config.hscroll_step == 0 {
+            return Err("hscroll_step must be at least 1".to_string());
+        }
+        if config.reading_width == 0 {
+            return Err("reading_width must be at least 1".to_string());
+        }
+        for (i, preprocessor) in config.preprocessors.iter().enumerate() {
+            preprocessor
+                .validate()
+                .map_err(|message| format!("preprocessor {}: {}", i + 1, message))?;
+        }
+        for (i, injection) in config.injections.iter().enumerate() {
+            injection
+                .validate()
+                .map_err(|message| format!("injection {}: {}", i + 1, message))?;
+        }
+        Ok(config)
+    }
+}
+
+/// Location of the configuration file
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("MAT_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// mat's configuration directory: `$XDG_CONFIG_HOME/mat`, falling back to `~/.config/mat`
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("mat"))
+}
+
+/// mat's cache directory: `$XDG_CACHE_HOME/mat`, falling back to `~/.cache/mat`
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir_in(std::env::var_os("XDG_CACHE_HOME"), std::env::var_os("HOME"), ".cache")
+}
+
+/// mat's state directory: `$XDG_STATE_HOME/mat`, falling back to `~/.local/state/mat`
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir_in(std::env::var_os("XDG_STATE_HOME"), std::env::var_os("HOME"), ".local/state")
+}
+
+/// `mat` under the XDG directory `xdg_home`, or under `fallback` in the home directory
+fn xdg_dir_in(xdg_home: Option<OsString>, home: Option<OsString>, fallback: &str) -> Option<PathBuf> {
+    let dir = xdg_home
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| PathBuf::from(home).join(fallback)))?;
+    Some(dir.join("mat"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(Config::parse("hscroll_step = 10").unwrap().hscroll_step, 10);
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert_eq!(Config::parse("keys = \"less\"").unwrap().keys, KeyProfile::Less);
+        assert_eq!(Config::parse("reading_width = 72").unwrap().reading_width, 72);
+        assert!(Config::parse("usage_stats = true").unwrap().usage_stats);
+
+        let config = Config::parse("start_at_end = [\"log\", \"out\"]").unwrap();
+        assert!(config.starts_at_end(Path::new("/var/log/app.LOG")));
+        assert!(!config.starts_at_end(Path::new("notes.txt")));
+        assert!(!config.starts_at_end(Path::new("log")));
+
+        let config = Config::parse(
+            "[[preprocessor]]\nextensions = [\"pdf\"]\ncommand = \"pdftotext {} -\"\n\n\
+             [[preprocessor]]\nmime = [\"application/vnd.sqlite3\"]\ncommand = \"sqlite3 {} .dump\"",
+        )
+        .unwrap();
+        assert_eq!(config.preprocessors.len(), 2);
+        assert_eq!(config.preprocessors[1].mime, vec!["application/vnd.sqlite3"]);
+
+        let config =
+            Config::parse("[[injection]]\nhosts = [\"lua\"]\npattern = 'sql\\(\"(.*)\"\\)'\nlanguage = \"SQL\"").unwrap();
+        assert_eq!(config.injections[0].language, "SQL");
+    }
+
+    #[test]
+    fn test_parse_invalid_config() {
+        assert!(Config::parse("hscroll_step = \"wide\"").is_err());
+        assert!(Config::parse("hscroll_step = 0").is_err());
+        assert!(Config::parse("reading_width = 0").is_err());
+        assert!(Config::parse("keys = \"emacs\"").is_err());
+        assert!(Config::parse("[[preprocessor]]\nextensions = [\"pdf\"]").is_err());
+        assert!(Config::parse("[[injection]]\nhosts = [\"py\"]\npattern = \"(\"\nlanguage = \"SQL\"").is_err());
+    }
+
+    #[test]
+    fn test_cache_dir() {
+        assert_eq!(
+            xdg_dir_in(Some("/cache".into()), Some("/home/u".into()), ".cache"),
+            Some(PathBuf::from("/cache/mat"))
+        );
+        assert_eq!(xdg_dir_in(None, Some("/home/u".into()), ".cache"), Some(PathBuf::from("/home/u/.cache/mat")));
+        assert_eq!(
+            xdg_dir_in(Some("".into()), Some("/home/u".into()), ".local/state"),
+            Some(PathBuf::from("/home/u/.local/state/mat"))
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Characters drawn for decorations
+//!
+//! Borders, bullets and indicators use Unicode box drawing and symbols, or
+//! plain ASCII on terminals that can't show them (`--ascii`, a non-UTF-8
+//! locale, the Linux console). The set is chosen once at startup with
+//! [`use_ascii`]; everything drawing decorations asks [`glyphs`] for it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Terminals known to lack (wide) Unicode glyphs
+const ASCII_TERMINALS: &[&str] = &["linux", "vt100", "vt102", "vt220", "cons25", "ansi", "dumb"];
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// One set of decoration characters
+#[derive(Debug)]
+pub struct Glyphs {
+    pub horizontal: &'static str,
+    pub vertical: &'static str,
+    pub cross: &'static str,
+    /// Joint of a horizontal rule with a vertical line below it
+    pub down_tee: &'static str,
+    /// Joint of a horizontal rule with a vertical line above it
+    pub up_tee: &'static str,
+    /// Joint of a vertical line with a horizontal rule to its right
+    pub right_tee: &'static str,
+    /// Joint of a vertical line with a horizontal rule to its left
+    pub left_tee: &'static str,
+    /// Corners of a frame: top left, top right, bottom left, bottom right
+    pub corners: [&'static str; 4],
+    pub double_horizontal: &'static str,
+    pub double_vertical: &'static str,
+    /// Corners of a double frame: top left, top right, bottom left, bottom right
+    pub double_corners: [&'static str; 4],
+    pub ellipsis: &'static str,
+    /// Marks a line continuing past the right edge
+    pub truncated: &'static str,
+    pub times: &'static str,
+    /// Bullets of unordered lists by nesting level
+    pub bullets: [&'static str; 3],
+    /// Markers of headings H2 to H6
+    pub heading_marks: [&'static str; 5],
+    pub current_match: char,
+    pub match_mark: char,
+    pub closed_fold: char,
+    pub bookmark: char,
+    pub bar_full: &'static str,
+    pub bar_empty: &'static str,
+    /// Between the parts of the pager's breadcrumb
+    pub breadcrumb: &'static str,
+}
+
+/// Box drawing and symbols
+pub const UNICODE_GLYPHS: Glyphs = Glyphs {
+    horizontal: "─",
+    vertical: "│",
+    cross: "┼",
+    down_tee: "┬",
+    up_tee: "┴",
+    right_tee: "├",
+    left_tee: "┤",
+    corners: ["┌", "┐", "└", "┘"],
+    double_horizontal: "═",
+    double_vertical: "║",
+    double_corners: ["╔", "╗", "╚", "╝"],
+    ellipsis: "…",
+    truncated: "…",
+    times: "×",
+    bullets: ["•", "◦", "▪"],
+    heading_marks: ["◈", "▸", "◆", "◇", "·"],
+    current_match: '▶',
+    match_mark: '●',
+    closed_fold: '▸',
+    bookmark: '◆',
+    bar_full: "█",
+    bar_empty: "░",
+    breadcrumb: "›",
+};
+
+/// Plain ASCII for limited terminals
+pub const ASCII_GLYPHS: Glyphs = Glyphs {
+    horizontal: "-",
+    vertical: "|",
+    cross: "+",
+    down_tee: "+",
+    up_tee: "+",
+    right_tee: "+",
+    left_tee: "+",
+    corners: ["+", "+", "+", "+"],
+    double_horizontal: "=",
+    double_vertical: "|",
+    double_corners: ["+", "+", "+", "+"],
+    ellipsis: "...",
+    truncated: ">",
+    times: "x",
+    bullets: ["*", "-", "+"],
+    heading_marks: ["*", ">", "*", "-", "."],
+    current_match: '>',
+    match_mark: '*',
+    closed_fold: '+',
+    bookmark: '#',
+    bar_full: "#",
+    bar_empty: ".",
+    breadcrumb: ">",
+};
+
+/// Draw decorations with ASCII (or Unicode) from now on
+pub fn use_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// The decoration characters in use
+pub fn glyphs() -> &'static Glyphs {
+    if ASCII.load(Ordering::Relaxed) {
+        &ASCII_GLYPHS
+    } else {
+        &UNICODE_GLYPHS
+    }
+}
+
+/// Whether the terminal is unlikely to show Unicode decorations
+///
+/// That is a terminal type known for it, or a locale (`LC_ALL`, `LC_CTYPE`,
+/// `LANG`, the first one set) that isn't UTF-8.
+pub fn terminal_lacks_unicode() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    lacks_unicode(std::env::var("TERM").ok().as_deref(), locale.as_deref())
+}
+
+fn lacks_unicode(term: Option<&str>, locale: Option<&str>) -> bool {
+    let is_utf8 = |locale: &str| {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    };
+    term.is_some_and(|term| ASCII_TERMINALS.contains(&term)) || locale.is_some_and(|locale| !is_utf8(locale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lacks_unicode() {
+        assert!(!lacks_unicode(Some("xterm-256color"), Some("en_US.UTF-8")));
+        assert!(!lacks_unicode(Some("xterm-256color"), None));
+        assert!(lacks_unicode(Some("linux"), Some("en_US.UTF-8")));
+        assert!(lacks_unicode(Some("xterm"), Some("C")));
+        assert!(!lacks_unicode(None, Some("de_DE.utf8")));
+    }
+}
@@ -0,0 +1,127 @@
+//! Hex dump of binary input (`--hex`)
+//!
+//! Each line shows 16 bytes as an offset, the bytes in hex in two groups of
+//! eight, and the bytes as ASCII with `.` for those that aren't printable, as
+//! `hexdump -C` prints them. Everything is text of the line, so searching
+//! finds byte sequences (`6c 6c`) as well as strings.
+
+use ratatui::style::Color;
+
+use super::{Document, Line, SpanStyle, StyledSpan};
+
+/// Bytes shown per line
+const BYTES_PER_LINE: usize = 16;
+
+/// Kinds of bytes, colored apart so structure stands out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteClass {
+    Null,
+    Printable,
+    Whitespace,
+    Control,
+    High,
+}
+
+impl ByteClass {
+    fn of(byte: u8) -> Self {
+        match byte {
+            0 => ByteClass::Null,
+            b'\t' | b'\n' | b'\r' | b' ' => ByteClass::Whitespace,
+            0x21..=0x7e => ByteClass::Printable,
+            0x80..=0xff => ByteClass::High,
+            _ => ByteClass::Control,
+        }
+    }
+
+    fn style(self) -> SpanStyle {
+        match self {
+            ByteClass::Null => SpanStyle::new().fg(Color::DarkGray),
+            ByteClass::Printable => SpanStyle::new(),
+            ByteClass::Whitespace => SpanStyle::new().fg(Color::Green),
+            ByteClass::Control => SpanStyle::new().fg(Color::Yellow),
+            ByteClass::High => SpanStyle::new().fg(Color::Magenta),
+        }
+    }
+}
+
+/// Build a hex dump document of `bytes`, one numbered line per 16 bytes
+pub fn hexdump(bytes: &[u8], source_name: String) -> Document {
+    let lines: Vec<Line> = bytes
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(i, chunk)| dump_line(i + 1, i * BYTES_PER_LINE, chunk))
+        .collect();
+    let max_line_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
+    Document {
+        lines,
+        max_line_width,
+        source_name,
+        encoding: "binary".to_string(),
+    }
+}
+
+/// The line for the bytes of `chunk`, found at `offset` in the input
+fn dump_line(number: usize, offset: usize, chunk: &[u8]) -> Line {
+    let mut spans = vec![StyledSpan::new(format!("{:08x}  ", offset), SpanStyle::new().fg(Color::DarkGray))];
+
+    for i in 0..BYTES_PER_LINE {
+        let gap = if i == 7 { "  " } else { " " };
+        match chunk.get(i) {
+            Some(&byte) => {
+                push_span(&mut spans, &format!("{:02x}", byte), ByteClass::of(byte).style());
+                push_span(&mut spans, gap, SpanStyle::new());
+            }
+            None => push_span(&mut spans, &format!("  {}", gap), SpanStyle::new()),
+        }
+    }
+    push_span(&mut spans, " |", SpanStyle::new());
+    for &byte in chunk {
+        let shown = if ByteClass::of(byte) == ByteClass::Printable || byte == b' ' { byte as char } else { '.' };
+        push_span(&mut spans, &shown.to_string(), ByteClass::of(byte).style());
+    }
+    push_span(&mut spans, "|", SpanStyle::new());
+
+    let mut line = Line::plain(number, "");
+    line.spans = spans;
+    line
+}
+
+/// Append `text` to the last span if it has the same style, so runs of bytes of one kind share a span
+fn push_span(spans: &mut Vec<StyledSpan>, text: &str, style: SpanStyle) {
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => spans.push(StyledSpan::new(text, style)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump() {
+        let doc = hexdump(b"Hello\x00World, hex dump!\n\xff", "a.bin".to_string());
+        let texts: Vec<String> = doc.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "00000000  48 65 6c 6c 6f 00 57 6f  72 6c 64 2c 20 68 65 78  |Hello.World, hex|",
+                "00000010  20 64 75 6d 70 21 0a ff                           | dump!..|",
+            ]
+        );
+        assert_eq!(doc.lines.iter().map(|l| l.number).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(doc.encoding, "binary");
+        assert_eq!(doc.max_line_width, texts[0].len());
+
+        // The null byte is dimmed in both columns
+        let dim: Vec<&str> = doc.lines[0]
+            .spans
+            .iter()
+            .filter(|s| s.style.fg == Some(Color::DarkGray))
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(dim, vec!["00000000  ", "00", "."]);
+
+        assert!(hexdump(b"", "empty".to_string()).lines.is_empty());
+    }
+}
@@ -1,6 +1,13 @@
+use std::ops::Range;
+
 use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
 use unicode_width::UnicodeWidthStr;
 
+/// Maximum size (in bytes) of a single display line; longer source lines are
+/// split into continuation chunks so rendering never walks a multi-megabyte span
+pub const LONG_LINE_CHUNK_SIZE: usize = 16 * 1024;
+
 /// Style for a span of text
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SpanStyle {
@@ -77,6 +84,27 @@ impl SpanStyle {
         style
     }
 
+    /// ANSI SGR parameters for this style, e.g. `1;38;5;208` (empty when plain)
+    pub fn to_ansi_sgr(&self) -> String {
+        let mut codes: Vec<String> = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if self.italic {
+            codes.push("3".to_string());
+        }
+        if self.underline {
+            codes.push("4".to_string());
+        }
+        if let Some(fg) = self.fg {
+            codes.push(ansi_color_code(fg, 30));
+        }
+        if let Some(bg) = self.bg {
+            codes.push(ansi_color_code(bg, 40));
+        }
+        codes.join(";")
+    }
+
     /// Check if this is the default plain style (no styling)
     pub fn is_plain(&self) -> bool {
         self.fg.is_none()
@@ -87,6 +115,33 @@ impl SpanStyle {
     }
 }
 
+/// SGR code for `color`, with `base` 30 for foreground and 40 for background
+fn ansi_color_code(color: Color, base: u8) -> String {
+    let basic = |offset: u8| (base + offset).to_string();
+    let bright = |offset: u8| (base + 60 + offset).to_string();
+    match color {
+        Color::Reset => basic(9),
+        Color::Black => basic(0),
+        Color::Red => basic(1),
+        Color::Green => basic(2),
+        Color::Yellow => basic(3),
+        Color::Blue => basic(4),
+        Color::Magenta => basic(5),
+        Color::Cyan => basic(6),
+        Color::Gray => basic(7),
+        Color::DarkGray => bright(0),
+        Color::LightRed => bright(1),
+        Color::LightGreen => bright(2),
+        Color::LightYellow => bright(3),
+        Color::LightBlue => bright(4),
+        Color::LightMagenta => bright(5),
+        Color::LightCyan => bright(6),
+        Color::White => bright(7),
+        Color::Indexed(i) => format!("{};5;{}", base + 8, i),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
+
 /// A span of styled text
 #[derive(Debug, Clone, PartialEq)]
 pub struct StyledSpan {
@@ -129,6 +184,19 @@ pub struct Line {
     /// Whether this line is grep context (for future use with context styling)
     #[allow(dead_code)]
     pub is_context: bool,
+    /// Whether this line is a continuation chunk of the previous source line
+    pub continuation: bool,
+    /// The markdown heading this line shows, if any
+    pub heading: Option<Heading>,
+}
+
+/// A heading of a rendered markdown document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Level, 1 to 6
+    pub level: u8,
+    /// Text of the heading, without decorations
+    pub title: String,
 }
 
 impl Line {
@@ -139,7 +207,33 @@ impl Line {
             spans: vec![StyledSpan::plain(text)],
             is_match: false,
             is_context: false,
+            continuation: false,
+            heading: None,
+        }
+    }
+
+    /// Create plain lines for one source line, splitting it into bounded
+    /// chunks if it exceeds `LONG_LINE_CHUNK_SIZE`
+    pub fn chunked(number: usize, text: &str) -> Vec<Self> {
+        if text.len() <= LONG_LINE_CHUNK_SIZE {
+            return vec![Self::plain(number, text)];
+        }
+
+        let mut lines = Vec::with_capacity(text.len() / LONG_LINE_CHUNK_SIZE + 1);
+        let mut rest = text;
+        while !rest.is_empty() {
+            // Split on a char boundary at or before the chunk size
+            let mut split = rest.len().min(LONG_LINE_CHUNK_SIZE);
+            while !rest.is_char_boundary(split) {
+                split -= 1;
+            }
+            let (chunk, tail) = rest.split_at(split);
+            let mut line = Self::plain(number, chunk);
+            line.continuation = !lines.is_empty();
+            lines.push(line);
+            rest = tail;
         }
+        lines
     }
 
     /// Create a separator line (used between grep groups)
@@ -152,6 +246,23 @@ impl Line {
             )],
             is_match: false,
             is_context: false,
+            continuation: false,
+            heading: None,
+        }
+    }
+
+    /// Create a file name banner line (used between concatenated files)
+    pub fn header(name: &str) -> Self {
+        Self {
+            number: 0,
+            spans: vec![StyledSpan::new(
+                format!("==> {} <==", name),
+                SpanStyle::new().fg(Color::Cyan).bold(),
+            )],
+            is_match: false,
+            is_context: false,
+            continuation: false,
+            heading: None,
         }
     }
 
@@ -164,6 +275,20 @@ impl Line {
     pub fn text(&self) -> String {
         self.spans.iter().map(|s| s.text.as_str()).collect()
     }
+
+    /// Text of this line with ANSI escape sequences for its styled spans
+    pub fn ansi_text(&self) -> String {
+        let mut out = String::new();
+        for span in &self.spans {
+            let sgr = span.style.to_ansi_sgr();
+            if sgr.is_empty() {
+                out.push_str(&span.text);
+            } else {
+                out.push_str(&format!("\x1b[{}m{}\x1b[0m", sgr, span.text));
+            }
+        }
+        out
+    }
 }
 
 /// A document containing multiple lines
@@ -182,10 +307,11 @@ pub struct Document {
 impl Document {
     /// Create a document from text content
     pub fn from_text(text: &str, source_name: String, encoding: String) -> Self {
+        // `lines` drops `\r\n` endings, but not a `\r` before the end of the text
         let lines: Vec<Line> = text
             .lines()
             .enumerate()
-            .map(|(i, line_text)| Line::plain(i + 1, line_text))
+            .flat_map(|(i, line_text)| Line::chunked(i + 1, line_text.strip_suffix('\r').unwrap_or(line_text)))
             .collect();
 
         let max_line_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
@@ -207,12 +333,135 @@ impl Document {
     pub fn recalculate_max_width(&mut self) {
         self.max_line_width = self.lines.iter().map(|l| l.width()).max().unwrap_or(0);
     }
+
+    /// Concatenate documents like `cat` does with multiple files
+    ///
+    /// Each document is paired with the line count of its unfiltered source.
+    /// With `file_headers`, each document is preceded by a banner line. With
+    /// `continuous_numbers`, line numbers keep counting across documents
+    /// instead of restarting at 1 for each one.
+    pub fn concat(documents: Vec<(Document, usize)>, file_headers: bool, continuous_numbers: bool) -> Self {
+        let source_name = documents
+            .iter()
+            .map(|(d, _)| d.source_name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let encoding = documents.first().map(|(d, _)| d.encoding.clone()).unwrap_or_default();
+
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for (i, (document, source_lines)) in documents.into_iter().enumerate() {
+            if file_headers {
+                if i > 0 {
+                    lines.push(Line::plain(0, ""));
+                }
+                lines.push(Line::header(&document.source_name));
+            }
+
+            for mut line in document.lines {
+                // Separators and banners (number 0) are not renumbered
+                if continuous_numbers && line.number > 0 {
+                    line.number += offset;
+                }
+                lines.push(line);
+            }
+            offset += source_lines;
+        }
+
+        let max_line_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
+
+        Self {
+            lines,
+            max_line_width,
+            source_name,
+            encoding,
+        }
+    }
+}
+
+/// A match in a list of lines, as the byte range it covers on each line it spans
+pub type MatchPieces = Vec<(usize, Range<usize>)>;
+
+/// Matches of `pattern` in the source lines that start in `range` of `lines`
+///
+/// A long line split into continuation chunks is matched as the one line it
+/// is, so a match across chunks is found (in a piece per chunk) and `^`
+/// anchors only at its start.
+pub fn source_line_matches(lines: &[Line], range: Range<usize>, pattern: &Regex) -> Vec<MatchPieces> {
+    let mut matches = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let mut end = start + 1;
+        while end < lines.len() && lines[end].continuation {
+            end += 1;
+        }
+
+        let texts: Vec<String> = lines[start..end].iter().map(Line::text).collect();
+        let text = texts.concat();
+        for found in pattern.find_iter(&text) {
+            let mut pieces = Vec::new();
+            let mut offset = 0;
+            for (i, chunk) in texts.iter().enumerate() {
+                let chunk_end = offset + chunk.len();
+                let (piece_start, piece_end) = (found.start().max(offset), found.end().min(chunk_end));
+                // An empty match belongs to the chunk it is in, or the last one at its very end
+                let empty_here = found.is_empty() && found.start() >= offset && (found.start() < chunk_end || i + 1 == texts.len());
+                if piece_start < piece_end || empty_here {
+                    pieces.push((start + i, piece_start - offset..piece_end.max(piece_start) - offset));
+                }
+                offset = chunk_end;
+            }
+            matches.push(pieces);
+        }
+        start = end;
+    }
+    matches
+}
+
+/// Byte ranges of the matches of `pattern` on each of `lines`, split as [`source_line_matches`] does
+pub fn match_ranges_by_line(lines: &[Line], pattern: &Regex) -> Vec<Vec<Range<usize>>> {
+    let mut ranges = vec![Vec::new(); lines.len()];
+    for (line_idx, range) in source_line_matches(lines, 0..lines.len(), pattern).into_iter().flatten() {
+        ranges[line_idx].push(range);
+    }
+    ranges
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn doc(text: &str, name: &str) -> Document {
+        Document::from_text(text, name.to_string(), "UTF-8".to_string())
+    }
+
+    #[test]
+    fn test_concat_continuous_numbers() {
+        let combined = Document::concat(vec![(doc("a\nb", "one"), 2), (doc("c", "two"), 1)], false, true);
+        let numbers: Vec<usize> = combined.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(combined.source_name, "one, two");
+    }
+
+    #[test]
+    fn test_concat_offsets_by_unfiltered_line_count() {
+        // First file was filtered down to its line 2 out of 10
+        let mut first = doc("x", "one");
+        first.lines[0].number = 2;
+        let combined = Document::concat(vec![(first, 10), (doc("y", "two"), 1)], false, true);
+        let numbers: Vec<usize> = combined.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![2, 11]);
+    }
+
+    #[test]
+    fn test_concat_per_file_numbers_with_headers() {
+        let combined = Document::concat(vec![(doc("a\nb", "one"), 2), (doc("c", "two"), 1)], true, false);
+        let texts: Vec<String> = combined.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["==> one <==", "a", "b", "", "==> two <==", "c"]);
+        let numbers: Vec<usize> = combined.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![0, 1, 2, 0, 0, 1]);
+    }
+
     #[test]
     fn test_span_style_builder() {
         let style = SpanStyle::new().fg(Color::Red).bold().underline();
@@ -248,10 +497,29 @@ mod tests {
             ],
             is_match: false,
             is_context: false,
+            continuation: false,
+            heading: None,
         };
         assert_eq!(line.text(), "Hello, World!");
     }
 
+    #[test]
+    fn test_line_ansi_text() {
+        let line = Line {
+            number: 1,
+            spans: vec![
+                StyledSpan::new("fn", SpanStyle::new().fg(Color::Magenta).bold()),
+                StyledSpan::plain(" "),
+                StyledSpan::new("main", SpanStyle::new().fg(Color::Rgb(1, 2, 3)).bg(Color::DarkGray)),
+            ],
+            is_match: false,
+            is_context: false,
+            continuation: false,
+            heading: None,
+        };
+        assert_eq!(line.ansi_text(), "\x1b[1;35mfn\x1b[0m \x1b[38;2;1;2;3;100mmain\x1b[0m");
+    }
+
     #[test]
     fn test_document_from_text() {
         let text = "Line 1\nLine 2\nLine 3";
@@ -264,6 +532,58 @@ mod tests {
         assert_eq!(doc.max_line_width, 6);
     }
 
+    #[test]
+    fn test_document_from_crlf_text() {
+        let doc = Document::from_text("one\r\ntwo\r\nlast\r", "dos.txt".to_string(), "UTF-8".to_string());
+
+        let texts: Vec<String> = doc.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["one", "two", "last"]);
+        assert_eq!(doc.max_line_width, 4);
+    }
+
+    #[test]
+    fn test_long_line_is_chunked() {
+        let long = "x".repeat(LONG_LINE_CHUNK_SIZE * 2 + 10);
+        let text = format!("short\n{}\nafter", long);
+        let doc = Document::from_text(&text, "test.txt".to_string(), "UTF-8".to_string());
+
+        // short + 3 chunks + after
+        assert_eq!(doc.line_count(), 5);
+        assert!(!doc.lines[1].continuation);
+        assert!(doc.lines[2].continuation);
+        assert!(doc.lines[3].continuation);
+        assert_eq!(doc.lines[3].number, 2);
+        assert_eq!(doc.lines[4].number, 3);
+        assert_eq!(doc.max_line_width, LONG_LINE_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_chunked_respects_char_boundaries() {
+        let long = "世".repeat(LONG_LINE_CHUNK_SIZE);
+        let chunks = Line::chunked(1, &long);
+        assert!(chunks.len() > 1);
+        let joined: String = chunks.iter().map(|l| l.text()).collect();
+        assert_eq!(joined, long);
+    }
+
+    #[test]
+    fn test_matches_across_chunks() {
+        let text = format!("{}needle{}", "a".repeat(LONG_LINE_CHUNK_SIZE - 3), "b".repeat(10));
+        let doc = Document::from_text(&format!("{}\nneedle", text), "test.txt".to_string(), "UTF-8".to_string());
+        assert_eq!(doc.line_count(), 3);
+
+        // The match is split between the chunks, and the next line's is found too
+        let matches = source_line_matches(&doc.lines, 0..3, &Regex::new("needle").unwrap());
+        let start = LONG_LINE_CHUNK_SIZE - 3;
+        assert_eq!(matches, vec![vec![(0, start..LONG_LINE_CHUNK_SIZE), (1, 0..3)], vec![(2, 0..6)]]);
+
+        // A continuation chunk doesn't start a line
+        assert!(source_line_matches(&doc.lines, 0..3, &Regex::new("^dle").unwrap()).is_empty());
+
+        let ranges = match_ranges_by_line(&doc.lines, &Regex::new("needle").unwrap());
+        assert_eq!(ranges, vec![vec![start..LONG_LINE_CHUNK_SIZE], vec![0..3], vec![0..6]]);
+    }
+
     #[test]
     fn test_empty_document() {
         let doc = Document::from_text("", "test.txt".to_string(), "UTF-8".to_string());
@@ -1,3 +1,16 @@
+pub mod glyphs;
+mod hexdump;
 mod line;
+mod sgr;
+mod style;
+pub mod timestamp;
+mod wrap;
 
-pub use line::{Document, Line, SpanStyle, StyledSpan};
+#[allow(unused_imports)]
+pub use line::{
+    match_ranges_by_line, source_line_matches, Document, Heading, Line, SpanStyle, StyledSpan, LONG_LINE_CHUNK_SIZE,
+};
+pub use hexdump::hexdump;
+pub use sgr::apply_sgr;
+pub use style::OutputStyle;
+pub use wrap::{wrap_document, wrap_rows};
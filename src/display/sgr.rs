@@ -0,0 +1,142 @@
+//! SGR parameters (`1;31`, `38;5;208`, `38:2::255:128:0`) as span styles
+//!
+//! Used for the escape sequences of `--ansi` input and for the `GREP_COLORS`
+//! entries, which are written the same way.
+
+use ratatui::style::Color;
+
+use super::SpanStyle;
+
+/// Update `style` with the SGR parameters `params`, e.g. `1;38;5;208`
+///
+/// An empty parameter is 0, so an empty list resets the style, as `ESC [ m`
+/// does. Returns `None` at the first malformed parameter, leaving `style` with
+/// the ones before it applied.
+pub fn apply_sgr(params: &str, style: &mut SpanStyle) -> Option<()> {
+    let mut params = params.split(';');
+    while let Some(param) = params.next() {
+        if param.contains(':') {
+            apply_subparams(param, style)?;
+            continue;
+        }
+        let code = number(param)?;
+        match code {
+            0 => *style = SpanStyle::new(),
+            1 => style.bold = true,
+            3 => style.italic = true,
+            4 => style.underline = true,
+            22 => style.bold = false,
+            23 => style.italic = false,
+            24 => style.underline = false,
+            30..=37 => style.fg = Some(ansi_color(code - 30)),
+            38 => style.fg = Some(extended_color(&mut params)?),
+            39 => style.fg = None,
+            40..=47 => style.bg = Some(ansi_color(code - 40)),
+            48 => style.bg = Some(extended_color(&mut params)?),
+            49 => style.bg = None,
+            90..=97 => style.fg = Some(ansi_color(code - 90 + 8)),
+            100..=107 => style.bg = Some(ansi_color(code - 100 + 8)),
+            // Blink, reverse and the like have no span equivalent
+            _ => {}
+        }
+    }
+    Some(())
+}
+
+/// A parameter's value, 0 when it is empty
+fn number(param: &str) -> Option<u8> {
+    if param.is_empty() {
+        Some(0)
+    } else {
+        param.parse().ok()
+    }
+}
+
+/// The `5;n` or `2;r;g;b` tail of a 38/48 code, as separate parameters
+fn extended_color<'a>(params: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    match number(params.next()?)? {
+        5 => Some(Color::Indexed(number(params.next()?)?)),
+        2 => Some(Color::Rgb(number(params.next()?)?, number(params.next()?)?, number(params.next()?)?)),
+        _ => None,
+    }
+}
+
+/// A parameter with colon-separated sub-parameters: `38:5:n`, `38:2:cs:r:g:b` or `4:3`
+fn apply_subparams(param: &str, style: &mut SpanStyle) -> Option<()> {
+    let subs: Vec<&str> = param.split(':').collect();
+    match number(subs[0])? {
+        code @ (38 | 48) => {
+            let color = match (number(subs.get(1)?)?, subs.len()) {
+                (5, 3) => Color::Indexed(number(subs[2])?),
+                // The color space id (usually left empty) comes before the
+                // components, though some programs leave it out altogether
+                (2, 6) => Color::Rgb(number(subs[3])?, number(subs[4])?, number(subs[5])?),
+                (2, 5) => Color::Rgb(number(subs[2])?, number(subs[3])?, number(subs[4])?),
+                _ => return None,
+            };
+            if code == 38 {
+                style.fg = Some(color);
+            } else {
+                style.bg = Some(color);
+            }
+        }
+        // Curly, dotted and the other underline styles show as a plain underline; `4:0` is none
+        4 => style.underline = number(subs[1])? != 0,
+        _ => {}
+    }
+    Some(())
+}
+
+/// Color for a 256-color palette index 0-15
+fn ansi_color(index: u8) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(params: &str) -> Option<SpanStyle> {
+        let mut style = SpanStyle::new();
+        apply_sgr(params, &mut style).map(|_| style)
+    }
+
+    #[test]
+    fn test_apply_sgr() {
+        assert_eq!(parsed("01;31"), Some(SpanStyle::new().fg(Color::Red).bold()));
+        assert_eq!(parsed("1;31;0;92"), Some(SpanStyle::new().fg(Color::LightGreen)));
+        assert_eq!(parsed("38;5;208;48;2;10;20;30"), Some(SpanStyle::new().fg(Color::Indexed(208)).bg(Color::Rgb(10, 20, 30))));
+        assert_eq!(parsed(""), Some(SpanStyle::new()));
+        assert_eq!(parsed("red"), None);
+        assert_eq!(parsed("38;5"), None);
+        assert_eq!(parsed("38;5;300"), None);
+    }
+
+    #[test]
+    fn test_colon_subparameters() {
+        // The empty color space id doesn't shift the components
+        assert_eq!(parsed("38:2::255:128:0"), Some(SpanStyle::new().fg(Color::Rgb(255, 128, 0))));
+        assert_eq!(parsed("48:2:1:2:3"), Some(SpanStyle::new().bg(Color::Rgb(1, 2, 3))));
+        assert_eq!(parsed("1;38:5:208"), Some(SpanStyle::new().fg(Color::Indexed(208)).bold()));
+        assert_eq!(parsed("4:3"), Some(SpanStyle::new().underline()));
+        assert_eq!(parsed("4;4:0"), Some(SpanStyle::new()));
+        assert_eq!(parsed("38:2:1"), None);
+    }
+}
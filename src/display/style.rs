@@ -0,0 +1,168 @@
+use std::io::IsTerminal;
+
+use ratatui::style::Color;
+
+use crate::cli::{Args, ColorMode};
+use crate::error::MatError;
+use crate::platform;
+
+use super::glyphs::glyphs;
+use super::line::{Document, Line, SpanStyle, StyledSpan};
+
+/// Decorations around the text, selected with `--style`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputStyle {
+    /// File name banner above the content
+    pub header: bool,
+    /// Rules framing the content and a bar between numbers and text
+    pub grid: bool,
+    /// Line numbers
+    pub numbers: bool,
+    /// Marker between non-contiguous line ranges
+    pub snip: bool,
+    /// Whether `--style` was given at all (otherwise separators keep the grep `--` look)
+    pub explicit: bool,
+    /// Print styled spans with ANSI colors (`--color`)
+    pub color: bool,
+}
+
+impl OutputStyle {
+    /// Create the output style from CLI args (`-n` always adds numbers)
+    pub fn from_args(args: &Args) -> Result<Self, MatError> {
+        let mut style = match &args.style {
+            Some(spec) => Self::parse(spec)?,
+            None => Self::default(),
+        };
+        style.numbers |= args.line_numbers;
+        style.color = match args.color {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none()
+                    && platform::console_supports_ansi()
+            }
+        };
+        Ok(style)
+    }
+
+    /// Parse a comma-separated component list, e.g. `header,grid,numbers`
+    ///
+    /// `full` enables every component and `plain` none of them.
+    pub fn parse(spec: &str) -> Result<Self, MatError> {
+        let mut style = Self {
+            explicit: true,
+            ..Self::default()
+        };
+
+        for component in spec.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            match component {
+                "header" => style.header = true,
+                "grid" => style.grid = true,
+                "numbers" => style.numbers = true,
+                "snip" => style.snip = true,
+                "full" => {
+                    style.header = true;
+                    style.grid = true;
+                    style.numbers = true;
+                    style.snip = true;
+                }
+                "plain" => {}
+                _ => {
+                    return Err(MatError::InvalidStyle {
+                        component: component.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(style)
+    }
+
+    /// Restyle range separators: snip markers with `snip`, removed otherwise
+    ///
+    /// Without an explicit `--style` the document is left untouched.
+    pub fn apply_separators(&self, document: &mut Document) {
+        if !self.explicit {
+            return;
+        }
+
+        if self.snip {
+            for line in document.lines.iter_mut().filter(|l| is_separator(l)) {
+                *line = snip_line();
+            }
+        } else {
+            document.lines.retain(|l| !is_separator(l));
+        }
+        document.recalculate_max_width();
+    }
+}
+
+/// Whether the line is a grep group separator
+fn is_separator(line: &Line) -> bool {
+    line.number == 0 && line.spans.len() == 1 && line.spans[0].text == "--"
+}
+
+/// Marker line shown where lines were skipped
+fn snip_line() -> Line {
+    Line {
+        number: 0,
+        spans: vec![StyledSpan::new(
+            format!("{0} 8< {0}", glyphs().horizontal.repeat(3)),
+            SpanStyle::new().fg(Color::DarkGray),
+        )],
+        is_match: false,
+        is_context: false,
+        continuation: false,
+        heading: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_components() {
+        let style = OutputStyle::parse("header,numbers").unwrap();
+        assert!(style.header && style.numbers);
+        assert!(!style.grid && !style.snip);
+
+        let style = OutputStyle::parse("full").unwrap();
+        assert!(style.header && style.grid && style.numbers && style.snip);
+
+        let style = OutputStyle::parse("plain").unwrap();
+        assert!(!style.header && !style.grid && !style.numbers && !style.snip);
+        assert!(style.explicit);
+    }
+
+    #[test]
+    fn test_parse_invalid_component() {
+        assert!(matches!(
+            OutputStyle::parse("header,frame"),
+            Err(MatError::InvalidStyle { component }) if component == "frame"
+        ));
+    }
+
+    #[test]
+    fn test_apply_separators() {
+        let make_doc = || Document {
+            lines: vec![Line::plain(1, "a"), Line::separator(), Line::plain(9, "b")],
+            max_line_width: 2,
+            source_name: "test".to_string(),
+            encoding: "UTF-8".to_string(),
+        };
+
+        let mut doc = make_doc();
+        OutputStyle::default().apply_separators(&mut doc);
+        assert_eq!(doc.lines[1].text(), "--");
+
+        let mut doc = make_doc();
+        OutputStyle::parse("snip").unwrap().apply_separators(&mut doc);
+        assert_eq!(doc.lines[1].text(), "─── 8< ───");
+
+        let mut doc = make_doc();
+        OutputStyle::parse("numbers").unwrap().apply_separators(&mut doc);
+        assert_eq!(doc.lines.len(), 2);
+    }
+}
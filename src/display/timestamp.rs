@@ -0,0 +1,296 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::cli::TimestampMode;
+use crate::error::MatError;
+
+use super::line::{Document, Line, StyledSpan};
+
+/// ISO 8601 / RFC 3339, e.g. `2024-01-15T10:23:45.123Z` or `2024-01-15 10:23:45`
+static ISO_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[?(?P<ts>\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d{1,9})?(?:Z|[+-]\d{2}:?\d{2})?)")
+        .unwrap()
+});
+
+/// Syslog, e.g. `Jan 15 10:23:45` (no year)
+static SYSLOG_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<ts>(?:Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec) [ \d]\d \d{2}:\d{2}:\d{2})").unwrap()
+});
+
+/// Common Log Format, e.g. `[15/Jan/2024:10:23:45 +0000]`
+static CLF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[?(?P<ts>\d{2}/(?:Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec)/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4})")
+        .unwrap()
+});
+
+/// Unix epoch in seconds (with optional fraction) or milliseconds
+static EPOCH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[?(?P<ts>\d{10}(?:\.\d{1,9}|\d{3})?)\b").unwrap());
+
+/// A timestamp found at the start of a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    /// Byte range of the timestamp in the line text
+    pub range: Range<usize>,
+    /// The parsed instant
+    pub time: DateTime<Utc>,
+}
+
+/// Detect and parse a timestamp at the start of a line
+///
+/// Timestamps without a zone are taken to be in local time.
+pub fn parse_line_timestamp(text: &str) -> Option<Timestamp> {
+    if let Some(m) = ISO_RE.captures(text).and_then(|c| c.name("ts")) {
+        return parse_iso(m.as_str()).map(|time| Timestamp { range: m.range(), time });
+    }
+    if let Some(m) = CLF_RE.captures(text).and_then(|c| c.name("ts")) {
+        let time = DateTime::parse_from_str(m.as_str(), "%d/%b/%Y:%H:%M:%S %z").ok()?;
+        return Some(Timestamp {
+            range: m.range(),
+            time: time.with_timezone(&Utc),
+        });
+    }
+    if let Some(m) = SYSLOG_RE.captures(text).and_then(|c| c.name("ts")) {
+        return parse_syslog(m.as_str()).map(|time| Timestamp { range: m.range(), time });
+    }
+    if let Some(m) = EPOCH_RE.captures(text).and_then(|c| c.name("ts")) {
+        return parse_epoch(m.as_str()).map(|time| Timestamp { range: m.range(), time });
+    }
+    None
+}
+
+fn parse_iso(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.replacen(' ', "T", 1).replace(',', ".");
+    if let Some(naive) = s.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(naive, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    if let Ok(time) = DateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f%z") {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    local_to_utc(&naive)
+}
+
+fn parse_syslog(s: &str) -> Option<DateTime<Utc>> {
+    let now = Local::now();
+    let with_year = |year: i32| {
+        NaiveDateTime::parse_from_str(&format!("{} {}", year, s), "%Y %b %e %H:%M:%S")
+            .ok()
+            .and_then(|naive| local_to_utc(&naive))
+    };
+    let time = with_year(now.year())?;
+    // Syslog has no year: a date in the future must be from last year
+    if time > now.with_timezone(&Utc) + Duration::days(1) {
+        with_year(now.year() - 1)
+    } else {
+        Some(time)
+    }
+}
+
+fn parse_epoch(s: &str) -> Option<DateTime<Utc>> {
+    match s.split_once('.') {
+        Some((secs, frac)) => {
+            let nanos: u32 = format!("{:0<9}", frac).parse().ok()?;
+            Utc.timestamp_opt(secs.parse().ok()?, nanos).single()
+        }
+        None if s.len() == 13 => Utc.timestamp_millis_opt(s.parse().ok()?).single(),
+        None => Utc.timestamp_opt(s.parse().ok()?, 0).single(),
+    }
+}
+
+fn local_to_utc(naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local.from_local_datetime(naive).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+/// Format the distance between `time` and `now`, e.g. `2m13s ago`
+pub fn format_relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = now.signed_duration_since(time);
+    let secs = delta.num_seconds().unsigned_abs();
+
+    let amount = if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else if secs < 86400 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else {
+        format!("{}d{}h", secs / 86400, secs % 86400 / 3600)
+    };
+
+    if delta < Duration::zero() {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+/// Render a timestamp in the given display mode (`None` for `Original`)
+pub fn format_timestamp(time: DateTime<Utc>, mode: TimestampMode, now: DateTime<Utc>) -> Option<String> {
+    match mode {
+        TimestampMode::Original => None,
+        TimestampMode::Relative => Some(format_relative(time, now)),
+        TimestampMode::Local => Some(time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S%.f %:z").to_string()),
+        TimestampMode::Utc => Some(time.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()),
+    }
+}
+
+/// Rewrite line-leading timestamps in the document according to `mode`
+pub fn render_timestamps(document: &mut Document, mode: TimestampMode, now: DateTime<Utc>) {
+    if mode == TimestampMode::Original {
+        return;
+    }
+
+    for line in document.lines.iter_mut().filter(|l| !l.continuation) {
+        if let Some(ts) = parse_line_timestamp(&line.text()) {
+            if let Some(rendered) = format_timestamp(ts.time, mode, now) {
+                replace_text_range(line, ts.range, &rendered);
+            }
+        }
+    }
+    document.recalculate_max_width();
+}
+
+/// Replace a byte range of the line text, keeping the surrounding span styles
+///
+/// The replacement takes the style of the span where the range starts.
+fn replace_text_range(line: &mut Line, range: Range<usize>, replacement: &str) {
+    let mut spans = Vec::with_capacity(line.spans.len() + 2);
+    let mut offset = 0;
+
+    for span in line.spans.drain(..) {
+        let (start, end) = (offset, offset + span.text.len());
+        offset = end;
+
+        let cut_start = range.start.clamp(start, end) - start;
+        let cut_end = range.end.clamp(start, end) - start;
+
+        if cut_start > 0 {
+            spans.push(StyledSpan::new(&span.text[..cut_start], span.style.clone()));
+        }
+        if range.start >= start && range.start < end {
+            spans.push(StyledSpan::new(replacement, span.style.clone()));
+        }
+        if cut_end < span.text.len() && range.end < end {
+            spans.push(StyledSpan::new(&span.text[cut_end..], span.style));
+        }
+    }
+
+    line.spans = spans;
+}
+
+/// Parse a jump interval like `30s`, `5m`, `2h` or `1d`
+pub fn parse_time_step(value: &str) -> Result<Duration, MatError> {
+    let invalid = || MatError::InvalidTimeStep {
+        value: value.to_string(),
+    };
+
+    let value_trimmed = value.trim();
+    let split = value_trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value_trimmed.len());
+    let (number, unit) = value_trimmed.split_at(split);
+    let number: i64 = number.parse().map_err(|_| invalid())?;
+    if number == 0 {
+        return Err(invalid());
+    }
+
+    match unit {
+        "s" | "" => Ok(Duration::seconds(number)),
+        "m" => Ok(Duration::minutes(number)),
+        "h" => Ok(Duration::hours(number)),
+        "d" => Ok(Duration::days(number)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::SpanStyle;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_iso_timestamps() {
+        let ts = parse_line_timestamp("2024-01-15T10:23:45Z INFO started").unwrap();
+        assert_eq!(ts.range, 0..20);
+        assert_eq!(ts.time, utc("2024-01-15T10:23:45Z"));
+
+        let ts = parse_line_timestamp("[2024-01-15 10:23:45.500+02:00] boot").unwrap();
+        assert_eq!(ts.range, 1..30);
+        assert_eq!(ts.time, utc("2024-01-15T08:23:45.5Z"));
+
+        // Naive timestamps are local time, which varies; just check detection
+        assert!(parse_line_timestamp("2024-01-15 10:23:45 naive").is_some());
+    }
+
+    #[test]
+    fn test_parse_other_formats() {
+        let ts = parse_line_timestamp("[15/Jan/2024:10:23:45 +0000] GET /").unwrap();
+        assert_eq!(ts.time, utc("2024-01-15T10:23:45Z"));
+
+        let ts = parse_line_timestamp("1705314225 event").unwrap();
+        assert_eq!(ts.time, utc("2024-01-15T10:23:45Z"));
+        let ts = parse_line_timestamp("1705314225500 event").unwrap();
+        assert_eq!(ts.time, utc("2024-01-15T10:23:45.5Z"));
+
+        assert!(parse_line_timestamp("Jan 15 10:23:45 host sshd[1]: ok").is_some());
+        assert!(parse_line_timestamp("no timestamp here").is_none());
+        assert!(parse_line_timestamp("12345 is not an epoch").is_none());
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = utc("2024-01-15T12:00:00Z");
+        assert_eq!(format_relative(utc("2024-01-15T11:57:47Z"), now), "2m13s ago");
+        assert_eq!(format_relative(utc("2024-01-15T11:59:55Z"), now), "5s ago");
+        assert_eq!(format_relative(utc("2024-01-15T09:55:00Z"), now), "2h05m ago");
+        assert_eq!(format_relative(utc("2024-01-13T09:00:00Z"), now), "2d3h ago");
+        assert_eq!(format_relative(utc("2024-01-15T12:00:30Z"), now), "in 30s");
+    }
+
+    #[test]
+    fn test_render_timestamps_keeps_rest_of_line() {
+        let mut doc = Document::from_text(
+            "2024-01-15T11:57:47Z first\nplain line",
+            "app.log".to_string(),
+            "UTF-8".to_string(),
+        );
+        render_timestamps(&mut doc, TimestampMode::Relative, utc("2024-01-15T12:00:00Z"));
+        assert_eq!(doc.lines[0].text(), "2m13s ago first");
+        assert_eq!(doc.lines[1].text(), "plain line");
+
+        let mut doc = Document::from_text("[1705314225] x", "app.log".to_string(), "UTF-8".to_string());
+        render_timestamps(&mut doc, TimestampMode::Utc, utc("2024-01-15T12:00:00Z"));
+        assert_eq!(doc.lines[0].text(), "[2024-01-15T10:23:45Z] x");
+    }
+
+    #[test]
+    fn test_replace_text_range_across_spans() {
+        let mut line = Line::plain(1, "");
+        line.spans = vec![
+            StyledSpan::new("2024-01-", SpanStyle::new().bold()),
+            StyledSpan::plain("15 rest"),
+        ];
+        replace_text_range(&mut line, 0..10, "now");
+        assert_eq!(line.text(), "now rest");
+        assert!(line.spans[0].style.bold);
+    }
+
+    #[test]
+    fn test_parse_time_step() {
+        assert_eq!(parse_time_step("30s").unwrap(), Duration::seconds(30));
+        assert_eq!(parse_time_step("5m").unwrap(), Duration::minutes(5));
+        assert_eq!(parse_time_step("2h").unwrap(), Duration::hours(2));
+        assert_eq!(parse_time_step("1d").unwrap(), Duration::days(1));
+        assert_eq!(parse_time_step("90").unwrap(), Duration::seconds(90));
+        assert!(parse_time_step("0m").is_err());
+        assert!(parse_time_step("5w").is_err());
+        assert!(parse_time_step("m").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Hard wrapping for printed output
+//!
+//! The pager soft-wraps at the screen (or `--wrap-width`) as it draws. Printed
+//! output with a fixed `--wrap-width` is broken into rows here instead, so it
+//! comes out the same whatever terminal it is viewed on. Both break lines
+//! with [`wrap_rows`]: at any column, or (`--wrap word`) after the last blank
+//! that fits, cutting only words wider than the whole row.
+
+use unicode_width::UnicodeWidthChar;
+
+use super::line::{Document, Line, StyledSpan};
+
+/// One row of a wrapped line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrapRow {
+    /// Character offset into the line where the row starts
+    pub char_offset: usize,
+    /// Number of characters in the row
+    pub char_count: usize,
+    /// Display columns of the row
+    pub width: usize,
+}
+
+/// Break `text` into rows of at most `width` columns, at word boundaries if `at_words`
+///
+/// Breaking at words, a blank that doesn't fit at the end of a row is dropped
+/// rather than starting the next one. An empty text is one empty row.
+pub fn wrap_rows(text: &str, width: usize, at_words: bool) -> Vec<WrapRow> {
+    let mut rows = Vec::new();
+    let mut row = WrapRow {
+        char_offset: 0,
+        char_count: 0,
+        width: 0,
+    };
+    // The row as it would end at the last blank in it
+    let mut last_break: Option<WrapRow> = None;
+    for (i, ch) in text.chars().enumerate() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if row.width + ch_width > width && row.width > 0 {
+            if at_words && ch.is_whitespace() {
+                rows.push(row);
+                row = WrapRow {
+                    char_offset: i + 1,
+                    char_count: 0,
+                    width: 0,
+                };
+                last_break = None;
+                continue;
+            }
+            match last_break.take().filter(|_| at_words) {
+                // The word moves to the next row whole, unless it doesn't fit there either
+                Some(head) if row.width - head.width + ch_width <= width => {
+                    rows.push(head);
+                    row = WrapRow {
+                        char_offset: head.char_offset + head.char_count,
+                        char_count: row.char_count - head.char_count,
+                        width: row.width - head.width,
+                    };
+                }
+                _ => {
+                    rows.push(row);
+                    row = WrapRow {
+                        char_offset: i,
+                        char_count: 0,
+                        width: 0,
+                    };
+                }
+            }
+        }
+        row.char_count += 1;
+        row.width += ch_width;
+        if at_words && ch.is_whitespace() {
+            last_break = Some(row);
+        }
+    }
+    if row.char_count > 0 || rows.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Break every line wider than `width` columns into rows, keeping span styles
+///
+/// Rows after the first one of a line get line number 0, so they print without
+/// a number. Continuation chunks of a long line are rejoined before wrapping.
+pub fn wrap_document(document: &Document, width: usize, at_words: bool) -> Document {
+    let mut lines = Vec::with_capacity(document.lines.len());
+    let mut start = 0;
+    while start < document.lines.len() {
+        let mut end = start + 1;
+        while end < document.lines.len() && document.lines[end].continuation {
+            end += 1;
+        }
+        let group = &document.lines[start..end];
+        let text: String = group.iter().map(|line| line.text()).collect();
+        let rows = wrap_rows(&text, width, at_words);
+        let spans = group.iter().flat_map(|line| line.spans.iter());
+        lines.extend(split_spans(spans, &rows).into_iter().enumerate().map(|(i, spans)| Line {
+            number: if i == 0 { group[0].number } else { 0 },
+            spans,
+            is_match: group[0].is_match,
+            is_context: group[0].is_context,
+            continuation: false,
+            heading: if i == 0 { group[0].heading.clone() } else { None },
+        }));
+        start = end;
+    }
+
+    let mut wrapped = Document {
+        lines,
+        max_line_width: 0,
+        source_name: document.source_name.clone(),
+        encoding: document.encoding.clone(),
+    };
+    wrapped.recalculate_max_width();
+    wrapped
+}
+
+/// Split one line's spans into `rows`, dropping the characters between rows
+fn split_spans<'a>(spans: impl Iterator<Item = &'a StyledSpan>, rows: &[WrapRow]) -> Vec<Vec<StyledSpan>> {
+    let mut split: Vec<Vec<StyledSpan>> = vec![Vec::new(); rows.len()];
+    let mut row = 0;
+    let mut char_idx = 0;
+    for span in spans {
+        let mut text = String::new();
+        for ch in span.text.chars() {
+            while row < rows.len() && char_idx >= rows[row].char_offset + rows[row].char_count {
+                if !text.is_empty() {
+                    split[row].push(StyledSpan::new(std::mem::take(&mut text), span.style.clone()));
+                }
+                row += 1;
+            }
+            if row < rows.len() && char_idx >= rows[row].char_offset {
+                text.push(ch);
+            }
+            char_idx += 1;
+        }
+        if !text.is_empty() && row < rows.len() {
+            split[row].push(StyledSpan::new(text, span.style.clone()));
+        }
+    }
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::SpanStyle;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_wrap_document() {
+        let doc = Document::from_text("abcdefgh\n\nxy", "f.txt".to_string(), "UTF-8".to_string());
+        let wrapped = wrap_document(&doc, 3, false);
+
+        let rows: Vec<(usize, String)> = wrapped.lines.iter().map(|l| (l.number, l.text())).collect();
+        let expected = [(1, "abc"), (0, "def"), (0, "gh"), (2, ""), (3, "xy")];
+        assert_eq!(rows, expected.map(|(n, t)| (n, t.to_string())));
+    }
+
+    #[test]
+    fn test_wrap_keeps_styles_and_wide_chars() {
+        let red = SpanStyle::new().fg(Color::Red);
+        let mut line = Line::plain(1, "ab");
+        line.spans.push(StyledSpan::new("世界", red.clone()));
+        let doc = Document {
+            lines: vec![line],
+            max_line_width: 6,
+            source_name: "f.txt".to_string(),
+            encoding: "UTF-8".to_string(),
+        };
+
+        let wrapped = wrap_document(&doc, 4, false);
+        assert_eq!(wrapped.lines.len(), 2);
+        assert_eq!(wrapped.lines[0].text(), "ab世");
+        assert_eq!(wrapped.lines[1].spans, vec![StyledSpan::new("界", red)]);
+    }
+
+    #[test]
+    fn test_wrap_at_words() {
+        let texts = |text: &str, width: usize| -> Vec<String> {
+            let rows = wrap_rows(text, width, true);
+            rows.iter().map(|r| text.chars().skip(r.char_offset).take(r.char_count).collect()).collect()
+        };
+        assert_eq!(texts("the quick brown fox", 10), ["the quick ", "brown fox"]);
+        // The blank at the break is dropped
+        assert_eq!(texts("the quick brown", 9), ["the quick", "brown"]);
+        // Words wider than a row are cut
+        assert_eq!(texts("a supercalifragilistic b", 8), ["a ", "supercal", "ifragili", "stic b"]);
+        assert_eq!(texts("", 8), [""]);
+        assert_eq!(wrap_rows("abcdefgh", 3, false).len(), 3);
+
+        let doc = Document::from_text("one two three", "f.txt".to_string(), "UTF-8".to_string());
+        let rows: Vec<String> = wrap_document(&doc, 8, true).lines.iter().map(|l| l.text()).collect();
+        assert_eq!(rows, ["one two ", "three"]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Exit code for successful execution
@@ -10,6 +10,12 @@ pub const EXIT_ERROR: i32 = 1;
 /// Exit code for invalid arguments (bad regex, invalid flags, invalid line range)
 pub const EXIT_INVALID_ARGS: i32 = 2;
 
+/// Exit code for quitting the pager after selecting a line with Enter (`--report-action`)
+pub const EXIT_SELECTED: i32 = 3;
+
+/// Exit code when interrupted with Ctrl+C (128 + SIGINT, like shells report)
+pub const EXIT_INTERRUPTED: i32 = 130;
+
 /// Custom error type for mat
 #[derive(Error, Debug)]
 #[allow(dead_code)]
@@ -34,14 +40,42 @@ pub enum MatError {
     #[error("Empty pattern provided. Did you mean to omit -s/-g?")]
     EmptyPattern,
 
+    /// Pattern file without any pattern
+    #[error("Pattern file '{path}' contains no patterns")]
+    EmptyPatternFile { path: PathBuf },
+
     /// Binary file detected
-    #[error("Binary file detected: '{path}'. Use --force-binary to view anyway")]
+    #[error("Binary file detected: '{path}'. Use --hex (or --force-binary) to view a hex dump")]
     BinaryFile { path: PathBuf },
 
     /// Invalid line range format
     #[error("Invalid line range format: '{range}'. Expected formats: X:Y, :Y, X:, or X")]
     InvalidLineRange { range: String },
 
+    /// Unknown `--style` component
+    #[error("Invalid style component: '{component}'. Expected: header, grid, numbers, snip, full, plain")]
+    InvalidStyle { component: String },
+
+    /// `--extract` without a grep pattern that has named groups
+    #[error("--extract needs a --grep pattern with named groups, e.g. -g '(?P<status>\\d{{3}})'")]
+    ExtractNeedsNamedGroups,
+
+    /// `--count-matches` without a search pattern
+    #[error("--count-matches needs a --search pattern")]
+    CountNeedsSearch,
+
+    /// Invalid `--time-step` duration
+    #[error("Invalid time step: '{value}'. Expected a number with s, m, h or d (e.g. 30s, 5m)")]
+    InvalidTimeStep { value: String },
+
+    /// Invalid `--keys` sequence
+    #[error("Invalid key sequence: {message}")]
+    InvalidKeys { message: String },
+
+    /// Configuration file could not be parsed
+    #[error("Invalid config file '{path}': {message}")]
+    InvalidConfig { path: PathBuf, message: String },
+
     /// Encoding detection/conversion failed
     #[error("Failed to detect or convert encoding for '{path}'")]
     EncodingError { path: PathBuf },
@@ -49,6 +83,50 @@ pub enum MatError {
     /// Follow mode with stdin
     #[error("Cannot use follow mode (-f) with stdin. Follow mode requires a file.")]
     FollowModeStdin,
+
+    /// --tee without piped input
+    #[error("--tee saves piped input, but stdin is not read.")]
+    TeeWithoutStdin,
+
+    /// Follow mode with more than one file
+    #[error("Cannot use follow mode (-f) with multiple files.")]
+    FollowModeMultipleFiles,
+
+    /// A configured preprocessor command failed
+    #[error("Preprocessor '{command}' failed for '{path}': {message}")]
+    Preprocessor {
+        command: String,
+        path: PathBuf,
+        message: String,
+    },
+
+    /// Text could not be extracted from a PDF file
+    #[error("Could not extract text from PDF '{path}': {message}")]
+    Pdf { path: PathBuf, message: String },
+
+    /// A compressed input could not be decompressed
+    #[error("Could not decompress '{path}' ({format}): {message}")]
+    Decompress {
+        path: PathBuf,
+        format: &'static str,
+        message: String,
+    },
+
+    /// `--spellcheck` without a dictionary to check against
+    #[error("No spelling dictionary found. Install hunspell-en-us or pass one with --dictionary FILE")]
+    NoDictionary,
+
+    /// A file over `--max-memory` would have been read whole
+    #[error(
+        "'{path}' is {}, over the --max-memory limit of {}. View part of it with --lines or --grep, or raise the limit",
+        format_size(*.size),
+        format_size(*.limit)
+    )]
+    TooLarge { path: PathBuf, size: u64, limit: u64 },
+
+    /// Loading was cancelled with Ctrl+C
+    #[error("Interrupted while loading '{path}'. Use --open-partial to view what was read")]
+    Interrupted { path: PathBuf },
 }
 
 impl MatError {
@@ -57,10 +135,90 @@ impl MatError {
         match self {
             MatError::InvalidRegex { .. }
             | MatError::InvalidLineRange { .. }
-            | MatError::FollowModeStdin => EXIT_INVALID_ARGS,
+            | MatError::InvalidStyle { .. }
+            | MatError::InvalidTimeStep { .. }
+            | MatError::InvalidKeys { .. }
+            | MatError::InvalidConfig { .. }
+            | MatError::ExtractNeedsNamedGroups
+            | MatError::CountNeedsSearch
+            | MatError::FollowModeStdin
+            | MatError::TeeWithoutStdin
+            | MatError::FollowModeMultipleFiles => EXIT_INVALID_ARGS,
+            MatError::Interrupted { .. } => EXIT_INTERRUPTED,
             _ => EXIT_ERROR,
         }
     }
+
+    /// Stable machine-readable name of the error kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MatError::Io { .. } => "io",
+            MatError::InvalidRegex { .. } => "invalid_regex",
+            MatError::EmptyPattern => "empty_pattern",
+            MatError::EmptyPatternFile { .. } => "empty_pattern_file",
+            MatError::BinaryFile { .. } => "binary_file",
+            MatError::InvalidLineRange { .. } => "invalid_line_range",
+            MatError::InvalidStyle { .. } => "invalid_style",
+            MatError::ExtractNeedsNamedGroups => "extract_needs_named_groups",
+            MatError::CountNeedsSearch => "count_needs_search",
+            MatError::InvalidTimeStep { .. } => "invalid_time_step",
+            MatError::InvalidKeys { .. } => "invalid_keys",
+            MatError::InvalidConfig { .. } => "invalid_config",
+            MatError::EncodingError { .. } => "encoding_error",
+            MatError::FollowModeStdin => "follow_mode_stdin",
+            MatError::TeeWithoutStdin => "tee_without_stdin",
+            MatError::FollowModeMultipleFiles => "follow_mode_multiple_files",
+            MatError::Preprocessor { .. } => "preprocessor",
+            MatError::Pdf { .. } => "pdf",
+            MatError::Decompress { .. } => "decompress",
+            MatError::NoDictionary => "no_dictionary",
+            MatError::TooLarge { .. } => "too_large",
+            MatError::Interrupted { .. } => "interrupted",
+        }
+    }
+
+    /// File the error is about, if any
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            MatError::Io { path, .. }
+            | MatError::EmptyPatternFile { path }
+            | MatError::BinaryFile { path }
+            | MatError::InvalidConfig { path, .. }
+            | MatError::EncodingError { path }
+            | MatError::Preprocessor { path, .. }
+            | MatError::Pdf { path, .. }
+            | MatError::Decompress { path, .. }
+            | MatError::TooLarge { path, .. }
+            | MatError::Interrupted { path } => (!path.as_os_str().is_empty()).then_some(path.as_path()),
+            _ => None,
+        }
+    }
+
+    /// Single-line JSON object for `--error-format json`
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "kind": self.kind(),
+            "path": self.path().map(|p| p.display().to_string()),
+            "message": self.to_string(),
+            "exit_code": self.exit_code(),
+        })
+        .to_string()
+    }
+}
+
+/// A byte count in the largest binary unit it reaches, e.g. "1.5 GiB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
 }
 
 impl From<std::io::Error> for MatError {
@@ -71,3 +229,39 @@ impl From<std::io::Error> for MatError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_json() {
+        let error = MatError::BinaryFile {
+            path: PathBuf::from("a.bin"),
+        };
+        let json: serde_json::Value = serde_json::from_str(&error.to_json()).unwrap();
+        assert_eq!(json["kind"], "binary_file");
+        assert_eq!(json["path"], "a.bin");
+        assert_eq!(json["exit_code"], EXIT_ERROR);
+        assert!(json["message"].as_str().unwrap().contains("--force-binary"));
+
+        let json: serde_json::Value = serde_json::from_str(&MatError::EmptyPattern.to_json()).unwrap();
+        assert!(json["path"].is_null());
+        assert_eq!(json["exit_code"], EXIT_ERROR);
+    }
+
+    #[test]
+    fn test_too_large_message() {
+        let error = MatError::TooLarge {
+            path: PathBuf::from("huge.log"),
+            size: 3 * 1024 * 1024 * 1024 / 2,
+            limit: 512 * 1024 * 1024,
+        };
+        assert_eq!(
+            error.to_string(),
+            "'huge.log' is 1.5 GiB, over the --max-memory limit of 512.0 MiB. \
+             View part of it with --lines or --grep, or raise the limit"
+        );
+        assert_eq!(error.path(), Some(Path::new("huge.log")));
+    }
+}
@@ -0,0 +1,134 @@
+//! Line checksums and comparison with a baseline file
+//!
+//! `--line-hashes` prefixes every line with a short hash of its text, so equal
+//! lines are easy to spot across files. `--compare OTHER` marks the lines that
+//! don't occur anywhere in OTHER, answering "what's new in today's log". A
+//! line-leading timestamp is left out of the hash, since it differs on every
+//! run of an otherwise identical log line.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use ratatui::style::Color;
+
+use crate::display::timestamp::parse_line_timestamp;
+use crate::display::{Document, SpanStyle, StyledSpan};
+
+/// Width of the hash column, including its trailing space
+const HASH_WIDTH: usize = 7;
+
+/// Width of the new-line marker column, including its trailing space
+const MARKER_WIDTH: usize = 2;
+
+/// Hash of a line's text, ignoring a leading timestamp and surrounding whitespace
+pub fn line_hash(text: &str) -> u64 {
+    let text = match parse_line_timestamp(text) {
+        Some(timestamp) => &text[timestamp.range.end..],
+        None => text,
+    };
+    let mut hasher = DefaultHasher::new();
+    text.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The lines of the file compared against
+pub struct Baseline {
+    hashes: HashSet<u64>,
+}
+
+impl Baseline {
+    /// Baseline of every line of `text`
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            hashes: text.lines().map(line_hash).collect(),
+        }
+    }
+
+    /// Whether a line with this text occurs in the baseline
+    pub fn contains(&self, text: &str) -> bool {
+        self.hashes.contains(&line_hash(text))
+    }
+}
+
+/// Prefix lines with their hash (`show_hashes`) and a `+` where they are missing from `baseline`
+///
+/// Continuation chunks share the prefix of their line; synthetic lines get
+/// blank columns.
+pub fn annotate_lines(document: &mut Document, show_hashes: bool, baseline: Option<&Baseline>) {
+    if !show_hashes && baseline.is_none() {
+        return;
+    }
+    let dim = SpanStyle::new().fg(Color::DarkGray);
+    let new_style = SpanStyle::new().fg(Color::Green).bold();
+
+    let lines = &mut document.lines;
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = start + 1;
+        while end < lines.len() && lines[end].continuation {
+            end += 1;
+        }
+
+        let mut prefix = Vec::new();
+        if lines[start].number == 0 {
+            let width = if show_hashes { HASH_WIDTH } else { 0 } + if baseline.is_some() { MARKER_WIDTH } else { 0 };
+            prefix.push(StyledSpan::new(" ".repeat(width), SpanStyle::default()));
+        } else {
+            let text: String = lines[start..end].iter().map(|l| l.text()).collect();
+            if let Some(baseline) = baseline {
+                if baseline.contains(&text) {
+                    prefix.push(StyledSpan::new("  ", SpanStyle::default()));
+                } else {
+                    prefix.push(StyledSpan::new("+ ", new_style.clone()));
+                }
+            }
+            if show_hashes {
+                prefix.push(StyledSpan::new(format!("{:06x} ", line_hash(&text) >> 40), dim.clone()));
+            }
+        }
+        lines[start].spans.splice(0..0, prefix);
+        start = end;
+    }
+
+    document.recalculate_max_width();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_hash_ignores_timestamp() {
+        assert_eq!(
+            line_hash("2024-05-01T10:00:00Z INFO started"),
+            line_hash("2024-05-02T08:30:12Z INFO started")
+        );
+        assert_ne!(line_hash("INFO started"), line_hash("INFO stopped"));
+    }
+
+    #[test]
+    fn test_annotate_marks_new_lines() {
+        let baseline = Baseline::from_text("2024-05-01T10:00:00Z ready\nshared\n");
+        let mut doc = Document::from_text(
+            "2024-05-02T10:00:00Z ready\nnew error\nshared",
+            "today.log".to_string(),
+            "UTF-8".to_string(),
+        );
+
+        annotate_lines(&mut doc, false, Some(&baseline));
+        let texts: Vec<String> = doc.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["  2024-05-02T10:00:00Z ready", "+ new error", "  shared"]);
+    }
+
+    #[test]
+    fn test_annotate_hashes() {
+        let mut doc = Document::from_text("same\nsame\nother", "a.log".to_string(), "UTF-8".to_string());
+        annotate_lines(&mut doc, true, None);
+
+        let hashes: Vec<String> = doc.lines.iter().map(|l| l.spans[0].text.clone()).collect();
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_eq!(hashes[0].len(), HASH_WIDTH);
+    }
+}
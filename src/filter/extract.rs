@@ -0,0 +1,129 @@
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
+
+use crate::display::{Document, Line, SpanStyle, StyledSpan};
+use crate::error::MatError;
+
+/// Gap between table columns
+const COLUMN_GAP: &str = "  ";
+
+/// Build a table of named capture group values, one row per matching line
+///
+/// Columns are the named groups of `pattern` in order; rows keep the line
+/// number of the line they were extracted from. Groups that did not
+/// participate in a match are left empty.
+pub fn extract_table(document: &Document, pattern: &Regex) -> Result<Document, MatError> {
+    let names: Vec<&str> = pattern.capture_names().flatten().collect();
+    if names.is_empty() {
+        return Err(MatError::ExtractNeedsNamedGroups);
+    }
+
+    let mut rows: Vec<(usize, Vec<String>)> = Vec::new();
+    for line in document.lines.iter().filter(|l| l.number > 0 && !l.continuation) {
+        let text = line.text();
+        if let Some(caps) = pattern.captures(&text) {
+            let values = names
+                .iter()
+                .map(|name| caps.name(name).map_or(String::new(), |m| m.as_str().to_string()))
+                .collect();
+            rows.push((line.number, values));
+        }
+    }
+
+    let mut widths: Vec<usize> = names.iter().map(|n| UnicodeWidthStr::width(*n)).collect();
+    for (_, values) in &rows {
+        for (width, value) in widths.iter_mut().zip(values) {
+            *width = (*width).max(UnicodeWidthStr::width(value.as_str()));
+        }
+    }
+
+    let header_cells: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+    let mut lines = vec![Line {
+        number: 0,
+        spans: vec![StyledSpan::new(
+            format_row(&header_cells, &widths),
+            SpanStyle::new().bold().underline(),
+        )],
+        is_match: false,
+        is_context: false,
+        continuation: false,
+        heading: None,
+    }];
+    lines.extend(
+        rows.iter()
+            .map(|(number, values)| Line::plain(*number, &format_row(values, &widths))),
+    );
+
+    let mut table = Document {
+        lines,
+        max_line_width: 0,
+        source_name: document.source_name.clone(),
+        encoding: document.encoding.clone(),
+    };
+    table.recalculate_max_width();
+    Ok(table)
+}
+
+/// Pad cells to their column widths (the last column is not padded)
+fn format_row(cells: &[String], widths: &[usize]) -> String {
+    let mut row = String::new();
+    for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if i > 0 {
+            row.push_str(COLUMN_GAP);
+        }
+        row.push_str(cell);
+        if i + 1 < cells.len() {
+            let pad = width.saturating_sub(UnicodeWidthStr::width(cell.as_str()));
+            row.push_str(&" ".repeat(pad));
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_doc(text: &str) -> Document {
+        Document::from_text(text, "access.log".to_string(), "UTF-8".to_string())
+    }
+
+    #[test]
+    fn test_extract_table() {
+        let doc = create_doc("GET /index 200\nnoise\nPOST /login 401");
+        let pattern = Regex::new(r"(?P<method>[A-Z]+) (?P<path>\S+) (?P<status>\d+)").unwrap();
+
+        let table = extract_table(&doc, &pattern).unwrap();
+        let texts: Vec<String> = table.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "method  path    status",
+                "GET     /index  200",
+                "POST    /login  401",
+            ]
+        );
+        let numbers: Vec<usize> = table.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn test_extract_optional_group() {
+        let doc = create_doc("user=bob\nuser=amy id=7");
+        let pattern = Regex::new(r"user=(?P<user>\w+)(?: id=(?P<id>\d+))?").unwrap();
+
+        let table = extract_table(&doc, &pattern).unwrap();
+        assert_eq!(table.lines[1].text(), "bob   ");
+        assert_eq!(table.lines[2].text(), "amy   7");
+    }
+
+    #[test]
+    fn test_extract_requires_named_groups() {
+        let doc = create_doc("x");
+        let pattern = Regex::new(r"(\d+)").unwrap();
+        assert!(matches!(
+            extract_table(&doc, &pattern),
+            Err(MatError::ExtractNeedsNamedGroups)
+        ));
+    }
+}
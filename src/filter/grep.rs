@@ -1,8 +1,13 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::Path;
+
 use regex::Regex;
 
 use crate::cli::Args;
-use crate::display::{Document, Line, SpanStyle, StyledSpan};
+use crate::display::{match_ranges_by_line, Document, Line, SpanStyle, StyledSpan};
 use crate::error::MatError;
+use crate::theme::GrepColors;
 
 /// Options for grep filtering
 #[derive(Debug)]
@@ -13,21 +18,25 @@ pub struct GrepOptions {
     pub before: usize,
     /// Lines to show after match
     pub after: usize,
+    /// Keep the lines that don't match instead (`-v`)
+    pub invert: bool,
+    /// Match, context and separator styles
+    pub colors: GrepColors,
 }
 
 impl GrepOptions {
     /// Create GrepOptions from CLI args
     pub fn from_args(args: &Args) -> Result<Option<Self>, MatError> {
-        let pattern_str = match &args.grep {
-            Some(p) => p,
+        // Like grep -f, a pattern file alone filters; with -s it only highlights
+        let mut patterns = match &args.grep {
+            Some(p) if p.is_empty() => return Err(MatError::EmptyPattern),
+            Some(p) => vec![p.clone()],
+            None if args.pattern_file.is_some() && args.search.is_none() => Vec::new(),
             None => return Ok(None),
         };
+        patterns.extend(pattern_file_patterns(args)?);
 
-        if pattern_str.is_empty() {
-            return Err(MatError::EmptyPattern);
-        }
-
-        let pattern = build_regex(pattern_str, args)?;
+        let pattern = build_regex_from_patterns(&patterns, args)?;
 
         // Determine context lines
         let (before, after) = if let Some(c) = args.context {
@@ -40,6 +49,8 @@ impl GrepOptions {
             pattern,
             before,
             after,
+            invert: args.invert_match,
+            colors: GrepColors::from_env(),
         }))
     }
 }
@@ -93,6 +104,65 @@ pub fn build_regex(pattern: &str, args: &Args) -> Result<Regex, MatError> {
     })
 }
 
+/// Read a grep -f style pattern file: one pattern per line, blank lines ignored
+pub fn read_pattern_file(path: &Path) -> Result<Vec<String>, MatError> {
+    let content = std::fs::read_to_string(path).map_err(|e| MatError::Io {
+        source: e,
+        path: path.to_path_buf(),
+    })?;
+
+    let patterns: Vec<String> = content
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if patterns.is_empty() {
+        return Err(MatError::EmptyPatternFile {
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(patterns)
+}
+
+/// Patterns from `--pattern-file`, if given
+pub fn pattern_file_patterns(args: &Args) -> Result<Vec<String>, MatError> {
+    match &args.pattern_file {
+        Some(path) => read_pattern_file(path),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Combine patterns into a single alternation with the given CLI options
+///
+/// Each pattern is validated on its own first, so errors name the culprit.
+pub fn build_regex_from_patterns(patterns: &[String], args: &Args) -> Result<Regex, MatError> {
+    if let [pattern] = patterns {
+        return build_regex(pattern, args);
+    }
+
+    let mut alternatives = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        if args.fixed_strings {
+            alternatives.push(format!("(?:{})", regex::escape(pattern)));
+        } else {
+            Regex::new(pattern).map_err(|e| MatError::InvalidRegex {
+                source: e,
+                pattern: pattern.clone(),
+            })?;
+            alternatives.push(format!("(?:{})", pattern));
+        }
+    }
+
+    let combined = format!("(?:{})", alternatives.join("|"));
+    let pattern_str = build_regex_pattern(&combined, args.ignore_case, false, args.word_regexp, args.line_regexp);
+    Regex::new(&pattern_str).map_err(|e| MatError::InvalidRegex {
+        source: e,
+        pattern: combined,
+    })
+}
+
 /// Filter a document to only include matching lines and context
 pub fn grep_filter(document: &Document, options: &GrepOptions) -> Document {
     let total_lines = document.lines.len();
@@ -105,13 +175,20 @@ pub fn grep_filter(document: &Document, options: &GrepOptions) -> Document {
         };
     }
 
-    // First pass: find all matching line indices
+    // First pass: find all matching line indices (the non-matching ones when inverted),
+    // matching a chunked long line as a whole
     let mut match_indices: Vec<usize> = Vec::new();
-    for (i, line) in document.lines.iter().enumerate() {
-        let text = line.text();
-        if options.pattern.is_match(&text) {
-            match_indices.push(i);
+    let mut start = 0;
+    while start < total_lines {
+        let mut end = start + 1;
+        while end < total_lines && document.lines[end].continuation {
+            end += 1;
+        }
+        let text: String = document.lines[start..end].iter().map(|l| l.text()).collect();
+        if options.pattern.is_match(&text) != options.invert {
+            match_indices.extend(start..end);
         }
+        start = end;
     }
 
     if match_indices.is_empty() {
@@ -126,8 +203,17 @@ pub fn grep_filter(document: &Document, options: &GrepOptions) -> Document {
     // Second pass: build ranges including context
     let mut ranges: Vec<(usize, usize)> = Vec::new();
     for &match_idx in &match_indices {
-        let start = match_idx.saturating_sub(options.before);
-        let end = (match_idx + options.after + 1).min(total_lines);
+        let mut start = match_idx.saturating_sub(options.before);
+        let mut end = (match_idx + options.after + 1).min(total_lines);
+
+        // Never cut a chunked long line in half
+        while start > 0 && document.lines[start].continuation {
+            start -= 1;
+        }
+        while end < total_lines && document.lines[end].continuation {
+            end += 1;
+        }
+
         ranges.push((start, end));
     }
 
@@ -141,7 +227,7 @@ pub fn grep_filter(document: &Document, options: &GrepOptions) -> Document {
     for (start, end) in merged_ranges {
         // Add separator if there's a gap
         if !result_lines.is_empty() && start > last_end {
-            result_lines.push(Line::separator());
+            result_lines.push(options.colors.separator_line());
         }
 
         for i in start..end {
@@ -154,15 +240,14 @@ pub fn grep_filter(document: &Document, options: &GrepOptions) -> Document {
                 spans: original_line.spans.clone(),
                 is_match,
                 is_context: !is_match,
+                continuation: original_line.continuation,
+                heading: original_line.heading.clone(),
             };
 
             // Context lines get dim styling
             // Match lines keep their spans - highlighting applied later after syntax highlighting
             if !is_match {
-                line.spans = vec![StyledSpan::new(
-                    text,
-                    SpanStyle::default().fg(ratatui::style::Color::DarkGray),
-                )];
+                line.spans = vec![StyledSpan::new(text, options.colors.context.clone())];
             }
 
             result_lines.push(line);
@@ -181,54 +266,151 @@ pub fn grep_filter(document: &Document, options: &GrepOptions) -> Document {
     }
 }
 
-/// Highlight all matches of the pattern in the text
-pub fn highlight_matches(text: &str, pattern: &Regex) -> Vec<StyledSpan> {
-    let mut spans = Vec::new();
-    let mut last_end = 0;
+/// Grep filtering of lines arriving below a filtered document (`--follow` with `--grep`)
+///
+/// Lines are kept as `grep_filter` would keep them, with context carried over
+/// between batches: the last lines seen wait as before-context of a match to
+/// come, and a match keeps the lines after it as after-context.
+#[derive(Debug)]
+pub struct GrepStream {
+    options: GrepOptions,
+    /// Lines dropped since the last kept line, up to `before` of them
+    recent: VecDeque<Line>,
+    /// Lines still to keep as after-context of the last match
+    after_left: usize,
+    /// Whether a line was dropped for good since the last kept line, which a separator marks
+    gap: bool,
+    /// Whether any line was kept yet (no separator goes above the first)
+    kept_any: bool,
+    /// Whether the last source line (which continuation chunks follow) was kept, and as a match
+    last: Option<bool>,
+}
 
-    // Use cyan background for grep matches (different from search which uses yellow)
-    let match_style = SpanStyle::default()
-        .fg(ratatui::style::Color::Black)
-        .bg(ratatui::style::Color::Cyan);
-    let normal_style = SpanStyle::default();
+impl GrepStream {
+    /// Continue filtering below `document`, the unfiltered lines so far
+    pub fn new(options: GrepOptions, document: &Document) -> Self {
+        let mut stream = Self {
+            options,
+            recent: VecDeque::new(),
+            after_left: 0,
+            gap: false,
+            kept_any: false,
+            last: None,
+        };
+        // Running the lines through sets up the context; they are shown already
+        stream.filter(document.lines.clone());
+        stream
+    }
 
-    for mat in pattern.find_iter(text) {
-        // Add non-matching text before this match
-        if mat.start() > last_end {
-            spans.push(StyledSpan::new(&text[last_end..mat.start()], normal_style.clone()));
+    /// The lines of `lines` to show, styled like grep output, with separators between groups
+    pub fn filter(&mut self, lines: Vec<Line>) -> Vec<Line> {
+        let mut kept = Vec::new();
+        for mut line in lines {
+            if line.continuation {
+                if let Some(is_match) = self.last {
+                    kept.push(self.style(line, is_match));
+                }
+                continue;
+            }
+            let is_match = self.options.pattern.is_match(&line.text()) != self.options.invert;
+            if is_match {
+                if self.gap && self.kept_any {
+                    kept.push(self.options.colors.separator_line());
+                }
+                for context in std::mem::take(&mut self.recent) {
+                    kept.push(self.style(context, false));
+                }
+                self.after_left = self.options.after;
+            } else if self.after_left > 0 {
+                self.after_left -= 1;
+            } else {
+                // Dropped, unless a match comes within `before` lines
+                line.is_match = false;
+                self.recent.push_back(line);
+                if self.recent.len() > self.options.before {
+                    self.recent.pop_front();
+                    self.gap = true;
+                }
+                self.last = None;
+                continue;
+            }
+            kept.push(self.style(line, is_match));
+            self.last = Some(is_match);
+            self.gap = false;
+            self.kept_any = true;
         }
-        // Add the matched text with highlight
-        spans.push(StyledSpan::new(mat.as_str(), match_style.clone()));
-        last_end = mat.end();
+        kept
     }
 
-    // Add any remaining text after the last match
-    if last_end < text.len() {
-        spans.push(StyledSpan::new(&text[last_end..], normal_style.clone()));
+    /// A kept line styled as a match or as context
+    fn style(&self, mut line: Line, is_match: bool) -> Line {
+        line.is_match = is_match;
+        line.is_context = !is_match;
+        if !is_match {
+            line.spans = vec![StyledSpan::new(line.text(), self.options.colors.context.clone())];
+        } else if !self.options.invert {
+            line.spans = highlight_matches(&line.spans, &self.options.pattern, &self.options.colors.matched);
+        }
+        line
     }
+}
 
-    // If no matches found (shouldn't happen since we only call this for matching lines),
-    // return the whole text as a single span
-    if spans.is_empty() {
-        spans.push(StyledSpan::new(text, normal_style));
+/// Give the matches of `pattern` in the text of `spans` the `match_style`, like grep --color
+///
+/// Spans are split at the match boundaries; text outside the matches keeps its
+/// style (syntax highlighting, say).
+pub fn highlight_matches(spans: &[StyledSpan], pattern: &Regex, match_style: &SpanStyle) -> Vec<StyledSpan> {
+    let text: String = spans.iter().map(|s| s.text.as_str()).collect();
+    let matches: Vec<Range<usize>> = pattern.find_iter(&text).map(|m| m.range()).collect();
+    highlight_ranges(spans, &matches, match_style)
+}
+
+/// Overlay `match_style` on the (sorted, byte) `ranges` of the text of `spans`
+fn highlight_ranges(spans: &[StyledSpan], ranges: &[Range<usize>], match_style: &SpanStyle) -> Vec<StyledSpan> {
+    let text: String = spans.iter().map(|s| s.text.as_str()).collect();
+    let matches: Vec<(usize, usize)> = ranges.iter().filter(|r| !r.is_empty()).map(|r| (r.start, r.end)).collect();
+    if matches.is_empty() {
+        return spans.to_vec();
     }
 
-    spans
+    let mut result = Vec::with_capacity(spans.len() + 2 * matches.len());
+    let mut offset = 0;
+    for span in spans {
+        let span_end = offset + span.text.len();
+        let mut pos = offset;
+        for &(start, end) in matches.iter().filter(|&&(start, end)| start < span_end && end > offset) {
+            let (start, end) = (start.max(offset), end.min(span_end));
+            if start > pos {
+                result.push(StyledSpan::new(&text[pos..start], span.style.clone()));
+            }
+            result.push(StyledSpan::new(&text[start..end], match_style.clone()));
+            pos = end;
+        }
+        if pos < span_end {
+            result.push(StyledSpan::new(&text[pos..span_end], span.style.clone()));
+        }
+        offset = span_end;
+    }
+    result
 }
 
 /// Apply grep match highlighting to a document
 /// This should be called AFTER syntax highlighting to overlay match highlights
-pub fn apply_grep_highlight(document: &mut Document, pattern: &Regex) {
-    for line in &mut document.lines {
+pub fn apply_grep_highlight(document: &mut Document, options: &GrepOptions) {
+    // Lines kept by an inverted grep have nothing to highlight
+    if options.invert {
+        return;
+    }
+    let ranges = match_ranges_by_line(&document.lines, &options.pattern);
+    for (line, ranges) in document.lines.iter_mut().zip(ranges) {
         if line.is_match {
-            let text = line.text();
-            line.spans = highlight_matches(&text, pattern);
+            line.spans = highlight_ranges(&line.spans, &ranges, &options.colors.matched);
         }
     }
 }
 
 /// Merge overlapping ranges
-fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+pub(crate) fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
     if ranges.is_empty() {
         return vec![];
     }
@@ -255,6 +437,42 @@ fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pattern_file_alternation() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"timeout\n\nrefused\r\n").unwrap();
+
+        let args = Args {
+            pattern_file: Some(file.path().to_path_buf()),
+            word_regexp: true,
+            ..Default::default()
+        };
+        let opts = GrepOptions::from_args(&args).unwrap().unwrap();
+        assert!(opts.pattern.is_match("connection refused"));
+        assert!(opts.pattern.is_match("read timeout"));
+        assert!(!opts.pattern.is_match("timeouts"));
+        assert!(!opts.pattern.is_match("unrelated"));
+    }
+
+    #[test]
+    fn test_pattern_file_fixed_strings() {
+        let patterns = vec!["a.b".to_string(), "[x]".to_string()];
+        let args = Args {
+            fixed_strings: true,
+            ..Default::default()
+        };
+        let pattern = build_regex_from_patterns(&patterns, &args).unwrap();
+        assert!(pattern.is_match("[x]"));
+        assert!(!pattern.is_match("aXb"));
+    }
+
+    #[test]
+    fn test_pattern_file_reports_bad_pattern() {
+        let patterns = vec!["ok".to_string(), "(broken".to_string()];
+        let err = build_regex_from_patterns(&patterns, &Args::default()).unwrap_err();
+        assert!(matches!(err, MatError::InvalidRegex { pattern, .. } if pattern == "(broken"));
+    }
+
     fn create_test_doc() -> Document {
         let text = "apple\nbanana\ncherry\napricot\nblueberry\ncoconut\navocado";
         Document::from_text(text, "test.txt".to_string(), "UTF-8".to_string())
@@ -267,6 +485,8 @@ mod tests {
             pattern: Regex::new("a").unwrap(),
             before: 0,
             after: 0,
+            invert: false,
+            colors: GrepColors::default(),
         };
 
         let filtered = grep_filter(&doc, &options);
@@ -278,6 +498,33 @@ mod tests {
         assert_eq!(match_lines.len(), 4);
     }
 
+    #[test]
+    fn test_grep_stream_matches_grep_filter() {
+        let doc = create_test_doc();
+        let options = || GrepOptions {
+            pattern: Regex::new("^a").unwrap(),
+            before: 1,
+            after: 0,
+            invert: false,
+            colors: GrepColors::default(),
+        };
+        let expected: Vec<(usize, String)> = grep_filter(&doc, &options()).lines.iter().map(|l| (l.number, l.text())).collect();
+
+        // The lines arrive in two batches, below the first two
+        let head = Document {
+            lines: doc.lines[..2].to_vec(),
+            ..doc.clone()
+        };
+        let mut stream = GrepStream::new(options(), &head);
+        let mut kept = grep_filter(&head, &options()).lines;
+        kept.extend(stream.filter(doc.lines[2..4].to_vec()));
+        kept.extend(stream.filter(doc.lines[4..].to_vec()));
+        let kept: Vec<(usize, String)> = kept.iter().map(|l| (l.number, l.text())).collect();
+        assert_eq!(kept, expected);
+        // apple | separator, cherry, apricot | separator, coconut, avocado
+        assert_eq!(kept.iter().filter(|(number, _)| *number == 0).count(), 2);
+    }
+
     #[test]
     fn test_grep_with_context() {
         let doc = create_test_doc();
@@ -285,6 +532,8 @@ mod tests {
             pattern: Regex::new("cherry").unwrap(),
             before: 1,
             after: 1,
+            invert: false,
+            colors: GrepColors::default(),
         };
 
         let filtered = grep_filter(&doc, &options);
@@ -306,6 +555,8 @@ mod tests {
             pattern: Regex::new("^(apple|coconut)$").unwrap(),
             before: 0,
             after: 0,
+            invert: false,
+            colors: GrepColors::default(),
         };
 
         let filtered = grep_filter(&doc, &options);
@@ -315,6 +566,79 @@ mod tests {
         assert_eq!(filtered.lines[1].number, 0); // separator has number 0
     }
 
+    #[test]
+    fn test_grep_invert_match() {
+        let doc = create_test_doc();
+        let options = GrepOptions {
+            pattern: Regex::new("a").unwrap(),
+            before: 0,
+            after: 1,
+            invert: true,
+            colors: GrepColors::default(),
+        };
+
+        let mut filtered = grep_filter(&doc, &options);
+        apply_grep_highlight(&mut filtered, &options);
+
+        // cherry, blueberry and coconut have no "a"; apricot and avocado are context
+        let numbers: Vec<usize> = filtered.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![3, 4, 5, 6, 7]);
+        assert!(filtered.lines[0].is_match && filtered.lines[2].is_match && filtered.lines[3].is_match);
+        assert!(filtered.lines[1].is_context && filtered.lines[4].is_context);
+        assert_eq!(filtered.lines[0].spans.len(), 1);
+    }
+
+    #[test]
+    fn test_grep_keeps_whole_chunked_line() {
+        let long = format!("{}needle", "x".repeat(crate::display::LONG_LINE_CHUNK_SIZE));
+        let text = format!("before\n{}\nafter", long);
+        let doc = Document::from_text(&text, "test.txt".to_string(), "UTF-8".to_string());
+        let options = GrepOptions {
+            pattern: Regex::new("needle").unwrap(),
+            before: 0,
+            after: 0,
+            invert: false,
+            colors: GrepColors::default(),
+        };
+
+        let filtered = grep_filter(&doc, &options);
+
+        // Both chunks of line 2, none of the others
+        assert_eq!(filtered.lines.len(), 2);
+        assert!(filtered.lines.iter().all(|l| l.number == 2));
+        assert!(filtered.lines[1].continuation);
+    }
+
+    #[test]
+    fn test_grep_matches_across_chunks() {
+        let chunk = crate::display::LONG_LINE_CHUNK_SIZE;
+        let long = format!("{}needle{}", "x".repeat(chunk - 3), "x".repeat(10));
+        let text = format!("before\n{}\nxafter", long);
+        let doc = Document::from_text(&text, "test.txt".to_string(), "UTF-8".to_string());
+        let options = |pattern: &str| GrepOptions {
+            pattern: Regex::new(pattern).unwrap(),
+            before: 0,
+            after: 0,
+            invert: false,
+            colors: GrepColors::default(),
+        };
+
+        // "nee" ends the first chunk and "dle" starts the second
+        let mut filtered = grep_filter(&doc, &options("needle"));
+        assert_eq!(filtered.lines.len(), 2);
+        assert!(filtered.lines.iter().all(|l| l.number == 2 && l.is_match));
+        apply_grep_highlight(&mut filtered, &options("needle"));
+        let matched = GrepColors::default().matched;
+        assert_eq!(filtered.lines[0].spans.last().unwrap(), &StyledSpan::new("nee", matched.clone()));
+        assert_eq!(filtered.lines[1].spans[0], StyledSpan::new("dle", matched));
+
+        // `^` anchors at the start of the source line, not of its chunks
+        let filtered = grep_filter(&doc, &options("^x"));
+        let numbers: Vec<usize> = filtered.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![2, 2, 3]);
+        assert!(grep_filter(&doc, &options("^dle")).lines.is_empty());
+    }
+
     #[test]
     fn test_merge_ranges() {
         let ranges = vec![(0, 3), (2, 5), (7, 10)];
@@ -372,7 +696,7 @@ mod tests {
     fn test_highlight_matches() {
         let pattern = Regex::new("test").unwrap();
         let text = "this is a test string with test";
-        let spans = highlight_matches(text, &pattern);
+        let spans = highlight_matches(&[StyledSpan::plain(text)], &pattern, &GrepColors::default().matched);
 
         // Should have 5 spans: "this is a ", "test", " string with ", "test", ""
         // Actually the last "" won't be added since last_end == text.len()
@@ -391,7 +715,7 @@ mod tests {
     fn test_highlight_matches_at_start() {
         let pattern = Regex::new("hello").unwrap();
         let text = "hello world";
-        let spans = highlight_matches(text, &pattern);
+        let spans = highlight_matches(&[StyledSpan::plain(text)], &pattern, &GrepColors::default().matched);
 
         assert_eq!(spans.len(), 2);
         assert_eq!(spans[0].text, "hello");
@@ -402,10 +726,29 @@ mod tests {
     fn test_highlight_matches_at_end() {
         let pattern = Regex::new("world").unwrap();
         let text = "hello world";
-        let spans = highlight_matches(text, &pattern);
+        let spans = highlight_matches(&[StyledSpan::plain(text)], &pattern, &GrepColors::default().matched);
 
         assert_eq!(spans.len(), 2);
         assert_eq!(spans[0].text, "hello ");
         assert_eq!(spans[1].text, "world");
     }
+
+    #[test]
+    fn test_highlight_matches_keeps_syntax_spans() {
+        let keyword = SpanStyle::new().fg(ratatui::style::Color::Magenta);
+        let spans = vec![StyledSpan::new("fn", keyword.clone()), StyledSpan::plain(" main_loop()")];
+        let matched = GrepColors::default().matched;
+        let spans = highlight_matches(&spans, &Regex::new("n main").unwrap(), &matched);
+
+        let parts: Vec<(&str, &SpanStyle)> = spans.iter().map(|s| (s.text.as_str(), &s.style)).collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("f", &keyword),
+                ("n", &matched),
+                (" main", &matched),
+                ("_loop()", &SpanStyle::default()),
+            ]
+        );
+    }
 }
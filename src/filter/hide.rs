@@ -0,0 +1,54 @@
+use regex::Regex;
+
+use crate::display::Document;
+
+/// Remove the lines matching `pattern`, keeping everything else as is
+///
+/// A line split into continuation chunks is hidden as a whole when any part
+/// of it matches. Synthetic lines (separators, headers) are always kept.
+pub fn hide_matching(document: &Document, pattern: &Regex) -> Document {
+    let lines = &document.lines;
+    let mut result = Vec::with_capacity(lines.len());
+
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = start + 1;
+        while end < lines.len() && lines[end].continuation {
+            end += 1;
+        }
+
+        let group = &lines[start..end];
+        let text: String = group.iter().map(|l| l.text()).collect();
+        if group[0].number == 0 || !pattern.is_match(&text) {
+            result.extend_from_slice(group);
+        }
+        start = end;
+    }
+
+    let mut kept = Document {
+        lines: result,
+        max_line_width: 0,
+        source_name: document.source_name.clone(),
+        encoding: document.encoding.clone(),
+    };
+    kept.recalculate_max_width();
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hide_matching() {
+        let doc = Document::from_text(
+            "GET /health 200\nGET /api 500\nGET /health 200\nPOST /api 201",
+            "access.log".to_string(),
+            "UTF-8".to_string(),
+        );
+        let kept = hide_matching(&doc, &Regex::new("/health").unwrap());
+
+        let numbers: Vec<usize> = kept.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![2, 4]);
+    }
+}
@@ -1,3 +1,15 @@
+mod compare;
+mod extract;
 mod grep;
+mod hide;
+mod squeeze;
 
-pub use grep::{apply_grep_highlight, build_regex, build_regex_pattern, grep_filter, GrepOptions};
+pub(crate) use grep::merge_ranges;
+pub use grep::{
+    apply_grep_highlight, build_regex, build_regex_from_patterns, build_regex_pattern, grep_filter, pattern_file_patterns,
+    GrepOptions, GrepStream,
+};
+pub use compare::{annotate_lines, Baseline};
+pub use extract::extract_table;
+pub use hide::hide_matching;
+pub use squeeze::squeeze_repeated;
@@ -0,0 +1,96 @@
+use ratatui::style::Color;
+
+use crate::display::glyphs::glyphs;
+use crate::display::{Document, Line, SpanStyle, StyledSpan};
+
+/// Collapse runs of consecutive identical lines into their first line
+///
+/// The kept line gets a dimmed `(× N)` suffix with the run length. Lines split
+/// into continuation chunks are never merged.
+pub fn squeeze_repeated(document: &Document) -> Document {
+    let lines = &document.lines;
+    let mut result = Vec::with_capacity(lines.len());
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        if !is_single_chunk(lines, i) || line.number == 0 {
+            result.push(line.clone());
+            i += 1;
+            continue;
+        }
+
+        let text = line.text();
+        let mut run = 1;
+        while i + run < lines.len()
+            && is_single_chunk(lines, i + run)
+            && lines[i + run].number != 0
+            && lines[i + run].text() == text
+        {
+            run += 1;
+        }
+
+        let mut kept = line.clone();
+        if run > 1 {
+            kept.spans.push(repeat_span(run));
+        }
+        result.push(kept);
+        i += run;
+    }
+
+    let mut squeezed = Document {
+        lines: result,
+        max_line_width: 0,
+        source_name: document.source_name.clone(),
+        encoding: document.encoding.clone(),
+    };
+    squeezed.recalculate_max_width();
+    squeezed
+}
+
+/// Whether the line at `idx` is a whole source line (not split into chunks)
+fn is_single_chunk(lines: &[Line], idx: usize) -> bool {
+    !lines[idx].continuation && lines.get(idx + 1).map_or(true, |next| !next.continuation)
+}
+
+/// Suffix span showing how many times a line was repeated
+fn repeat_span(count: usize) -> StyledSpan {
+    StyledSpan::new(format!(" ({} {})", glyphs().times, count), SpanStyle::new().fg(Color::DarkGray))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_doc(text: &str) -> Document {
+        Document::from_text(text, "test.log".to_string(), "UTF-8".to_string())
+    }
+
+    #[test]
+    fn test_squeeze_runs() {
+        let doc = create_doc("start\nspam\nspam\nspam\nend\nend");
+        let squeezed = squeeze_repeated(&doc);
+
+        let texts: Vec<String> = squeezed.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["start", "spam (× 3)", "end (× 2)"]);
+        // Original line numbers are kept
+        let numbers: Vec<usize> = squeezed.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn test_squeeze_no_repeats() {
+        let doc = create_doc("a\nb\na");
+        let squeezed = squeeze_repeated(&doc);
+        assert_eq!(squeezed.line_count(), 3);
+        assert_eq!(squeezed.lines[2].text(), "a");
+    }
+
+    #[test]
+    fn test_squeeze_keeps_separators() {
+        let mut doc = create_doc("x\nx");
+        doc.lines.insert(1, Line::separator());
+        let squeezed = squeeze_repeated(&doc);
+        assert_eq!(squeezed.line_count(), 3);
+    }
+}
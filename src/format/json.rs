@@ -0,0 +1,110 @@
+//! Pretty-printing of JSON
+//!
+//! Valid JSON is re-indented as `jq` prints it: one member or element per
+//! line, two spaces per level, `"key": value`. The text is re-spaced rather
+//! than parsed into values and written back, so keys keep their order and
+//! numbers their exact digits. Empty objects and arrays stay `{}` and `[]`.
+
+use serde::de::IgnoredAny;
+
+/// Indentation added per nesting level
+const INDENT: &str = "  ";
+
+/// Whether `text` is a JSON object or array on a single line, as `curl` prints API responses
+pub fn is_single_line_json(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with(['{', '[']) && !text.contains('\n') && serde_json::from_str::<IgnoredAny>(text).is_ok()
+}
+
+/// Re-indent `text`, or `None` if it isn't a JSON object or array
+pub fn format_json(text: &str) -> Option<String> {
+    let text = text.trim();
+    if !text.starts_with(['{', '[']) || serde_json::from_str::<IgnoredAny>(text).is_err() {
+        return None;
+    }
+
+    let mut formatted = String::with_capacity(text.len() * 2);
+    let mut depth = 0usize;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                formatted.push(c);
+                while let Some(c) = chars.next() {
+                    formatted.push(c);
+                    match c {
+                        '\\' => formatted.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' | '[' => {
+                formatted.push(c);
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if let Some(close) = chars.next_if(|c| matches!(c, '}' | ']')) {
+                    formatted.push(close);
+                } else {
+                    depth += 1;
+                    new_line(&mut formatted, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                new_line(&mut formatted, depth);
+                formatted.push(c);
+            }
+            ',' => {
+                formatted.push(c);
+                new_line(&mut formatted, depth);
+            }
+            ':' => formatted.push_str(": "),
+            c if c.is_whitespace() => {}
+            c => formatted.push(c),
+        }
+    }
+    formatted.push('\n');
+    Some(formatted)
+}
+
+fn new_line(formatted: &mut String, depth: usize) {
+    formatted.push('\n');
+    formatted.push_str(&INDENT.repeat(depth));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_json() {
+        let text = r#"{"name":"mat","tags":["pager", "viewer"],"empty":{},"none":[ ],"quote":"a \"b\", {c}: d","size":1.50e3}"#;
+        assert!(is_single_line_json(text));
+        assert_eq!(
+            format_json(text).unwrap(),
+            concat!(
+                "{\n",
+                "  \"name\": \"mat\",\n",
+                "  \"tags\": [\n",
+                "    \"pager\",\n",
+                "    \"viewer\"\n",
+                "  ],\n",
+                "  \"empty\": {},\n",
+                "  \"none\": [],\n",
+                "  \"quote\": \"a \\\"b\\\", {c}: d\",\n",
+                "  \"size\": 1.50e3\n",
+                "}\n"
+            )
+        );
+        assert_eq!(format_json("[]\n").unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_not_json() {
+        assert_eq!(format_json("{\"open\": "), None);
+        assert_eq!(format_json("\"just a string\""), None);
+        assert_eq!(format_json("[1, 2] trailing"), None);
+        assert!(!is_single_line_json("{\n  \"a\": 1\n}"));
+        assert!(!is_single_line_json("[INFO] started"));
+    }
+}
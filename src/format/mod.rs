@@ -0,0 +1,44 @@
+//! Reformatting of machine-formatted input
+//!
+//! Markup and JSON are re-indented on request (`--pretty`), and so is JSON
+//! piped in on a single line; database result tables and CSV/TSV files are
+//! recognized and re-rendered automatically.
+
+mod json;
+mod table;
+mod xml;
+
+pub use json::{format_json, is_single_line_json};
+pub use table::{ColumnSort, Table, TableView};
+pub use xml::format_markup;
+
+/// Markup dialect of a file, from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    Xml,
+    /// HTML, where void elements never close and script/style content is raw text
+    Html,
+}
+
+impl Markup {
+    /// Dialect for a file extension (case-insensitive)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "html" | "htm" => Some(Markup::Html),
+            "xml" | "xhtml" | "svg" | "xsd" | "xsl" | "xslt" | "rss" | "atom" | "plist" => Some(Markup::Xml),
+            _ => None,
+        }
+    }
+
+    /// Dialect for input without an extension, recognized by its content
+    pub fn sniff(text: &str) -> Option<Self> {
+        let start: String = text.trim_start().chars().take(15).collect::<String>().to_ascii_lowercase();
+        if start.starts_with("<!doctype html") || start.starts_with("<html") {
+            Some(Markup::Html)
+        } else if start.starts_with("<?xml") {
+            Some(Markup::Xml)
+        } else {
+            None
+        }
+    }
+}
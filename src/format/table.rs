@@ -0,0 +1,434 @@
+//! Result tables printed by database command-line clients
+//!
+//! `psql` and `mysql` draw their results with ASCII borders:
+//!
+//! ```text
+//!  id | name        +----+------+
+//! ----+------       | id | name |
+//!   1 | Ada         +----+------+
+//! (1 row)           |  1 | Ada  |
+//!                   +----+------+
+//! ```
+//!
+//! The column boundaries are taken from the `+` of the border line, so cells
+//! containing `|` still split correctly. CSV and TSV files are read into the
+//! same model. A recognized table is re-rendered with aligned columns (numbers
+//! to the right) and a header the pager keeps pinned; the pager can sort its
+//! rows by a column and hide columns through a [`TableView`].
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use ratatui::style::Color;
+use unicode_width::UnicodeWidthStr;
+
+use crate::display::glyphs::glyphs;
+use crate::display::{Document, Line, SpanStyle, StyledSpan};
+
+/// A result table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Lines after the table, like `(3 rows)`
+    pub footer: Vec<String>,
+}
+
+impl Table {
+    /// Parse `text` if it is exactly one psql or mysql result table
+    pub fn parse(text: &str) -> Option<Self> {
+        let lines: Vec<&str> = text.lines().skip_while(|l| l.trim().is_empty()).collect();
+        let first = *lines.first()?;
+        if first.starts_with('+') {
+            parse_mysql(&lines)
+        } else {
+            parse_psql(&lines)
+        }
+    }
+
+    /// Parse CSV (`,`) or TSV (`\t`) text whose first record is the header
+    ///
+    /// Quoted fields may contain the delimiter, `""` for a quote and line
+    /// breaks, which are shown as spaces.
+    pub fn parse_delimited(text: &str, delimiter: char) -> Option<Self> {
+        let mut records = parse_records(text, delimiter).into_iter();
+        let header = records.next()?;
+        if header.len() < 2 {
+            return None;
+        }
+        Some(Table {
+            header,
+            rows: records.collect(),
+            footer: Vec::new(),
+        })
+    }
+
+    /// Display width of each column, hidden ones counting as 0
+    fn widths(&self, view: &TableView) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.header.iter().map(|h| UnicodeWidthStr::width(h.as_str())).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(UnicodeWidthStr::width(cell.as_str()));
+            }
+        }
+        for &hidden in &view.hidden {
+            if let Some(width) = widths.get_mut(hidden) {
+                *width = 0;
+            }
+        }
+        widths
+    }
+
+    /// Indices of the columns `view` shows
+    pub fn visible_columns(&self, view: &TableView) -> Vec<usize> {
+        (0..self.header.len()).filter(|i| !view.hidden.contains(i)).collect()
+    }
+
+    /// Indices of the rows in the order `view` sorts them
+    fn row_order(&self, view: &TableView) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        let Some(sort) = view.sort else {
+            return order;
+        };
+        let cell = |row: usize| self.rows[row].get(sort.column).map_or("", String::as_str);
+        if self.is_numeric(sort.column) {
+            // NULL and empty cells sort last either way
+            let value = |row: usize| cell(row).parse::<f64>().ok();
+            order.sort_by(|&a, &b| match (value(a), value(b)) {
+                (Some(x), Some(y)) if sort.descending => y.total_cmp(&x),
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+        } else if sort.descending {
+            order.sort_by(|&a, &b| cell(b).cmp(cell(a)));
+        } else {
+            order.sort_by(|&a, &b| cell(a).cmp(cell(b)));
+        }
+        order
+    }
+
+    /// Whether every value of column `i` is a number (`NULL` and empty cells aside)
+    fn is_numeric(&self, i: usize) -> bool {
+        let mut values = self
+            .rows
+            .iter()
+            .filter_map(|row| row.get(i))
+            .filter(|cell| !cell.is_empty() && *cell != "NULL")
+            .peekable();
+        values.peek().is_some() && values.all(|cell| cell.parse::<f64>().is_ok())
+    }
+
+    /// Screen columns where each visible table column starts and ends in the rendered table
+    pub fn column_spans(&self, view: &TableView) -> Vec<(usize, usize)> {
+        let separator_width = UnicodeWidthStr::width(column_separator().as_str());
+        let widths = self.widths(view);
+        let mut spans = Vec::with_capacity(self.header.len());
+        let mut col = 0;
+        for i in self.visible_columns(view) {
+            spans.push((col, col + widths[i]));
+            col += widths[i] + separator_width;
+        }
+        spans
+    }
+
+    /// Render as a document: header, rule, one line per row numbered from 1, footer
+    pub fn to_document(&self, source_name: String, encoding: String) -> Document {
+        self.render(&TableView::default(), source_name, encoding)
+    }
+
+    /// Render with the rows sorted and columns hidden as `view` says
+    ///
+    /// Rows keep the number of their position in the input, so references to
+    /// them stay valid whatever the order.
+    pub fn render(&self, view: &TableView, source_name: String, encoding: String) -> Document {
+        let widths = self.widths(view);
+        let columns = self.visible_columns(view);
+        let numeric: Vec<bool> = (0..widths.len()).map(|i| self.is_numeric(i)).collect();
+        let separator = column_separator();
+        let format_row = |cells: &[String]| {
+            let padded: Vec<String> = columns
+                .iter()
+                .map(|&i| {
+                    let width = widths[i];
+                    let cell = cells.get(i).map_or("", String::as_str);
+                    let pad = " ".repeat(width.saturating_sub(UnicodeWidthStr::width(cell)));
+                    if numeric[i] {
+                        format!("{}{}", pad, cell)
+                    } else {
+                        format!("{}{}", cell, pad)
+                    }
+                })
+                .collect();
+            padded.join(&separator).trim_end().to_string()
+        };
+        let g = glyphs();
+        let rule: Vec<String> = columns.iter().map(|&i| g.horizontal.repeat(widths[i])).collect();
+        let dim = SpanStyle::new().fg(Color::DarkGray);
+
+        let mut lines = vec![
+            synthetic_line(format_row(&self.header), SpanStyle::new().bold()),
+            synthetic_line(rule.join(&format!("{0}{1}{0}", g.horizontal, g.cross)), dim.clone()),
+        ];
+        lines.extend(self.row_order(view).into_iter().map(|i| Line::plain(i + 1, &format_row(&self.rows[i]))));
+        lines.extend(self.footer.iter().map(|footer| synthetic_line(footer.clone(), dim.clone())));
+
+        let mut document = Document {
+            lines,
+            max_line_width: 0,
+            source_name,
+            encoding,
+        };
+        document.recalculate_max_width();
+        document
+    }
+}
+
+/// Column a table is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// How a table is shown in the pager: row order and hidden columns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableView {
+    /// Column the rows are sorted by (input order when `None`)
+    pub sort: Option<ColumnSort>,
+    /// Indices of the hidden columns
+    pub hidden: BTreeSet<usize>,
+}
+
+/// Separator between rendered columns
+fn column_separator() -> String {
+    format!(" {} ", glyphs().vertical)
+}
+
+/// An unnumbered line (header, rule, footer)
+fn synthetic_line(text: String, style: SpanStyle) -> Line {
+    Line {
+        number: 0,
+        spans: vec![StyledSpan::new(text, style)],
+        is_match: false,
+        is_context: false,
+        continuation: false,
+        heading: None,
+    }
+}
+
+/// Positions of the `+` in a border line made of `-` and `+`
+fn border_joints(line: &str) -> Option<Vec<usize>> {
+    let line = line.trim_end();
+    if line.len() < 3 || !line.contains('-') || !line.chars().all(|c| c == '-' || c == '+') {
+        return None;
+    }
+    Some(line.char_indices().filter(|&(_, c)| c == '+').map(|(i, _)| i).collect())
+}
+
+/// Cells of `line` split at `boundaries`, which must all hold a `|`
+///
+/// Text before the first and after the last boundary are cells too, unless the
+/// boundaries include the edges (mysql draws outer borders).
+fn split_cells(line: &str, boundaries: &[usize], outer: bool) -> Option<Vec<String>> {
+    let chars: Vec<char> = line.trim_end().chars().collect();
+    if boundaries.iter().any(|&b| chars.get(b) != Some(&'|')) {
+        return None;
+    }
+    let mut cells = Vec::with_capacity(boundaries.len() + 1);
+    let mut start = 0;
+    for &boundary in boundaries {
+        cells.push(chars[start..boundary.min(chars.len())].iter().collect::<String>());
+        start = boundary + 1;
+    }
+    cells.push(chars[start.min(chars.len())..].iter().collect());
+    if outer {
+        // Drop the empty text outside the outer borders
+        cells.remove(0);
+        cells.pop();
+    }
+    Some(cells.into_iter().map(|cell| cell.trim().to_string()).collect())
+}
+
+/// Records of delimited text, skipping blank lines
+fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\r' | '\n' if quoted => {
+                if c == '\n' {
+                    field.push(' ');
+                }
+            }
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            c if c == delimiter && !quoted => record.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.len() > 1 || !record[0].is_empty() {
+        records.push(record);
+    }
+    records
+}
+
+/// psql: header, border, rows, optional `(N rows)`
+fn parse_psql(lines: &[&str]) -> Option<Table> {
+    let boundaries = border_joints(lines.get(1)?)?;
+    let header = split_cells(lines[0], &boundaries, false)?;
+
+    let mut rows = Vec::new();
+    let mut footer = Vec::new();
+    for line in &lines[2..] {
+        if !footer.is_empty() || line.trim().is_empty() {
+            if !line.trim().is_empty() {
+                return None;
+            }
+            continue;
+        }
+        if is_psql_footer(line) {
+            footer.push(line.trim().to_string());
+            continue;
+        }
+        rows.push(split_cells(line, &boundaries, false)?);
+    }
+    // A single column has no `|` to recognize it by
+    if boundaries.is_empty() && footer.is_empty() {
+        return None;
+    }
+    Some(Table { header, rows, footer })
+}
+
+/// `(3 rows)` or `(1 row)`
+fn is_psql_footer(line: &str) -> bool {
+    line.trim()
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(" rows)").or_else(|| rest.strip_suffix(" row)")))
+        .is_some_and(|count| count.parse::<usize>().is_ok())
+}
+
+/// mysql: border, header, border, rows, border, optional `N rows in set`
+fn parse_mysql(lines: &[&str]) -> Option<Table> {
+    let border = lines[0].trim_end();
+    let boundaries = border_joints(border)?;
+    if boundaries.first() != Some(&0) || boundaries.len() < 2 {
+        return None;
+    }
+    let header = split_cells(lines.get(1)?, &boundaries, true)?;
+    if lines.get(2)?.trim_end() != border {
+        return None;
+    }
+
+    let mut rows = Vec::new();
+    let mut end = None;
+    for (i, line) in lines.iter().enumerate().skip(3) {
+        if line.trim_end() == border {
+            end = Some(i);
+            break;
+        }
+        rows.push(split_cells(line, &boundaries, true)?);
+    }
+    let mut footer = Vec::new();
+    for line in &lines[end? + 1..] {
+        if line.contains(" in set") {
+            footer.push(line.trim().to_string());
+        } else if !line.trim().is_empty() {
+            return None;
+        }
+    }
+    Some(Table { header, rows, footer })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_psql() {
+        let text = " id | name | note\n----+------+------\n  1 | Ada  | a|b\n 12 | Bob  |\n(2 rows)\n\n";
+        let table = Table::parse(text).unwrap();
+        assert_eq!(table.header, cells(&["id", "name", "note"]));
+        assert_eq!(table.rows, vec![cells(&["1", "Ada", "a|b"]), cells(&["12", "Bob", ""])]);
+        assert_eq!(table.footer, cells(&["(2 rows)"]));
+    }
+
+    #[test]
+    fn test_parse_mysql() {
+        let text = "+----+------+\n| id | name |\n+----+------+\n|  1 | Ada  |\n+----+------+\n1 row in set (0.00 sec)\n";
+        let table = Table::parse(text).unwrap();
+        assert_eq!(table.header, cells(&["id", "name"]));
+        assert_eq!(table.rows, vec![cells(&["1", "Ada"])]);
+        assert_eq!(table.footer, cells(&["1 row in set (0.00 sec)"]));
+    }
+
+    #[test]
+    fn test_other_text_is_not_a_table() {
+        assert_eq!(Table::parse("a | b\nplain text\n"), None);
+        assert_eq!(Table::parse("title\n-----\nbody\n"), None);
+        assert_eq!(Table::parse(" a | b\n---+---\n 1 | 2\n(1 row)\nmore output\n"), None);
+        assert_eq!(Table::parse("+---+\n| a |\n+---+\n| 1 |\n"), None);
+    }
+
+    #[test]
+    fn test_render_table() {
+        let table = Table::parse(" id | name\n----+------\n  1 | Ada\n 12 | Bob\n(2 rows)").unwrap();
+        let document = table.to_document("stdin".to_string(), "UTF-8".to_string());
+        let texts: Vec<String> = document.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["id │ name", "───┼─────", " 1 │ Ada", "12 │ Bob", "(2 rows)"]);
+        assert_eq!(document.lines.iter().map(|l| l.number).collect::<Vec<_>>(), vec![0, 0, 1, 2, 0]);
+        assert_eq!(table.column_spans(&TableView::default()), vec![(0, 2), (5, 9)]);
+    }
+
+    #[test]
+    fn test_parse_delimited() {
+        let table = Table::parse_delimited("name,note\r\nAda,\"a, \"\"b\"\"\"\n\nBob,\"two\nlines\"\n", ',').unwrap();
+        assert_eq!(table.header, cells(&["name", "note"]));
+        assert_eq!(table.rows, vec![cells(&["Ada", "a, \"b\""]), cells(&["Bob", "two lines"])]);
+        assert_eq!(Table::parse_delimited("a\tb\n1\t2", '\t').unwrap().rows, vec![cells(&["1", "2"])]);
+        assert_eq!(Table::parse_delimited("just text\n", ','), None);
+    }
+
+    #[test]
+    fn test_render_sorted_and_hidden() {
+        let table = Table::parse_delimited("name,age\nBob,9\nAda,36\nEve,\nCy,100", ',').unwrap();
+        let texts = |view: &TableView| -> Vec<String> {
+            let document = table.render(view, "t.csv".to_string(), "UTF-8".to_string());
+            document.lines[2..].iter().map(|l| format!("{}:{}", l.number, l.text())).collect()
+        };
+
+        // Numbers sort by value, empty cells last
+        let mut view = TableView {
+            sort: Some(ColumnSort { column: 1, descending: false }),
+            ..TableView::default()
+        };
+        assert_eq!(texts(&view), vec!["1:Bob  │   9", "2:Ada  │  36", "4:Cy   │ 100", "3:Eve  │"]);
+        view.sort = Some(ColumnSort { column: 0, descending: true });
+        assert_eq!(texts(&view), vec!["3:Eve  │", "4:Cy   │ 100", "1:Bob  │   9", "2:Ada  │  36"]);
+
+        view.hidden.insert(0);
+        assert_eq!(texts(&view)[0], "3:");
+        assert_eq!(table.column_spans(&view), vec![(0, 3)]);
+    }
+}
@@ -0,0 +1,274 @@
+//! Re-indenting of XML and HTML
+//!
+//! The markup is split into tags, text and other constructs (comments, CDATA,
+//! declarations) without building a tree, then written one construct per line,
+//! indented by element depth. An element holding only a short text stays on one
+//! line (`<name>Ada</name>`). Malformed markup never fails: unterminated
+//! constructs are kept as text and stray closing tags just stop dedenting at 0.
+
+use super::Markup;
+
+/// Indentation added per nesting level
+const INDENT: &str = "  ";
+
+/// HTML elements without a closing tag
+const HTML_VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// HTML elements whose content is kept verbatim
+const HTML_RAW: &[&str] = &["script", "style", "pre", "textarea"];
+
+/// A piece of markup
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    /// Opening tag
+    Open(&'a str),
+    /// Closing tag
+    Close(&'a str),
+    /// Self-closing or void tag, comment, CDATA, doctype or processing instruction
+    Leaf(&'a str),
+    /// Text between tags
+    Text(&'a str),
+    /// Content of an HTML raw text element, kept as is
+    Raw(&'a str),
+}
+
+/// Re-indent `text` as `markup`
+pub fn format_markup(text: &str, markup: Markup) -> String {
+    let tokens = tokenize(text, markup);
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let indent = INDENT.repeat(depth);
+        match tokens[i] {
+            Token::Open(tag) => {
+                // Keep `<a>text</a>` and `<a></a>` on one line
+                match (tokens.get(i + 1), tokens.get(i + 2)) {
+                    (Some(Token::Text(inner)), Some(Token::Close(close))) if !inner.trim().contains('\n') => {
+                        lines.push(format!("{}{}{}{}", indent, one_line(tag), inner.trim(), one_line(close)));
+                        i += 3;
+                        continue;
+                    }
+                    (Some(Token::Close(close)), _) => {
+                        lines.push(format!("{}{}{}", indent, one_line(tag), one_line(close)));
+                        i += 2;
+                        continue;
+                    }
+                    _ => {}
+                }
+                lines.push(format!("{}{}", indent, one_line(tag)));
+                depth += 1;
+            }
+            Token::Close(tag) => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!("{}{}", INDENT.repeat(depth), one_line(tag)));
+            }
+            Token::Leaf(leaf) => {
+                // Comments may span lines; only the first one is re-indented
+                let mut leaf_lines = leaf.lines();
+                if let Some(first) = leaf_lines.next() {
+                    lines.push(format!("{}{}", indent, first));
+                }
+                lines.extend(leaf_lines.map(str::to_string));
+            }
+            Token::Text(text) => {
+                for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    lines.push(format!("{}{}", indent, line));
+                }
+            }
+            Token::Raw(raw) => {
+                let raw = raw.trim_matches(|c| c == '\n' || c == '\r');
+                if !raw.trim().is_empty() {
+                    lines.extend(raw.lines().map(str::to_string));
+                }
+            }
+        }
+        i += 1;
+    }
+
+    let mut formatted = lines.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+/// A tag with its line breaks (between attributes) replaced by spaces
+fn one_line(tag: &str) -> String {
+    if !tag.contains('\n') {
+        return tag.to_string();
+    }
+    tag.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Split markup into tokens
+fn tokenize(text: &str, markup: Markup) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    // Start of the text run before the next construct
+    let mut text_start = 0;
+    let mut pos = 0;
+
+    while let Some(offset) = text[pos..].find('<') {
+        let start = pos + offset;
+        // A `<` that starts no construct (`a < b`) is text
+        let Some((token, len)) = markup_token(&text[start..]) else {
+            pos = start + 1;
+            continue;
+        };
+        if start > text_start {
+            tokens.push(Token::Text(&text[text_start..start]));
+        }
+        pos = start + len;
+
+        match token {
+            Token::Open(tag) if markup == Markup::Html => {
+                let name = tag_name(tag).to_ascii_lowercase();
+                if HTML_VOID.contains(&name.as_str()) {
+                    tokens.push(Token::Leaf(tag));
+                } else if HTML_RAW.contains(&name.as_str()) {
+                    // Everything up to the matching closing tag is content
+                    let rest = &text[pos..];
+                    let end = find_ignore_case(rest, &format!("</{}", name)).unwrap_or(rest.len());
+                    tokens.push(Token::Open(tag));
+                    tokens.push(Token::Raw(&rest[..end]));
+                    pos += end;
+                } else {
+                    tokens.push(Token::Open(tag));
+                }
+            }
+            token => tokens.push(token),
+        }
+        text_start = pos;
+    }
+    if text_start < text.len() {
+        tokens.push(Token::Text(&text[text_start..]));
+    }
+    tokens
+}
+
+/// The construct at the start of `text` (which starts with `<`) and its length
+fn markup_token(text: &str) -> Option<(Token<'_>, usize)> {
+    let delimited = |open: &str, close: &str| {
+        text.strip_prefix(open)?;
+        let end = text[open.len()..].find(close)? + open.len() + close.len();
+        Some((Token::Leaf(&text[..end]), end))
+    };
+
+    if text.starts_with("<!--") {
+        delimited("<!--", "-->")
+    } else if text.starts_with("<![CDATA[") {
+        delimited("<![CDATA[", "]]>")
+    } else if text.starts_with("<?") {
+        delimited("<?", "?>")
+    } else if text.starts_with("<!") {
+        delimited("<!", ">")
+    } else if text.starts_with("</") {
+        let end = text.find('>')? + 1;
+        Some((Token::Close(&text[..end]), end))
+    } else if text[1..].starts_with(|c: char| c.is_alphabetic() || c == '_' || c == ':') {
+        let end = tag_end(text)?;
+        let tag = &text[..end];
+        let token = if tag.ends_with("/>") {
+            Token::Leaf(tag)
+        } else {
+            Token::Open(tag)
+        };
+        Some((token, end))
+    } else {
+        None
+    }
+}
+
+/// Length of the tag at the start of `text`, skipping `>` in quoted attribute values
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Element name of an opening tag
+fn tag_name(tag: &str) -> &str {
+    let name = &tag[1..];
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+/// Byte offset of `needle` (ASCII) in `haystack`, ignoring ASCII case
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_minified_xml() {
+        let xml = r#"<?xml version="1.0"?><people><person id="1" note="a>b"><name>Ada</name><tags/></person><!-- end --></people>"#;
+        assert_eq!(
+            format_markup(xml, Markup::Xml),
+            concat!(
+                "<?xml version=\"1.0\"?>\n",
+                "<people>\n",
+                "  <person id=\"1\" note=\"a>b\">\n",
+                "    <name>Ada</name>\n",
+                "    <tags/>\n",
+                "  </person>\n",
+                "  <!-- end -->\n",
+                "</people>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_html() {
+        let html = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><script>if (a < b) { go(); }</script></head>\
+                    <body><p>Hello <b>world</b></p><br></body></html>";
+        assert_eq!(
+            format_markup(html, Markup::Html),
+            concat!(
+                "<!DOCTYPE html>\n",
+                "<html>\n",
+                "  <head>\n",
+                "    <meta charset=\"utf-8\">\n",
+                "    <script>\n",
+                "if (a < b) { go(); }\n",
+                "    </script>\n",
+                "  </head>\n",
+                "  <body>\n",
+                "    <p>\n",
+                "      Hello\n",
+                "      <b>world</b>\n",
+                "    </p>\n",
+                "    <br>\n",
+                "  </body>\n",
+                "</html>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_malformed_markup_is_kept() {
+        assert_eq!(format_markup("a < b</x></y><unclosed", Markup::Xml), "a < b\n</x>\n</y>\n<unclosed\n");
+    }
+
+    #[test]
+    fn test_sniff_markup() {
+        assert_eq!(Markup::sniff("  <?xml version=\"1.0\"?><a/>"), Some(Markup::Xml));
+        assert_eq!(Markup::sniff("<!DOCTYPE html>\n<html>"), Some(Markup::Html));
+        assert_eq!(Markup::sniff("{\"json\": true}"), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Word-level emphasis in diffs
+//!
+//! In a diff (a `.diff` or `.patch` file, `-l diff`, or input that starts
+//! like one, such as `git diff | mat`), each run of removed lines in a hunk is
+//! paired line by line with the run of added lines after it, and within a
+//! pair only the words that changed get a stronger background, the way delta
+//! shows them. Pairs with too little in common are left alone: emphasizing
+//! nearly every word would show nothing.
+
+use std::ops::Range;
+
+use ratatui::style::Color;
+
+use crate::display::Document;
+use crate::theme::Theme;
+
+use super::paths::restyle_range;
+
+/// Pairs with more tokens than this (multiplied) aren't compared, to bound the work
+const MAX_COMPARED: usize = 250_000;
+
+/// Share of a pair's text that must be unchanged for its changes to be emphasized
+const MIN_SIMILARITY: f64 = 0.4;
+
+/// Whether `document` starts the way a diff does
+pub fn looks_like_diff(document: &Document) -> bool {
+    let mut lines = document.lines.iter().map(|line| line.text());
+    let Some(first) = lines.next() else {
+        return false;
+    };
+    first.starts_with("diff ")
+        || first.starts_with("Index: ")
+        || (first.starts_with("--- ") && lines.next().is_some_and(|second| second.starts_with("+++ ")))
+}
+
+/// Emphasize the changed words of paired removed and added lines
+pub fn apply_word_diff(document: &mut Document, theme: Theme) {
+    let (removed_bg, added_bg) = if theme.is_light() {
+        (Color::Rgb(255, 180, 180), Color::Rgb(170, 235, 170))
+    } else {
+        (Color::Rgb(120, 30, 30), Color::Rgb(30, 100, 30))
+    };
+    for (old, new) in paired_lines(document) {
+        let old_text = document.lines[old].text();
+        let new_text = document.lines[new].text();
+        let Some((old_ranges, new_ranges)) = changed_ranges(&old_text[1..], &new_text[1..]) else {
+            continue;
+        };
+        for (idx, ranges, bg) in [(old, old_ranges, removed_bg), (new, new_ranges, added_bg)] {
+            let line = &mut document.lines[idx];
+            for range in ranges {
+                // Past the leading - or +
+                let range = range.start + 1..range.end + 1;
+                line.spans = restyle_range(&line.spans, &range, |style| style.clone().bg(bg));
+            }
+        }
+    }
+}
+
+/// Indices of removed and added lines paired up, hunk by hunk
+fn paired_lines(document: &Document) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let mut in_hunk = false;
+    let mut removed: Vec<usize> = Vec::new();
+    let mut added: Vec<usize> = Vec::new();
+    for (idx, line) in document.lines.iter().enumerate() {
+        let text = line.text();
+        let starts_run = text.starts_with('-') && !added.is_empty();
+        if !text.starts_with(['-', '+']) || starts_run {
+            pairs.extend(removed.drain(..).zip(added.drain(..)));
+        }
+        if text.starts_with("@@") {
+            in_hunk = true;
+        } else if text.starts_with("diff ") {
+            in_hunk = false;
+        } else if !in_hunk {
+            continue;
+        } else if text.starts_with("+++ ") && removed.len() == 1 && document.lines[removed[0]].text().starts_with("--- ") {
+            // The file names of the next file's diff
+            removed.clear();
+            in_hunk = false;
+        } else if text.starts_with('-') {
+            removed.push(idx);
+        } else if text.starts_with('+') {
+            added.push(idx);
+        }
+    }
+    pairs.extend(removed.into_iter().zip(added));
+    pairs
+}
+
+/// Byte ranges of `text` split into words, runs of whitespace, and single other characters
+fn tokens(text: &str) -> Vec<Range<usize>> {
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            1
+        } else if c.is_whitespace() {
+            2
+        } else {
+            0
+        }
+    };
+    let mut previous = None;
+    for (i, c) in text.char_indices() {
+        let kind = class(c);
+        match tokens.last_mut() {
+            Some(last) if kind != 0 && previous == Some(kind) => last.end = i + c.len_utf8(),
+            _ => tokens.push(i..i + c.len_utf8()),
+        }
+        previous = Some(kind);
+    }
+    tokens
+}
+
+/// Byte ranges of changed words in the old and in the new line
+type ChangedRanges = (Vec<Range<usize>>, Vec<Range<usize>>);
+
+/// Byte ranges of the words that differ between `old` and `new`, unless they have too little in common
+fn changed_ranges(old: &str, new: &str) -> Option<ChangedRanges> {
+    let old_tokens = tokens(old);
+    let new_tokens = tokens(new);
+    if old_tokens.len() * new_tokens.len() > MAX_COMPARED {
+        return None;
+    }
+    let (old_kept, new_kept) = common_tokens(old, &old_tokens, new, &new_tokens);
+
+    let unchanged: usize = old_tokens.iter().zip(&old_kept).filter(|(_, kept)| **kept).map(|(t, _)| t.len()).sum();
+    let total = old.len().max(new.len());
+    if total == 0 || (unchanged as f64) < MIN_SIMILARITY * total as f64 {
+        return None;
+    }
+    Some((merge_changed(old, &old_tokens, &old_kept), merge_changed(new, &new_tokens, &new_kept)))
+}
+
+/// Which tokens of each side are part of their longest common subsequence
+fn common_tokens(old: &str, old_tokens: &[Range<usize>], new: &str, new_tokens: &[Range<usize>]) -> (Vec<bool>, Vec<bool>) {
+    let (n, m) = (old_tokens.len(), new_tokens.len());
+    // lengths[i][j]: longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut old_kept = vec![false; n];
+    let mut new_kept = vec![false; m];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+            old_kept[i] = true;
+            new_kept[j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_kept, new_kept)
+}
+
+/// Ranges of the changed tokens, joined across the whitespace between them
+fn merge_changed(text: &str, tokens: &[Range<usize>], kept: &[bool]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if kept[i] {
+            continue;
+        }
+        let bridged = ranges.last().is_some_and(|last| {
+            last.end == token.start || (i >= 1 && last.end == tokens[i - 1].start && text[tokens[i - 1].clone()].trim().is_empty())
+        });
+        match ranges.last_mut() {
+            Some(last) if bridged => last.end = token.end,
+            _ => ranges.push(token.clone()),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emphasized(document: &Document, idx: usize) -> Vec<String> {
+        document.lines[idx].spans.iter().filter(|s| s.style.bg.is_some()).map(|s| s.text.clone()).collect()
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let text = "diff --git a/x.rs b/x.rs\n--- a/x.rs\n+++ b/x.rs\n@@ -1,3 +1,3 @@\n let total = compute(a, b);\n-let timeout = Duration::from_secs(30);\n+let timeout = Duration::from_millis(300);\n-unrelated\n+completely different text\n";
+        let mut doc = Document::from_text(text, "x.diff".to_string(), "UTF-8".to_string());
+        assert!(looks_like_diff(&doc));
+        apply_word_diff(&mut doc, Theme::Dark);
+
+        assert_eq!(emphasized(&doc, 5), vec!["from_secs", "30"]);
+        assert_eq!(emphasized(&doc, 6), vec!["from_millis", "300"]);
+        // Too different to emphasize anything
+        assert!(emphasized(&doc, 7).is_empty());
+        assert!(emphasized(&doc, 8).is_empty());
+        // Headers and context are left alone
+        assert!((0..5).all(|i| emphasized(&doc, i).is_empty()));
+        assert_eq!(doc.lines[6].text(), "+let timeout = Duration::from_millis(300);");
+    }
+
+    #[test]
+    fn test_changed_words_join_across_spaces() {
+        let (old, new) = changed_ranges("set the old value here", "set a new value here").unwrap();
+        assert_eq!(old, vec![4..11]);
+        assert_eq!(new, vec![4..9]);
+    }
+
+    #[test]
+    fn test_looks_like_diff() {
+        let doc = |text: &str| Document::from_text(text, "stdin".to_string(), "UTF-8".to_string());
+        assert!(looks_like_diff(&doc("--- a\n+++ b\n@@ -1 +1 @@\n")));
+        assert!(!looks_like_diff(&doc("--- a list\n- item\n")));
+        assert!(!looks_like_diff(&doc("")));
+    }
+}
@@ -0,0 +1,171 @@
+//! `linguist-language` overrides from `.gitattributes`
+//!
+//! Repositories pin the language of files with unusual extensions the way
+//! GitHub's linguist reads it, e.g. `*.inc linguist-language=PHP`. The
+//! `.gitattributes` files from the repository root down to the file's directory
+//! are read in that order, followed by `.git/info/attributes`, and the last
+//! matching line wins, as in git.
+
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+/// Linguist names that differ from the syntax names of the bundled syntaxes
+const LINGUIST_ALIASES: &[(&str, &str)] = &[("Shell", "Bash"), ("Dotenv", "Bash"), ("Vim-Script", "VimL")];
+
+/// Language set by `linguist-language` for `path`, mapped to a syntax name where they differ
+pub fn linguist_language(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let root = path.ancestors().skip(1).find(|dir| dir.join(".git").exists())?;
+    let relative = path.strip_prefix(root).ok()?;
+
+    let mut language = None;
+    for (dir, file) in attribute_files(root, relative) {
+        let Ok(text) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        let Ok(below) = relative.strip_prefix(&dir) else {
+            continue;
+        };
+        let below = below.to_string_lossy().replace('\\', "/");
+        if let Some(found) = language_in(&text, &below) {
+            language = Some(found);
+        }
+    }
+
+    language.map(|name| match LINGUIST_ALIASES.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(&name)) {
+        Some((_, syntax)) => syntax.to_string(),
+        None => name,
+    })
+}
+
+/// Attribute files that apply to `relative`, each with the directory its patterns are relative to
+fn attribute_files(root: &Path, relative: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut files: Vec<(PathBuf, PathBuf)> = relative
+        .ancestors()
+        .skip(1)
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(|dir| (dir.clone(), root.join(&dir).join(".gitattributes")))
+        .collect();
+    files.push((PathBuf::new(), root.join(".git").join("info").join("attributes")));
+    files
+}
+
+/// Language of the last line of an attributes file that matches `path` and sets it
+fn language_in(text: &str, path: &str) -> Option<String> {
+    let mut language = None;
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next().filter(|p| !p.starts_with('#')) else {
+            continue;
+        };
+        let value = fields.rev().find_map(|attr| attr.strip_prefix("linguist-language="));
+        if let Some(value) = value {
+            if pattern_matches(pattern, path) {
+                language = Some(value.to_string());
+            }
+        }
+    }
+    language
+}
+
+/// Whether a gitattributes pattern matches `path` (relative, `/`-separated)
+///
+/// A pattern without a slash matches the file name at any depth; one with a
+/// slash is matched against the whole path.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    let target = if anchored {
+        path
+    } else {
+        path.rsplit('/').next().unwrap_or(path)
+    };
+    glob_regex(pattern).is_some_and(|re| re.is_match(target))
+}
+
+/// Anchored regex for a glob with `*`, `?`, `**` and `[...]`
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let mut re = String::from("^");
+    let chars: Vec<char> = glob.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                // `**/` spans any number of directories, a trailing `**` everything
+                if chars.get(i + 2) == Some(&'/') {
+                    re.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    re.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            '[' => {
+                let end = chars[i + 1..].iter().position(|&c| c == ']')? + i + 1;
+                let class: String = chars[i + 1..end].iter().collect();
+                let class = class.strip_prefix('!').map_or(class.clone(), |rest| format!("^{}", rest));
+                re.push('[');
+                re.push_str(&class.replace('\\', "\\\\"));
+                re.push(']');
+                i = end;
+            }
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    re.push('$');
+    Regex::new(&re).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("*.inc", "lib/db.inc"));
+        assert!(!pattern_matches("*.inc", "lib/db.include"));
+        assert!(pattern_matches("/scripts/*", "scripts/build"));
+        assert!(!pattern_matches("/scripts/*", "tools/scripts/build"));
+        assert!(pattern_matches("docs/**/*.txt", "docs/a/b/notes.txt"));
+        assert!(pattern_matches("docs/**/*.txt", "docs/notes.txt"));
+        assert!(pattern_matches("file[0-9].x", "file3.x"));
+        assert!(!pattern_matches("file[!0-9].x", "file3.x"));
+    }
+
+    #[test]
+    fn test_last_match_wins() {
+        let text = "# comment\n*.inc linguist-language=PHP\nvendor/*.inc linguist-vendored\n\
+                    special.inc linguist-language=SQL\n";
+        assert_eq!(language_in(text, "lib/db.inc"), Some("PHP".to_string()));
+        assert_eq!(language_in(text, "special.inc"), Some("SQL".to_string()));
+        assert_eq!(language_in(text, "main.rs"), None);
+    }
+
+    #[test]
+    fn test_linguist_language_in_repo() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join(".git")).unwrap();
+        std::fs::create_dir_all(repo.path().join("build")).unwrap();
+        std::fs::write(repo.path().join(".gitattributes"), "*.tpl linguist-language=Shell\n").unwrap();
+        std::fs::write(repo.path().join("build/.gitattributes"), "deploy.tpl linguist-language=Python\n").unwrap();
+        std::fs::write(repo.path().join("build/run.tpl"), "echo").unwrap();
+        std::fs::write(repo.path().join("build/deploy.tpl"), "print()").unwrap();
+
+        assert_eq!(linguist_language(&repo.path().join("build/run.tpl")), Some("Bash".to_string()));
+        assert_eq!(linguist_language(&repo.path().join("build/deploy.tpl")), Some("Python".to_string()));
+
+        // Outside a repository there is nothing to consult
+        let plain = tempfile::tempdir().unwrap();
+        std::fs::write(plain.path().join(".gitattributes"), "*.tpl linguist-language=Shell\n").unwrap();
+        std::fs::write(plain.path().join("run.tpl"), "echo").unwrap();
+        assert_eq!(linguist_language(&plain.path().join("run.tpl")), None);
+    }
+}
@@ -0,0 +1,315 @@
+//! Highlighting of code embedded in other languages
+//!
+//! An injection rule picks regions out of the lines of a host language, such as
+//! SQL in a string literal or the shell command of a Dockerfile `RUN`, and
+//! highlights them with another syntax on top of the syntect pass over the file.
+//! Rules from `[[injection]]` tables of the configuration file are tried before
+//! the built-in ones.
+//!
+//! A comment ending in a `mat:lang=NAME` marker switches the lines after it to
+//! NAME, up to a `mat:lang=end` marker (or the next one), for files where no
+//! rule can tell: SQL migrations in a shell script, a heredoc, a mixed-language
+//! notebook export. Marked regions win over everything else.
+
+use std::ops::Range;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::display::{Document, Line, StyledSpan};
+use crate::theme::Theme;
+
+use super::syntax::{detect_language, find_syntax, highlight_spans, highlighter, syntax_by_token};
+
+/// One `[[injection]]` entry of the configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Injection {
+    /// Languages the rule applies in, by syntax name or file extension (case-insensitive)
+    pub hosts: Vec<String>,
+    /// Regex matched against each line; the `code` group, else the first group
+    /// that took part in the match, is the embedded code
+    pub pattern: String,
+    /// Syntax the embedded code is highlighted with, by name or file extension
+    pub language: String,
+}
+
+impl Injection {
+    /// Check that the entry is usable, describing the problem otherwise
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hosts.is_empty() {
+            return Err("injection needs `hosts`".to_string());
+        }
+        if self.language.is_empty() {
+            return Err("injection needs a `language`".to_string());
+        }
+        Regex::new(&self.pattern).map(|_| ()).map_err(|e| format!("injection `pattern`: {}", e))
+    }
+}
+
+/// Start of an SQL statement, specific enough to leave prose strings alone
+const SQL_STATEMENT: &str = concat!(
+    r"(?i:select\s.*?\sfrom|insert\s+into|update\s+\w+\s+set|delete\s+from|with\s+\w+\s+as",
+    r"|(?:create|alter|drop)\s+(?:table|index|view))\b",
+);
+
+/// Languages whose string literals may hold SQL
+const SQL_HOSTS: &[&str] = &[
+    "Python", "JavaScript", "TypeScript", "Rust", "Go", "Java", "Ruby", "PHP", "Kotlin", "C#", "Scala",
+];
+
+/// Built-in rules besides SQL strings, as (hosts, pattern, language)
+const BUILTIN_RULES: &[(&[&str], &str, &str)] = &[
+    // Shell commands of Dockerfile instructions
+    (&["Dockerfile"], r"^\s*(?i:RUN)\s+(?P<code>.+)$", "sh"),
+    // HTML in tagged template literals
+    (&["JavaScript", "TypeScript"], r"html`(?P<code>[^`]*)", "HTML"),
+    // Scriptlets of embedded JavaScript templates
+    (&["ejs"], r"<%[=_-]?(?P<code>.*?)[_-]?%>", "JavaScript"),
+];
+
+/// A `mat:lang=NAME` marker at the end of a line, before the comment's closing `*/`, `-->` or `*)`
+static LANGUAGE_MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bmat:lang=([\w+#.-]+)\s*(?:\*/|-->|\*\))?\s*$").expect("valid regex"));
+
+/// A rule with its pattern compiled
+struct Rule {
+    hosts: Vec<String>,
+    pattern: Regex,
+    language: String,
+}
+
+impl Rule {
+    fn new(hosts: Vec<String>, pattern: &str, language: &str) -> Option<Self> {
+        Some(Self {
+            hosts,
+            pattern: Regex::new(pattern).ok()?,
+            language: language.to_string(),
+        })
+    }
+
+    /// Byte range of the embedded code of each match in `text`
+    fn regions(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern
+            .captures_iter(text)
+            .filter_map(|caps| caps.name("code").or_else(|| caps.iter().skip(1).flatten().next()))
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+}
+
+static BUILTIN: Lazy<Vec<Rule>> = Lazy::new(|| {
+    // Double- or single-quoted, with the statement running to the closing quote
+    let sql = format!(r#""((?:{0})[^"]*)"|'((?:{0})[^']*)'"#, SQL_STATEMENT);
+    std::iter::once((SQL_HOSTS, sql.as_str(), "SQL"))
+        .chain(BUILTIN_RULES.iter().copied())
+        .filter_map(|(hosts, pattern, language)| {
+            Rule::new(hosts.iter().map(|h| h.to_string()).collect(), pattern, language)
+        })
+        .collect()
+});
+
+/// Names the document's language goes by: the explicit language, the detected one,
+/// the syntect syntax name and the file extension
+fn host_names(document: &Document, language: Option<&str>) -> Vec<String> {
+    let source_name = document.source_name.as_str();
+    let mut names: Vec<String> = language.into_iter().map(str::to_string).collect();
+    names.extend(detect_language(source_name).map(str::to_string));
+    names.extend(find_syntax(language, source_name).map(|syntax| syntax.name.clone()));
+    names.extend(source_name.rsplit(['.', '/']).next().map(str::to_string));
+    names
+}
+
+/// Replace the byte range `range` of a line with `spans`, keeping the spans around it
+fn splice(line: &mut Line, range: Range<usize>, spans: Vec<StyledSpan>) {
+    let mut result = Vec::with_capacity(line.spans.len() + spans.len());
+    let mut replacement = Some(spans);
+    let mut offset = 0;
+    for span in std::mem::take(&mut line.spans) {
+        let (start, end) = (offset, offset + span.text.len());
+        offset = end;
+        if start < range.start {
+            let keep = range.start.min(end) - start;
+            result.push(StyledSpan::new(&span.text[..keep], span.style.clone()));
+        }
+        if end > range.start {
+            if let Some(spans) = replacement.take() {
+                result.extend(spans);
+            }
+        }
+        if end > range.end {
+            let skip = range.end.max(start) - start;
+            result.push(StyledSpan::new(&span.text[skip..], span.style));
+        }
+    }
+    if let Some(spans) = replacement {
+        result.extend(spans);
+    }
+    line.spans = result.into_iter().filter(|span| !span.text.is_empty()).collect();
+}
+
+/// Highlight the code embedded in a document by the rules for its language
+pub fn apply_injections(document: &mut Document, language: Option<&str>, theme: Theme, injections: &[Injection]) {
+    let hosts = host_names(document, language);
+    let configured: Vec<Rule> = injections
+        .iter()
+        .filter_map(|i| Rule::new(i.hosts.clone(), &i.pattern, &i.language))
+        .collect();
+    let rules = configured
+        .iter()
+        .chain(BUILTIN.iter())
+        .filter(|rule| rule.hosts.iter().any(|host| hosts.iter().any(|name| name.eq_ignore_ascii_case(host))));
+
+    for rule in rules {
+        let Some(syntax) = syntax_by_token(&rule.language) else {
+            continue;
+        };
+        let mut idx = 0;
+        while idx < document.lines.len() {
+            let text = document.lines[idx].text();
+            let regions = rule.regions(&text);
+            idx += 1;
+            let Some(last) = regions.last().cloned() else {
+                continue;
+            };
+            for range in regions {
+                let Some(mut highlighter) = highlighter(syntax, theme) else {
+                    return;
+                };
+                let Some(spans) = highlight_spans(&mut highlighter, &text[range.clone()]) else {
+                    continue;
+                };
+                splice(&mut document.lines[idx - 1], range.clone(), spans);
+
+                // A trailing backslash carries the code on to the next lines
+                let mut continued = range == last && text[range].trim_end().ends_with('\\');
+                while continued && idx < document.lines.len() {
+                    let next = document.lines[idx].text();
+                    if let Some(spans) = highlight_spans(&mut highlighter, &next) {
+                        splice(&mut document.lines[idx], 0..next.len(), spans);
+                    }
+                    continued = next.trim_end().ends_with('\\');
+                    idx += 1;
+                }
+            }
+        }
+    }
+    apply_language_markers(document, theme);
+}
+
+/// Highlight the lines between `mat:lang=NAME` and `mat:lang=end` markers as NAME
+fn apply_language_markers(document: &mut Document, theme: Theme) {
+    let mut region = None;
+    for line in &mut document.lines {
+        let text = line.text();
+        // The marker lines themselves stay comments of the host language
+        if let Some(caps) = LANGUAGE_MARKER.captures(&text) {
+            let name = &caps[1];
+            region = if name.eq_ignore_ascii_case("end") {
+                None
+            } else if let Some(syntax) = syntax_by_token(name) {
+                log::debug!("{}: line {} starts a region of {}", document.source_name, line.number, syntax.name);
+                highlighter(syntax, theme)
+            } else {
+                log::warn!("{}: line {}: unknown language {} in mat:lang marker", document.source_name, line.number, name);
+                None
+            };
+            continue;
+        }
+        if let Some(ref mut highlighter) = region {
+            if let Some(spans) = highlight_spans(highlighter, &text) {
+                splice(line, 0..text.len(), spans);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::SpanStyle;
+
+    fn document(text: &str, name: &str) -> Document {
+        Document::from_text(text, name.to_string(), "UTF-8".to_string())
+    }
+
+    #[test]
+    fn test_splice_keeps_surrounding_spans() {
+        let mut line = Line::plain(1, "");
+        line.spans = vec![StyledSpan::new("ab", SpanStyle::new().bold()), StyledSpan::plain("cdef")];
+        splice(&mut line, 1..4, vec![StyledSpan::plain("XYZ")]);
+        let texts: Vec<&str> = line.spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["a", "XYZ", "ef"]);
+        assert!(line.spans[0].style.bold);
+    }
+
+    #[test]
+    fn test_sql_in_python_string() {
+        let mut doc = document("rows = db.execute(\"SELECT id FROM users\")\nname = \"select from the list\"", "q.py");
+        let plain = doc.clone();
+        apply_injections(&mut doc, None, Theme::Dark, &[]);
+        assert_eq!(doc.lines[1].spans, plain.lines[1].spans);
+        // The SQL keyword gets a span of its own, distinct from the string color
+        let select = doc.lines[0].spans.iter().find(|s| s.text.contains("SELECT")).unwrap();
+        assert_eq!(select.text.trim(), "SELECT");
+        assert_eq!(doc.lines[0].text(), "rows = db.execute(\"SELECT id FROM users\")");
+    }
+
+    #[test]
+    fn test_dockerfile_run_continues_lines() {
+        let text = "FROM alpine\nRUN apk add curl && \\\n    echo \"done\"\nCMD [\"sh\"]";
+        let mut doc = document(text, "Dockerfile");
+        apply_injections(&mut doc, None, Theme::Dark, &[]);
+        assert!(doc.lines[0].spans.len() == 1);
+        assert!(doc.lines[1].spans.len() > 1);
+        assert!(doc.lines[2].spans.len() > 1);
+        assert!(doc.lines[3].spans.len() == 1);
+        assert_eq!(doc.lines[2].text(), "    echo \"done\"");
+    }
+
+    #[test]
+    fn test_language_markers() {
+        let text = "#!/bin/sh\n# mat:lang=sql\nSELECT id FROM users;\n# mat:lang=end\necho done\n/* mat:lang=nosuch */\necho still sh";
+        let mut host = document(text, "migrate.sh");
+        crate::highlight::apply_syntax_highlight(&mut host, None, Theme::Dark);
+        let mut doc = host.clone();
+        apply_injections(&mut doc, None, Theme::Dark, &[]);
+
+        // Only the marked line is highlighted as SQL
+        for idx in [0, 1, 3, 4, 5, 6] {
+            assert_eq!(doc.lines[idx].spans, host.lines[idx].spans, "line {}", idx);
+        }
+        assert_ne!(doc.lines[2].spans, host.lines[2].spans);
+        let select = doc.lines[2].spans.iter().find(|s| s.text.contains("SELECT")).unwrap();
+        assert_eq!(select.text.trim(), "SELECT");
+        assert_eq!(doc.lines[2].text(), "SELECT id FROM users;");
+
+        // A marker has to end its line, so prose mentioning one is left alone
+        assert!(LANGUAGE_MARKER.is_match("-- mat:lang=Python"));
+        assert!(LANGUAGE_MARKER.is_match("<!-- mat:lang=c++ -->"));
+        assert!(!LANGUAGE_MARKER.is_match("use `mat:lang=sql` ... `mat:lang=end` markers"));
+    }
+
+    #[test]
+    fn test_configured_injection() {
+        let injection = Injection {
+            hosts: vec!["txt".to_string()],
+            pattern: r"^sql: (?P<code>.*)$".to_string(),
+            language: "SQL".to_string(),
+        };
+        assert!(injection.validate().is_ok());
+        let mut doc = document("sql: SELECT 1\nplain", "notes.txt");
+        apply_injections(&mut doc, None, Theme::Dark, &[injection]);
+        assert!(doc.lines[0].spans.len() > 2);
+        assert_eq!(doc.lines[1].spans.len(), 1);
+
+        let invalid = Injection {
+            hosts: vec!["txt".to_string()],
+            pattern: "(".to_string(),
+            language: "SQL".to_string(),
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
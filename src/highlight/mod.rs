@@ -1,7 +1,26 @@
+mod diff;
+mod gitattributes;
+mod inject;
+mod paths;
+mod ripgrep;
 mod search;
+#[cfg(feature = "spellcheck")]
+mod spell;
 mod syntax;
+mod themes;
+mod trace;
+mod user_syntaxes;
 
+pub use diff::{apply_word_diff, looks_like_diff};
+pub use gitattributes::linguist_language;
+pub use inject::{apply_injections, Injection};
+pub use paths::{apply_path_highlight, path_links, restyle_range, PathLink};
+pub use ripgrep::ripgrep_source;
 #[allow(unused_imports)]
 pub use search::{apply_search_highlight, MatchPosition, SearchState};
 #[allow(unused_imports)]
-pub use syntax::{apply_syntax_highlight, detect_language};
+pub use syntax::{apply_syntax_highlight, detect_language, highlight_code, print_languages};
+#[cfg(feature = "spellcheck")]
+pub use spell::{apply_spell_highlight, Dictionary};
+pub use themes::{find_theme, print_themes};
+pub use trace::{apply_trace_highlight, trace_references, FileReference};
@@ -0,0 +1,174 @@
+//! File path detection and styling
+//!
+//! Tokens that look like paths, such as `src/main.rs:42`, `./build.sh` or
+//! `/etc/nginx/nginx.conf`, are underlined in any document, so the pager can
+//! step through them and open one in a new buffer or in the editor. A token
+//! counts as a path when it starts at the root, the home directory or `./`,
+//! when its last component has an extension, or when a line number follows
+//! it; `and/or` and the paths of URLs are left alone.
+
+use std::ops::Range;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::display::{Document, SpanStyle, StyledSpan};
+
+use super::trace::FileReference;
+
+/// `src/main.rs:42:5`, `/etc/hosts`, `~/notes.md`, or a bare `main.rs:42`
+static PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?P<path>(?:~|\.{1,2})?/(?:[\w.+-]+/)*[\w.+-]+|(?:[\w.+-]+/)+[\w.+-]+|[\w+-][\w.+-]*\.[A-Za-z]\w*)",
+        r"(?::(?P<line>\d+)(?::(?P<col>\d+))?)?",
+    ))
+    .unwrap()
+});
+
+/// Characters that continue a word, a URL or a path before a match
+const JOINED_BEFORE: &[char] = &['/', '.', ':', '~', '+', '-', '@', '\\'];
+
+/// A path in a document line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathLink {
+    /// Index of the line in the document
+    pub line_idx: usize,
+    /// Byte range of the path (and its line number) in the line's text
+    pub range: Range<usize>,
+    /// The path, with line 1 when none is given
+    pub reference: FileReference,
+}
+
+/// Byte range and reference of every path in `text`
+pub fn find_paths(text: &str) -> Vec<(Range<usize>, FileReference)> {
+    let mut paths = Vec::new();
+    for caps in PATH_RE.captures_iter(text) {
+        let whole = caps.get(0).expect("group 0 always matches");
+        let joined = text[..whole.start()].chars().next_back().is_some_and(|c| c.is_alphanumeric() || JOINED_BEFORE.contains(&c));
+        if joined {
+            continue;
+        }
+        let line = caps.name("line");
+        // A sentence ending in a path doesn't make its full stop part of it
+        let mut path = caps["path"].to_string();
+        if line.is_none() {
+            path.truncate(path.trim_end_matches('.').len());
+        }
+        let start = whole.start();
+        let end = if line.is_some() { whole.end() } else { start + path.len() };
+        if !is_path_like(&path, line.is_some()) {
+            continue;
+        }
+        paths.push((
+            start..end,
+            FileReference {
+                path,
+                line: line.and_then(|l| l.as_str().parse().ok()).unwrap_or(1),
+                column: caps.name("col").and_then(|c| c.as_str().parse().ok()),
+            },
+        ));
+    }
+    paths
+}
+
+/// Whether a matched token is more likely a path than a word pair like `and/or`
+fn is_path_like(path: &str, has_line: bool) -> bool {
+    let anchored = ["/", "./", "../", "~/"].iter().any(|prefix| path.starts_with(prefix));
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let has_extension = name
+        .rsplit_once('.')
+        .is_some_and(|(stem, ext)| !stem.is_empty() && ext.starts_with(|c: char| c.is_ascii_alphabetic()));
+    // A bare name needs a line number too, or every `example.com` would be a file
+    if !path.contains('/') {
+        return has_line && has_extension;
+    }
+    anchored || has_extension || has_line
+}
+
+/// Every path in the document, in reading order
+pub fn path_links(document: &Document) -> Vec<PathLink> {
+    document
+        .lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line_idx, line)| {
+            find_paths(&line.text()).into_iter().map(move |(range, reference)| PathLink {
+                line_idx,
+                range,
+                reference,
+            })
+        })
+        .collect()
+}
+
+/// `spans` with the text in the byte range `range` restyled by `restyle`
+pub fn restyle_range(spans: &[StyledSpan], range: &Range<usize>, restyle: impl Fn(&SpanStyle) -> SpanStyle) -> Vec<StyledSpan> {
+    let mut result = Vec::with_capacity(spans.len() + 2);
+    let mut offset = 0;
+    for span in spans {
+        let span_range = offset..offset + span.text.len();
+        offset = span_range.end;
+        let start = range.start.clamp(span_range.start, span_range.end) - span_range.start;
+        let end = range.end.clamp(span_range.start, span_range.end) - span_range.start;
+        let parts = [
+            (&span.text[..start], span.style.clone()),
+            (&span.text[start..end], restyle(&span.style)),
+            (&span.text[end..], span.style.clone()),
+        ];
+        result.extend(parts.into_iter().filter(|(text, _)| !text.is_empty()).map(|(text, style)| StyledSpan::new(text, style)));
+    }
+    result
+}
+
+/// Underline the paths of a document, keeping their colors
+pub fn apply_path_highlight(document: &mut Document) {
+    for line in &mut document.lines {
+        let text = line.text();
+        for (range, _) in find_paths(&text) {
+            line.spans = restyle_range(&line.spans, &range, |style| style.clone().underline());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(text: &str) -> Vec<(&str, usize)> {
+        find_paths(text).into_iter().map(|(range, r)| (&text[range], r.line)).collect()
+    }
+
+    #[test]
+    fn test_find_paths() {
+        assert_eq!(paths("error at src/foo.rs:42:7: oops"), vec![("src/foo.rs:42:7", 42)]);
+        assert_eq!(paths("edit /etc/nginx/nginx.conf and ./run"), vec![("/etc/nginx/nginx.conf", 1), ("./run", 1)]);
+        assert_eq!(paths("(main.rs:10) see ~/notes.md."), vec![("main.rs:10", 10), ("~/notes.md", 1)]);
+        // Word pairs, dates, domains and URLs aren't paths
+        assert!(paths("read and/or write on 2024/05/01 at example.com").is_empty());
+        assert!(paths("see https://example.com/docs/index.html").is_empty());
+
+        let (_, reference) = &find_paths("lib/a.py:3:9")[0];
+        assert_eq!(reference.column, Some(9));
+    }
+
+    #[test]
+    fn test_apply_path_highlight() {
+        let mut doc = Document::from_text("open src/lib.rs now\nnothing here", "notes.txt".to_string(), "UTF-8".to_string());
+        doc.lines[0].spans = vec![StyledSpan::new("open src/", SpanStyle::new().bold()), StyledSpan::plain("lib.rs now")];
+        apply_path_highlight(&mut doc);
+
+        let underlined: Vec<(&str, bool)> = doc.lines[0]
+            .spans
+            .iter()
+            .filter(|s| s.style.underline)
+            .map(|s| (s.text.as_str(), s.style.bold))
+            .collect();
+        assert_eq!(underlined, vec![("src/", true), ("lib.rs", false)]);
+        assert_eq!(doc.lines[0].text(), "open src/lib.rs now");
+        assert!(doc.lines[1].spans.iter().all(|s| !s.style.underline));
+
+        let links = path_links(&doc);
+        assert_eq!(links.len(), 1);
+        assert_eq!((links[0].line_idx, links[0].range.clone()), (0, 5..15));
+    }
+}
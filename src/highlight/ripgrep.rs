@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use regex::Regex;
+
+use crate::cli::Args;
+use crate::input::large::LARGE_FILE_THRESHOLD;
+use crate::input::InputSource;
+use crate::sandbox::exec_allowed;
+
+/// Name of the ripgrep executable
+const RIPGREP_BIN: &str = "rg";
+
+/// Decide whether searching the given source should be delegated to ripgrep
+///
+/// Only plain file views qualify: rendered markdown does not map 1:1 to the
+/// lines on disk, and small files are faster to search in-process.
+pub fn ripgrep_source(args: &Args, source: &InputSource, is_markdown: bool) -> Option<PathBuf> {
+    if !args.ripgrep || is_markdown {
+        return None;
+    }
+
+    match source {
+        InputSource::File(path) => {
+            let size = std::fs::metadata(path).ok()?.len();
+            (size >= LARGE_FILE_THRESHOLD).then(|| path.clone())
+        }
+        InputSource::Stdin => None,
+    }
+}
+
+/// Run `rg --json` and return the (1-indexed) numbers of matching lines
+///
+/// Returns None if ripgrep is not installed or fails, so callers can fall
+/// back to the in-process search.
+pub fn ripgrep_matching_lines(path: &Path, pattern: &Regex) -> Option<Vec<usize>> {
+    if !exec_allowed() {
+        return None;
+    }
+    let output = Command::new(RIPGREP_BIN)
+        .args(["--json", "--no-config", "--text", "-e"])
+        .arg(pattern.as_str())
+        .arg("--")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    // Exit code 1 means "no matches", anything else non-zero is an error
+    match output.status.code() {
+        Some(0) | Some(1) => {}
+        _ => return None,
+    }
+
+    Some(parse_json_line_numbers(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Extract line numbers from ripgrep's JSON Lines output
+fn parse_json_line_numbers(output: &str) -> Vec<usize> {
+    let mut numbers: Vec<usize> = output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|msg| msg["type"] == "match")
+        .filter_map(|msg| msg["data"]["line_number"].as_u64())
+        .map(|n| n as usize)
+        .collect();
+
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_line_numbers() {
+        let output = r#"{"type":"begin","data":{"path":{"text":"a.log"}}}
+{"type":"match","data":{"path":{"text":"a.log"},"lines":{"text":"error 1\n"},"line_number":3,"absolute_offset":10,"submatches":[{"match":{"text":"error"},"start":0,"end":5}]}}
+{"type":"match","data":{"path":{"text":"a.log"},"lines":{"text":"error 2\n"},"line_number":7,"absolute_offset":40,"submatches":[{"match":{"text":"error"},"start":0,"end":5}]}}
+{"type":"end","data":{"path":{"text":"a.log"}}}
+{"type":"summary","data":{}}"#;
+
+        assert_eq!(parse_json_line_numbers(output), vec![3, 7]);
+    }
+
+    #[test]
+    fn test_parse_json_ignores_garbage() {
+        assert!(parse_json_line_numbers("not json\n{}\n").is_empty());
+    }
+
+    #[test]
+    fn test_ripgrep_source_requires_flag() {
+        let args = Args::default();
+        let source = InputSource::File(PathBuf::from("Cargo.toml"));
+        assert_eq!(ripgrep_source(&args, &source, false), None);
+    }
+}
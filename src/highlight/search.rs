@@ -1,10 +1,19 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
 use ratatui::style::Color;
 use regex::Regex;
 
 use crate::cli::Args;
-use crate::display::{Document, SpanStyle, StyledSpan};
+use crate::display::{match_ranges_by_line, source_line_matches, Document, SpanStyle, StyledSpan};
 use crate::error::MatError;
-use crate::filter::build_regex;
+use crate::filter::{build_regex_from_patterns, pattern_file_patterns};
+use crate::input::large::LazyDocument;
+
+use super::ripgrep::ripgrep_matching_lines;
+
+/// Lines scanned per step of a search in progress; shorter documents are searched at once
+pub const SEARCH_CHUNK_LINES: usize = 20_000;
 
 /// Position of a match in the document
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -26,6 +35,10 @@ pub struct SearchState {
     pub matches: Vec<MatchPosition>,
     /// Current match index (None if no navigation yet)
     pub current_match: Option<usize>,
+    /// File whose match discovery is delegated to ripgrep (large files only)
+    pub ripgrep_source: Option<PathBuf>,
+    /// While the search is in progress, the index of the next line to scan
+    pub resume_from: Option<usize>,
 }
 
 impl SearchState {
@@ -40,48 +53,141 @@ impl SearchState {
             return Err(MatError::EmptyPattern);
         }
 
-        let pattern = build_regex(pattern_str, args)?;
+        let mut patterns = vec![pattern_str.clone()];
+        patterns.extend(pattern_file_patterns(args)?);
+        let pattern = build_regex_from_patterns(&patterns, args)?;
 
-        Ok(Some(Self {
+        Ok(Some(Self::new(pattern)))
+    }
+
+    /// Create search state for an already compiled pattern
+    pub fn new(pattern: Regex) -> Self {
+        Self {
             pattern,
             matches: Vec::new(),
             current_match: None,
-        }))
+            ripgrep_source: None,
+            resume_from: None,
+        }
     }
 
     /// Find all matches in the document and store positions
     pub fn find_matches(&mut self, document: &Document) {
+        self.resume_from = None;
+        if let Some(ref path) = self.ripgrep_source {
+            if let Some(numbers) = ripgrep_matching_lines(path, &self.pattern) {
+                self.find_matches_in_lines(document, &numbers);
+                return;
+            }
+            // ripgrep unavailable or failed: fall back to scanning in-process
+        }
+
+        self.matches.clear();
+        self.scan(document, 0..document.line_count());
+    }
+
+    /// Start finding matches, scanning only the first chunk of a long document
+    ///
+    /// The rest is scanned a chunk at a time by [`Self::continue_search`], so a
+    /// huge file stays interactive and the matches found so far can be visited.
+    pub fn start_search(&mut self, document: &Document) {
+        if self.ripgrep_source.is_some() || document.line_count() <= SEARCH_CHUNK_LINES {
+            self.find_matches(document);
+            return;
+        }
         self.matches.clear();
+        self.resume_from = Some(0);
+        self.continue_search(document);
+    }
+
+    /// Scan the next chunk of a search in progress, returns whether there is more to scan
+    pub fn continue_search(&mut self, document: &Document) -> bool {
+        let Some(start) = self.resume_from else {
+            return false;
+        };
+        // A step ends past the continuation chunks of its last line, which are matched with it
+        let lines = &document.lines;
+        let mut end = (start + SEARCH_CHUNK_LINES).min(lines.len());
+        while end < lines.len() && lines[end].continuation {
+            end += 1;
+        }
+        self.scan(document, start..end);
+        self.resume_from = (end < document.line_count()).then_some(end);
+        self.resume_from.is_some()
+    }
+
+    /// Whether part of the document is still to be searched
+    pub fn is_searching(&self) -> bool {
+        self.resume_from.is_some()
+    }
 
-        for (line_idx, line) in document.lines.iter().enumerate() {
-            let text = line.text();
-            for mat in self.pattern.find_iter(&text) {
-                self.matches.push(MatchPosition {
-                    line_idx,
-                    start_col: mat.start(),
-                    end_col: mat.end(),
-                });
+    /// Add the matches on the source lines starting in `range`, which come after those already found
+    ///
+    /// A match across the chunks of a long line is found at the chunk it starts in.
+    fn scan(&mut self, document: &Document, range: Range<usize>) {
+        let found = source_line_matches(&document.lines, range, &self.pattern);
+        self.matches.extend(found.iter().map(|pieces| {
+            let (line_idx, ref cols) = pieces[0];
+            MatchPosition {
+                line_idx,
+                start_col: cols.start,
+                end_col: cols.end,
+            }
+        }));
+    }
+
+    /// Find matches only on lines whose (1-indexed) number is in `line_numbers`
+    ///
+    /// `line_numbers` must be sorted. Columns are recomputed on our own text, so
+    /// they stay correct after tab expansion and ANSI stripping.
+    pub fn find_matches_in_lines(&mut self, document: &Document, line_numbers: &[usize]) {
+        self.matches.clear();
+
+        let lines = &document.lines;
+        let mut start = 0;
+        while start < lines.len() {
+            let mut end = start + 1;
+            while end < lines.len() && lines[end].continuation {
+                end += 1;
+            }
+            if line_numbers.binary_search(&lines[start].number).is_ok() {
+                self.scan(document, start..end);
             }
+            start = end;
         }
     }
 
+    /// Find all matches in a lazily-loaded document without materializing its lines
+    pub fn find_matches_lazy(&mut self, document: &LazyDocument) {
+        self.matches = document.find_matches(&self.pattern);
+    }
+
     /// Get total number of matches
     pub fn match_count(&self) -> usize {
         self.matches.len()
     }
 
+    /// Check whether any match falls on the given line (matches are sorted by line)
+    pub fn line_has_match(&self, line_idx: usize) -> bool {
+        let i = self.matches.partition_point(|m| m.line_idx < line_idx);
+        matches!(self.matches.get(i), Some(m) if m.line_idx == line_idx)
+    }
+
     /// Get current match position (1-indexed for display)
     pub fn current_match_display(&self) -> Option<usize> {
         self.current_match.map(|i| i + 1)
     }
 
     /// Navigate to next match, returns the line index to scroll to
+    ///
+    /// While the search is in progress, the last match found so far doesn't wrap around.
     pub fn next_match(&mut self) -> Option<usize> {
         if self.matches.is_empty() {
             return None;
         }
 
         let next = match self.current_match {
+            Some(i) if i + 1 == self.matches.len() && self.is_searching() => return None,
             Some(i) => (i + 1) % self.matches.len(),
             None => 0,
         };
@@ -110,6 +216,30 @@ impl SearchState {
         self.current_match = Some(prev);
         Some(self.matches[prev].line_idx)
     }
+
+    /// Make the match at `index` (0-indexed) current, returns the line index to scroll to
+    pub fn select_match(&mut self, index: usize) -> Option<usize> {
+        let line_idx = self.matches.get(index)?.line_idx;
+        self.current_match = Some(index);
+        Some(line_idx)
+    }
+
+    /// Recompile the pattern with or without case-insensitive matching
+    ///
+    /// Matches are left as they were; the caller re-runs [`Self::find_matches`].
+    pub fn set_ignore_case(&mut self, ignore_case: bool) {
+        let source = self.pattern.as_str();
+        let bare = source.strip_prefix("(?i)").unwrap_or(source);
+        let pattern_str = if ignore_case {
+            format!("(?i){}", bare)
+        } else {
+            bare.to_string()
+        };
+        // Adding or removing a leading flag group cannot make a valid pattern invalid
+        if let Ok(pattern) = Regex::new(&pattern_str) {
+            self.pattern = pattern;
+        }
+    }
 }
 
 /// Style for search highlighting
@@ -128,10 +258,8 @@ pub fn highlight_style() -> SpanStyle {
 pub fn apply_search_highlight(document: &mut Document, pattern: &Regex) {
     let search_style = highlight_style();
 
-    for line in &mut document.lines {
-        let text = line.text();
-        let matches: Vec<_> = pattern.find_iter(&text).collect();
-
+    let ranges = match_ranges_by_line(&document.lines, pattern);
+    for (line, matches) in document.lines.iter_mut().zip(ranges) {
         if matches.is_empty() {
             continue;
         }
@@ -148,13 +276,13 @@ pub fn apply_search_highlight(document: &mut Document, pattern: &Regex) {
             let mut last_pos = 0;
             for mat in &matches {
                 // Skip matches that don't overlap with this span
-                if mat.end() <= span_start || mat.start() >= span_end {
+                if mat.end <= span_start || mat.start >= span_end {
                     continue;
                 }
 
                 // Calculate overlap within this span
-                let overlap_start = mat.start().saturating_sub(span_start).min(span.text.len());
-                let overlap_end = (mat.end() - span_start).min(span.text.len());
+                let overlap_start = mat.start.saturating_sub(span_start).min(span.text.len());
+                let overlap_end = (mat.end - span_start).min(span.text.len());
 
                 // Add text before the match (with original style)
                 if overlap_start > last_pos {
@@ -271,6 +399,42 @@ mod tests {
         assert_eq!(spans[0].text, "Hello world");
     }
 
+    #[test]
+    fn test_find_matches_in_lines() {
+        let doc = Document::from_text(
+            "error a\nerror b\nerror c",
+            "test.txt".to_string(),
+            "UTF-8".to_string(),
+        );
+        let mut state = SearchState::new(Regex::new("error").unwrap());
+
+        state.find_matches_in_lines(&doc, &[1, 3]);
+
+        let lines: Vec<usize> = state.matches.iter().map(|m| m.line_idx).collect();
+        assert_eq!(lines, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_search_across_chunks() {
+        let chunk = crate::display::LONG_LINE_CHUNK_SIZE;
+        let text = format!("{}needle{}\nneedle", "x".repeat(chunk - 3), "x".repeat(10));
+        let mut doc = Document::from_text(&text, "test.txt".to_string(), "UTF-8".to_string());
+        let mut state = SearchState::new(Regex::new("needle").unwrap());
+
+        // Found once, where it starts
+        state.find_matches(&doc);
+        let found: Vec<(usize, usize)> = state.matches.iter().map(|m| (m.line_idx, m.start_col)).collect();
+        assert_eq!(found, vec![(0, chunk - 3), (2, 0)]);
+        state.find_matches_in_lines(&doc, &[1]);
+        assert_eq!(state.match_count(), 1);
+
+        // Highlighted on both chunks
+        apply_search_highlight(&mut doc, &state.pattern);
+        assert_eq!(doc.lines[0].spans.last().unwrap().text, "nee");
+        assert_eq!(doc.lines[1].spans[0].text, "dle");
+        assert_eq!(doc.lines[1].spans[0].style, highlight_style());
+    }
+
     #[test]
     fn test_search_state_navigation() {
         let pattern = Regex::new("a").unwrap();
@@ -294,6 +458,8 @@ mod tests {
                 },
             ],
             current_match: None,
+            ripgrep_source: None,
+            resume_from: None,
         };
 
         // First next goes to first match
@@ -316,4 +482,37 @@ mod tests {
         assert_eq!(state.prev_match(), Some(5));
         assert_eq!(state.current_match, Some(2));
     }
+
+    #[test]
+    fn test_search_in_chunks() {
+        let text: String = (0..SEARCH_CHUNK_LINES * 2 + 10).map(|i| format!("line {}\n", i)).collect();
+        let doc = Document::from_text(&text, "big.log".to_string(), "UTF-8".to_string());
+        let mut state = SearchState::new(Regex::new("0$").unwrap());
+
+        state.start_search(&doc);
+        assert!(state.is_searching());
+        assert_eq!(state.match_count(), SEARCH_CHUNK_LINES / 10);
+        // The last match found so far doesn't wrap around to the first
+        assert_eq!(state.select_match(state.match_count() - 1), Some(SEARCH_CHUNK_LINES - 10));
+        assert_eq!(state.next_match(), None);
+
+        assert!(state.continue_search(&doc));
+        assert!(!state.continue_search(&doc));
+        assert!(!state.is_searching());
+        assert_eq!(state.match_count(), SEARCH_CHUNK_LINES * 2 / 10 + 1);
+        assert_eq!(state.next_match(), Some(SEARCH_CHUNK_LINES));
+    }
+
+    #[test]
+    fn test_set_ignore_case() {
+        let mut state = SearchState::new(Regex::new("Error").unwrap());
+
+        state.set_ignore_case(true);
+        assert_eq!(state.pattern.as_str(), "(?i)Error");
+        assert!(state.pattern.is_match("error"));
+
+        state.set_ignore_case(false);
+        assert_eq!(state.pattern.as_str(), "Error");
+        assert!(!state.pattern.is_match("error"));
+    }
 }
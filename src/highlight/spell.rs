@@ -0,0 +1,219 @@
+//! Spell checking of prose against a word list
+//!
+//! Dictionaries are hunspell `.dic` files or plain word lists such as
+//! `/usr/share/dict/words`. Affix rules are not applied; instead common English
+//! endings are stripped before a word is looked up, which covers most plurals
+//! and verb forms of the listed stems.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use ratatui::style::Color;
+use regex::Regex;
+
+use crate::cli::Args;
+use crate::display::{Document, StyledSpan};
+use crate::error::MatError;
+
+/// Words, with inner apostrophes (don't, reader's)
+static WORD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\p{L}+(?:['’]\p{L}+)*").unwrap());
+
+/// Endings stripped to find the stem, with what replaces them
+const SUFFIXES: &[(&str, &str)] = &[
+    ("'s", ""),
+    ("’s", ""),
+    ("ies", "y"),
+    ("ied", "y"),
+    ("es", ""),
+    ("s", ""),
+    ("ed", ""),
+    ("ed", "e"),
+    ("d", ""),
+    ("ing", ""),
+    ("ing", "e"),
+    ("ly", ""),
+    ("er", ""),
+    ("est", ""),
+];
+
+/// Set of correctly spelled words
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Parse a hunspell `.dic` file (a word count, then `word/FLAGS` lines) or a plain word list
+    pub fn parse(text: &str) -> Self {
+        let words = text
+            .lines()
+            .filter_map(|line| line.split('/').next())
+            .map(str::trim)
+            .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+            .map(str::to_lowercase)
+            .collect();
+        Self { words }
+    }
+
+    /// Read a dictionary file
+    pub fn load(path: &Path) -> Result<Self, MatError> {
+        std::fs::read_to_string(path).map(|text| Self::parse(&text)).map_err(|source| MatError::Io {
+            source,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// First installed system dictionary for the current locale, falling back to English
+    pub fn system() -> Option<Self> {
+        system_dictionaries().iter().find(|path| path.is_file()).and_then(|path| Self::load(path).ok())
+    }
+
+    /// Dictionary for `--spellcheck`: `--dictionary FILE`, else a system one
+    pub fn from_args(args: &Args) -> Result<Option<Self>, MatError> {
+        match args.dictionary {
+            Some(ref path) => Self::load(path).map(Some),
+            None if args.spellcheck => Self::system().map(Some).ok_or(MatError::NoDictionary),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether a word, or the stem left after removing a common ending, is listed
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        if self.words.contains(&word) {
+            return true;
+        }
+        SUFFIXES.iter().any(|(suffix, replacement)| {
+            word.strip_suffix(suffix)
+                .filter(|stem| stem.chars().count() >= 2)
+                .is_some_and(|stem| self.words.contains(&format!("{}{}", stem, replacement)))
+        })
+    }
+
+    /// Byte ranges of the misspelled words in a line of text
+    pub fn misspellings(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        for token in text.split_whitespace() {
+            let start = offset + text[offset..].find(token).unwrap_or(0);
+            offset = start + token.len();
+            // Paths, addresses, identifiers and numbers are not words
+            if token.contains(|c: char| c.is_ascii_digit() || "/\\@_=<>{}#".contains(c)) {
+                continue;
+            }
+            for word in WORD_RE.find_iter(token) {
+                if is_checked(word.as_str()) && !self.contains(word.as_str()) {
+                    ranges.push((start + word.start(), start + word.end()));
+                }
+            }
+        }
+        ranges
+    }
+}
+
+/// Whether a word is worth looking up: not a single letter, an acronym or camelCase
+fn is_checked(word: &str) -> bool {
+    word.chars().count() > 1 && !word.chars().skip(1).any(char::is_uppercase)
+}
+
+/// Dictionary files looked for, most specific first
+fn system_dictionaries() -> Vec<PathBuf> {
+    let locale = std::env::var("DICTIONARY")
+        .or_else(|_| std::env::var("LANG"))
+        .ok()
+        .and_then(|lang| lang.split('.').next().map(str::to_string))
+        .filter(|lang| !lang.is_empty() && lang != "C" && lang != "POSIX");
+    let mut names: Vec<String> = locale.into_iter().collect();
+    names.push("en_US".to_string());
+
+    let mut paths = Vec::new();
+    for name in &names {
+        for dir in ["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"] {
+            paths.push(Path::new(dir).join(format!("{}.dic", name)));
+        }
+    }
+    paths.push(PathBuf::from("/usr/share/dict/words"));
+    paths
+}
+
+/// Underline misspelled words in red, keeping the rest of each span's style
+pub fn apply_spell_highlight(document: &mut Document, dictionary: &Dictionary) {
+    for line in &mut document.lines {
+        let misspelled = dictionary.misspellings(&line.text());
+        if misspelled.is_empty() {
+            continue;
+        }
+
+        let mut spans = Vec::with_capacity(line.spans.len() + misspelled.len() * 2);
+        let mut span_start = 0;
+        for span in &line.spans {
+            let span_end = span_start + span.text.len();
+            let mut last = 0;
+            for &(start, end) in &misspelled {
+                if end <= span_start || start >= span_end {
+                    continue;
+                }
+                let from = start.saturating_sub(span_start).max(last);
+                let to = (end - span_start).min(span.text.len());
+                if from > last {
+                    spans.push(StyledSpan::new(&span.text[last..from], span.style.clone()));
+                }
+                let mut style = span.style.clone().underline();
+                style.fg = Some(Color::Red);
+                spans.push(StyledSpan::new(&span.text[from..to], style));
+                last = to;
+            }
+            if last < span.text.len() {
+                spans.push(StyledSpan::new(&span.text[last..], span.style.clone()));
+            }
+            span_start = span_end;
+        }
+        line.spans = spans;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::SpanStyle;
+
+    fn dictionary() -> Dictionary {
+        Dictionary::parse("5\nthe/S\ncat/MS\nsit/S\nhappy/UT\nmat\n")
+    }
+
+    #[test]
+    fn test_contains_stems() {
+        let dictionary = dictionary();
+        assert!(dictionary.contains("The"));
+        assert!(dictionary.contains("cats"));
+        assert!(dictionary.contains("cat's"));
+        assert!(dictionary.contains("sits"));
+        assert!(!dictionary.contains("dgo"));
+    }
+
+    #[test]
+    fn test_misspellings_skip_identifiers() {
+        let dictionary = dictionary();
+        let text = "the catt sits on src/mat.rs, NASA and camelCase x";
+        let ranges = dictionary.misspellings(text);
+        let words: Vec<&str> = ranges.iter().map(|&(s, e)| &text[s..e]).collect();
+        assert_eq!(words, vec!["catt", "on", "and"]);
+    }
+
+    #[test]
+    fn test_highlight_keeps_styles() {
+        let mut document = Document::from_text("-", "t.md".to_string(), "UTF-8".to_string());
+        document.lines[0].spans = vec![
+            StyledSpan::new("the ca", SpanStyle::new().bold()),
+            StyledSpan::new("tt mat", SpanStyle::new()),
+        ];
+        apply_spell_highlight(&mut document, &dictionary());
+
+        let spans = &document.lines[0].spans;
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["the ", "ca", "tt", " mat"]);
+        assert!(spans[1].style.underline && spans[1].style.bold);
+        assert_eq!(spans[2].style.fg, Some(Color::Red));
+        assert!(!spans[3].style.underline);
+    }
+}
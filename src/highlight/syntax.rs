@@ -1,28 +1,34 @@
 use once_cell::sync::Lazy;
 use ratatui::style::Color;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style as SyntectStyle, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::Style as SyntectStyle;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
 
 use crate::display::{Document, SpanStyle, StyledSpan};
+use crate::error::MatError;
+use crate::input::strip_compression_suffix;
 use crate::theme::Theme;
 
+use super::themes::THEME_SET;
+use super::user_syntaxes::load_user_syntaxes;
+
 /// Precompiled syntax set (built at compile time with custom syntaxes)
 static SYNTAX_SET_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/syntax_set.packdump"));
 
-/// Lazily loaded syntax set
-static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(|| {
-    syntect::dumps::from_uncompressed_data(SYNTAX_SET_DATA).expect("Failed to load syntax set")
-});
+/// Lazily loaded syntax set, with the user's own syntaxes added when there are any
+static SYNTAX_SET: Lazy<SyntaxSet> =
+    Lazy::new(|| load_user_syntaxes(bundled_syntax_set).unwrap_or_else(bundled_syntax_set));
 
-/// Lazily loaded theme set
-static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+fn bundled_syntax_set() -> SyntaxSet {
+    syntect::dumps::from_uncompressed_data(SYNTAX_SET_DATA).expect("Failed to load syntax set")
+}
 
 /// Get the appropriate syntect theme name for our theme
 fn syntect_theme_name(theme: Theme) -> &'static str {
     match theme {
         Theme::Light => "base16-ocean.light",
         Theme::Dark => "base16-ocean.dark",
+        Theme::Named { name, .. } => name,
     }
 }
 
@@ -42,9 +48,9 @@ fn syntect_to_span_style(style: SyntectStyle) -> SpanStyle {
     }
 }
 
-/// Detect language from file extension
+/// Detect language from file extension (of the name without `.gz` and the like)
 pub fn detect_language(filename: &str) -> Option<&'static str> {
-    let extension = filename.rsplit('.').next()?;
+    let extension = strip_compression_suffix(filename).rsplit('.').next()?;
 
     // Map common extensions to syntect names
     match extension.to_lowercase().as_str() {
@@ -87,7 +93,11 @@ pub fn detect_language(filename: &str) -> Option<&'static str> {
         "cs" => Some("C#"),
         "vb" => Some("Visual Basic"),
         "ps1" | "psm1" => Some("PowerShell"),
-        "dockerfile" => Some("Dockerfile"),
+        "dockerfile" | "containerfile" => Some("Dockerfile"),
+        // Templates are HTML with tags of their own
+        "hbs" | "handlebars" | "mustache" | "j2" | "jinja" | "jinja2" | "njk" | "twig" | "liquid" | "ejs" => {
+            Some("HTML")
+        }
         "makefile" | "mk" => Some("Makefile"),
         "cmake" => Some("CMake"),
         "tf" => Some("Terraform"),
@@ -99,59 +109,114 @@ pub fn detect_language(filename: &str) -> Option<&'static str> {
     }
 }
 
-/// Apply syntax highlighting to a document
-pub fn apply_syntax_highlight(document: &mut Document, language: Option<&str>, theme: Theme) {
+/// Syntax for a document: the explicit language, else one detected from the file name
+pub(super) fn find_syntax(language: Option<&str>, source_name: &str) -> Option<&'static SyntaxReference> {
     let syntax_set = &*SYNTAX_SET;
-    let theme_set = &*THEME_SET;
-
-    // Try to find the syntax
-    let syntax = if let Some(lang) = language {
+    if let Some(lang) = language {
         // Try explicit language first
         syntax_set
             .find_syntax_by_name(lang)
             .or_else(|| syntax_set.find_syntax_by_extension(lang))
+            .or_else(|| syntax_set.find_syntax_by_token(lang))
     } else {
         // Try to detect from filename
-        detect_language(&document.source_name)
+        detect_language(source_name)
             .and_then(|lang| syntax_set.find_syntax_by_name(lang))
             .or_else(|| {
                 // Try extension directly
-                let ext = document.source_name.rsplit('.').next().unwrap_or("");
+                let ext = strip_compression_suffix(source_name).rsplit('.').next().unwrap_or("");
                 syntax_set.find_syntax_by_extension(ext)
             })
-    };
+    }
+}
+
+/// Syntax by name (case-insensitive) or file extension, e.g. `SQL` or `sh`
+pub(super) fn syntax_by_token(token: &str) -> Option<&'static SyntaxReference> {
+    SYNTAX_SET.find_syntax_by_token(token)
+}
+
+/// Line highlighter for `syntax` in the syntect theme matching `theme`
+pub(super) fn highlighter(syntax: &'static SyntaxReference, theme: Theme) -> Option<HighlightLines<'static>> {
+    let theme = THEME_SET.themes.get(syntect_theme_name(theme))?;
+    Some(HighlightLines::new(syntax, theme))
+}
+
+/// Highlight the next line of text, continuing the highlighter's parse state
+pub(super) fn highlight_spans(highlighter: &mut HighlightLines, text: &str) -> Option<Vec<StyledSpan>> {
+    let ranges = highlighter.highlight_line(text, &SYNTAX_SET).ok()?;
+    Some(
+        ranges
+            .into_iter()
+            .map(|(style, text)| StyledSpan::new(text, syntect_to_span_style(style)))
+            .collect(),
+    )
+}
+
+/// Spans of each line of `text` highlighted as `language`, if that syntax exists
+pub fn highlight_code(text: &str, language: &str, theme: Theme) -> Option<Vec<Vec<StyledSpan>>> {
+    let mut highlighter = highlighter(syntax_by_token(language)?, theme)?;
+    text.lines().map(|line| highlight_spans(&mut highlighter, line)).collect()
+}
 
-    let syntax = match syntax {
+/// Name and file extensions of every syntax, sorted by name
+///
+/// Hidden syntaxes, which only other syntaxes embed, are left out.
+pub fn language_list() -> Vec<(String, Vec<String>)> {
+    let mut languages: Vec<(String, Vec<String>)> = SYNTAX_SET
+        .syntaxes()
+        .iter()
+        .filter(|syntax| !syntax.hidden)
+        .map(|syntax| (syntax.name.clone(), syntax.file_extensions.clone()))
+        .collect();
+    languages.sort_by_key(|(name, _)| name.to_lowercase());
+    languages
+}
+
+/// Print every syntax with its file extensions, one per line
+pub fn print_languages() -> Result<(), MatError> {
+    let languages = language_list();
+    let width = languages.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (name, extensions) in languages {
+        let line = format!("{:<width$}  {}", name, extensions.join(", "), width = width);
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+/// Apply syntax highlighting to a document
+pub fn apply_syntax_highlight(document: &mut Document, language: Option<&str>, theme: Theme) {
+    let syntax = match find_syntax(language, &document.source_name) {
         Some(s) => s,
-        None => return, // No syntax found, leave document as-is
+        None => {
+            // No syntax found, leave document as-is
+            match language {
+                Some(language) => log::info!("{}: not highlighted, no syntax named {}", document.source_name, language),
+                None => log::info!("{}: not highlighted, no syntax for its name", document.source_name),
+            }
+            return;
+        }
     };
 
-    let theme_name = syntect_theme_name(theme);
-    let theme = match theme_set.themes.get(theme_name) {
-        Some(t) => t,
-        None => return, // Theme not found
+    let mut highlighter = match highlighter(syntax, theme) {
+        Some(h) => h,
+        None => {
+            log::warn!("no highlighting theme for {:?}", theme);
+            return;
+        }
     };
-
-    let mut highlighter = HighlightLines::new(syntax, theme);
+    let how = if language.is_some() { "as given" } else { "detected from its name" };
+    log::info!("{}: highlighted as {} ({})", document.source_name, syntax.name, how);
 
     for line in &mut document.lines {
         let text = line.text();
 
-        match highlighter.highlight_line(&text, syntax_set) {
-            Ok(ranges) => {
-                let spans: Vec<StyledSpan> = ranges
-                    .into_iter()
-                    .map(|(style, text)| {
-                        StyledSpan::new(text, syntect_to_span_style(style))
-                    })
-                    .collect();
-
-                if !spans.is_empty() {
-                    line.spans = spans;
-                }
-            }
-            Err(_) => {
-                // On error, leave the line as-is
+        // On error, leave the line as-is
+        if let Some(spans) = highlight_spans(&mut highlighter, &text) {
+            if !spans.is_empty() {
+                line.spans = spans;
             }
         }
     }
@@ -161,6 +226,24 @@ pub fn apply_syntax_highlight(document: &mut Document, language: Option<&str>, t
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bundled_set_is_the_build_packdump() {
+        // TOML only exists in assets/syntaxes, so finding it proves the packdump is used
+        let bundled = bundled_syntax_set();
+        assert!(bundled.find_syntax_by_name("TOML").is_some());
+        assert!(bundled.find_syntax_by_name("Bash").is_some());
+    }
+
+    #[test]
+    fn test_language_list() {
+        let languages = language_list();
+        let rust = languages.iter().find(|(name, _)| name == "Rust").unwrap();
+        assert!(rust.1.contains(&"rs".to_string()));
+        assert!(languages.iter().any(|(name, _)| name == "TOML"));
+        let names: Vec<String> = languages.iter().map(|(name, _)| name.to_lowercase()).collect();
+        assert!(names.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
     #[test]
     fn test_detect_language() {
         assert_eq!(detect_language("main.rs"), Some("Rust"));
@@ -179,7 +262,7 @@ mod tests {
 
         // After highlighting, spans should be modified
         // The exact styling depends on syntect, but we can verify spans exist
-        assert!(doc.lines[0].spans.len() > 0);
+        assert!(!doc.lines[0].spans.is_empty());
     }
 
     #[test]
@@ -191,7 +274,7 @@ mod tests {
         apply_syntax_highlight(&mut doc, Some("Python"), Theme::Dark);
 
         // Should have been highlighted
-        assert!(doc.lines[0].spans.len() > 0);
+        assert!(!doc.lines[0].spans.is_empty());
     }
 
     #[test]
@@ -213,7 +296,7 @@ mod tests {
 
         apply_syntax_highlight(&mut doc, None, Theme::Dark);
 
-        assert!(doc.lines[0].spans.len() > 0, "Bash highlighting should produce spans");
+        assert!(!doc.lines[0].spans.is_empty(), "Bash highlighting should produce spans");
     }
 
     #[test]
@@ -223,6 +306,6 @@ mod tests {
 
         apply_syntax_highlight(&mut doc, None, Theme::Dark);
 
-        assert!(doc.lines[0].spans.len() > 0, "TOML highlighting should produce spans");
+        assert!(!doc.lines[0].spans.is_empty(), "TOML highlighting should produce spans");
     }
 }
@@ -0,0 +1,176 @@
+//! Syntax highlighting color themes (`--theme NAME`, `--list-themes`)
+//!
+//! Besides `light` and `dark` (the base16 ocean themes, picked by the
+//! terminal's background), every theme bundled with syntect can be chosen by
+//! name, and so can `.tmTheme` files under `~/.config/mat/themes/`, named by
+//! their file name without the extension. That is the format bat uses, so its
+//! theme files can be copied over as they are. Names match however they are
+//! written: `solarized-dark` finds `Solarized (dark)`, and bat's `GitHub`
+//! finds `InspiredGitHub`.
+
+use std::fs;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use ratatui::style::Color;
+use syntect::highlighting::{Theme as SyntectTheme, ThemeSet};
+
+use crate::config::config_dir;
+use crate::display::{Line, StyledSpan};
+use crate::error::MatError;
+use crate::theme::Theme;
+
+use super::highlight_code;
+
+/// The bundled themes, with the user's added
+pub(super) static THEME_SET: Lazy<ThemeSet> = Lazy::new(|| {
+    let mut set = ThemeSet::load_defaults();
+    if let Some(dir) = config_dir() {
+        load_themes_from(&dir.join("themes"), &mut set);
+    }
+    set
+});
+
+/// Names bat gives themes that syntect bundles under another name
+const BAT_NAMES: &[(&str, &str)] = &[("github", "InspiredGitHub"), ("base16", "base16-ocean.dark")];
+
+/// Code shown in each theme by `--list-themes`
+const PREVIEW: &str = "// Greet everyone\nfn greet(names: &[&str]) -> usize {\n    for name in names {\n        println!(\"Hello, {}!\", name);\n    }\n    names.len() * 2\n}";
+
+/// Add the `.tmTheme` files in `dir` to `set`, named by their file stems
+fn load_themes_from(dir: &Path, set: &mut ThemeSet) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tmTheme")))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        match ThemeSet::get_theme(&path) {
+            Ok(theme) => {
+                log::debug!("loaded theme {} from {}", name, path.display());
+                set.themes.insert(name, theme);
+            }
+            // A broken theme leaves the others working
+            Err(e) => log::warn!("ignoring theme {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// `name` without case, spaces or punctuation, so `Solarized (dark)` is `solarizeddark`
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// The theme named `name` in `set`, written any way
+fn find_in<'a>(set: &'a ThemeSet, name: &str) -> Option<(&'a str, &'a SyntectTheme)> {
+    let wanted = normalize(name);
+    let wanted = BAT_NAMES
+        .iter()
+        .find(|(bat, _)| *bat == wanted)
+        .map_or(wanted, |(_, syntect)| normalize(syntect));
+    set.themes
+        .iter()
+        .find(|(name, _)| normalize(name) == wanted)
+        .map(|(name, theme)| (name.as_str(), theme))
+}
+
+/// Whether `theme` has a light background, so the pager's own colors can match
+fn is_light(theme: &SyntectTheme) -> bool {
+    theme.settings.background.is_some_and(|c| {
+        let luminance = 0.2126 * c.r as f64 + 0.7152 * c.g as f64 + 0.0722 * c.b as f64;
+        luminance > 127.5
+    })
+}
+
+/// The theme named `name`, bundled or the user's
+pub fn find_theme(name: &str) -> Option<Theme> {
+    let (name, theme) = find_in(&THEME_SET, name)?;
+    Some(Theme::Named {
+        name,
+        light: is_light(theme),
+    })
+}
+
+/// Print the name of every theme, each with the preview code highlighted in it when `color` is set
+pub fn print_themes(color: bool) -> Result<(), MatError> {
+    let mut out = String::new();
+    for (name, theme) in &THEME_SET.themes {
+        let light = is_light(theme);
+        let shade = if light { "light" } else { "dark" };
+        if !color {
+            out.push_str(&format!("{} ({})\n", name, shade));
+            continue;
+        }
+        out.push_str(&format!("\x1b[1m{}\x1b[0m ({})\n", name, shade));
+        // On the theme's own background, padded into a block
+        let background = theme.settings.background.map(|c| Color::Rgb(c.r, c.g, c.b));
+        let width = PREVIEW.lines().map(str::len).max().unwrap_or(0) + 2;
+        for spans in highlight_code(PREVIEW, "Rust", Theme::Named { name, light }).unwrap_or_default() {
+            let mut line = Line::plain(0, " ");
+            line.spans.extend(spans);
+            line.spans.push(StyledSpan::plain(" ".repeat(width.saturating_sub(line.width()))));
+            for span in &mut line.spans {
+                span.style.bg = background;
+            }
+            out.push_str(&format!("    {}\n", line.ansi_text()));
+        }
+        out.push('\n');
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_theme_by_any_spelling() {
+        assert_eq!(
+            find_theme("solarized-dark"),
+            Some(Theme::Named {
+                name: "Solarized (dark)",
+                light: false
+            })
+        );
+        assert_eq!(
+            find_theme("GitHub"),
+            Some(Theme::Named {
+                name: "InspiredGitHub",
+                light: true
+            })
+        );
+        assert!(find_theme("Base16-Eighties.Dark").is_some());
+        assert_eq!(find_theme("no such theme"), None);
+    }
+
+    #[test]
+    fn test_load_user_themes() {
+        let dir = tempfile::tempdir().unwrap();
+        let plist = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><plist version="1.0"><dict>"#,
+            r#"<key>name</key><string>Paper</string><key>settings</key><array>"#,
+            r#"<dict><key>settings</key><dict><key>background</key><string>#FAFAF0</string>"#,
+            r#"<key>foreground</key><string>#202020</string></dict></dict>"#,
+            r#"<dict><key>scope</key><string>comment</string><key>settings</key><dict>"#,
+            r#"<key>foreground</key><string>#808080</string></dict></dict>"#,
+            r#"</array></dict></plist>"#
+        );
+        fs::write(dir.path().join("my-paper.tmTheme"), plist).unwrap();
+        fs::write(dir.path().join("broken.tmTheme"), "not a plist").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+
+        let mut set = ThemeSet::default();
+        load_themes_from(dir.path(), &mut set);
+        assert_eq!(set.themes.keys().collect::<Vec<_>>(), vec!["my-paper"]);
+        let (name, theme) = find_in(&set, "My Paper").unwrap();
+        assert_eq!(name, "my-paper");
+        assert!(is_light(theme));
+    }
+}
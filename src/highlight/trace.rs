@@ -0,0 +1,379 @@
+//! Stack trace detection and styling
+//!
+//! Rust panics and backtraces, Java exceptions and Python tracebacks are
+//! recognized line by line in logs. Headers (the panic or exception line) are
+//! shown in bold red, frames inside the runtime or third-party packages are
+//! dimmed, and the `file:line` reference of every frame is underlined, so the
+//! pager can jump between the references and open them in an editor.
+
+use std::ops::Range;
+
+use once_cell::sync::Lazy;
+use ratatui::style::Color;
+use regex::Regex;
+
+use crate::display::{Document, SpanStyle, StyledSpan};
+
+/// `thread 'main' panicked at src/main.rs:10:5:`
+static RUST_PANIC_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"thread '[^']*' panicked at (?P<path>[^\s:]+):(?P<line>\d+)(?::(?P<col>\d+))?").unwrap());
+/// `   4: std::rt::lang_start` (only inside a backtrace)
+static RUST_FRAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*\d+: +(?P<func>\S+)").unwrap());
+/// `             at ./src/main.rs:10:5`
+static RUST_LOCATION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s+at (?P<path>[^\s()]+?):(?P<line>\d+)(?::(?P<col>\d+))?$").unwrap());
+/// `at com.example.Foo.bar(Foo.java:42)`
+static JAVA_FRAME_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s+at (?P<func>[\w$.<>/~-]+)\((?:(?P<path>[\w$.-]+):(?P<line>\d+)|[^)]*)\)").unwrap()
+});
+/// `... 12 more`
+static JAVA_MORE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s+\.\.\. \d+ (?:more|common frames omitted)").unwrap());
+/// `  File "app/models.py", line 42, in save`
+static PYTHON_FRAME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s+File "(?P<path>[^"]+)", line (?P<line>\d+)"#).unwrap());
+/// Java exception, `Caused by:` and Python exception lines, e.g. `java.lang.IllegalStateException: closed`
+static EXCEPTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:Exception in thread .*|Caused by: .*|(?:[\w$]+\.)*[A-Z][\w$]*(?:Error|Exception|Exit|Interrupt)(?::.*)?)$")
+        .unwrap()
+});
+
+/// A qualified exception name anywhere in a line, e.g. `java.io.IOException`
+static EXCEPTION_NAME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(?:[\w$]+\.)+[A-Z][\w$]*(?:Error|Exception)\b").unwrap());
+
+/// Function prefixes of frames inside the runtime (Rust std, the JDK)
+const NOISE_FUNCTIONS: &[&str] = &[
+    "std::", "core::", "alloc::", "<std::", "<core::", "<alloc::", "rust_begin_unwind", "__rust", "_start",
+    "__libc_start", "java.", "javax.", "jdk.", "sun.", "com.sun.", "kotlin.", "scala.", "org.junit.", "junit.",
+];
+/// Path fragments of frames in installed packages (Python, the Rust toolchain and registry)
+const NOISE_PATHS: &[&str] = &["/lib/python", "site-packages", "dist-packages", "<frozen", "/rustc/", "/.cargo/registry/"];
+
+/// A source location a trace line refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReference {
+    pub path: String,
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Column (1-indexed), if given
+    pub column: Option<usize>,
+}
+
+/// Role of a line in a stack trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// The panic or exception line
+    Header,
+    /// A call frame
+    Frame,
+    /// The source line Python prints below a frame
+    Source,
+}
+
+/// A line recognized as part of a stack trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TraceLine {
+    role: Role,
+    /// Whether the frame is inside the runtime or an installed package
+    noise: bool,
+    /// Byte range of the reference in the line, and the reference
+    reference: Option<(Range<usize>, FileReference)>,
+}
+
+/// Recognize the stack trace lines among `lines`
+fn classify(texts: &[&str]) -> Vec<Option<TraceLine>> {
+    let mut in_trace = false;
+    // The previous line, when it is a frame a location or source line may belong to
+    let mut previous: Option<TraceLine> = None;
+    let mut result = Vec::new();
+
+    for text in texts.iter().copied() {
+        let line = classify_line(text, in_trace, previous.as_ref());
+        in_trace = match line {
+            Some(ref line) => line.role != Role::Header || text.trim_end() == "stack backtrace:",
+            None => false,
+        };
+        previous = line.clone().filter(|line| line.role == Role::Frame);
+        result.push(line);
+    }
+
+    // A Java trace starts with the exception line, which may follow a log prefix
+    for i in 1..result.len() {
+        let starts_trace = result[i].as_ref().is_some_and(|line| line.role == Role::Frame) && result[i - 1].is_none();
+        if starts_trace && EXCEPTION_NAME_RE.is_match(texts[i - 1]) {
+            result[i - 1] = Some(TraceLine {
+                role: Role::Header,
+                noise: false,
+                reference: None,
+            });
+        }
+    }
+    result
+}
+
+/// Recognize one line, given the state left by the lines before it
+///
+/// `in_trace` is set below a trace line other than a final header, where the
+/// numbered frames of a Rust backtrace are recognized.
+fn classify_line(text: &str, in_trace: bool, previous: Option<&TraceLine>) -> Option<TraceLine> {
+    if let Some(caps) = RUST_PANIC_RE.captures(text) {
+        return Some(TraceLine {
+            role: Role::Header,
+            noise: false,
+            reference: reference(&caps),
+        });
+    }
+    if text.trim_end() == "stack backtrace:" || text.contains("Traceback (most recent call last):") {
+        return Some(TraceLine {
+            role: Role::Header,
+            noise: false,
+            reference: None,
+        });
+    }
+    if let Some(caps) = JAVA_FRAME_RE.captures(text).or_else(|| PYTHON_FRAME_RE.captures(text)) {
+        let reference = reference(&caps);
+        let noise = caps.name("func").is_some_and(|f| is_noise_function(f.as_str()))
+            || reference.as_ref().is_some_and(|(_, r)| is_noise_path(&r.path));
+        return Some(TraceLine {
+            role: Role::Frame,
+            noise,
+            reference,
+        });
+    }
+    if let Some(caps) = RUST_LOCATION_RE.captures(text) {
+        let reference = reference(&caps);
+        // The location belongs to the frame above it
+        let noise = previous.is_some_and(|p| p.noise) || reference.as_ref().is_some_and(|(_, r)| is_noise_path(&r.path));
+        return Some(TraceLine {
+            role: Role::Frame,
+            noise,
+            reference,
+        });
+    }
+    if JAVA_MORE_RE.is_match(text) {
+        return Some(TraceLine {
+            role: Role::Frame,
+            noise: true,
+            reference: None,
+        });
+    }
+    if in_trace {
+        if let Some(caps) = RUST_FRAME_RE.captures(text) {
+            return Some(TraceLine {
+                role: Role::Frame,
+                noise: is_noise_function(&caps["func"]),
+                reference: None,
+            });
+        }
+    }
+    if let Some(frame) = previous {
+        // Python shows the source line of each frame, indented below it
+        if text.starts_with("    ") && !text.trim().is_empty() {
+            return Some(TraceLine {
+                role: Role::Source,
+                noise: frame.noise,
+                reference: None,
+            });
+        }
+    }
+    // The exception ending a Python traceback, or a `Caused by:` of a Java one
+    if (in_trace && EXCEPTION_RE.is_match(text)) || text.starts_with("Caused by: ") {
+        return Some(TraceLine {
+            role: Role::Header,
+            noise: false,
+            reference: None,
+        });
+    }
+    None
+}
+
+/// The reference captured as `path`, `line` and `col`, with its byte range
+fn reference(caps: &regex::Captures) -> Option<(Range<usize>, FileReference)> {
+    let path = caps.name("path")?;
+    let line = caps.name("line")?;
+    let column = caps.name("col");
+    let end = column.map_or(line.end(), |c| c.end());
+    Some((
+        path.start()..end,
+        FileReference {
+            path: path.as_str().to_string(),
+            line: line.as_str().parse().ok()?,
+            column: column.and_then(|c| c.as_str().parse().ok()),
+        },
+    ))
+}
+
+fn is_noise_function(function: &str) -> bool {
+    NOISE_FUNCTIONS.iter().any(|prefix| function.starts_with(prefix))
+}
+
+fn is_noise_path(path: &str) -> bool {
+    NOISE_PATHS.iter().any(|fragment| path.contains(fragment))
+}
+
+/// Text of each document line, with continuation chunks of long lines left blank
+fn line_texts(document: &Document) -> Vec<String> {
+    document
+        .lines
+        .iter()
+        .map(|l| if l.continuation { String::new() } else { l.text() })
+        .collect()
+}
+
+/// Restyle the stack trace lines of a document
+pub fn apply_trace_highlight(document: &mut Document) {
+    let texts = line_texts(document);
+    let classified = classify(&texts.iter().map(String::as_str).collect::<Vec<_>>());
+    let header_style = SpanStyle::new().fg(Color::LightRed).bold();
+    let noise_style = SpanStyle::new().fg(Color::DarkGray);
+    let reference_style = SpanStyle::new().fg(Color::Cyan).underline();
+
+    for ((line, text), trace) in document.lines.iter_mut().zip(&texts).zip(classified) {
+        let Some(trace) = trace else {
+            continue;
+        };
+        let base = match trace.role {
+            Role::Header => header_style.clone(),
+            _ if trace.noise => noise_style.clone(),
+            // Frames of the application keep their styling, apart from the reference
+            _ if trace.reference.is_none() => continue,
+            _ => SpanStyle::new(),
+        };
+        line.spans = match trace.reference {
+            Some((range, _)) if !trace.noise => vec![
+                StyledSpan::new(&text[..range.start], base.clone()),
+                StyledSpan::new(&text[range.clone()], reference_style.clone()),
+                StyledSpan::new(&text[range.end..], base),
+            ]
+            .into_iter()
+            .filter(|span| !span.text.is_empty())
+            .collect(),
+            _ => vec![StyledSpan::new(text.as_str(), base)],
+        };
+    }
+}
+
+/// Index and reference of every stack trace line referring to a source location
+pub fn trace_references(document: &Document) -> Vec<(usize, FileReference)> {
+    let texts = line_texts(document);
+    classify(&texts.iter().map(String::as_str).collect::<Vec<_>>())
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, trace)| trace.and_then(|t| t.reference).map(|(_, reference)| (i, reference)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roles(text: &str) -> Vec<Option<(Role, bool)>> {
+        classify(&text.lines().collect::<Vec<_>>())
+            .into_iter()
+            .map(|line| line.map(|l| (l.role, l.noise)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_panic_and_backtrace() {
+        let log = "\
+thread 'main' panicked at src/main.rs:10:5:
+called `Option::unwrap()` on a `None` value
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/abc/library/std/src/panicking.rs:665:5
+   1: mat::run
+             at ./src/main.rs:42:9
+note: run with `RUST_BACKTRACE=full` for a verbose backtrace.";
+        assert_eq!(
+            roles(log),
+            vec![
+                Some((Role::Header, false)),
+                None,
+                Some((Role::Header, false)),
+                Some((Role::Frame, true)),
+                Some((Role::Frame, true)),
+                Some((Role::Frame, false)),
+                Some((Role::Frame, false)),
+                None,
+            ]
+        );
+        let doc = Document::from_text(log, "crash.log".to_string(), "UTF-8".to_string());
+        let references = trace_references(&doc);
+        assert_eq!(references[0].0, 0);
+        assert_eq!(
+            references[0].1,
+            FileReference {
+                path: "src/main.rs".to_string(),
+                line: 10,
+                column: Some(5)
+            }
+        );
+        assert_eq!(references.last().map(|(i, r)| (*i, r.line)), Some((6, 42)));
+    }
+
+    #[test]
+    fn test_java_exception() {
+        let log = "\
+2024-05-01 12:00:00 ERROR request failed
+java.lang.IllegalStateException: connection closed
+\tat com.example.db.Pool.take(Pool.java:88)
+\tat java.base/java.lang.Thread.run(Thread.java:829)
+Caused by: java.io.IOException: reset
+\t... 3 more
+2024-05-01 12:00:01 INFO retrying";
+        assert_eq!(
+            roles(log),
+            vec![
+                None,
+                Some((Role::Header, false)),
+                Some((Role::Frame, false)),
+                Some((Role::Frame, true)),
+                Some((Role::Header, false)),
+                Some((Role::Frame, true)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_traceback() {
+        let log = "\
+Traceback (most recent call last):
+  File \"/usr/lib/python3.12/runpy.py\", line 198, in _run_module_as_main
+    return _run_code(code, main_globals, None,
+  File \"app/models.py\", line 42, in save
+    self.validate()
+ValueError: name is required";
+        assert_eq!(
+            roles(log),
+            vec![
+                Some((Role::Header, false)),
+                Some((Role::Frame, true)),
+                Some((Role::Source, true)),
+                Some((Role::Frame, false)),
+                Some((Role::Source, false)),
+                Some((Role::Header, false)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_underlines_references() {
+        let mut doc = Document::from_text(
+            "Traceback (most recent call last):\n  File \"app.py\", line 3, in main\nplain line",
+            "err.log".to_string(),
+            "UTF-8".to_string(),
+        );
+        apply_trace_highlight(&mut doc);
+        assert!(doc.lines[0].spans[0].style.bold);
+        let underlined: Vec<&str> = doc.lines[1]
+            .spans
+            .iter()
+            .filter(|s| s.style.underline)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(underlined, vec!["app.py\", line 3"]);
+        assert!(doc.lines[2].spans[0].style.is_plain());
+    }
+}
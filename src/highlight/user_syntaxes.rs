@@ -0,0 +1,128 @@
+//! Syntaxes added by the user at runtime
+//!
+//! `.sublime-syntax` files under `~/.config/mat/syntaxes/` are compiled on top
+//! of the bundled syntax set. Compiling is slow, so the result is dumped to
+//! `~/.cache/mat/syntaxes-<key>.packdump`, where the key changes whenever a
+//! grammar file does (or mat is upgraded), and reused until then.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use syntect::parsing::SyntaxSet;
+
+use crate::config::{cache_dir, config_dir};
+use crate::sandbox::exec_allowed;
+
+/// The bundled syntaxes plus those in the user's syntax directory, if there are any
+pub(super) fn load_user_syntaxes(bundled: impl FnOnce() -> SyntaxSet) -> Option<SyntaxSet> {
+    let dir = config_dir()?.join("syntaxes");
+    // The cache is written to, which read-only mode forbids
+    let cache = cache_dir().filter(|_| exec_allowed());
+    load_from(&dir, cache.as_deref(), bundled)
+}
+
+/// Build (or read back from `cache`) the bundled syntaxes extended with the grammars in `dir`
+fn load_from(dir: &Path, cache: Option<&Path>, bundled: impl FnOnce() -> SyntaxSet) -> Option<SyntaxSet> {
+    let files = syntax_files(dir);
+    if files.is_empty() {
+        return None;
+    }
+    let dump = cache.map(|cache| cache.join(format!("syntaxes-{:016x}.packdump", cache_key(&files))));
+    if let Some(set) = dump.as_ref().and_then(|path| syntect::dumps::from_uncompressed_dump_file(path).ok()) {
+        return Some(set);
+    }
+
+    let mut builder = bundled().into_builder();
+    if let Err(e) = builder.add_from_folder(dir, true) {
+        // A broken grammar leaves the bundled syntaxes working
+        log::warn!("ignoring syntaxes in {}: {}", dir.display(), e);
+        return None;
+    }
+    let set = builder.build();
+
+    // A failure to cache only costs a rebuild next time
+    if let (Some(cache), Some(dump)) = (cache, dump) {
+        remove_stale_dumps(cache);
+        let _ = fs::create_dir_all(cache).map(|_| syntect::dumps::dump_to_uncompressed_file(&set, &dump));
+    }
+    Some(set)
+}
+
+/// `.sublime-syntax` files under `dir`, sorted so the cache key is stable
+fn syntax_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            files.extend(syntax_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "sublime-syntax") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Hash of the mat version and each grammar file's path, size and modification time
+fn cache_key(files: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    for file in files {
+        file.hash(&mut hasher);
+        if let Ok(metadata) = fs::metadata(file) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().and_then(|m| m.duration_since(UNIX_EPOCH).ok()).hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Delete the dumps of earlier versions of the grammars
+fn remove_stale_dumps(cache: &Path) {
+    let Ok(entries) = fs::read_dir(cache) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with("syntaxes-") && name.ends_with(".packdump") {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAMMAR: &str = "%YAML 1.2\n---\nname: Frob\nfile_extensions: [frob]\nscope: source.frob\n\
+                           contexts:\n  main:\n    - match: '\\bfrob\\b'\n      scope: keyword.control.frob\n";
+
+    #[test]
+    fn test_user_syntax_is_added_and_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("nested/frob.sublime-syntax"), GRAMMAR).unwrap();
+
+        let set = load_from(dir.path(), Some(cache.path()), SyntaxSet::new).unwrap();
+        assert!(set.find_syntax_by_extension("frob").is_some());
+        let dumps: Vec<_> = fs::read_dir(cache.path()).unwrap().flatten().collect();
+        assert_eq!(dumps.len(), 1);
+
+        // The second load reads the dump instead of compiling
+        let set = load_from(dir.path(), Some(cache.path()), || panic!("should use the cache")).unwrap();
+        assert!(set.find_syntax_by_name("Frob").is_some());
+    }
+
+    #[test]
+    fn test_no_user_syntaxes() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_from(dir.path(), None, SyntaxSet::new).is_none());
+        assert!(load_from(&dir.path().join("missing"), None, SyntaxSet::new).is_none());
+    }
+}
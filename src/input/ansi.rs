@@ -0,0 +1,122 @@
+//! Colors of piped input (`--ansi`)
+//!
+//! Programs like `git diff --color` and `rg --color=always` color their output
+//! with SGR escape sequences (`ESC [ 31 m`). These are turned into the styles
+//! of [`StyledSpan`]s, so the pager draws the colors instead of the escapes.
+//! Other escape sequences (cursor movement, OSC 8 hyperlinks) are dropped.
+
+use crate::display::{apply_sgr, Document, SpanStyle, StyledSpan};
+
+/// Replace the escape sequences in every line of `document` by span styles,
+/// returning whether there were any
+///
+/// A style set on one line carries over to the next until it is reset, as it
+/// does in a terminal.
+pub fn apply_ansi_styles(document: &mut Document) -> bool {
+    let mut style = SpanStyle::default();
+    let mut found = false;
+    for line in &mut document.lines {
+        let text = line.text();
+        let escaped = text.contains('\x1b');
+        if escaped || style != SpanStyle::default() {
+            line.spans = parse_ansi(&text, &mut style);
+        }
+        found |= escaped;
+    }
+    document.recalculate_max_width();
+    found
+}
+
+/// Split `text` into spans styled by its SGR sequences, starting in `style`
+///
+/// `style` is left as it is at the end of the text.
+pub fn parse_ansi(text: &str, style: &mut SpanStyle) -> Vec<StyledSpan> {
+    let mut spans: Vec<StyledSpan> = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            current.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte, which is `m` for SGR
+            Some('[') => {
+                let mut params = String::new();
+                let mut command = None;
+                for next in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&next) {
+                        command = Some(next);
+                        break;
+                    }
+                    params.push(next);
+                }
+                if command == Some('m') {
+                    // A malformed parameter drops the rest of the sequence, as terminals do
+                    let mut next_style = style.clone();
+                    let _ = apply_sgr(&params, &mut next_style);
+                    if next_style != *style {
+                        push_span(&mut spans, std::mem::take(&mut current), style);
+                        *style = next_style;
+                    }
+                }
+            }
+            // OSC: up to BEL or ESC \
+            Some(']') => {
+                while let Some(next) = chars.next() {
+                    if next == '\x07' {
+                        break;
+                    }
+                    if next == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    push_span(&mut spans, current, style);
+    if spans.is_empty() {
+        spans.push(StyledSpan::plain(""));
+    }
+    spans
+}
+
+/// Add `text` in `style`, unless it is empty
+fn push_span(spans: &mut Vec<StyledSpan>, text: String, style: &SpanStyle) {
+    if !text.is_empty() {
+        spans.push(StyledSpan::new(text, style.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_parse_sgr_colors() {
+        let mut style = SpanStyle::default();
+        let spans = parse_ansi("\x1b[1;31m-old\x1b[0m kept \x1b[38;5;208mx\x1b[38:2::1:2:3my\x1b[m", &mut style);
+
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans[0], StyledSpan::new("-old", SpanStyle::new().fg(Color::Red).bold()));
+        assert_eq!(spans[1], StyledSpan::plain(" kept "));
+        assert_eq!(spans[2].style.fg, Some(Color::Indexed(208)));
+        assert_eq!(spans[3].style.fg, Some(Color::Rgb(1, 2, 3)));
+        assert_eq!(style, SpanStyle::default());
+    }
+
+    #[test]
+    fn test_drops_other_sequences_and_carries_style() {
+        let text = "\x1b]8;;file:///a.rs\x1b\\a.rs\x1b]8;;\x1b\\ \x1b[2K\x1b[32mgreen\nstill green\x1b[39m";
+        let mut document = Document::from_text(text, "stdin".to_string(), "UTF-8".to_string());
+        assert!(apply_ansi_styles(&mut document));
+
+        assert_eq!(document.lines[0].text(), "a.rs green");
+        assert_eq!(document.lines[1].spans, vec![StyledSpan::new("still green", SpanStyle::new().fg(Color::Green))]);
+        assert_eq!(document.max_line_width, 11);
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::OnceCell;
+
+use crate::display::glyphs::glyphs;
+use crate::error::EXIT_INTERRUPTED;
+
+/// Size of each read while loading input
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Delay before a progress line is shown (fast loads stay silent)
+const PROGRESS_DELAY: Duration = Duration::from_millis(250);
+
+/// Set by the Ctrl+C handler while input is loading
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a load is in progress, so that Ctrl+C cancels it instead of stopping mat
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+/// Guards one-time installation of the signal handler
+static HANDLER: OnceCell<bool> = OnceCell::new();
+
+/// A load that Ctrl+C cancels, for as long as it is alive
+///
+/// The handler can't be uninstalled, so outside a load it stops mat the way
+/// the default one would. Once the pager puts the terminal into raw mode
+/// Ctrl+C arrives as a key event instead.
+pub struct Loading(());
+
+impl Loading {
+    /// Start a load, forgetting the interrupt of an earlier one
+    pub fn start() -> Self {
+        HANDLER.get_or_init(|| ctrlc::set_handler(on_interrupt).is_ok());
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        LOADING.store(true, Ordering::SeqCst);
+        Loading(())
+    }
+
+    /// The flag Ctrl+C sets to cancel this load
+    pub fn cancel_flag(&self) -> &'static AtomicBool {
+        &INTERRUPTED
+    }
+}
+
+impl Drop for Loading {
+    fn drop(&mut self) {
+        LOADING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn on_interrupt() {
+    if LOADING.load(Ordering::SeqCst) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    } else {
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+}
+
+/// Result of a cancellable read
+#[derive(Debug)]
+pub struct ReadOutcome {
+    /// Bytes read so far
+    pub bytes: Vec<u8>,
+    /// Whether reading stopped early because of an interrupt
+    pub interrupted: bool,
+}
+
+/// Read everything from `reader` in chunks, stopping early if `cancel` is set
+///
+/// When reading takes noticeably long and stderr is a terminal, a progress
+/// line is shown (with a percentage if `total` is known).
+pub fn read_cancellable<R: Read>(
+    mut reader: R,
+    total: Option<u64>,
+    label: &str,
+    cancel: &AtomicBool,
+) -> io::Result<ReadOutcome> {
+    let mut bytes = Vec::with_capacity(total.unwrap_or(0) as usize);
+    let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+    let started = Instant::now();
+    let show_progress = io::stderr().is_terminal();
+    let mut progress_shown = false;
+
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let n = match reader.read(&mut chunk) {
+            Ok(0) => {
+                clear_progress(progress_shown);
+                return Ok(ReadOutcome {
+                    bytes,
+                    interrupted: false,
+                });
+            }
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                clear_progress(progress_shown);
+                return Err(e);
+            }
+        };
+        bytes.extend_from_slice(&chunk[..n]);
+
+        if show_progress && started.elapsed() >= PROGRESS_DELAY {
+            eprint!("\r{}", progress_line(label, bytes.len() as u64, total));
+            let _ = io::stderr().flush();
+            progress_shown = true;
+        }
+    }
+
+    clear_progress(progress_shown);
+    Ok(ReadOutcome {
+        bytes,
+        interrupted: true,
+    })
+}
+
+/// Format the progress line for a load in progress
+fn progress_line(label: &str, done: u64, total: Option<u64>) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    match total {
+        Some(total) if total > 0 => format!(
+            "mat: loading {}{} {:>3}% ({:.1}/{:.1} MB, Ctrl+C to cancel)",
+            label,
+            glyphs().ellipsis,
+            done * 100 / total,
+            done as f64 / MB,
+            total as f64 / MB
+        ),
+        _ => format!(
+            "mat: loading {}{} {:.1} MB (Ctrl+C to cancel)",
+            label,
+            glyphs().ellipsis,
+            done as f64 / MB
+        ),
+    }
+}
+
+/// Erase the progress line if one was printed
+fn clear_progress(shown: bool) {
+    if shown {
+        eprint!("\r\x1b[2K");
+        let _ = io::stderr().flush();
+    }
+}
+
+/// Drop a trailing incomplete line from a partially read buffer
+pub fn truncate_to_last_line(bytes: &mut Vec<u8>) {
+    match bytes.iter().rposition(|&b| b == b'\n') {
+        Some(pos) => bytes.truncate(pos + 1),
+        None => bytes.clear(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_cancellable_complete() {
+        let cancel = AtomicBool::new(false);
+        let outcome = read_cancellable(Cursor::new(b"hello\nworld\n".to_vec()), None, "test", &cancel).unwrap();
+        assert!(!outcome.interrupted);
+        assert_eq!(outcome.bytes, b"hello\nworld\n");
+    }
+
+    #[test]
+    fn test_read_cancellable_interrupted() {
+        let cancel = AtomicBool::new(true);
+        let outcome = read_cancellable(Cursor::new(b"hello".to_vec()), Some(5), "test", &cancel).unwrap();
+        assert!(outcome.interrupted);
+        assert!(outcome.bytes.is_empty());
+    }
+
+    #[test]
+    fn test_truncate_to_last_line() {
+        let mut bytes = b"one\ntwo\nthr".to_vec();
+        truncate_to_last_line(&mut bytes);
+        assert_eq!(bytes, b"one\ntwo\n");
+
+        let mut bytes = b"partial".to_vec();
+        truncate_to_last_line(&mut bytes);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_progress_line() {
+        let line = progress_line("big.log", 512 * 1024 * 1024, Some(1024 * 1024 * 1024));
+        assert!(line.contains("50%"));
+        assert!(line.contains("big.log"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Compressed inputs
+//!
+//! A file (or stdin) starting with the magic bytes of gzip, xz, zstd or bzip2
+//! is decompressed before the rest of the pipeline, so `app.log.gz` and man
+//! pages can be viewed directly. gzip is handled in-process; the others go
+//! through their command line tools (`xz -dc` and so on).
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use flate2::read::MultiGzDecoder;
+
+use crate::error::MatError;
+use crate::sandbox::{exec_allowed, EXEC_DISABLED};
+
+/// A recognized compression format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Xz,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// The format of `bytes`, from their magic bytes
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x1f\x8b\x08") {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(b"\xfd7zXZ\x00") {
+            Some(Compression::Xz)
+        } else if bytes.starts_with(b"\x28\xb5\x2f\xfd") {
+            Some(Compression::Zstd)
+        } else if bytes.len() >= 5
+            && bytes.starts_with(b"BZh")
+            && (b'1'..=b'9').contains(&bytes[3])
+            // A compressed block, or the end of an empty stream
+            && matches!(bytes[4], 0x31 | 0x17)
+        {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the format, as shown in errors
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+
+    /// Decompress all of `bytes`; `path` is only used in errors
+    pub fn decompress(self, bytes: &[u8], path: &Path) -> Result<Vec<u8>, MatError> {
+        let result = match self {
+            Compression::Gzip => {
+                let mut out = Vec::new();
+                MultiGzDecoder::new(bytes).read_to_end(&mut out).map(|_| out)
+            }
+            Compression::Xz => run_filter("xz", bytes),
+            Compression::Zstd => run_filter("zstd", bytes),
+            Compression::Bzip2 => run_filter("bzip2", bytes),
+        };
+        result.map_err(|e| MatError::Decompress {
+            path: path.to_path_buf(),
+            format: self.name(),
+            message: e.to_string(),
+        })
+    }
+}
+
+/// `name` without a compression extension: `app.log.gz` -> `app.log`
+pub fn strip_compression_suffix(name: &str) -> &str {
+    [".gz", ".xz", ".zst", ".bz2"]
+        .iter()
+        .find_map(|suffix| {
+            let stem = name.len().checked_sub(suffix.len())?;
+            name.get(stem..)
+                .filter(|end| end.eq_ignore_ascii_case(suffix))
+                .map(|_| &name[..stem])
+        })
+        .unwrap_or(name)
+}
+
+/// Pipe `input` through `program -dc` and return what it prints
+fn run_filter(program: &str, input: &[u8]) -> io::Result<Vec<u8>> {
+    if !exec_allowed() {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("`{}` is {}", program, EXEC_DISABLED)));
+    }
+    let mut child = Command::new(program)
+        .arg("-dc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(e.kind(), format!("`{}` is not installed", program)),
+            _ => e,
+        })?;
+
+    // Feed stdin from another thread so a full stdout pipe can't block both sides
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let output = thread::scope(|scope| {
+        scope.spawn(move || {
+            let _ = stdin.write_all(input);
+        });
+        child.wait_with_output()
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().next().unwrap_or("").trim().to_string();
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            if reason.is_empty() { output.status.to_string() } else { reason },
+        ));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(Compression::sniff(b"\x1f\x8b\x08\x00"), Some(Compression::Gzip));
+        assert_eq!(Compression::sniff(b"\xfd7zXZ\x00\x00"), Some(Compression::Xz));
+        assert_eq!(Compression::sniff(b"\x28\xb5\x2f\xfd\x24"), Some(Compression::Zstd));
+        assert_eq!(Compression::sniff(b"BZh91AY&SY"), Some(Compression::Bzip2));
+        // Text that merely starts like bzip2
+        assert_eq!(Compression::sniff(b"BZh9 is not compressed"), None);
+        assert_eq!(Compression::sniff(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_strip_compression_suffix() {
+        assert_eq!(strip_compression_suffix("logs/app.log.gz"), "logs/app.log");
+        assert_eq!(strip_compression_suffix("ls.1.GZ"), "ls.1");
+        assert_eq!(strip_compression_suffix("data.tar.zst"), "data.tar");
+        assert_eq!(strip_compression_suffix("notes.md"), "notes.md");
+        assert_eq!(strip_compression_suffix("é.gz"), "é");
+    }
+
+    #[test]
+    fn test_gzip_roundtrip() {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"line 1\nline 2\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let compression = Compression::sniff(&compressed).unwrap();
+        let bytes = compression.decompress(&compressed, Path::new("a.log.gz")).unwrap();
+        assert_eq!(bytes, b"line 1\nline 2\n");
+
+        let error = compression.decompress(&compressed[..10], Path::new("a.log.gz")).unwrap_err();
+        assert_eq!(error.kind(), "decompress");
+    }
+}
@@ -33,45 +33,65 @@ pub fn detect_encoding(bytes: &[u8]) -> &'static str {
     "Latin-1"
 }
 
+/// What decoding did to the input, shown in the status bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// The input started with a byte order mark
+    pub bom: bool,
+    /// The byte order mark was kept as a U+FEFF character (`--keep-bom`)
+    pub bom_kept: bool,
+    /// Malformed sequences replaced with U+FFFD
+    pub replaced: usize,
+}
+
+impl DecodeStats {
+    /// Status bar name of `encoding`, e.g. "UTF-8 BOM" or "UTF-16LE BOM, 3 replaced"
+    pub fn label(&self, encoding: &str) -> String {
+        let mut label = encoding.trim_end_matches("-BOM").to_string();
+        if self.bom {
+            label.push_str(if self.bom_kept { " BOM (kept)" } else { " BOM" });
+        }
+        if self.replaced > 0 {
+            label.push_str(&format!(", {} replaced", self.replaced));
+        }
+        label
+    }
+}
+
 /// Decode bytes to a String using the detected encoding
 pub fn decode_bytes(bytes: Vec<u8>, encoding: &str) -> Result<String, MatError> {
-    match encoding {
+    decode_with_stats(bytes, encoding, false).map(|(text, _)| text)
+}
+
+/// Decode bytes using the detected encoding, reporting what decoding involved
+///
+/// The byte order mark is stripped unless `keep_bom` is set. Malformed
+/// sequences are replaced with U+FFFD and counted.
+pub fn decode_with_stats(bytes: Vec<u8>, encoding: &str, keep_bom: bool) -> Result<(String, DecodeStats), MatError> {
+    let (decoder, bom_len) = match encoding {
         "UTF-8" => {
             // Already validated as UTF-8
-            Ok(String::from_utf8(bytes).unwrap_or_else(|e| {
-                String::from_utf8_lossy(e.as_bytes()).into_owned()
-            }))
-        }
-        "UTF-8-BOM" => {
-            // Skip the BOM and decode as UTF-8
-            let without_bom = if bytes.len() >= 3 { &bytes[3..] } else { &bytes };
-            Ok(String::from_utf8_lossy(without_bom).into_owned())
-        }
-        "UTF-16LE" => {
-            // Use encoding_rs to convert UTF-16LE to UTF-8
-            let (cow, _, had_errors) = encoding_rs::UTF_16LE.decode(&bytes);
-            if had_errors {
-                // Still return the result, but log a warning perhaps
-                Ok(cow.into_owned())
-            } else {
-                Ok(cow.into_owned())
-            }
-        }
-        "UTF-16BE" => {
-            // Use encoding_rs to convert UTF-16BE to UTF-8
-            let (cow, _, had_errors) = encoding_rs::UTF_16BE.decode(&bytes);
-            if had_errors {
-                Ok(cow.into_owned())
-            } else {
-                Ok(cow.into_owned())
+            if let Ok(text) = String::from_utf8(bytes.clone()) {
+                return Ok((text, DecodeStats::default()));
             }
+            (encoding_rs::UTF_8, 0)
         }
-        "Latin-1" | _ => {
-            // Latin-1 is a direct byte-to-codepoint mapping
-            let (cow, _, _) = encoding_rs::WINDOWS_1252.decode(&bytes);
-            Ok(cow.into_owned())
-        }
-    }
+        "UTF-8-BOM" => (encoding_rs::UTF_8, UTF8_BOM.len()),
+        "UTF-16LE" => (encoding_rs::UTF_16LE, UTF16_LE_BOM.len()),
+        "UTF-16BE" => (encoding_rs::UTF_16BE, UTF16_BE_BOM.len()),
+        // Latin-1 is a direct byte-to-codepoint mapping
+        _ => (encoding_rs::WINDOWS_1252, 0),
+    };
+
+    // A kept BOM decodes to U+FEFF like any other character
+    let skip = if keep_bom { 0 } else { bom_len.min(bytes.len()) };
+    let (cow, had_errors) = decoder.decode_without_bom_handling(&bytes[skip..]);
+    let stats = DecodeStats {
+        bom: bom_len > 0,
+        bom_kept: bom_len > 0 && keep_bom,
+        replaced: if had_errors { cow.matches('\u{FFFD}').count() } else { 0 },
+    };
+    Ok((cow.into_owned(), stats))
 }
 
 #[cfg(test)]
@@ -131,4 +151,29 @@ mod tests {
         let result = decode_bytes(bytes, "Latin-1").unwrap();
         assert!(result.contains('ä') || result.contains('ö') || result.contains('ü'));
     }
+
+    #[test]
+    fn test_decode_utf16_keep_bom() {
+        let bytes = vec![0xFF, 0xFE, 0x48, 0x00]; // BOM + "H" in UTF-16LE
+        let (text, stats) = decode_with_stats(bytes.clone(), "UTF-16LE", false).unwrap();
+        assert_eq!(text, "H");
+        assert!(stats.bom && !stats.bom_kept);
+
+        let (text, stats) = decode_with_stats(bytes, "UTF-16LE", true).unwrap();
+        assert_eq!(text, "\u{FEFF}H");
+        assert_eq!(stats.label("UTF-16LE"), "UTF-16LE BOM (kept)");
+    }
+
+    #[test]
+    fn test_decode_stats_count_replacements() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice(b"a\xFFb\xFEc");
+        let (text, stats) = decode_with_stats(bytes, "UTF-8-BOM", false).unwrap();
+        assert_eq!(text, "a\u{FFFD}b\u{FFFD}c");
+        assert_eq!(stats.replaced, 2);
+        assert_eq!(stats.label("UTF-8-BOM"), "UTF-8 BOM, 2 replaced");
+
+        let (_, stats) = decode_with_stats(b"plain".to_vec(), "UTF-8", false).unwrap();
+        assert_eq!(stats.label("UTF-8"), "UTF-8");
+    }
 }
@@ -1,14 +1,49 @@
-use std::fs;
+use std::fs::{self, File};
 use std::path::Path;
 
 use crate::error::MatError;
+use crate::platform::display_path;
 
-/// Read file contents as raw bytes
-pub fn read_file(path: &Path) -> Result<Vec<u8>, MatError> {
-    fs::read(path).map_err(|source| MatError::Io {
+use super::cancel::{read_cancellable, Loading, ReadOutcome};
+
+/// Read file contents as raw bytes (cancellable with Ctrl+C)
+pub fn read_file(path: &Path) -> Result<ReadOutcome, MatError> {
+    let to_mat_error = |source| MatError::Io {
         source,
         path: path.to_path_buf(),
-    })
+    };
+
+    #[cfg(windows)]
+    if let Some(device) = crate::platform::device_name(path) {
+        return read_device(device).map_err(to_mat_error);
+    }
+
+    let file = File::open(path).map_err(to_mat_error)?;
+    let size = fs::metadata(path).map(|m| m.len()).ok();
+    let label = display_path(path);
+
+    let loading = Loading::start();
+    read_cancellable(file, size, &label, loading.cancel_flag()).map_err(to_mat_error)
+}
+
+/// Read a Windows device: `NUL` is empty, `CON` is the console
+#[cfg(windows)]
+fn read_device(device: crate::platform::Device) -> std::io::Result<ReadOutcome> {
+    use crate::platform::Device;
+    match device {
+        Device::Null => Ok(ReadOutcome {
+            bytes: Vec::new(),
+            interrupted: false,
+        }),
+        Device::Console => {
+            let loading = Loading::start();
+            read_cancellable(std::io::stdin(), None, "CON", loading.cancel_flag())
+        }
+        Device::Other => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "is a device, not a file",
+        )),
+    }
 }
 
 /// Detect file extension from path
@@ -23,6 +58,11 @@ pub fn is_markdown_extension(ext: &str) -> bool {
     matches!(ext, "md" | "markdown" | "mdown" | "mkd" | "mkdn")
 }
 
+/// Check if extension indicates a prose (plain text) file
+pub fn is_prose_extension(ext: &str) -> bool {
+    matches!(ext, "txt" | "text" | "rst" | "adoc" | "asciidoc" | "org" | "pdf")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,4 +92,12 @@ mod tests {
         assert!(!is_markdown_extension("txt"));
         assert!(!is_markdown_extension("rs"));
     }
+
+    #[test]
+    fn test_is_prose_extension() {
+        assert!(is_prose_extension("txt"));
+        assert!(is_prose_extension("rst"));
+        assert!(!is_prose_extension("rs"));
+        assert!(!is_prose_extension("json"));
+    }
 }
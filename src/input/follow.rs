@@ -1,6 +1,15 @@
+//! Following a file as it grows (`-f`)
+//!
+//! The file is followed by name, like `tail -F`: when the path comes to refer
+//! to another file (a log rotated away and recreated) or the file shrinks (a
+//! log truncated in place), reading starts over from the start of the new
+//! content, and the rotation is reported so the pager can mark it.
+
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::platform::file_identity;
 
 /// Reader that follows a file for new content (tail -f style)
 pub struct FollowReader {
@@ -8,19 +17,25 @@ pub struct FollowReader {
     path: PathBuf,
     /// Current position in the file
     position: u64,
+    /// Device and inode of the file being read, to notice it being replaced
+    identity: Option<(u64, u64)>,
+    /// Whether the file was replaced or truncated since the last [`Self::take_rotated`]
+    rotated: bool,
 }
 
 impl FollowReader {
     /// Create a new follow reader for the given file
     pub fn new(path: PathBuf, start_at_end: bool) -> io::Result<Self> {
         let file = File::open(&path)?;
-        let position = if start_at_end {
-            file.metadata()?.len()
-        } else {
-            0
-        };
-
-        Ok(Self { path, position })
+        let metadata = file.metadata()?;
+        let position = if start_at_end { metadata.len() } else { 0 };
+
+        Ok(Self {
+            path,
+            position,
+            identity: file_identity(&metadata),
+            rotated: false,
+        })
     }
 
     /// Check for new content and return any new lines
@@ -29,13 +44,16 @@ impl FollowReader {
         let metadata = file.metadata()?;
         let current_size = metadata.len();
 
-        // Check if file has grown
+        // A new file under the name, or the old one truncated, is read from its start
+        let identity = file_identity(&metadata);
+        if identity != self.identity || current_size < self.position {
+            let how = if identity != self.identity { "replaced" } else { "truncated" };
+            log::info!("{}: {}, reading it from the start", self.path.display(), how);
+            self.identity = identity;
+            self.position = 0;
+            self.rotated = true;
+        }
         if current_size <= self.position {
-            // File hasn't grown (or was truncated)
-            if current_size < self.position {
-                // File was truncated, reset position
-                self.position = 0;
-            }
             return Ok(Vec::new());
         }
 
@@ -52,7 +70,7 @@ impl FollowReader {
                 Ok(0) => break, // EOF
                 Ok(_) => {
                     // Remove trailing newline
-                    let trimmed = line.trim_end_matches(|c| c == '\n' || c == '\r');
+                    let trimmed = line.trim_end_matches(['\n', '\r']);
                     new_lines.push(trimmed.to_string());
                 }
                 Err(e) => return Err(e),
@@ -65,6 +83,16 @@ impl FollowReader {
         Ok(new_lines)
     }
 
+    /// Whether the file was replaced or truncated since the last call
+    pub fn take_rotated(&mut self) -> bool {
+        std::mem::take(&mut self.rotated)
+    }
+
+    /// Path of the followed file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Get the current file position
     #[allow(dead_code)]
     pub fn position(&self) -> u64 {
@@ -128,4 +156,28 @@ mod tests {
         assert_eq!(new_lines[0], "Line 1");
         assert_eq!(new_lines[1], "Line 2");
     }
+
+    #[test]
+    fn test_follow_reader_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "old 1\nold 2\n").unwrap();
+        let mut reader = FollowReader::new(path.clone(), true).unwrap();
+        assert!(reader.check_for_new_content().unwrap().is_empty());
+
+        // Rotated away and recreated, with less content than was read of the old file
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        std::fs::write(&path, "new\n").unwrap();
+        assert_eq!(reader.check_for_new_content().unwrap(), vec!["new"]);
+        assert!(reader.take_rotated());
+        assert!(!reader.take_rotated());
+
+        // Truncated in place (copytruncate)
+        std::fs::OpenOptions::new().write(true).truncate(true).open(&path).unwrap();
+        assert!(reader.check_for_new_content().unwrap().is_empty());
+        assert!(reader.take_rotated());
+        std::fs::write(&path, "after truncation\n").unwrap();
+        assert_eq!(reader.check_for_new_content().unwrap(), vec!["after truncation"]);
+        assert!(!reader.take_rotated());
+    }
 }
@@ -1,5 +1,7 @@
 //! Large file support using memory mapping and lazy loading.
-//! This module is prepared for future integration but not yet used in the main flow.
+//! Files over `--max-memory` are loaded through it; the pager doesn't use it yet.
+//! Grep and search can run directly over the mapping so filtering a huge file does
+//! not force every line to be materialized.
 
 #![allow(dead_code)]
 
@@ -7,11 +9,16 @@ use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::thread;
 
 use lru::LruCache;
 use memmap2::Mmap;
+use regex::bytes::Regex as BytesRegex;
+use regex::Regex;
 
-use crate::display::{Line, SpanStyle, StyledSpan};
+use crate::display::{Document, Line, SpanStyle, StyledSpan};
+use crate::filter::{merge_ranges, GrepOptions};
+use crate::highlight::MatchPosition;
 
 /// Threshold for using lazy loading (10MB)
 pub const LARGE_FILE_THRESHOLD: u64 = 10 * 1024 * 1024;
@@ -108,6 +115,21 @@ impl LazyDocument {
             return None;
         }
 
+        // Convert to string (lossy for non-UTF8)
+        let text = String::from_utf8_lossy(self.line_bytes(idx)).to_string();
+
+        Some(Line {
+            number: idx + 1, // 1-indexed
+            spans: vec![StyledSpan::new(text, SpanStyle::default())],
+            is_match: false,
+            is_context: false,
+            continuation: false,
+            heading: None,
+        })
+    }
+
+    /// Get the raw bytes of a line, without the line terminator
+    fn line_bytes(&self, idx: usize) -> &[u8] {
         let start = self.line_offsets[idx] as usize;
         let end = self.line_offsets[idx + 1] as usize;
 
@@ -122,23 +144,134 @@ impl LazyDocument {
         };
 
         // Remove carriage return if present (Windows line endings)
-        let bytes = if bytes.ends_with(b"\r") {
+        if bytes.ends_with(b"\r") {
             &bytes[..bytes.len() - 1]
         } else {
             bytes
-        };
+        }
+    }
 
-        // Convert to string (lossy for non-UTF8)
-        let text = String::from_utf8_lossy(bytes).to_string();
+    /// Scan every line of the mapping in parallel, collecting per-line results
+    ///
+    /// Lines are split into contiguous slices, one per worker thread, and the
+    /// results are concatenated in line order.
+    fn scan_lines<T, F>(&self, scan: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &[u8], &mut Vec<T>) + Sync,
+    {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let per_worker = ((self.total_lines + workers - 1) / workers).max(SCAN_MIN_LINES_PER_WORKER);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.total_lines)
+                .step_by(per_worker)
+                .map(|start| {
+                    let end = (start + per_worker).min(self.total_lines);
+                    let scan = &scan;
+                    scope.spawn(move || {
+                        let mut results = Vec::new();
+                        for idx in start..end {
+                            scan(idx, self.line_bytes(idx), &mut results);
+                        }
+                        results
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("line scan worker panicked"))
+                .collect()
+        })
+    }
 
-        Some(Line {
-            number: idx + 1, // 1-indexed
-            spans: vec![StyledSpan::new(text, SpanStyle::default())],
-            is_match: false,
-            is_context: false,
+    /// Find indices of all lines matching the pattern, scanning the mmap directly
+    pub fn matching_lines(&self, pattern: &Regex) -> Vec<usize> {
+        let pattern = bytes_regex(pattern);
+        self.scan_lines(|idx, bytes, out| {
+            if pattern.is_match(bytes) {
+                out.push(idx);
+            }
         })
     }
 
+    /// Find all match positions, scanning the mmap directly
+    pub fn find_matches(&self, pattern: &Regex) -> Vec<MatchPosition> {
+        let pattern = bytes_regex(pattern);
+        self.scan_lines(|line_idx, bytes, out| {
+            out.extend(pattern.find_iter(bytes).map(|m| MatchPosition {
+                line_idx,
+                start_col: m.start(),
+                end_col: m.end(),
+            }));
+        })
+    }
+
+    /// Grep the lazy document, loading only matched lines and their context
+    pub fn grep_filter(&self, options: &GrepOptions) -> Document {
+        let match_indices = if options.invert {
+            let pattern = bytes_regex(&options.pattern);
+            self.scan_lines(|idx, bytes, out| {
+                if !pattern.is_match(bytes) {
+                    out.push(idx);
+                }
+            })
+        } else {
+            self.matching_lines(&options.pattern)
+        };
+
+        let ranges = match_indices
+            .iter()
+            .map(|&idx| {
+                let start = idx.saturating_sub(options.before);
+                let end = (idx + options.after + 1).min(self.total_lines);
+                (start, end)
+            })
+            .collect();
+
+        let mut lines: Vec<Line> = Vec::new();
+        for (start, end) in merge_ranges(ranges) {
+            if !lines.is_empty() {
+                lines.push(options.colors.separator_line());
+            }
+            for idx in start..end {
+                let is_match = match_indices.binary_search(&idx).is_ok();
+                let mut line = self.load_line(idx).expect("index within range");
+                line.is_match = is_match;
+                line.is_context = !is_match;
+                if !is_match {
+                    for span in &mut line.spans {
+                        span.style = options.colors.context.clone();
+                    }
+                }
+                lines.push(line);
+            }
+        }
+
+        let max_line_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
+
+        Document {
+            lines,
+            max_line_width,
+            source_name: self.source_name.clone(),
+            encoding: self.encoding.clone(),
+        }
+    }
+
+    /// A document of the lines from `start` to `end` (0-indexed, exclusive), loaded without caching
+    pub fn range_document(&self, start: usize, end: usize) -> Document {
+        let lines: Vec<Line> = (start..end.min(self.total_lines)).filter_map(|idx| self.load_line(idx)).collect();
+        let max_line_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
+
+        Document {
+            lines,
+            max_line_width,
+            source_name: self.source_name.clone(),
+            encoding: self.encoding.clone(),
+        }
+    }
+
     /// Get a range of lines (returns a vector of cloned lines)
     pub fn get_lines(&mut self, start: usize, end: usize) -> Vec<Line> {
         let end = end.min(self.total_lines);
@@ -169,6 +302,16 @@ impl LazyDocument {
     }
 }
 
+/// Minimum number of lines handed to each scan worker (avoid spawning
+/// threads for small files)
+const SCAN_MIN_LINES_PER_WORKER: usize = 64 * 1024;
+
+/// Convert a pattern to its byte-oriented equivalent so lines can be matched
+/// without decoding them first
+fn bytes_regex(pattern: &Regex) -> BytesRegex {
+    BytesRegex::new(pattern.as_str()).expect("pattern already compiled as a str regex")
+}
+
 /// Build line offset index by scanning the file
 fn build_line_offsets(data: &[u8]) -> Vec<u64> {
     let mut offsets = vec![0];
@@ -197,6 +340,7 @@ pub fn count_lines(path: &std::path::Path) -> io::Result<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::GrepColors;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -261,6 +405,51 @@ mod tests {
         assert!(doc.line_cache.len() <= 100);
     }
 
+    #[test]
+    fn test_lazy_matching_lines_and_positions() {
+        let mut temp = NamedTempFile::new().unwrap();
+        writeln!(temp, "error: one").unwrap();
+        writeln!(temp, "ok").unwrap();
+        writeln!(temp, "fine error error").unwrap();
+        temp.flush().unwrap();
+
+        let doc = LazyDocument::new(temp.path().to_path_buf()).unwrap();
+        let pattern = Regex::new("error").unwrap();
+
+        assert_eq!(doc.matching_lines(&pattern), vec![0, 2]);
+
+        let matches = doc.find_matches(&pattern);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[1].line_idx, 2);
+        assert_eq!(matches[1].start_col, 5);
+        assert_eq!(matches[2].start_col, 11);
+    }
+
+    #[test]
+    fn test_lazy_grep_filter_with_context() {
+        let mut temp = NamedTempFile::new().unwrap();
+        for i in 1..=10 {
+            writeln!(temp, "Line {}", i).unwrap();
+        }
+        temp.flush().unwrap();
+
+        let doc = LazyDocument::new(temp.path().to_path_buf()).unwrap();
+        let options = GrepOptions {
+            pattern: Regex::new("^Line (2|8)$").unwrap(),
+            before: 1,
+            after: 0,
+            invert: false,
+            colors: GrepColors::default(),
+        };
+
+        let filtered = doc.grep_filter(&options);
+        let numbers: Vec<usize> = filtered.lines.iter().map(|l| l.number).collect();
+        // 1, 2, separator, 7, 8
+        assert_eq!(numbers, vec![1, 2, 0, 7, 8]);
+        assert!(filtered.lines[1].is_match);
+        assert!(filtered.lines[0].is_context);
+    }
+
     #[test]
     fn test_should_use_lazy_loading() {
         let mut temp = NamedTempFile::new().unwrap();
@@ -1,22 +1,44 @@
+mod ansi;
 mod binary;
+mod cancel;
+mod decompress;
 mod encoding;
 mod file;
 mod follow;
+mod pages;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod preprocess;
 pub mod large;
 mod stdin;
+mod stream;
+mod tee;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cli::Args;
 use crate::error::MatError;
+use crate::platform::display_path;
 
+use cancel::ReadOutcome;
+use decompress::Compression;
+use pages::mark_page_breaks;
+use preprocess::{preprocess, Preprocessed, Renderer};
+
+pub use ansi::{apply_ansi_styles, parse_ansi};
 pub use binary::is_binary;
-pub use encoding::{decode_bytes, detect_encoding};
-pub use file::{detect_extension, is_markdown_extension, read_file};
+pub use decompress::strip_compression_suffix;
+pub use cancel::truncate_to_last_line;
+pub use encoding::{decode_bytes, decode_with_stats, detect_encoding};
+pub use file::{detect_extension, is_markdown_extension, is_prose_extension, read_file};
 pub use follow::FollowReader;
-// Large file support is available but not yet integrated into the main flow
+pub use pages::parse_page_marker;
+pub use preprocess::Preprocessor;
+// Large file support is only used for files over --max-memory so far
 // pub use large::{LazyDocument, LARGE_FILE_THRESHOLD, should_use_lazy_loading};
-pub use stdin::{is_stdin_piped, read_stdin};
+pub use stdin::{is_stdin_piped, read_stdin, stream_stdin};
+pub use stream::{InputEvent, InputStream};
+pub use tee::tee_stdin;
 
 /// Represents the source of input
 #[derive(Debug, Clone)]
@@ -27,6 +49,44 @@ pub enum InputSource {
     Stdin,
 }
 
+/// Position to open a file at, from a `file:line[:col]` argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Column (1-indexed), if given
+    pub column: Option<usize>,
+}
+
+/// Split a `file:line[:col]` reference (as printed by compilers and grep -n)
+///
+/// Returns `None` when `path` names an existing file as-is, so files with
+/// colons in their names keep working, or when the file part doesn't exist.
+pub fn parse_file_location(path: &Path) -> Option<(PathBuf, Location)> {
+    if path.exists() {
+        return None;
+    }
+    let text = path.to_str()?;
+    let text = text.strip_suffix(':').unwrap_or(text);
+
+    let number = |s: &str| s.parse::<usize>().ok().filter(|&n| n > 0);
+    let (rest, last) = text.rsplit_once(':')?;
+    let last = number(last)?;
+    let (file, location) = match rest.rsplit_once(':') {
+        Some((file, line)) if number(line).is_some() && Path::new(file).exists() => (
+            file,
+            Location {
+                line: number(line)?,
+                column: Some(last),
+            },
+        ),
+        _ => (rest, Location { line: last, column: None }),
+    };
+
+    let file = PathBuf::from(file);
+    file.exists().then_some((file, location))
+}
+
 /// Holds the loaded content with metadata
 #[derive(Debug)]
 pub struct Content {
@@ -34,13 +94,14 @@ pub struct Content {
     pub text: String,
     /// Name of the source (filename or "stdin")
     pub source_name: String,
-    /// File extension if applicable (for future language detection)
-    #[allow(dead_code)]
+    /// File extension if applicable
     pub extension: Option<String>,
     /// Whether this should be treated as markdown
     pub is_markdown: bool,
-    /// Detected or assumed encoding
+    /// Detected or assumed encoding, with BOM and replacement notes for the status bar
     pub encoding: String,
+    /// Bytes to show as a hex dump (`--hex`, or a binary file with `--force-binary`); `text` is then empty
+    pub binary: Option<Vec<u8>>,
 }
 
 /// Expand tabs to spaces with proper alignment
@@ -110,22 +171,84 @@ pub fn strip_ansi(text: &str) -> String {
 }
 
 /// Load content from the given input source
-pub fn load_content(source: InputSource, args: &Args) -> Result<Content, MatError> {
-    let (raw_bytes, source_name, extension) = match &source {
+///
+/// Files handled by one of `preprocessors` are converted (or get their
+/// renderer) before the rest of the pipeline.
+pub fn load_content(source: InputSource, args: &Args, preprocessors: &[Preprocessor]) -> Result<Content, MatError> {
+    let mut renderer = None;
+    // Converted text may separate pages with form feeds
+    let mut converted = false;
+    let (outcome, mut source_name) = match &source {
         InputSource::File(path) => {
-            let bytes = read_file(path)?;
-            let name = path.display().to_string();
-            let ext = detect_extension(path);
-            (bytes, name, ext)
+            let outcome = match preprocess(path, preprocessors)? {
+                Some(Preprocessed::Converted(bytes)) => {
+                    converted = true;
+                    ReadOutcome {
+                        bytes,
+                        interrupted: false,
+                    }
+                }
+                Some(Preprocessed::Render(selected)) => {
+                    renderer = Some(selected);
+                    read_file(path)?
+                }
+                #[cfg(feature = "pdf")]
+                None if is_pdf(path) => {
+                    converted = true;
+                    ReadOutcome {
+                        bytes: pdf::extract_pdf_text(path)?,
+                        interrupted: false,
+                    }
+                }
+                None => read_file(path)?,
+            };
+            let name = display_path(path);
+            (outcome, name)
         }
         InputSource::Stdin => {
-            let bytes = read_stdin()?;
-            (bytes, "stdin".to_string(), None)
+            let outcome = read_stdin()?;
+            (outcome, "stdin".to_string())
+        }
+    };
+
+    // Ctrl+C while loading: exit, or keep what was read so far
+    let mut raw_bytes = outcome.bytes;
+    if outcome.interrupted {
+        if !args.open_partial {
+            return Err(MatError::Interrupted { path: source_name.into() });
+        }
+        truncate_to_last_line(&mut raw_bytes);
+        source_name.push_str(" (partial)");
+    }
+
+    // Compressed input is viewed decompressed, typed by the name without `.gz`
+    let compression = if converted { None } else { Compression::sniff(&raw_bytes) };
+    if let Some(compression) = compression {
+        raw_bytes = compression.decompress(&raw_bytes, Path::new(&source_name))?;
+        log::info!("{}: {} compressed, {} bytes decompressed", source_name, compression.name(), raw_bytes.len());
+    }
+    let extension = match &source {
+        InputSource::File(path) if compression.is_some() => {
+            detect_extension(Path::new(strip_compression_suffix(&path.to_string_lossy())))
         }
+        InputSource::File(path) => detect_extension(path),
+        InputSource::Stdin => None,
     };
 
-    // Check for binary content
-    if !args.force_binary && is_binary(&raw_bytes) {
+    // Binary content is refused, or shown as a hex dump
+    let binary = is_binary(&raw_bytes);
+    if args.hex || (binary && args.force_binary) {
+        log::info!("{}: {} bytes shown as a hex dump", source_name, raw_bytes.len());
+        return Ok(Content {
+            text: String::new(),
+            source_name,
+            extension,
+            is_markdown: false,
+            encoding: "binary".to_string(),
+            binary: Some(raw_bytes),
+        });
+    }
+    if binary {
         let path = match source {
             InputSource::File(p) => p,
             InputSource::Stdin => PathBuf::from("stdin"),
@@ -135,49 +258,106 @@ pub fn load_content(source: InputSource, args: &Args) -> Result<Content, MatErro
 
     // Detect and decode encoding
     let encoding_name = detect_encoding(&raw_bytes);
-    let text = decode_bytes(raw_bytes, encoding_name)?;
+    let byte_count = raw_bytes.len();
+    let (text, decode_stats) = decode_with_stats(raw_bytes, encoding_name, args.keep_bom)?;
+    log::info!(
+        "{}: {} bytes decoded as {}{}",
+        source_name,
+        byte_count,
+        decode_stats.label(encoding_name),
+        if encoding_name == "Latin-1" { " (not valid UTF-8)" } else { "" }
+    );
 
-    // Strip ANSI unless --ansi flag is set
+    // Strip ANSI unless --ansi flag is set (the escapes then become span styles)
     let text = if args.ansi { text } else { strip_ansi(&text) };
 
     // Expand tabs to spaces (4 spaces per tab)
     let text = expand_tabs(&text, 4);
 
+    // Introduce each page of converted documents with a marker line
+    let text = if converted { mark_page_breaks(&text) } else { text };
+
     // Determine if markdown
     let is_markdown = if args.no_markdown {
         false
     } else if args.markdown {
         true
+    } else if let Some(renderer) = renderer {
+        renderer == Renderer::Markdown
     } else {
         extension
             .as_ref()
             .map(|e| is_markdown_extension(e))
             .unwrap_or(false)
     };
+    if is_markdown {
+        let reason = if args.markdown { "--markdown" } else if renderer.is_some() { "a preprocessor" } else { "its extension" };
+        log::debug!("{}: markdown, by {}", source_name, reason);
+    }
 
     Ok(Content {
         text,
         source_name,
         extension,
         is_markdown,
-        encoding: encoding_name.to_string(),
+        encoding: decode_stats.label(encoding_name),
+        binary: None,
     })
 }
 
-/// Determine the input source from CLI args
-pub fn determine_input_source(args: &Args) -> Option<InputSource> {
-    match &args.file {
-        Some(path) if path.as_os_str() == "-" => Some(InputSource::Stdin),
-        Some(path) => Some(InputSource::File(path.clone())),
-        None if is_stdin_piped() => Some(InputSource::Stdin),
-        None => None,
+/// Whether `path` is a PDF, by extension or by its first bytes
+#[cfg(feature = "pdf")]
+fn is_pdf(path: &Path) -> bool {
+    detect_extension(path).is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+        || preprocess::sniff_mime(path) == Some("application/pdf")
+}
+
+/// Determine the input sources from CLI args (empty if there is nothing to read)
+pub fn determine_input_sources(args: &Args) -> Vec<InputSource> {
+    if args.files.is_empty() {
+        return if is_stdin_piped() {
+            vec![InputSource::Stdin]
+        } else {
+            Vec::new()
+        };
     }
+
+    args.files
+        .iter()
+        .map(|path| {
+            if path.as_os_str() == "-" {
+                InputSource::Stdin
+            } else if let Some((file, _)) = parse_file_location(path) {
+                InputSource::File(file)
+            } else {
+                InputSource::File(path.clone())
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_file_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}\n").unwrap();
+        let at = |suffix: &str| parse_file_location(Path::new(&format!("{}{}", file.display(), suffix)));
+
+        assert_eq!(at(":12"), Some((file.clone(), Location { line: 12, column: None })));
+        assert_eq!(at(":12:7"), Some((file.clone(), Location { line: 12, column: Some(7) })));
+        // grep -n style trailing colon
+        assert_eq!(at(":12:"), Some((file.clone(), Location { line: 12, column: None })));
+
+        assert_eq!(at(""), None);
+        assert_eq!(at(":0"), None);
+        assert_eq!(at(":x"), None);
+        assert_eq!(parse_file_location(Path::new("/nonexistent/file.rs:3")), None);
+    }
+
     #[test]
     fn test_expand_tabs_basic() {
         assert_eq!(expand_tabs("a\tb", 4), "a   b");
@@ -0,0 +1,69 @@
+//! Page breaks in converted documents
+//!
+//! Text extracted from a PDF (by the `pdf` feature or a `pdftotext`
+//! preprocessor) separates pages with form feeds. Each page is introduced by a
+//! visible marker line instead, which `:page N` in the pager jumps to.
+
+use crate::display::glyphs::glyphs;
+
+/// Separator between pages in extracted text
+pub const PAGE_BREAK: char = '\x0c';
+
+/// Marker line introducing page `page` (1-indexed)
+pub fn page_marker(page: usize) -> String {
+    let rule = glyphs().horizontal.repeat(3);
+    format!("{} Page {} {}", rule, page, rule)
+}
+
+/// Page number of a marker line, `None` for any other line
+pub fn parse_page_marker(text: &str) -> Option<usize> {
+    let rule = glyphs().horizontal.repeat(3);
+    text.strip_prefix(&rule)?
+        .strip_prefix(" Page ")?
+        .strip_suffix(&rule)?
+        .strip_suffix(' ')?
+        .parse()
+        .ok()
+}
+
+/// Replace form feeds with page marker lines
+///
+/// Text without form feeds is returned unchanged. A trailing form feed (as
+/// `pdftotext` prints after the last page) doesn't start an empty page.
+pub fn mark_page_breaks(text: &str) -> String {
+    if !text.contains(PAGE_BREAK) {
+        return text.to_string();
+    }
+    let text = text.strip_suffix(PAGE_BREAK).unwrap_or(text);
+
+    let mut marked = String::with_capacity(text.len() + 32);
+    for (i, page) in text.split(PAGE_BREAK).enumerate() {
+        marked.push_str(&page_marker(i + 1));
+        marked.push('\n');
+        marked.push_str(page);
+        if !page.is_empty() && !page.ends_with('\n') {
+            marked.push('\n');
+        }
+    }
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_page_breaks() {
+        assert_eq!(mark_page_breaks("no pages\n"), "no pages\n");
+        assert_eq!(
+            mark_page_breaks("intro\n\x0cbody\x0c"),
+            "─── Page 1 ───\nintro\n─── Page 2 ───\nbody\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_page_marker() {
+        assert_eq!(parse_page_marker(&page_marker(12)), Some(12));
+        assert_eq!(parse_page_marker("Page 12"), None);
+    }
+}
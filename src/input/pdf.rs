@@ -0,0 +1,21 @@
+//! Text extraction from PDF files (`pdf` feature)
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use crate::error::MatError;
+
+use super::pages::PAGE_BREAK;
+
+/// Extract the text of every page, separated by form feeds
+pub fn extract_pdf_text(path: &Path) -> Result<Vec<u8>, MatError> {
+    let error = |message: String| MatError::Pdf {
+        path: path.to_path_buf(),
+        message,
+    };
+    // The parser panics on some malformed or unsupported files
+    let pages = panic::catch_unwind(AssertUnwindSafe(|| pdf_extract::extract_text_by_pages(path)))
+        .map_err(|_| error("unsupported PDF structure".to_string()))?
+        .map_err(|e| error(e.to_string()))?;
+    Ok(pages.join(&PAGE_BREAK.to_string()).into_bytes())
+}
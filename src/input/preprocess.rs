@@ -0,0 +1,273 @@
+//! Converters run on files before the standard pipeline
+//!
+//! The configuration maps file extensions or sniffed MIME types to an external
+//! command (`.pdf` -> `pdftotext {} -`) or to one of the internal renderers.
+//! Command output is cached under `$XDG_CACHE_HOME/mat/preprocess` (falling
+//! back to `~/.cache`), keyed by the file's path, size and modification time
+//! and the command, so reopening an unchanged file skips the conversion.
+
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+
+use crate::config::cache_dir;
+use crate::error::MatError;
+use crate::sandbox::exec_allowed;
+
+use super::file::detect_extension;
+
+/// Internal renderer a preprocessor can select
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Renderer {
+    /// Render as markdown
+    Markdown,
+    /// Show as plain text, even with a markdown extension
+    Text,
+}
+
+/// One `[[preprocessor]]` entry of the configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preprocessor {
+    /// File extensions handled, without the dot (case-insensitive)
+    pub extensions: Vec<String>,
+    /// MIME types handled, as sniffed from the file's first bytes
+    pub mime: Vec<String>,
+    /// Command printing the converted text; split on whitespace, with `{}`
+    /// replaced by the file path (appended when there is no `{}`)
+    pub command: Option<String>,
+    /// Internal renderer to use instead of running a command
+    pub renderer: Option<Renderer>,
+}
+
+impl Preprocessor {
+    /// Check that the entry is usable, describing the problem otherwise
+    pub fn validate(&self) -> Result<(), String> {
+        if self.extensions.is_empty() && self.mime.is_empty() {
+            return Err("preprocessor needs `extensions` or `mime`".to_string());
+        }
+        match (&self.command, self.renderer) {
+            (Some(command), None) if command.split_whitespace().next().is_none() => {
+                Err("preprocessor `command` is empty".to_string())
+            }
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => Err("preprocessor needs exactly one of `command` or `renderer`".to_string()),
+        }
+    }
+
+    /// Whether the entry handles a file with this extension and MIME type
+    fn handles(&self, extension: Option<&str>, mime: Option<&str>) -> bool {
+        extension.is_some_and(|ext| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+            || mime.is_some_and(|mime| self.mime.iter().any(|m| m == mime))
+    }
+}
+
+/// Result of preprocessing a file
+#[derive(Debug, PartialEq, Eq)]
+pub enum Preprocessed {
+    /// Text printed by the converter command
+    Converted(Vec<u8>),
+    /// Read the file normally, with this renderer
+    Render(Renderer),
+}
+
+/// Run the first preprocessor handling `path`, if any
+pub fn preprocess(path: &Path, preprocessors: &[Preprocessor]) -> Result<Option<Preprocessed>, MatError> {
+    if preprocessors.is_empty() {
+        return Ok(None);
+    }
+    let extension = detect_extension(path);
+    let mime = if preprocessors.iter().any(|p| !p.mime.is_empty()) {
+        sniff_mime(path)
+    } else {
+        None
+    };
+    let Some(preprocessor) = preprocessors.iter().find(|p| p.handles(extension.as_deref(), mime)) else {
+        return Ok(None);
+    };
+
+    match (&preprocessor.command, preprocessor.renderer) {
+        // Without running the command the file is read as it is
+        (Some(command), _) if !exec_allowed() => {
+            log::info!("{}: not converted with `{}` under --no-exec", path.display(), command);
+            Ok(None)
+        }
+        (Some(command), _) => {
+            log::info!("{}: converted with `{}`", path.display(), command);
+            convert_cached(path, command).map(|bytes| Some(Preprocessed::Converted(bytes)))
+        }
+        (None, Some(renderer)) => Ok(Some(Preprocessed::Render(renderer))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// MIME type recognized from the file's magic bytes
+pub(super) fn sniff_mime(path: &Path) -> Option<&'static str> {
+    let mut head = [0u8; 16];
+    let mut file = File::open(path).ok()?;
+    let len = file.read(&mut head).ok()?;
+    mime_from_magic(&head[..len])
+}
+
+/// MIME type for well-known magic bytes at the start of a file
+fn mime_from_magic(head: &[u8]) -> Option<&'static str> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"SQLite format 3\0", "application/vnd.sqlite3"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x89PNG", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF8", "image/gif"),
+    ];
+    MAGIC.iter().find(|(magic, _)| head.starts_with(magic)).map(|(_, mime)| *mime)
+}
+
+/// Convert `path` with `command`, reusing a cached result when the file is unchanged
+fn convert_cached(path: &Path, command: &str) -> Result<Vec<u8>, MatError> {
+    let cache_path = cache_dir()
+        .map(|dir| dir.join("preprocess"))
+        .and_then(|dir| cache_key(path, command).map(|key| dir.join(format!("{:016x}.out", key))));
+    if let Some(bytes) = cache_path.as_ref().and_then(|p| fs::read(p).ok()) {
+        return Ok(bytes);
+    }
+
+    let bytes = run_command(path, command)?;
+    // A failure to cache only costs a conversion next time
+    if let Some(cache_path) = cache_path {
+        if let Some(dir) = cache_path.parent() {
+            let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&cache_path, &bytes));
+        }
+    }
+    Ok(bytes)
+}
+
+/// Run the converter and return what it printed
+fn run_command(path: &Path, command: &str) -> Result<Vec<u8>, MatError> {
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut args: Vec<OsString> = Vec::new();
+    let mut has_placeholder = false;
+    for word in words {
+        if word == "{}" {
+            args.push(path.as_os_str().to_os_string());
+            has_placeholder = true;
+        } else {
+            args.push(word.into());
+        }
+    }
+    if !has_placeholder {
+        args.push(path.as_os_str().to_os_string());
+    }
+
+    let error = |message: String| MatError::Preprocessor {
+        command: command.to_string(),
+        path: path.to_path_buf(),
+        message,
+    };
+    let output = Command::new(program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| error(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().next().unwrap_or("").trim().to_string();
+        return Err(error(if reason.is_empty() {
+            output.status.to_string()
+        } else {
+            reason
+        }));
+    }
+    Ok(output.stdout)
+}
+
+/// Cache key for `path` converted by `command`, changing whenever the file does
+fn cache_key(path: &Path, command: &str) -> Option<u64> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    let mut hasher = DefaultHasher::new();
+    fs::canonicalize(path).ok()?.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+    command.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn preprocessor(extensions: &[&str], command: Option<&str>, renderer: Option<Renderer>) -> Preprocessor {
+        Preprocessor {
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            mime: Vec::new(),
+            command: command.map(str::to_string),
+            renderer,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(preprocessor(&["pdf"], Some("pdftotext {} -"), None).validate().is_ok());
+        assert!(preprocessor(&["mdx"], None, Some(Renderer::Markdown)).validate().is_ok());
+        assert!(preprocessor(&[], Some("cat"), None).validate().is_err());
+        assert!(preprocessor(&["pdf"], None, None).validate().is_err());
+        assert!(preprocessor(&["pdf"], Some("cat"), Some(Renderer::Text)).validate().is_err());
+        assert!(preprocessor(&["pdf"], Some("  "), None).validate().is_err());
+    }
+
+    #[test]
+    fn test_mime_from_magic() {
+        assert_eq!(mime_from_magic(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(mime_from_magic(b"SQLite format 3\0"), Some("application/vnd.sqlite3"));
+        assert_eq!(mime_from_magic(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_preprocess_selects_by_extension() {
+        let mut file = tempfile::Builder::new().suffix(".LOG").tempfile().unwrap();
+        writeln!(file, "hello").unwrap();
+        let preprocessors = vec![
+            preprocessor(&["txt"], None, Some(Renderer::Markdown)),
+            preprocessor(&["log"], None, Some(Renderer::Text)),
+        ];
+
+        let result = preprocess(file.path(), &preprocessors).unwrap();
+        assert_eq!(result, Some(Preprocessed::Render(Renderer::Text)));
+        assert_eq!(preprocess(file.path(), &preprocessors[..1]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_run_command() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "converted").unwrap();
+
+        assert_eq!(run_command(file.path(), "cat").unwrap(), b"converted");
+        assert_eq!(run_command(file.path(), "cat {}").unwrap(), b"converted");
+        let error = run_command(file.path(), "mat-no-such-converter {}").unwrap_err();
+        assert_eq!(error.kind(), "preprocessor");
+    }
+
+    #[test]
+    fn test_cache_key_changes_with_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "one").unwrap();
+        let first = cache_key(file.path(), "cat").unwrap();
+        assert_eq!(cache_key(file.path(), "cat"), Some(first));
+        assert_ne!(cache_key(file.path(), "cat -n"), Some(first));
+
+        write!(file, " two").unwrap();
+        assert_ne!(cache_key(file.path(), "cat"), Some(first));
+    }
+}
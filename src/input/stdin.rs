@@ -1,17 +1,119 @@
 use std::io::{self, Read};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::error::MatError;
 
-/// Read all content from stdin into a buffer
-pub fn read_stdin() -> Result<Vec<u8>, MatError> {
-    let mut buffer = Vec::new();
-    io::stdin()
-        .read_to_end(&mut buffer)
-        .map_err(|source| MatError::Io {
+use super::cancel::{read_cancellable, Loading, ReadOutcome};
+use super::stream::InputStream;
+use super::tee::TeeReader;
+
+/// Size of each read while waiting to see whether stdin ends
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How long stdin gets to end before it is paged while still being read
+const STREAM_WAIT: Duration = Duration::from_millis(250);
+
+/// Stdin that was read to the end while deciding whether to stream it
+static PRELOADED: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Read all content from stdin into a buffer (cancellable with Ctrl+C)
+pub fn read_stdin() -> Result<ReadOutcome, MatError> {
+    if let Some(bytes) = PRELOADED.lock().unwrap().take() {
+        return Ok(ReadOutcome {
+            bytes,
+            interrupted: false,
+        });
+    }
+    let loading = Loading::start();
+    read_cancellable(TeeReader::new(io::stdin().lock()), None, "stdin", loading.cancel_flag()).map_err(|source| {
+        MatError::Io {
             source,
             path: std::path::PathBuf::from("stdin"),
-        })?;
-    Ok(buffer)
+        }
+    })
+}
+
+/// Give stdin a moment to end, then hand it over as a stream of lines if it hasn't
+///
+/// Returns `None` when stdin ended in time; its content is then kept for the
+/// next [`read_stdin`]. A pipe that is still open (`journalctl -f | mat`) is
+/// read on in the background, starting with the bytes received so far.
+pub fn stream_stdin() -> Result<Option<InputStream>, MatError> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = TeeReader::new(io::stdin().lock());
+        let mut chunk = vec![0u8; STREAM_CHUNK_SIZE];
+        loop {
+            let result = match stdin.read(&mut chunk) {
+                Ok(0) => return,
+                Ok(n) => Ok(chunk[..n].to_vec()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = result.is_err();
+            if sender.send(result).is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + STREAM_WAIT;
+    let mut head = Vec::new();
+    loop {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Ok(bytes)) => head.extend_from_slice(&bytes),
+            Ok(Err(source)) => {
+                return Err(MatError::Io {
+                    source,
+                    path: std::path::PathBuf::from("stdin"),
+                })
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                *PRELOADED.lock().unwrap() = Some(head);
+                return Ok(None);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                log::info!("stdin: still open after {:?}, paged as it arrives", STREAM_WAIT);
+                let reader = ChunkReader {
+                    receiver,
+                    pending: head,
+                    offset: 0,
+                };
+                return Ok(Some(InputStream::spawn_reader(reader)));
+            }
+        }
+    }
+}
+
+/// Reads the chunks sent by the stdin thread as one continuous stream
+struct ChunkReader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    /// Chunk being handed out
+    pending: Vec<u8>,
+    /// Bytes of `pending` already read
+    offset: usize,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.pending.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.pending = chunk?;
+                    self.offset = 0;
+                }
+                // The thread is done: stdin ended
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.pending.len() - self.offset);
+        buf[..n].copy_from_slice(&self.pending[self.offset..self.offset + n]);
+        self.offset += n;
+        Ok(n)
+    }
 }
 
 /// Check if stdin is a pipe (not a TTY)
@@ -35,4 +137,22 @@ mod tests {
         // This just verifies the function doesn't panic
         let _ = is_stdin_piped();
     }
+
+    #[test]
+    fn test_chunk_reader_joins_chunks() {
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Ok(b"one\nt".to_vec())).unwrap();
+        sender.send(Ok(Vec::new())).unwrap();
+        sender.send(Ok(b"wo\n".to_vec())).unwrap();
+        drop(sender);
+        let mut reader = ChunkReader {
+            receiver,
+            pending: b"zero\n".to_vec(),
+            offset: 0,
+        };
+
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "zero\none\ntwo\n");
+    }
 }
@@ -0,0 +1,250 @@
+//! Non-blocking input streams
+//!
+//! Blocking reads (a pipe, a followed file) run on a background thread and hand
+//! their lines over a channel. The pager drains every stream once per tick of
+//! its event loop, so input never stalls key handling or rendering. A followed
+//! file is only read when the file system reports a change to it, falling back
+//! to polling where there are no such notifications.
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::FollowReader;
+
+/// Something that happened on an input stream
+#[derive(Debug)]
+pub enum InputEvent {
+    /// New complete lines, without their line endings
+    Lines(Vec<String>),
+    /// The followed file was replaced or truncated; the lines that follow are the new content's
+    Rotated,
+    /// Reading failed; the stream is closed
+    Error(io::Error),
+    /// The input ended
+    Closed,
+}
+
+/// How often a watched file is read anyway, in case a change went unreported
+const RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lines read by a background thread, collected without blocking
+#[derive(Debug)]
+pub struct InputStream {
+    receiver: Receiver<InputEvent>,
+    /// Asks the thread to stop once the stream is dropped
+    stop: Arc<AtomicBool>,
+}
+
+impl InputStream {
+    /// Read lines from `reader` until it ends
+    pub fn spawn_reader<R: Read + Send + 'static>(reader: R) -> Self {
+        Self::spawn(move |sender, stop| read_lines(reader, &sender, &stop))
+    }
+
+    /// Read the lines appended to `reader`'s file whenever it changes
+    ///
+    /// Without change notifications the file is checked every `interval`; with
+    /// them, every `RECHECK_INTERVAL` too, for the file systems (network
+    /// mounts) that miss some changes.
+    pub fn spawn_follow(mut reader: FollowReader, interval: Duration) -> Self {
+        Self::spawn(move |sender, stop| {
+            let (wake, changes) = mpsc::channel();
+            let watcher = watch_file(reader.path(), wake);
+            match watcher {
+                Some(_) => log::debug!("{}: following on change notifications", reader.path().display()),
+                None => log::info!("{}: no change notifications, checking every {:?}", reader.path().display(), interval),
+            }
+            while !stop.load(Ordering::Relaxed) {
+                let event = match reader.check_for_new_content() {
+                    Ok(lines) if lines.is_empty() => None,
+                    Ok(lines) => {
+                        log::trace!("{}: {} new lines", reader.path().display(), lines.len());
+                        Some(InputEvent::Lines(lines))
+                    }
+                    // The file may be briefly missing while it is replaced; try again
+                    Err(e) => {
+                        log::debug!("{}: {}, trying again", reader.path().display(), e);
+                        None
+                    }
+                };
+                if reader.take_rotated() && sender.send(InputEvent::Rotated).is_err() {
+                    return;
+                }
+                if event.is_some_and(|event| sender.send(event).is_err()) {
+                    return;
+                }
+                if watcher.is_some() {
+                    // One read covers every change reported meanwhile
+                    let _ = changes.recv_timeout(RECHECK_INTERVAL.max(interval));
+                    while changes.try_recv().is_ok() {}
+                } else {
+                    thread::sleep(interval);
+                }
+            }
+        })
+    }
+
+    fn spawn(run: impl FnOnce(Sender<InputEvent>, Arc<AtomicBool>) + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        thread::spawn(move || run(sender, thread_stop));
+        Self { receiver, stop }
+    }
+
+    /// Everything received since the last call, without waiting
+    pub fn poll(&self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        // Disconnected means the thread ended after its last event was sent
+        while let Ok(event) = self.receiver.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+}
+
+impl Drop for InputStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Watch the directory of the file at `path`, sending `()` on `wake` when the file changes
+///
+/// The directory is watched rather than the file, so a file replaced under
+/// the same name is still reported. None where notifications aren't available.
+fn watch_file(path: &Path, wake: Sender<()>) -> Option<RecommendedWatcher> {
+    let name = path.file_name()?.to_os_string();
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // An error may mean missed changes, so it wakes the reader too
+        let ours = event.map_or(true, |event| event.paths.iter().any(|p| p.file_name() == Some(name.as_os_str())));
+        if ours {
+            let _ = wake.send(());
+        }
+    })
+    .ok()?;
+    watcher.watch(dir, RecursiveMode::NonRecursive).ok()?;
+    Some(watcher)
+}
+
+/// Send each chunk of complete lines as it arrives
+///
+/// Bytes are only decoded once a line is complete, so a UTF-8 sequence split
+/// across reads is never mangled.
+fn read_lines<R: Read>(reader: R, sender: &Sender<InputEvent>, stop: &AtomicBool) {
+    let mut reader = BufReader::new(reader);
+    let mut partial: Vec<u8> = Vec::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        let chunk = match reader.fill_buf() {
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                let _ = sender.send(InputEvent::Error(e));
+                return;
+            }
+        };
+        if chunk.is_empty() {
+            if !partial.is_empty() {
+                let _ = sender.send(InputEvent::Lines(vec![decode_line(&partial)]));
+            }
+            let _ = sender.send(InputEvent::Closed);
+            return;
+        }
+
+        partial.extend_from_slice(chunk);
+        let len = chunk.len();
+        reader.consume(len);
+
+        if let Some(end) = partial.iter().rposition(|&b| b == b'\n') {
+            let rest = partial.split_off(end + 1);
+            let mut lines: Vec<String> = partial.split(|&b| b == b'\n').map(decode_line).collect();
+            // The split after the final newline yields an empty tail
+            lines.pop();
+            partial = rest;
+            if sender.send(InputEvent::Lines(lines)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Decode a line, dropping a trailing `\r`
+fn decode_line(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use std::time::Instant;
+
+    /// Poll `stream` until `done` accepts the collected events or a second passes
+    fn collect(stream: &InputStream, done: impl Fn(&[InputEvent]) -> bool) -> Vec<InputEvent> {
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while !done(&events) && start.elapsed() < Duration::from_secs(1) {
+            events.extend(stream.poll());
+            thread::sleep(Duration::from_millis(5));
+        }
+        events
+    }
+
+    fn lines_of(events: &[InputEvent]) -> Vec<String> {
+        events
+            .iter()
+            .flat_map(|e| match e {
+                InputEvent::Lines(lines) => lines.clone(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reader_stream() {
+        let stream = InputStream::spawn_reader(Cursor::new("one\r\ntwo\n\nlast".as_bytes().to_vec()));
+        let events = collect(&stream, |events| matches!(events.last(), Some(InputEvent::Closed)));
+
+        assert_eq!(lines_of(&events), vec!["one", "two", "", "last"]);
+        assert!(matches!(events.last(), Some(InputEvent::Closed)));
+    }
+
+    #[test]
+    fn test_follow_stream() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "old").unwrap();
+        let reader = FollowReader::new(file.path().to_path_buf(), true).unwrap();
+        let stream = InputStream::spawn_follow(reader, Duration::from_millis(5));
+
+        writeln!(file, "new 1\nnew 2").unwrap();
+        file.flush().unwrap();
+        let events = collect(&stream, |events| lines_of(events).len() >= 2);
+
+        assert_eq!(lines_of(&events), vec!["new 1", "new 2"]);
+    }
+
+    #[test]
+    fn test_follow_stream_wakes_on_change() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let reader = FollowReader::new(file.path().to_path_buf(), true).unwrap();
+        // Far longer than `collect` waits: only a change notification can bring the line in time
+        let stream = InputStream::spawn_follow(reader, Duration::from_secs(60));
+
+        thread::sleep(Duration::from_millis(50));
+        writeln!(file, "changed").unwrap();
+        file.flush().unwrap();
+        let events = collect(&stream, |events| !lines_of(events).is_empty());
+
+        assert_eq!(lines_of(&events), vec!["changed"]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Saving piped input while viewing it (`--tee FILE`)
+//!
+//! Every byte read from stdin is also written to the tee file as it arrives,
+//! so the output of a one-off command is kept even when it is only paged.
+//! With `--tee-strip-ansi` escape sequences are left out of the copy; they are
+//! removed byte by byte, so a sequence split across two reads is still caught.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::error::MatError;
+
+/// The file stdin is copied to, if any
+static TEE: Mutex<Option<Tee>> = Mutex::new(None);
+
+/// Copy everything read from stdin from now on to `path`, without escape sequences if `strip_ansi`
+pub fn tee_stdin(path: &Path, strip_ansi: bool) -> Result<(), MatError> {
+    let file = File::create(path).map_err(|source| MatError::Io {
+        source,
+        path: path.to_path_buf(),
+    })?;
+    *TEE.lock().unwrap() = Some(Tee {
+        file,
+        strip_ansi,
+        escape: Escape::None,
+    });
+    Ok(())
+}
+
+/// A reader of stdin that copies what it reads to the tee file
+pub struct TeeReader<R> {
+    inner: R,
+}
+
+impl<R> TeeReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R: Read> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(tee) = TEE.lock().unwrap().as_mut() {
+            // A full disk must not stop the input from being shown
+            let _ = tee.write(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// Where the copy is in an escape sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    /// Not in one
+    None,
+    /// Just after ESC
+    Start,
+    /// In a CSI sequence (`ESC [`), up to its final byte
+    Csi,
+    /// In an OSC sequence (`ESC ]`), up to BEL or ST
+    Osc,
+    /// At an ESC inside an OSC sequence, which starts its end (ST, `ESC \`)
+    OscEnd,
+}
+
+struct Tee {
+    file: File,
+    strip_ansi: bool,
+    escape: Escape,
+}
+
+impl Tee {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.strip_ansi {
+            let text = strip_escapes(bytes, &mut self.escape);
+            self.file.write_all(&text)
+        } else {
+            self.file.write_all(bytes)
+        }
+    }
+}
+
+/// `bytes` without escape sequences, starting (and leaving) the scan in state `escape`
+fn strip_escapes(bytes: &[u8], escape: &mut Escape) -> Vec<u8> {
+    let mut text = Vec::with_capacity(bytes.len());
+    for &byte in bytes {
+        *escape = match (*escape, byte) {
+            (Escape::None, 0x1b) => Escape::Start,
+            (Escape::None, _) => {
+                text.push(byte);
+                Escape::None
+            }
+            (Escape::Start, b'[') => Escape::Csi,
+            (Escape::Start, b']') => Escape::Osc,
+            // Two-byte sequences end with their second byte
+            (Escape::Start, _) => Escape::None,
+            (Escape::Csi, 0x40..=0x7e) => Escape::None,
+            (Escape::Csi, _) => Escape::Csi,
+            (Escape::Osc, 0x07) => Escape::None,
+            (Escape::Osc, 0x1b) => Escape::OscEnd,
+            (Escape::Osc, _) => Escape::Osc,
+            (Escape::OscEnd, _) => Escape::None,
+        };
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_escapes_across_reads() {
+        let mut escape = Escape::None;
+        let mut text = strip_escapes(b"\x1b[1;3", &mut escape);
+        text.extend(strip_escapes(b"1mred\x1b[0m plain \x1b]8;;http://x\x1b\\link\x1b]8;;\x07", &mut escape));
+        assert_eq!(text, b"red plain link");
+        assert_eq!(escape, Escape::None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Options taken over from the `LESS` environment variable
+//!
+//! When mat is set as `PAGER`, programs that page through it (git, man,
+//! journalctl) leave their flags for less in `LESS`, often `FRX`. The flags
+//! with a mat equivalent are mapped onto it, so output paged through mat looks
+//! as it would in less:
+//!
+//! - `-R` keeps the colors of escape codes (`--ansi`)
+//! - `-S` chops long lines instead of wrapping them (`--wrap none`)
+//! - `-N` shows line numbers (`-n`)
+//! - `-i` / `-I` makes search case-insensitive (`-i`)
+//! - `-F` pages only what is taller than the screen (`--paging auto`); without
+//!   it everything is paged, however short (`--paging always`), as less does
+//! - `-X` leaves the last page on the screen on exit (`--keep-screen`)
+//!
+//! Other flags are ignored, along with the arguments of those that take one.
+//! Options given on the command line win over `LESS`.
+
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+
+use crate::cli::{Args, Paging, WrapMode};
+
+/// Short options of less that take a number, after which more flags may follow (`-x4R`)
+const TAKES_NUMBER: &[char] = &['b', 'h', 'j', 'x', 'y', 'z', '#'];
+
+/// Short options of less that take the rest of the word as their argument
+const TAKES_STRING: &[char] = &['k', 'o', 'O', 'p', 'P', 't', 'T', 'D'];
+
+/// The flags of a `LESS` value that mat has an equivalent for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LessFlags {
+    pub raw_control_chars: bool,
+    pub chop_long_lines: bool,
+    pub line_numbers: bool,
+    pub ignore_case: bool,
+    pub quit_if_one_screen: bool,
+    pub no_init: bool,
+}
+
+impl LessFlags {
+    /// Parse a `LESS` value such as `FRX`, `-R -S` or `--RAW-CONTROL-CHARS -x4`
+    pub fn parse(value: &str) -> Self {
+        let mut flags = Self::default();
+        let mut words = value.split_whitespace();
+        while let Some(word) = words.next() {
+            if let Some(long) = word.strip_prefix("--") {
+                flags.set_long(long.split('=').next().unwrap_or(long));
+                continue;
+            }
+            // `+cmd` runs a command at startup, which mat has no use for
+            if word.starts_with('+') {
+                continue;
+            }
+            let mut chars = word.trim_start_matches('-').chars().peekable();
+            while let Some(c) = chars.next() {
+                // An argument left out of the word is the next word
+                if TAKES_STRING.contains(&c) {
+                    if chars.peek().is_none() {
+                        words.next();
+                    }
+                    break;
+                }
+                // A bare number (`-4`) is the window size, like `-z4`
+                if TAKES_NUMBER.contains(&c) || c.is_ascii_digit() {
+                    if chars.peek().is_none() && !c.is_ascii_digit() {
+                        words.next();
+                    }
+                    while chars.next_if(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-')).is_some() {}
+                    continue;
+                }
+                flags.set_short(c);
+            }
+        }
+        flags
+    }
+
+    fn set_short(&mut self, c: char) {
+        match c {
+            'R' => self.raw_control_chars = true,
+            'S' => self.chop_long_lines = true,
+            'N' => self.line_numbers = true,
+            'i' | 'I' => self.ignore_case = true,
+            'F' => self.quit_if_one_screen = true,
+            'X' => self.no_init = true,
+            _ => {}
+        }
+    }
+
+    fn set_long(&mut self, name: &str) {
+        match name {
+            "RAW-CONTROL-CHARS" => self.raw_control_chars = true,
+            "chop-long-lines" => self.chop_long_lines = true,
+            "LINE-NUMBERS" => self.line_numbers = true,
+            "ignore-case" | "IGNORE-CASE" => self.ignore_case = true,
+            "quit-if-one-screen" => self.quit_if_one_screen = true,
+            "no-init" => self.no_init = true,
+            _ => {}
+        }
+    }
+
+    /// Turn on the mat options of the flags, leaving those given in `matches` alone
+    ///
+    /// Paging is set either way, since less pages short output unless told otherwise.
+    pub fn apply(&self, args: &mut Args, matches: &ArgMatches) {
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        args.ansi |= self.raw_control_chars;
+        args.line_numbers |= self.line_numbers;
+        args.ignore_case |= self.ignore_case;
+        args.keep_screen |= self.no_init;
+        if self.chop_long_lines && !given("wrap") {
+            args.wrap = WrapMode::None;
+        }
+        if !given("paging") {
+            args.paging = if self.quit_if_one_screen { Paging::Auto } else { Paging::Always };
+        }
+    }
+}
+
+/// Whether mat is paging piped input the way `PAGER` is used, rather than viewing files
+pub fn used_as_pager(args: &Args, stdin_is_terminal: bool) -> bool {
+    !stdin_is_terminal && args.files.iter().all(|file| file.as_os_str() == "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn parse_args(argv: &[&str]) -> (Args, ArgMatches) {
+        let matches = Args::command().get_matches_from(argv);
+        (Args::from_arg_matches(&matches).unwrap(), matches)
+    }
+
+    #[test]
+    fn test_parse_less_flags() {
+        let flags = LessFlags::parse("FRX");
+        assert!(flags.quit_if_one_screen && flags.raw_control_chars && flags.no_init);
+        assert!(!flags.chop_long_lines && !flags.line_numbers && !flags.ignore_case);
+
+        let flags = LessFlags::parse("-S -N --IGNORE-CASE");
+        assert!(flags.chop_long_lines && flags.line_numbers && flags.ignore_case);
+
+        // Arguments of other options aren't read as flags
+        assert_eq!(LessFlags::parse("-x4 -j.5 -PSprompt -k RX -b 256 -z-4 +Gg --tabs=4"), LessFlags::default());
+        // Flags may follow a number in the same word, but not a string
+        assert!(LessFlags::parse("-Mx4,8R").raw_control_chars);
+        assert!(LessFlags::parse("-#8R").raw_control_chars);
+        assert_eq!(LessFlags::parse("-oRX"), LessFlags::default());
+    }
+
+    #[test]
+    fn test_apply_less_flags() {
+        let (mut args, matches) = parse_args(&["mat"]);
+        LessFlags::parse("RSNiX").apply(&mut args, &matches);
+        assert!(args.ansi && args.line_numbers && args.ignore_case && args.keep_screen);
+        assert_eq!(args.wrap, WrapMode::None);
+        // Like less, short output is paged too unless -F says otherwise
+        assert_eq!(args.paging, Paging::Always);
+
+        let (mut args, matches) = parse_args(&["mat"]);
+        LessFlags::parse("FRX").apply(&mut args, &matches);
+        assert_eq!(args.paging, Paging::Auto);
+        let (mut args, matches) = parse_args(&["mat"]);
+        LessFlags::parse("").apply(&mut args, &matches);
+        assert_eq!(args.paging, Paging::Always);
+
+        // The command line wins
+        let (mut args, matches) = parse_args(&["mat", "--wrap", "word", "--paging", "always"]);
+        LessFlags::parse("SF").apply(&mut args, &matches);
+        assert_eq!(args.wrap, WrapMode::Word);
+        assert_eq!(args.paging, Paging::Always);
+    }
+
+    #[test]
+    fn test_used_as_pager() {
+        let (args, _) = parse_args(&["mat"]);
+        assert!(used_as_pager(&args, false));
+        assert!(!used_as_pager(&args, true));
+        let (args, _) = parse_args(&["mat", "-"]);
+        assert!(used_as_pager(&args, false));
+        let (args, _) = parse_args(&["mat", "notes.txt"]);
+        assert!(!used_as_pager(&args, false));
+    }
+}
@@ -0,0 +1,152 @@
+//! Diagnostics on stderr or in a file (`-q`, `--verbose`, `--log-file`)
+//!
+//! mat reports through the `log` macros. Warnings are shown by default and
+//! `-q` leaves only errors. Each `--verbose` adds a level: how inputs were
+//! decoded and why they were (or weren't) highlighted as a language, then
+//! follow mode events, then every read. Only mat's own messages are shown,
+//! not those of the libraries it uses.
+//!
+//! While the pager has the terminal, messages for stderr are held back and
+//! written once it is restored, so they don't draw over the screen.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+
+use crate::error::MatError;
+
+/// Where messages go, and the ones held back while the pager runs
+struct Logger {
+    file: Option<Mutex<File>>,
+    held: Mutex<Option<Vec<String>>>,
+}
+
+static LOGGER: OnceCell<Logger> = OnceCell::new();
+
+/// The level shown for `-q` and the number of `--verbose` flags
+pub fn level(quiet: bool, verbose: u8) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+/// Report messages up to `level`, to stderr or appended to `file`
+pub fn init(level: LevelFilter, file: Option<&Path>) -> Result<(), MatError> {
+    let file = match file {
+        Some(path) => Some(OpenOptions::new().create(true).append(true).open(path).map_err(|source| MatError::Io {
+            source,
+            path: path.to_path_buf(),
+        })?),
+        None => None,
+    };
+    let logger = LOGGER.get_or_init(|| Logger {
+        file: file.map(Mutex::new),
+        held: Mutex::new(None),
+    });
+    // Only fails if a logger is already set, which then stays
+    let _ = log::set_logger(logger);
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Hold back messages for stderr until [`release`], while the pager draws on the terminal
+pub fn hold() {
+    if let Some(mut held) = LOGGER.get().and_then(|logger| logger.held.lock().ok()) {
+        held.get_or_insert_with(Vec::new);
+    }
+}
+
+/// Write the messages held back since [`hold`], and the next ones as they come
+pub fn release() {
+    let held = LOGGER.get().and_then(|logger| logger.held.lock().ok()?.take());
+    let mut stderr = io::stderr().lock();
+    for message in held.into_iter().flatten() {
+        let _ = writeln!(stderr, "{}", message);
+    }
+}
+
+/// `mat: message` for warnings and errors, `mat: [debug input::follow] message` for the rest
+fn format(record: &Record) -> String {
+    match record.level() {
+        Level::Error | Level::Warn => format!("mat: {}", record.args()),
+        level => {
+            let level = level.as_str().to_ascii_lowercase();
+            match record.target().strip_prefix("mat::") {
+                Some(module) => format!("mat: [{} {}] {}", level, module, record.args()),
+                None => format!("mat: [{}] {}", level, record.args()),
+            }
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        metadata.level() <= log::max_level() && (target == "mat" || target.starts_with("mat::"))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = format(record);
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+                let _ = writeln!(file, "{} {}", time, message);
+            }
+            return;
+        }
+        if let Ok(mut held) = self.held.lock() {
+            if let Some(held) = held.as_mut() {
+                held.push(message);
+                return;
+            }
+        }
+        let _ = writeln!(io::stderr(), "{}", message);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!(level(false, 0), LevelFilter::Warn);
+        assert_eq!(level(true, 0), LevelFilter::Error);
+        assert_eq!(level(true, 2), LevelFilter::Error);
+        assert_eq!(level(false, 1), LevelFilter::Info);
+        assert_eq!(level(false, 2), LevelFilter::Debug);
+        assert_eq!(level(false, 9), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn test_format() {
+        let args = format_args!("stdin: decoded as UTF-8");
+        let record = Record::builder().level(Level::Info).target("mat::input").args(args).build();
+        assert_eq!(format(&record), "mat: [info input] stdin: decoded as UTF-8");
+        let record = Record::builder().level(Level::Debug).target("mat").args(args).build();
+        assert_eq!(format(&record), "mat: [debug] stdin: decoded as UTF-8");
+
+        let args = format_args!("ignoring syntaxes in ~/.config/mat/syntaxes");
+        let record = Record::builder().level(Level::Warn).target("mat::highlight").args(args).build();
+        assert_eq!(format(&record), "mat: ignoring syntaxes in ~/.config/mat/syntaxes");
+    }
+}
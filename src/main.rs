@@ -1,46 +1,116 @@
+mod bench;
 mod cli;
+mod config;
 mod display;
 mod error;
 mod filter;
+mod format;
 mod highlight;
 mod input;
+mod less;
+mod logging;
 mod markdown;
 mod pager;
+mod platform;
+mod sandbox;
+mod stats;
 mod theme;
+mod usage;
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
+use regex::Regex;
+use std::io::IsTerminal;
+use std::path::Path;
 use std::process::ExitCode;
 
-use cli::Args;
-use display::Document;
-use error::{MatError, EXIT_SUCCESS};
-use filter::{apply_grep_highlight, grep_filter, GrepOptions};
-use highlight::{apply_search_highlight, apply_syntax_highlight, SearchState};
-use input::{determine_input_source, load_content};
-use markdown::render_markdown;
-use pager::{filter_line_range, parse_line_range, print_document, run_pager};
-use theme::get_theme;
-
-fn run(args: Args) -> Result<(), MatError> {
-    // Determine input source
-    let source = match determine_input_source(&args) {
-        Some(s) => s,
-        None => {
-            eprintln!("mat: No input file specified. Use 'mat <file>' or pipe data to stdin.");
-            return Ok(());
-        }
-    };
+use cli::{Args, ColorMode, ErrorFormat, Numbering, WrapMode};
+use config::Config;
+use display::timestamp::render_timestamps;
+use display::{glyphs, hexdump, wrap_document, Document, OutputStyle};
+use error::{MatError, EXIT_INTERRUPTED, EXIT_SELECTED, EXIT_SUCCESS};
+use filter::{
+    annotate_lines, apply_grep_highlight, build_regex_from_patterns, extract_table, grep_filter, hide_matching,
+    squeeze_repeated, Baseline, GrepOptions,
+};
+use format::{format_json, format_markup, is_single_line_json, Markup, Table};
+use highlight::{
+    apply_injections, apply_path_highlight, apply_word_diff, looks_like_diff, apply_search_highlight, apply_syntax_highlight, apply_trace_highlight,
+    detect_language, find_theme, linguist_language, print_languages, print_themes, SearchState,
+};
+use input::large::LazyDocument;
+use input::{
+    apply_ansi_styles, determine_input_sources, is_prose_extension, load_content, parse_file_location, stream_stdin,
+    tee_stdin, InputSource,
+};
+use markdown::{markdown_task_counts, render_markdown, MarkdownOptions};
+use pager::{
+    filter_line_range, may_page, page_externally, parse_line_range, print_document, print_text_width, render_screen, run_pager,
+    should_page, use_external_pager, PagerFile, PagerSource, QuitAction,
+};
+use theme::{get_theme, Theme};
+
+/// Result of the per-file pipeline
+struct BuiltDocument {
+    /// The document to show
+    document: Document,
+    /// Whether it was rendered as markdown
+    is_markdown: bool,
+    /// Line count of the source before line range and grep filtering
+    source_lines: usize,
+    /// The highlighted document before grep filtering, when requested
+    unfiltered: Option<Document>,
+    /// A re-rendered table (database result or CSV), whose header the pager pins
+    table: Option<Table>,
+    /// Whether it is prose (markdown or plain text), which the pager's reading mode applies to
+    prose: bool,
+    /// The input as it came, when it was pretty-printed for the pager
+    raw: Option<Document>,
+    /// Whether its styles are the colors of its escape codes (`--ansi`)
+    ansi_colored: bool,
+    /// Checked and total task list items of a markdown document that has some
+    tasks: Option<(usize, usize)>,
+}
 
-    // Validate: follow mode requires a file, not stdin
-    if args.follow && matches!(source, input::InputSource::Stdin) {
-        return Err(MatError::FollowModeStdin);
+/// Load one input and run it through the per-file pipeline
+///
+/// With `keep_unfiltered`, a grep-filtered input also keeps its unfiltered
+/// document so the pager can search all lines.
+fn build_document(
+    source: &InputSource,
+    args: &Args,
+    config: &Config,
+    hide_pattern: Option<&Regex>,
+    grep_options: Option<&GrepOptions>,
+    baseline: Option<&Baseline>,
+    keep_unfiltered: bool,
+) -> Result<BuiltDocument, MatError> {
+    // A file over --max-memory is never read whole
+    if let (InputSource::File(path), Some(limit)) = (source, args.max_memory) {
+        let size = std::fs::metadata(path).map_or(0, |m| m.len());
+        if size > limit {
+            return build_mapped_document(path, size, limit, args, hide_pattern, grep_options);
+        }
     }
 
     // Load content
-    let content = load_content(source.clone(), &args)?;
+    let mut content = load_content(source.clone(), args, &config.preprocessors)?;
+    // A hex dump is styled as it is built, and is nothing to re-render
+    let binary = content.binary.take();
+
+    // An explicit --language wins over the repository's linguist overrides
+    let language = args.language.clone().or_else(|| match source {
+        InputSource::File(path) => {
+            let language = linguist_language(path);
+            if let Some(ref language) = language {
+                log::debug!("{}: language {} from .gitattributes", path.display(), language);
+            }
+            language
+        }
+        InputSource::Stdin => None,
+    });
 
     // Determine if we should render as markdown
-    let should_render_markdown = if args.no_markdown {
+    let should_render_markdown = if args.no_markdown || binary.is_some() {
         false
     } else if args.markdown {
         true
@@ -49,12 +119,86 @@ fn run(args: Args) -> Result<(), MatError> {
         content.is_markdown
     };
 
+    // Re-indent minified markup before anything counts its lines
+    if args.pretty && !should_render_markdown {
+        let markup = match content.extension {
+            Some(ref extension) => Markup::from_extension(extension),
+            None => Markup::sniff(&content.text),
+        };
+        if let Some(markup) = markup {
+            content.text = format_markup(&content.text, markup);
+        }
+    }
+    // JSON too, and without being asked when it is piped to the pager on one line (`curl ... | mat`);
+    // the input as it came is kept for the pager to switch back to
+    let json = !should_render_markdown
+        && binary.is_none()
+        && ((args.pretty && content.extension.as_deref().map_or(true, |e| e.eq_ignore_ascii_case("json")))
+            || (matches!(source, InputSource::Stdin) && may_page(args) && is_single_line_json(&content.text)));
+    let mut raw = None;
+    if let Some(pretty) = json.then(|| format_json(&content.text)).flatten() {
+        log::info!("{}: pretty-printed as JSON", content.source_name);
+        let text = std::mem::replace(&mut content.text, pretty);
+        if may_page(args) {
+            raw = Some(Document::from_text(&text, content.source_name.clone(), content.encoding.clone()));
+        }
+    }
+    let language = if raw.is_some() && language.is_none() { Some("JSON".to_string()) } else { language };
+
+    // Result tables of database clients are re-rendered with aligned columns, and so are
+    // CSV files in the pager (printed they stay CSV, for the next program in the pipeline)
+    let delimited = language.is_none() && may_page(args);
+    let table = if should_render_markdown || grep_options.is_some() || binary.is_some() {
+        None
+    } else {
+        match content.extension.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") if delimited => Table::parse_delimited(&content.text, ','),
+            Some("tsv") if delimited => Table::parse_delimited(&content.text, '\t'),
+            _ => Table::parse(&content.text),
+        }
+    };
+    let prose = should_render_markdown
+        || (table.is_none()
+            && match content.extension {
+                Some(ref extension) => is_prose_extension(extension),
+                None => language.is_none() && detect_language(&content.source_name).is_none(),
+            });
+
+    // The pager shows how many of a document's tasks are done
+    let tasks = should_render_markdown
+        .then(|| markdown_task_counts(&content.text))
+        .filter(|&(_, total)| total > 0);
+
+    // Determine theme for highlighting
+    let theme = get_theme(args.theme.as_deref());
+
     // Create document (with or without markdown rendering)
-    let mut document = if should_render_markdown {
-        render_markdown(&content.text, content.source_name)
+    let mut document = if let Some(ref bytes) = binary {
+        hexdump(bytes, content.source_name)
+    } else if let Some(ref table) = table {
+        table.to_document(content.source_name, content.encoding)
+    } else if should_render_markdown {
+        let options = MarkdownOptions {
+            link_references: args.link_references,
+            reading_time: args.reading_time,
+            theme: (!args.no_highlight).then_some(theme),
+        };
+        render_markdown(&content.text, content.source_name, &options)
     } else {
         Document::from_text(&content.text, content.source_name, content.encoding)
     };
+    // Colors of piped input (`git diff --color`) take the place of syntax highlighting
+    let ansi_colored = args.ansi && table.is_none() && !should_render_markdown && apply_ansi_styles(&mut document);
+    // A diff gets its changed words emphasized; input that starts like one (`git diff | mat`) is highlighted as one
+    let diff = binary.is_none()
+        && table.is_none()
+        && !should_render_markdown
+        && match language.as_deref().or_else(|| detect_language(&document.source_name)) {
+            Some(language) => language.eq_ignore_ascii_case("diff") || language.eq_ignore_ascii_case("patch"),
+            None => looks_like_diff(&document),
+        };
+    let language = if diff && language.is_none() { Some("Diff".to_string()) } else { language };
+    let source_lines = document.lines.last().map_or(0, |l| l.number);
 
     // Apply line range filter if specified
     if let Some(ref range) = args.lines {
@@ -62,58 +206,524 @@ fn run(args: Args) -> Result<(), MatError> {
         filter_line_range(&mut document, start, end);
     }
 
+    // Drop hidden lines before grep, so they never show up as context either
+    if let Some(pattern) = hide_pattern {
+        document = hide_matching(&document, pattern);
+    }
+
     // Apply grep filter if specified
-    let grep_options = GrepOptions::from_args(&args)?;
-    if let Some(ref opts) = grep_options {
+    let mut unfiltered = None;
+    if let Some(opts) = grep_options {
+        if keep_unfiltered && !args.extract {
+            unfiltered = Some(document.clone());
+        }
         document = grep_filter(&document, opts);
-    }
 
-    // Determine theme for highlighting
-    let theme = get_theme(args.theme.as_deref());
+        // Extraction replaces the lines with a table of captured values
+        if args.extract {
+            let table = extract_table(&document, &opts.pattern)?;
+            return Ok(BuiltDocument {
+                document: table,
+                is_markdown: false,
+                source_lines,
+                unfiltered: None,
+                table: None,
+                prose: false,
+                raw: None,
+                ansi_colored: false,
+                tasks: None,
+            });
+        }
+    }
 
     // Apply syntax highlighting if not disabled and not rendering markdown
-    // (markdown renderer already applies its own styling)
-    if !args.no_highlight && !should_render_markdown {
-        apply_syntax_highlight(&mut document, args.language.as_deref(), theme);
+    // (markdown renderer and tables already apply their own styling)
+    let styled_otherwise = if args.no_highlight {
+        Some("--no-highlight")
+    } else if binary.is_some() {
+        Some("shown as a hex dump")
+    } else if should_render_markdown {
+        Some("rendered as markdown")
+    } else if table.is_some() {
+        Some("shown as a table")
+    } else if ansi_colored {
+        Some("colored by its escape codes")
+    } else {
+        None
+    };
+    if let Some(reason) = styled_otherwise {
+        log::info!("{}: not syntax highlighted: {}", document.source_name, reason);
+    } else {
+        apply_syntax_highlight(&mut document, language.as_deref(), theme);
+        apply_injections(&mut document, language.as_deref(), theme, &config.injections);
+        if let Some(ref mut raw) = raw {
+            apply_syntax_highlight(raw, language.as_deref(), theme);
+        }
+        // Stack traces in logs are restyled on top
+        apply_trace_highlight(&mut document);
+        if let Some(ref mut unfiltered) = unfiltered {
+            apply_syntax_highlight(unfiltered, language.as_deref(), theme);
+            apply_injections(unfiltered, language.as_deref(), theme, &config.injections);
+            apply_trace_highlight(unfiltered);
+        }
+    }
+    // Changed words stand out in the syntax colors, or in those of the escape codes
+    if diff && !args.no_highlight && (styled_otherwise.is_none() || ansi_colored) {
+        apply_word_diff(&mut document, theme);
+        if let Some(ref mut unfiltered) = unfiltered {
+            apply_word_diff(unfiltered, theme);
+        }
+    }
+    // Paths are underlined in documents of every kind, so the pager can open them
+    if !args.no_highlight && table.is_none() && binary.is_none() {
+        apply_path_highlight(&mut document);
+        if let Some(ref mut unfiltered) = unfiltered {
+            apply_path_highlight(unfiltered);
+        }
     }
 
     // Apply grep match highlighting AFTER syntax highlighting
-    if let Some(ref opts) = grep_options {
-        apply_grep_highlight(&mut document, &opts.pattern);
+    if let Some(opts) = grep_options {
+        apply_grep_highlight(&mut document, opts);
+    }
+
+    // Hash and comparison columns go in front of the finished lines
+    annotate_lines(&mut document, args.line_hashes, baseline);
+    if let Some(ref mut unfiltered) = unfiltered {
+        annotate_lines(unfiltered, args.line_hashes, baseline);
+    }
+
+    Ok(BuiltDocument {
+        document,
+        is_markdown: should_render_markdown,
+        source_lines,
+        unfiltered,
+        table,
+        prose,
+        raw,
+        ansi_colored,
+        tasks,
+    })
+}
+
+/// Count the `--search` matches of a single file over `--max-memory` by scanning its mmap
+///
+/// `None` when the count isn't of such a file, or when `--lines`, `--grep`
+/// or `--hide` leave only part of it to count.
+fn count_mapped_matches(
+    sources: &[InputSource],
+    args: &Args,
+    state: &mut SearchState,
+) -> Result<Option<usize>, MatError> {
+    let (path, limit) = match (sources, args.max_memory) {
+        ([InputSource::File(path)], Some(limit)) => (path, limit),
+        _ => return Ok(None),
+    };
+    let partial = args.lines.is_some() || args.grep.is_some() || !args.hide.is_empty();
+    if partial || std::fs::metadata(path).map_or(0, |m| m.len()) <= limit {
+        return Ok(None);
+    }
+    let mapped = LazyDocument::new(path.to_path_buf()).map_err(|source| MatError::Io {
+        source,
+        path: path.to_path_buf(),
+    })?;
+    state.find_matches_lazy(&mapped);
+    Ok(Some(state.match_count()))
+}
+
+/// Load only the `--lines` range or the `--grep` matches of a file over `--max-memory`
+///
+/// The file stays memory mapped, so lines outside the range or without a match
+/// are never read into memory. Without either option the whole file would be
+/// loaded, which is refused. Markdown is shown as source.
+fn build_mapped_document(
+    path: &Path,
+    size: u64,
+    limit: u64,
+    args: &Args,
+    hide_pattern: Option<&Regex>,
+    grep_options: Option<&GrepOptions>,
+) -> Result<BuiltDocument, MatError> {
+    if args.lines.is_none() && grep_options.is_none() {
+        return Err(MatError::TooLarge {
+            path: path.to_path_buf(),
+            size,
+            limit,
+        });
+    }
+    let mapped = LazyDocument::new(path.to_path_buf()).map_err(|source| MatError::Io {
+        source,
+        path: path.to_path_buf(),
+    })?;
+    let source_lines = mapped.line_count();
+
+    let mut document = match args.lines {
+        Some(ref range) => {
+            let (start, end) = parse_line_range(range, source_lines)?;
+            let mut document = mapped.range_document(start - 1, end);
+            if let Some(pattern) = hide_pattern {
+                document = hide_matching(&document, pattern);
+            }
+            if let Some(opts) = grep_options {
+                document = grep_filter(&document, opts);
+            }
+            document
+        }
+        None => {
+            let opts = grep_options.expect("checked above");
+            let mut document = mapped.grep_filter(opts);
+            if let Some(pattern) = hide_pattern {
+                document = hide_matching(&document, pattern);
+            }
+            document
+        }
+    };
+    document.source_name = platform::display_path(path);
+
+    if let Some(opts) = grep_options.filter(|_| args.extract) {
+        document = extract_table(&document, &opts.pattern)?;
+    } else {
+        if !args.no_highlight {
+            let language = args.language.clone().or_else(|| linguist_language(path));
+            apply_syntax_highlight(&mut document, language.as_deref(), get_theme(args.theme.as_deref()));
+        }
+        if let Some(opts) = grep_options {
+            apply_grep_highlight(&mut document, opts);
+        }
     }
 
-    // Apply search highlighting if specified
-    let search_state = SearchState::from_args(&args)?;
-    if let Some(ref state) = search_state {
-        apply_search_highlight(&mut document, &state.pattern);
+    Ok(BuiltDocument {
+        document,
+        is_markdown: false,
+        source_lines,
+        unfiltered: None,
+        table: None,
+        prose: false,
+        raw: None,
+        ansi_colored: false,
+        tasks: None,
+    })
+}
+
+/// Whether a still-open pipe can be paged while it is read, like less does
+///
+/// Markdown, hex dumps, grep filtering, line ranges and re-indenting need the whole input,
+/// so with those stdin is read to the end first.
+fn can_stream_stdin(args: &Args, sources: &[InputSource]) -> bool {
+    matches!(sources, [InputSource::Stdin])
+        && may_page(args)
+        && !use_external_pager(args)
+        && !args.markdown
+        && !args.hex
+        && args.grep.is_none()
+        && args.pattern_file.is_none()
+        && args.hide.is_empty()
+        && args.lines.is_none()
+        && !args.pretty
+        && !args.count_matches
+        && !args.line_hashes
+        && args.compare.is_none()
+        && args.render_screen.is_none()
+}
+
+/// One-line "N matches on M lines" summary for `-s PAT -P`
+fn summarize_matches(state: &SearchState) -> String {
+    let count = state.match_count();
+    let mut lines: Vec<usize> = state.matches.iter().map(|m| m.line_idx).collect();
+    lines.dedup();
+    format!(
+        "{} {} on {} {}",
+        count,
+        if count == 1 { "match" } else { "matches" },
+        lines.len(),
+        if lines.len() == 1 { "line" } else { "lines" }
+    )
+}
+
+/// Run mat, returning the exit code of a successful run
+fn run(args: Args) -> Result<i32, MatError> {
+    // Windows consoles need escape code processing turned on before printing
+    platform::console_supports_ansi();
+    glyphs::use_ascii(args.ascii || (std::io::stdout().is_terminal() && glyphs::terminal_lacks_unicode()));
+    sandbox::forbid_exec(args.no_exec);
+    logging::init(logging::level(args.quiet, args.verbose), args.log_file.as_deref())?;
+
+    if args.bench {
+        return bench::run_bench(&args).map(|_| EXIT_SUCCESS);
+    }
+
+    if args.usage {
+        return usage::print_usage().map(|_| EXIT_SUCCESS);
+    }
+
+    if args.list_languages {
+        return print_languages().map(|_| EXIT_SUCCESS);
+    }
+    if args.list_themes {
+        return print_themes(OutputStyle::from_args(&args)?.color).map(|_| EXIT_SUCCESS);
+    }
+    if let Some(name) = args.theme.as_deref().filter(|name| Theme::from_str(name).is_none() && find_theme(name).is_none()) {
+        log::warn!("unknown theme {}, see --list-themes", name);
+    }
+
+    // Determine input sources
+    let sources = determine_input_sources(&args);
+    if sources.is_empty() {
+        eprintln!("mat: No input file specified. Use 'mat <file>' or pipe data to stdin.");
+        return Ok(EXIT_SUCCESS);
     }
 
-    // Get file path for follow mode (only for file inputs)
-    let file_path = match &source {
-        input::InputSource::File(p) => Some(p.clone()),
-        input::InputSource::Stdin => None,
+    if args.stats {
+        let config = Config::load()?;
+        return stats::print_stats(&sources, &args, &config).map(|_| EXIT_SUCCESS);
+    }
+
+    // Validate: follow mode requires a single file, not stdin
+    if args.follow {
+        if sources.len() > 1 {
+            return Err(MatError::FollowModeMultipleFiles);
+        }
+        if matches!(sources[0], InputSource::Stdin) {
+            return Err(MatError::FollowModeStdin);
+        }
+    }
+
+    if let Some(path) = &args.tee {
+        if !sources.iter().any(|source| matches!(source, InputSource::Stdin)) {
+            return Err(MatError::TeeWithoutStdin);
+        }
+        tee_stdin(path, args.tee_strip_ansi)?;
+    }
+
+    let config = Config::load()?;
+    usage::enable(config.usage_stats);
+    let grep_options = GrepOptions::from_args(&args)?;
+    let hide_pattern = if args.hide.is_empty() {
+        None
+    } else {
+        Some(build_regex_from_patterns(&args.hide, &args)?)
     };
+    if args.extract && grep_options.is_none() {
+        return Err(MatError::ExtractNeedsNamedGroups);
+    }
+    if args.count_matches && args.search.is_none() {
+        return Err(MatError::CountNeedsSearch);
+    }
+    if args.count_matches {
+        if let Some(mut state) = SearchState::from_args(&args)? {
+            if let Some(count) = count_mapped_matches(&sources, &args, &mut state)? {
+                println!("{}", count);
+                return Ok(EXIT_SUCCESS);
+            }
+        }
+    }
+    let mut output_style = OutputStyle::from_args(&args)?;
+    let baseline = match args.compare {
+        Some(ref path) => {
+            let content = load_content(InputSource::File(path.clone()), &args, &config.preprocessors)?;
+            Some(Baseline::from_text(&content.text))
+        }
+        None => None,
+    };
+
+    // A pipe that hasn't ended yet starts empty; the pager appends its lines as they arrive
+    let stdin_stream = if can_stream_stdin(&args, &sources) { stream_stdin()? } else { None };
+
+    // Searching all lines in the pager needs the unfiltered input too
+    let keep_unfiltered = sources.len() == 1 && may_page(&args);
+    let mut parts = Vec::with_capacity(sources.len());
+    if stdin_stream.is_some() {
+        parts.push(BuiltDocument {
+            document: Document::from_text("", "stdin".to_string(), "UTF-8".to_string()),
+            is_markdown: false,
+            source_lines: 0,
+            unfiltered: None,
+            table: None,
+            prose: args.language.is_none(),
+            raw: None,
+            ansi_colored: false,
+            tasks: None,
+        });
+    } else {
+        for source in &sources {
+            parts.push(build_document(
+                source,
+                &args,
+                &config,
+                hide_pattern.as_ref(),
+                grep_options.as_ref(),
+                baseline.as_ref(),
+                keep_unfiltered,
+            )?);
+        }
+    }
+
+    // Printed, the colors of --ansi input stay colors, for `less -R` and the like
+    if args.color == ColorMode::Auto && parts.iter().any(|part| part.ansi_colored) {
+        output_style.color = true;
+    }
+
+    // A single input is used as-is; several are concatenated like cat
+    let mut pager_source = PagerSource::default();
+    let mut pager_files = Vec::new();
+    let mut document = if parts.len() == 1 {
+        let built = parts.pop().unwrap();
+        let document = built.document;
+        pager_source.unfiltered = built.unfiltered;
+        pager_source.table = built.table;
+        pager_source.prose = built.prose;
+        pager_source.tasks = built.tasks;
+        pager_source.raw = built.raw;
+        pager_source.ripgrep_source = highlight::ripgrep_source(&args, &sources[0], built.is_markdown);
+        // Get file path for follow mode (only for file inputs)
+        pager_source.file_path = match &sources[0] {
+            InputSource::File(p) => Some(p.clone()),
+            InputSource::Stdin => None,
+        };
+        // `file:line[:col]` opens at that position
+        pager_source.start = args.files.first().and_then(|p| parse_file_location(p)).map(|(_, loc)| loc);
+        document
+    } else {
+        // The header style becomes one banner per file instead of a single title
+        pager_source.prose = parts.iter().all(|part| part.prose);
+        // The pager opens every file in a buffer of its own instead
+        if may_page(&args) {
+            for (part, source) in parts.iter().zip(&sources) {
+                let mut document = part.document.clone();
+                output_style.apply_separators(&mut document);
+                pager_files.push(PagerFile {
+                    document,
+                    file_path: match source {
+                        InputSource::File(p) => Some(p.clone()),
+                        InputSource::Stdin => None,
+                    },
+                    ripgrep_source: highlight::ripgrep_source(&args, source, part.is_markdown),
+                    table: part.table.clone(),
+                    prose: part.prose,
+                    tasks: part.tasks,
+                });
+            }
+        }
+        let file_headers = args.file_headers || output_style.header;
+        output_style.header = false;
+        let documents = parts.into_iter().map(|part| (part.document, part.source_lines)).collect();
+        Document::concat(documents, file_headers, args.numbering == Numbering::Continuous)
+    };
+    output_style.apply_separators(&mut document);
+    pager_source.stream = stdin_stream;
+
+    // Without a terminal the TUI can run in, page through $PAGER instead
+    // (streamed stdin is paged whatever its length, there is no telling yet)
+    let use_pager = pager_source.stream.is_some() || should_page(&args, &document, &output_style);
+    let external_pager = use_pager && use_external_pager(&args);
+    let use_tui = use_pager && !external_pager;
+    if use_tui && !pager_files.is_empty() {
+        let first = pager_files.remove(0);
+        document = first.document;
+        pager_source.file_path = first.file_path;
+        pager_source.ripgrep_source = first.ripgrep_source;
+        pager_source.table = first.table;
+        pager_source.prose = first.prose;
+        pager_source.tasks = first.tasks;
+        pager_source.files = pager_files;
+    }
+
+    // The pager applies view transforms itself so they can be toggled back
+    if !use_tui {
+        if args.squeeze_repeated {
+            document = squeeze_repeated(&document);
+        }
+        render_timestamps(&mut document, args.timestamps, chrono::Utc::now());
+        #[cfg(feature = "spellcheck")]
+        if let Some(dictionary) = highlight::Dictionary::from_args(&args)? {
+            if pager_source.prose {
+                highlight::apply_spell_highlight(&mut document, &dictionary);
+            }
+        }
+    }
+
+    // Apply search highlighting if specified (the pager highlights as a view transform)
+    let mut search_state = SearchState::from_args(&args)?;
+    let mut match_summary = None;
+    if let Some(ref mut state) = search_state {
+        if !use_tui {
+            apply_search_highlight(&mut document, &state.pattern);
+        }
+        state.ripgrep_source = pager_source.ripgrep_source.clone();
+        // Printed output, whatever decided against the pager, ends with a summary
+        if args.count_matches || !use_tui {
+            state.find_matches(&document);
+            if args.count_matches {
+                println!("{}", state.match_count());
+                return Ok(EXIT_SUCCESS);
+            }
+            match_summary = Some(summarize_matches(state));
+        }
+    }
+
+    // Printed output breaks at the wrap width, or where the terminal would cut it
+    if !use_tui && args.wrap_mode().wraps() {
+        let width = args.wrap_width.unwrap_or_else(|| print_text_width(&document, &output_style));
+        document = wrap_document(&document, width, args.wrap_mode() == WrapMode::Word);
+    }
 
     // Run pager or print directly
-    if args.no_pager {
-        print_document(&document, args.line_numbers).map_err(|e| MatError::Io {
-            source: e,
-            path: std::path::PathBuf::from("stdout"),
-        })?;
+    if let Some(size) = args.render_screen {
+        render_screen(document, &args, &config, &output_style, search_state, pager_source, size)?;
+    } else if use_tui {
+        let action = run_pager(document, &args, &config, &output_style, search_state, pager_source)?;
+        // Scripts can tell how the pager was left
+        if args.report_action {
+            return Ok(match action {
+                QuitAction::Quit => EXIT_SUCCESS,
+                QuitAction::Selected => EXIT_SELECTED,
+                QuitAction::Aborted => EXIT_INTERRUPTED,
+            });
+        }
+    } else if external_pager {
+        page_externally(&document, &output_style)?;
     } else {
-        run_pager(document, &args, search_state, file_path)?;
+        match print_document(&document, &output_style) {
+            // The reader is done (`mat file | head -1`), which is a normal way to end
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(EXIT_SUCCESS),
+            Err(e) => {
+                return Err(MatError::Io {
+                    source: e,
+                    path: std::path::PathBuf::from("stdout"),
+                })
+            }
+            Ok(()) => {}
+        }
+        // Goes to stderr so the printed document can still be piped on
+        if let Some(summary) = match_summary {
+            eprintln!("{}", summary);
+        }
     }
 
-    Ok(())
+    Ok(EXIT_SUCCESS)
 }
 
 fn main() -> ExitCode {
-    let args = Args::parse();
+    let command = Args::command();
+    let matches = command.clone().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // Paging for another program, take over what it set up for less
+    if less::used_as_pager(&args, std::io::stdin().is_terminal()) {
+        let value = std::env::var("LESS").unwrap_or_default();
+        less::LessFlags::parse(&value).apply(&mut args, &matches);
+    }
+    let error_format = args.error_format;
 
-    match run(args) {
-        Ok(()) => ExitCode::from(EXIT_SUCCESS as u8),
+    let result = run(args);
+    usage::record_flags(&command, &matches);
+    usage::save();
+    match result {
+        Ok(code) => ExitCode::from(code as u8),
         Err(e) => {
-            eprintln!("mat: {}", e);
+            match error_format {
+                ErrorFormat::Text => eprintln!("mat: {}", e),
+                ErrorFormat::Json => eprintln!("{}", e.to_json()),
+            }
             ExitCode::from(e.exit_code() as u8)
         }
     }
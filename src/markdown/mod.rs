@@ -1,3 +1,3 @@
 mod render;
 
-pub use render::render_markdown;
+pub use render::{markdown_task_counts, markdown_word_count, reading_minutes, render_markdown, MarkdownOptions};
@@ -1,21 +1,72 @@
-use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, LinkType, Options, Parser, Tag, TagEnd};
 use ratatui::style::Color;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::display::glyphs::glyphs;
+use crate::display::{Document, Heading, Line, SpanStyle, StyledSpan};
+use crate::highlight::highlight_code;
+use crate::theme::Theme;
+
+/// Words read per minute, for the reading time estimate
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Optional extras of the rendered document
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Follow each link with a `[N]` marker and list the URLs in a references section at the end
+    pub link_references: bool,
+    /// Open with a word count and reading time line
+    pub reading_time: bool,
+    /// Theme YAML front matter is highlighted in (left plain without one)
+    pub theme: Option<Theme>,
+}
 
-use crate::display::{Document, Line, SpanStyle, StyledSpan};
+/// Estimated minutes to read `words` words (at least one)
+pub fn reading_minutes(words: usize) -> usize {
+    ((words + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE).max(1)
+}
 
-/// Render markdown text to a styled document
-pub fn render_markdown(text: &str, source_name: String) -> Document {
-    let options = Options::ENABLE_STRIKETHROUGH
+/// Number of words in the prose of a markdown text, leaving out code blocks
+pub fn markdown_word_count(text: &str) -> usize {
+    let mut renderer = MarkdownRenderer::new();
+    renderer.render(Parser::new_ext(text, parser_options()));
+    renderer.words
+}
+
+/// Checked and total task list items (`- [x] done`) of a markdown text
+pub fn markdown_task_counts(text: &str) -> (usize, usize) {
+    let mut renderer = MarkdownRenderer::new();
+    renderer.render(Parser::new_ext(text, parser_options()));
+    renderer.tasks
+}
+
+fn parser_options() -> Options {
+    Options::ENABLE_STRIKETHROUGH
         | Options::ENABLE_TABLES
         | Options::ENABLE_TASKLISTS
-        | Options::ENABLE_HEADING_ATTRIBUTES;
-
-    let parser = Parser::new_ext(text, options);
+        | Options::ENABLE_HEADING_ATTRIBUTES
+}
 
+/// Render markdown text to a styled document
+pub fn render_markdown(text: &str, source_name: String, options: &MarkdownOptions) -> Document {
     let mut renderer = MarkdownRenderer::new();
+    let text = match split_front_matter(text) {
+        Some((front_matter, body)) => {
+            renderer.add_front_matter(front_matter, options.theme);
+            body
+        }
+        None => text,
+    };
+    let parser = Parser::new_ext(text, parser_options());
+
+    renderer.link_references = options.link_references;
     renderer.render(parser);
+    let words = options.reading_time.then_some(renderer.words);
 
-    let lines = renderer.into_lines();
+    let mut lines = renderer.into_lines();
+    if let Some(words) = words {
+        add_summary_line(&mut lines, reading_summary(words));
+    }
     let max_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
 
     Document {
@@ -26,6 +77,54 @@ pub fn render_markdown(text: &str, source_name: String) -> Document {
     }
 }
 
+/// YAML front matter between `---` lines at the very start, and the text after it
+fn split_front_matter(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// "1234 words, 7 min read" line for `--reading-time`
+fn reading_summary(words: usize) -> StyledSpan {
+    let text = format!("{} words, {} min read", words, reading_minutes(words));
+    StyledSpan::new(text, SpanStyle::new().fg(Color::DarkGray))
+}
+
+/// Put the summary and a blank line above the document, renumbering the lines below
+fn add_summary_line(lines: &mut Vec<Line>, summary: StyledSpan) {
+    for line in lines.iter_mut() {
+        line.number += 2;
+    }
+    lines.insert(0, Line::plain(2, ""));
+    lines.insert(
+        0,
+        Line {
+            number: 1,
+            spans: vec![summary],
+            is_match: false,
+            is_context: false,
+            continuation: false,
+            heading: None,
+        },
+    );
+}
+
+/// A table being rendered, buffered whole so its columns can be sized
+struct TableBuffer {
+    /// Alignment of each column
+    alignments: Vec<Alignment>,
+    /// Finished rows, the header first, each a list of cells
+    rows: Vec<Vec<Vec<StyledSpan>>>,
+    /// Cells of the row being read
+    row: Vec<Vec<StyledSpan>>,
+}
+
 /// Internal renderer state
 struct MarkdownRenderer {
     /// Accumulated lines
@@ -50,6 +149,24 @@ struct MarkdownRenderer {
     needs_list_prefix: bool,
     /// Current heading level (for adding underlines)
     current_heading: Option<HeadingLevel>,
+    /// Index of the first span of the current heading's text, after its prefix
+    heading_start: usize,
+    /// Heading the next flushed line shows
+    heading_mark: Option<Heading>,
+    /// Whether links get `[N]` markers and a references section
+    link_references: bool,
+    /// Link destinations in order of first appearance
+    links: Vec<String>,
+    /// Reference number of each open link (None for links that show their URL)
+    open_links: Vec<Option<usize>>,
+    /// Checked and total task list items
+    tasks: (usize, usize),
+    /// Words of prose seen so far (code blocks are not counted)
+    words: usize,
+    /// List depth of the checked task item being rendered dimmed
+    checked_item_depth: Option<usize>,
+    /// The table being read, if inside one
+    table: Option<TableBuffer>,
 }
 
 impl MarkdownRenderer {
@@ -66,6 +183,15 @@ impl MarkdownRenderer {
             list_ordered: Vec::new(),
             needs_list_prefix: false,
             current_heading: None,
+            heading_start: 0,
+            heading_mark: None,
+            link_references: false,
+            links: Vec::new(),
+            open_links: Vec::new(),
+            tasks: (0, 0),
+            words: 0,
+            checked_item_depth: None,
+            table: None,
         }
     }
 
@@ -78,6 +204,10 @@ impl MarkdownRenderer {
         if !self.current_line.is_empty() {
             self.flush_line();
         }
+
+        if !self.links.is_empty() {
+            self.add_references();
+        }
     }
 
     fn handle_event(&mut self, event: Event) {
@@ -111,18 +241,18 @@ impl MarkdownRenderer {
                 // Add side border prefix for H1 (top border added in end_tag after we know width)
                 if level == HeadingLevel::H1 {
                     let side_style = SpanStyle::new().fg(Color::Yellow);
-                    self.add_styled_text("║  ", side_style);
+                    self.add_styled_text(&format!("{}  ", glyphs().double_vertical), side_style);
                 } else if level == HeadingLevel::H2 {
                     // H2 gets inline prefix decoration
                     let decor_style = SpanStyle::new().fg(Color::Blue);
-                    self.add_styled_text("──◈ ", decor_style);
+                    let g = glyphs();
+                    self.add_styled_text(&format!("{0}{0}{1} ", g.horizontal, g.heading_marks[0]), decor_style);
                 } else {
                     // Other levels get simple prefix
                     let (prefix, prefix_style) = self.heading_prefix(level);
-                    if !prefix.is_empty() {
-                        self.add_styled_text(prefix, prefix_style);
-                    }
+                    self.add_styled_text(&prefix, prefix_style);
                 }
+                self.heading_start = self.current_line.len();
                 // Apply heading style
                 let style = self.heading_style(level);
                 self.push_style(style);
@@ -146,14 +276,15 @@ impl MarkdownRenderer {
                 let style = SpanStyle::new().fg(Color::DarkGray);
                 if let CodeBlockKind::Fenced(lang) = kind {
                     if !lang.is_empty() {
-                        self.add_styled_text(&format!("─── {} ", lang), style.clone());
+                        let rule = glyphs().horizontal;
+                        self.add_styled_text(&format!("{} {} ", rule.repeat(3), lang), style.clone());
                         // Fill to make it look like a box
-                        self.add_styled_text(&"─".repeat(30), style);
+                        self.add_styled_text(&rule.repeat(30), style);
                     } else {
-                        self.add_styled_text(&"─".repeat(40), style);
+                        self.add_styled_text(&glyphs().horizontal.repeat(40), style);
                     }
                 } else {
-                    self.add_styled_text(&"─".repeat(40), style);
+                    self.add_styled_text(&glyphs().horizontal.repeat(40), style);
                 }
                 self.flush_line();
             }
@@ -185,20 +316,33 @@ impl MarkdownRenderer {
                 let style = SpanStyle::new().fg(Color::DarkGray);
                 self.push_style(style);
             }
-            Tag::Link { .. } => {
+            Tag::Link { link_type, dest_url, .. } => {
                 // Style the link text with blue underline, no brackets
                 let style = SpanStyle::new().fg(Color::Blue).underline();
                 self.push_style(style);
+                let shows_url = matches!(link_type, LinkType::Autolink | LinkType::Email);
+                let reference = (self.link_references && !shows_url).then(|| self.link_number(&dest_url));
+                self.open_links.push(reference);
             }
             Tag::Image { .. } => {
                 let style = SpanStyle::new().fg(Color::Magenta);
                 self.add_styled_text("[Image: ", style.clone());
                 self.push_style(style);
             }
-            Tag::Table(_) => {
-                self.flush_line();
+            Tag::Table(alignments) => {
+                if !self.current_line.is_empty() || !self.lines.is_empty() {
+                    self.flush_line();
+                }
+                self.table = Some(TableBuffer {
+                    alignments,
+                    rows: Vec::new(),
+                    row: Vec::new(),
+                });
             }
-            Tag::TableHead | Tag::TableRow | Tag::TableCell => {}
+            Tag::TableHead => {
+                self.push_style(SpanStyle::new().bold());
+            }
+            Tag::TableRow | Tag::TableCell => {}
             Tag::FootnoteDefinition(_) => {}
             Tag::MetadataBlock(_) => {}
             Tag::DefinitionList
@@ -214,6 +358,14 @@ impl MarkdownRenderer {
                 self.pop_style();
                 // Add decorations based on heading level
                 if let Some(level) = self.current_heading.take() {
+                    let title: String = self.current_line[self.heading_start.min(self.current_line.len())..]
+                        .iter()
+                        .map(|span| span.text.as_str())
+                        .collect();
+                    let heading = Heading {
+                        level: level as u8,
+                        title: title.trim().to_string(),
+                    };
                     match level {
                         HeadingLevel::H1 => {
                             // Calculate content width (includes "║  " prefix which is 3 chars)
@@ -226,32 +378,38 @@ impl MarkdownRenderer {
                             // Save current line, we need to insert top border before it
                             let content_line = std::mem::take(&mut self.current_line);
 
-                            // Add top border
+                            // Add top border, where the heading starts
+                            self.heading_mark = Some(heading);
                             let border_style = SpanStyle::new().fg(Color::Yellow);
-                            self.add_styled_text("╔", border_style.clone());
-                            self.add_styled_text(&"═".repeat(border_width), border_style.clone());
-                            self.add_styled_text("╗", border_style.clone());
+                            let g = glyphs();
+                            let [top_left, top_right, bottom_left, bottom_right] = g.double_corners;
+                            self.add_styled_text(top_left, border_style.clone());
+                            self.add_styled_text(&g.double_horizontal.repeat(border_width), border_style.clone());
+                            self.add_styled_text(top_right, border_style.clone());
                             self.flush_line();
 
                             // Restore content line and add closing border
                             self.current_line = content_line;
-                            self.add_styled_text(" ║", border_style.clone());
+                            self.add_styled_text(&format!(" {}", g.double_vertical), border_style.clone());
                             self.flush_line();
 
                             // Add bottom border
-                            self.add_styled_text("╚", border_style.clone());
-                            self.add_styled_text(&"═".repeat(border_width), border_style.clone());
-                            self.add_styled_text("╝", border_style);
+                            self.add_styled_text(bottom_left, border_style.clone());
+                            self.add_styled_text(&g.double_horizontal.repeat(border_width), border_style.clone());
+                            self.add_styled_text(bottom_right, border_style);
                             self.flush_line();
                         }
                         HeadingLevel::H2 => {
                             // Trailing decoration on same line
                             let decor_style = SpanStyle::new().fg(Color::Blue);
-                            self.add_styled_text(" ◈", decor_style.clone());
-                            self.add_styled_text(&"─".repeat(30), decor_style);
+                            let g = glyphs();
+                            self.add_styled_text(&format!(" {}", g.heading_marks[0]), decor_style.clone());
+                            self.add_styled_text(&g.horizontal.repeat(30), decor_style);
+                            self.heading_mark = Some(heading);
                             self.flush_line();
                         }
                         _ => {
+                            self.heading_mark = Some(heading);
                             self.flush_line();
                         }
                     }
@@ -273,7 +431,7 @@ impl MarkdownRenderer {
                 self.in_code_block = false;
                 // Add bottom border for code block
                 let style = SpanStyle::new().fg(Color::DarkGray);
-                self.add_styled_text(&"─".repeat(40), style);
+                self.add_styled_text(&glyphs().horizontal.repeat(40), style);
                 self.flush_line();
             }
             TagEnd::List(_) => {
@@ -284,23 +442,45 @@ impl MarkdownRenderer {
                     self.flush_line();
                 }
             }
-            TagEnd::Item => {}
+            TagEnd::Item => {
+                if self.checked_item_depth == Some(self.list_depth) {
+                    self.checked_item_depth = None;
+                    self.pop_style();
+                }
+            }
             TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
                 self.pop_style();
             }
             TagEnd::Link => {
                 self.pop_style();
+                if let Some(Some(number)) = self.open_links.pop() {
+                    let style = SpanStyle::new().fg(Color::DarkGray);
+                    self.current_line.push(StyledSpan::new(format!("[{}]", number), style));
+                }
             }
             TagEnd::Image => {
                 self.pop_style();
                 self.current_line.push(StyledSpan::new("]", SpanStyle::new().fg(Color::Magenta)));
             }
-            TagEnd::Table => {}
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.add_table(table);
+                }
+            }
             TagEnd::TableHead | TagEnd::TableRow => {
-                self.flush_line();
+                if tag_end == TagEnd::TableHead {
+                    self.pop_style();
+                }
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
             }
             TagEnd::TableCell => {
-                self.add_text(" | ");
+                let cell = std::mem::take(&mut self.current_line);
+                if let Some(table) = &mut self.table {
+                    table.row.push(cell);
+                }
             }
             TagEnd::FootnoteDefinition => {}
             TagEnd::MetadataBlock(_) => {}
@@ -318,6 +498,10 @@ impl MarkdownRenderer {
             self.needs_list_prefix = false;
         }
 
+        if !self.in_code_block {
+            self.words += text.unicode_words().count();
+        }
+
         if self.in_code_block {
             // Code block: preserve formatting with monospace style
             let style = SpanStyle::new().fg(Color::Green);
@@ -351,14 +535,120 @@ impl MarkdownRenderer {
     fn add_horizontal_rule(&mut self) {
         self.flush_line();
         let style = SpanStyle::new().fg(Color::DarkGray);
-        self.add_styled_text("─".repeat(40).as_str(), style);
+        self.add_styled_text(&glyphs().horizontal.repeat(40), style);
         self.flush_line();
     }
 
     fn add_task_marker(&mut self, checked: bool) {
-        let marker = if checked { "[x] " } else { "[ ] " };
-        let style = SpanStyle::new().fg(Color::Magenta);
-        self.add_styled_text(marker, style);
+        // The marker comes before the item's text, so the list prefix is still pending
+        if self.needs_list_prefix {
+            self.add_list_prefix();
+            self.needs_list_prefix = false;
+        }
+        self.tasks.1 += 1;
+        if checked {
+            self.tasks.0 += 1;
+            self.add_styled_text("[x] ", SpanStyle::new().fg(Color::Green));
+            // Done items fade into the background, up to the end of the item
+            if self.checked_item_depth.is_none() {
+                self.checked_item_depth = Some(self.list_depth);
+                self.push_style(SpanStyle::new().fg(Color::DarkGray));
+            }
+        } else {
+            self.add_styled_text("[ ] ", SpanStyle::new().fg(Color::Magenta));
+        }
+    }
+
+    /// Show front matter as YAML between its `---` fences
+    fn add_front_matter(&mut self, yaml: &str, theme: Option<Theme>) {
+        let fence_style = SpanStyle::new().fg(Color::DarkGray);
+        self.add_styled_text("---", fence_style.clone());
+        self.flush_line();
+        match theme.and_then(|theme| highlight_code(yaml, "YAML", theme)) {
+            Some(lines) => {
+                for spans in lines {
+                    self.current_line = spans;
+                    self.flush_line();
+                }
+            }
+            None => {
+                for line in yaml.lines() {
+                    self.add_styled_text(line, SpanStyle::new());
+                    self.flush_line();
+                }
+            }
+        }
+        self.add_styled_text("---", fence_style);
+        self.flush_line();
+    }
+
+    /// Reference number for a link destination, reusing the number of a repeated URL
+    fn link_number(&mut self, url: &str) -> usize {
+        match self.links.iter().position(|link| link == url) {
+            Some(index) => index + 1,
+            None => {
+                self.links.push(url.to_string());
+                self.links.len()
+            }
+        }
+    }
+
+    /// Numbered list of link destinations, after a rule
+    fn add_references(&mut self) {
+        self.add_horizontal_rule();
+        let number_style = SpanStyle::new().fg(Color::DarkGray);
+        let url_style = SpanStyle::new().fg(Color::Blue);
+        for (index, url) in std::mem::take(&mut self.links).into_iter().enumerate() {
+            self.add_styled_text(&format!("[{}] ", index + 1), number_style.clone());
+            self.add_styled_text(&url, url_style.clone());
+            self.flush_line();
+        }
+    }
+
+    /// Draw a table in a frame, its columns as wide as their widest cell
+    fn add_table(&mut self, table: TableBuffer) {
+        let columns = table.alignments.len().max(table.rows.iter().map(|row| row.len()).max().unwrap_or(0));
+        let mut widths = vec![0; columns];
+        for row in &table.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.iter().map(|span| span.width()).sum());
+            }
+        }
+
+        let g = glyphs();
+        let border_style = SpanStyle::new().fg(Color::DarkGray);
+        let rule = |left: &str, joint: &str, right: &str| {
+            let segments: Vec<String> = widths.iter().map(|width| g.horizontal.repeat(width + 2)).collect();
+            format!("{}{}{}", left, segments.join(joint), right)
+        };
+        let [top_left, top_right, bottom_left, bottom_right] = g.corners;
+
+        self.add_styled_text(&rule(top_left, g.down_tee, top_right), border_style.clone());
+        self.flush_line();
+        for (i, row) in table.rows.into_iter().enumerate() {
+            if i == 1 {
+                self.add_styled_text(&rule(g.right_tee, g.cross, g.left_tee), border_style.clone());
+                self.flush_line();
+            }
+            let mut cells = row.into_iter();
+            for (column, &width) in widths.iter().enumerate() {
+                let cell = cells.next().unwrap_or_default();
+                let padding = width - cell.iter().map(|span| span.width()).sum::<usize>();
+                let (before, after) = match table.alignments.get(column) {
+                    Some(Alignment::Right) => (padding, 0),
+                    Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+                    _ => (0, padding),
+                };
+                self.add_styled_text(&format!("{} ", g.vertical), border_style.clone());
+                self.add_styled_text(&" ".repeat(before), SpanStyle::new());
+                self.current_line.extend(cell);
+                self.add_styled_text(&" ".repeat(after + 1), SpanStyle::new());
+            }
+            self.add_styled_text(g.vertical, border_style.clone());
+            self.flush_line();
+        }
+        self.add_styled_text(&rule(bottom_left, g.up_tee, bottom_right), border_style);
+        self.flush_line();
     }
 
     fn add_list_prefix(&mut self) {
@@ -378,12 +668,8 @@ impl MarkdownRenderer {
                 }
             } else {
                 // Unordered list
-                let bullet = match self.list_depth {
-                    1 => "• ",
-                    2 => "◦ ",
-                    _ => "▪ ",
-                };
-                let prefix = format!("{}{}", indent, bullet);
+                let bullet = glyphs().bullets[self.list_depth.clamp(1, 3) - 1];
+                let prefix = format!("{}{} ", indent, bullet);
                 let style = SpanStyle::new().fg(Color::Yellow);
                 self.add_styled_text(&prefix, style);
             }
@@ -392,7 +678,7 @@ impl MarkdownRenderer {
 
     fn add_blockquote_prefix(&mut self) {
         let style = SpanStyle::new().fg(Color::DarkGray);
-        self.current_line.push(StyledSpan::new("│ ", style));
+        self.current_line.push(StyledSpan::new(format!("{} ", glyphs().vertical), style));
     }
 
     fn add_styled_text(&mut self, text: &str, style: SpanStyle) {
@@ -412,16 +698,17 @@ impl MarkdownRenderer {
         }
     }
 
-    fn heading_prefix(&self, level: HeadingLevel) -> (&'static str, SpanStyle) {
+    fn heading_prefix(&self, level: HeadingLevel) -> (String, SpanStyle) {
         // H1 and H2 are handled separately with frames/decorations
-        match level {
-            HeadingLevel::H1 => ("", SpanStyle::default()),
-            HeadingLevel::H2 => ("", SpanStyle::default()),
-            HeadingLevel::H3 => ("▸ ", SpanStyle::new().fg(Color::Green).bold()),
-            HeadingLevel::H4 => ("◆ ", SpanStyle::new().fg(Color::Magenta).bold()),
-            HeadingLevel::H5 => ("◇ ", SpanStyle::new().fg(Color::Yellow).bold()),
-            HeadingLevel::H6 => ("· ", SpanStyle::new().fg(Color::DarkGray).bold()),
-        }
+        let marks = glyphs().heading_marks;
+        let (mark, style) = match level {
+            HeadingLevel::H1 | HeadingLevel::H2 => return (String::new(), SpanStyle::default()),
+            HeadingLevel::H3 => (marks[1], SpanStyle::new().fg(Color::Green).bold()),
+            HeadingLevel::H4 => (marks[2], SpanStyle::new().fg(Color::Magenta).bold()),
+            HeadingLevel::H5 => (marks[3], SpanStyle::new().fg(Color::Yellow).bold()),
+            HeadingLevel::H6 => (marks[4], SpanStyle::new().fg(Color::DarkGray).bold()),
+        };
+        (format!("{} ", mark), style)
     }
 
     fn current_style(&self) -> SpanStyle {
@@ -458,6 +745,8 @@ impl MarkdownRenderer {
                 spans,
                 is_match: false,
                 is_context: false,
+                continuation: false,
+                heading: self.heading_mark.take(),
             });
         }
         self.line_number += 1;
@@ -482,7 +771,7 @@ mod tests {
     #[test]
     fn test_render_heading() {
         let md = "# Hello World";
-        let doc = render_markdown(md, "test.md".to_string());
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
 
         assert!(!doc.lines.is_empty(), "Document should have lines");
         // H1 now has a frame, so "Hello World" is on line 1 (after top border)
@@ -490,10 +779,25 @@ mod tests {
         assert!(all_text.contains("Hello World"), "Expected 'Hello World' in document");
     }
 
+    #[test]
+    fn test_headings_are_recorded() {
+        let md = "# Guide\n\nIntro.\n\n## Install `mat`\n\nText.\n\n### Linux\n";
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
+        let headings: Vec<(usize, u8, &str)> = doc
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| l.heading.as_ref().map(|h| (i, h.level, h.title.as_str())))
+            .collect();
+        assert_eq!(headings, vec![(0, 1, "Guide"), (7, 2, "Install mat"), (12, 3, "Linux")]);
+        // A boxed heading is marked on its top border, so jumping to it shows the whole box
+        assert!(doc.lines[1].text().contains("Guide"));
+    }
+
     #[test]
     fn test_render_code_block() {
         let md = "```rust\nfn main() {}\n```";
-        let doc = render_markdown(md, "test.md".to_string());
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
 
         // Should have code block markers and content
         assert!(doc.lines.len() >= 3);
@@ -502,7 +806,7 @@ mod tests {
     #[test]
     fn test_render_list() {
         let md = "- Item 1\n- Item 2\n- Item 3";
-        let doc = render_markdown(md, "test.md".to_string());
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
 
         assert!(doc.lines.len() >= 3);
         let text = doc.lines[0].text();
@@ -512,7 +816,7 @@ mod tests {
     #[test]
     fn test_render_inline_code() {
         let md = "Use `println!` to print";
-        let doc = render_markdown(md, "test.md".to_string());
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
 
         let text = doc.lines[0].text();
         assert!(text.contains("println!"));
@@ -521,10 +825,102 @@ mod tests {
     #[test]
     fn test_render_emphasis() {
         let md = "This is *italic* and **bold**";
-        let doc = render_markdown(md, "test.md".to_string());
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
 
         let text = doc.lines[0].text();
         assert!(text.contains("italic"));
         assert!(text.contains("bold"));
     }
+
+    #[test]
+    fn test_render_table() {
+        let md = "| Name | Size |\n|:-----|-----:|\n| alpha | 1 |\n| b | `200` |";
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
+        let text: Vec<String> = doc.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(
+            text,
+            [
+                "┌───────┬──────┐",
+                "│ Name  │ Size │",
+                "├───────┼──────┤",
+                "│ alpha │    1 │",
+                "│ b     │  200 │",
+                "└───────┴──────┘",
+            ]
+        );
+        // Header cells are bold, inline code keeps its style
+        assert!(doc.lines[1].spans.iter().any(|span| span.text == "Name" && span.style.bold));
+        assert!(doc.lines[4].spans.iter().any(|span| span.text == "200" && span.style.fg == Some(Color::Cyan)));
+    }
+
+    #[test]
+    fn test_link_references() {
+        let md = "See [the docs](https://example.com/docs) and [again](https://example.com/docs).\n\n\
+                  Mail <me@example.com> or [home](https://example.com).";
+        let options = MarkdownOptions {
+            link_references: true,
+            ..MarkdownOptions::default()
+        };
+        let doc = render_markdown(md, "test.md".to_string(), &options);
+        let text: Vec<String> = doc.lines.iter().map(|l| l.text()).collect();
+
+        assert_eq!(text[0], "See the docs[1] and again[1].");
+        assert_eq!(text[2], "Mail me@example.com or home[2].");
+        assert_eq!(text[text.len() - 2], "[1] https://example.com/docs");
+        assert_eq!(text[text.len() - 1], "[2] https://example.com");
+
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
+        assert!(doc.lines.iter().all(|l| !l.text().contains("[1]")));
+    }
+
+    #[test]
+    fn test_task_list_summary() {
+        let md = "- [x] done\n- [ ] open\n- [x] also done";
+        let doc = render_markdown(md, "test.md".to_string(), &MarkdownOptions::default());
+        assert_eq!(markdown_task_counts(md), (2, 3));
+
+        // The summary is for the pager's status bar, the lines stay as they are
+        assert!(doc.lines[0].text().ends_with("[x] done"));
+        assert_eq!(doc.lines[0].number, 1);
+        // Checked items are dimmed, open ones keep the default style
+        assert_eq!(doc.lines[0].spans.last().unwrap().style.fg, Some(Color::DarkGray));
+        assert_eq!(doc.lines[1].spans.last().unwrap().style.fg, None);
+
+        let doc = render_markdown("- plain item", "test.md".to_string(), &MarkdownOptions::default());
+        assert!(doc.lines[0].text().contains("plain item"));
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let md = format!("# Title\n\n{}\n\n```\nlet code = not_counted;\n```\n\n- [x] ship", "word ".repeat(399));
+        assert_eq!(markdown_word_count(&md), 401);
+        assert_eq!(reading_minutes(401), 3);
+        assert_eq!(reading_minutes(0), 1);
+
+        let options = MarkdownOptions {
+            reading_time: true,
+            ..MarkdownOptions::default()
+        };
+        let doc = render_markdown(&md, "test.md".to_string(), &options);
+        assert_eq!(doc.lines[0].text(), "401 words, 3 min read");
+    }
+
+    #[test]
+    fn test_front_matter() {
+        let md = "---\ntitle: Notes\ntags: [a, b]\n---\n# Heading\n\nBody text.";
+        let options = MarkdownOptions {
+            theme: Some(Theme::Dark),
+            ..MarkdownOptions::default()
+        };
+        let doc = render_markdown(md, "test.md".to_string(), &options);
+        let text: Vec<String> = doc.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(&text[..4], &["---", "title: Notes", "tags: [a, b]", "---"]);
+        // Keys and values are highlighted apart
+        assert!(doc.lines[1].spans.len() > 1);
+        assert!(text.iter().any(|t| t.contains("Heading")));
+        assert_eq!(doc.lines[3].number, 4);
+
+        assert_eq!(split_front_matter("---\nno end"), None);
+        assert_eq!(split_front_matter("# No front matter\n---\n"), None);
+    }
 }
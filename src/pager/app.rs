@@ -1,12 +1,43 @@
-use std::path::PathBuf;
-
-use crate::cli::WrapMode;
-use crate::display::{Document, Line};
-use crate::highlight::SearchState;
-use crate::input::FollowReader;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "spellcheck")]
+use std::rc::Rc;
+
+use chrono::{Duration, Utc};
+use clap::ValueEnum;
+use regex::Regex;
+use unicode_width::UnicodeWidthChar;
+
+use crate::cli::{SearchScope, TimestampMode, WrapMode};
+use crate::config::KeyProfile;
+use crate::display::{wrap_rows, Document, Heading, Line};
+use crate::filter::{build_regex_pattern, squeeze_repeated};
+use crate::format::{ColumnSort, Table};
+use crate::display::timestamp::{parse_line_timestamp, render_timestamps};
+use crate::highlight::{apply_search_highlight, path_links, trace_references, FileReference, PathLink, SearchState};
+#[cfg(feature = "spellcheck")]
+use crate::highlight::{apply_spell_highlight, Dictionary};
+use crate::sandbox::{exec_allowed, EXEC_DISABLED};
+use crate::input::{parse_ansi, parse_page_marker, FollowReader, InputEvent, InputStream};
 use crate::theme::ThemeColors;
 
+use super::buffer::Buffer;
+use super::buffers::{BufferEntry, BufferList};
+use super::command;
+use super::filters::PagerFilter;
+use super::folds::{apply_folds, enclosing_headers, fold_header, foldable_lines};
+use super::gutter;
+use super::diagnostics::LineDiagnostics;
+use super::editor::resolve_reference;
+use super::frequency::Frequencies;
+use super::help::{help_line_count, help_rows};
+use super::histogram::Histogram;
+use super::panes::Pane;
+use super::results::{SearchResult, SearchResults};
 use super::search::InteractiveSearch;
+use super::toc::TableOfContents;
+
+/// How often a followed file is checked for new content
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
 
 /// Pager mode
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,46 +46,119 @@ pub enum Mode {
     Normal,
     /// Search mode with query input
     Search { query: String },
+    /// `:` command prompt
+    Command { input: String },
+    /// Keybinding cheat sheet, scrolled down `scroll` lines
+    Help { scroll: usize },
+}
+
+/// How the pager was left
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuitAction {
+    /// Quit normally (`q`)
+    #[default]
+    Quit,
+    /// Quit after selecting a line with Enter (`--report-action`)
+    Selected,
+    /// Aborted with Ctrl+C
+    Aborted,
+}
+
+/// How numbers in the line number gutter are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineNumberMode {
+    /// Original line numbers
+    #[default]
+    Absolute,
+    /// Distance from the anchor line (the anchor itself shows its absolute number)
+    Relative,
 }
 
 /// Main pager application state
 pub struct App {
-    /// The document being viewed
-    pub document: Document,
+    /// View state of the active document
+    pub buffer: Buffer,
+    /// The other open documents, in order with the active one left out
+    pub buffers: Vec<Buffer>,
+    /// Position of the active buffer among all open buffers
+    pub buffer_index: usize,
+    /// Whether `n` / `N` continue into the other buffers (after `:search`)
+    pub global_search: bool,
+    /// Matching lines of all buffers (results panel, shown while set)
+    pub search_results: Option<SearchResults>,
+    /// Headings of a markdown document (contents overlay, shown while set)
+    pub toc: Option<TableOfContents>,
+    /// The open buffers (buffer list overlay, shown while set)
+    pub buffer_list: Option<BufferList>,
     /// Original document (for restoring after search cancel)
     pub original_document: Option<Document>,
-    /// Current scroll line (0-indexed, top of viewport)
-    pub scroll_line: usize,
-    /// Current horizontal scroll offset (0-indexed)
-    pub scroll_col: usize,
     /// Current mode
     pub mode: Mode,
     /// Whether the app should quit
     pub should_quit: bool,
     /// Terminal size (width, height)
     pub terminal_size: (u16, u16),
-    /// Whether to show line numbers
-    pub show_line_numbers: bool,
-    /// Search state (if any)
-    pub search_state: Option<SearchState>,
     /// Theme colors for UI rendering
     pub theme_colors: ThemeColors,
     /// Interactive search state
     pub interactive_search: Option<InteractiveSearch>,
-    /// Whether case-insensitive search is enabled
+    /// Whether case-insensitive search is enabled (`/` uses it with the less key profile)
     pub ignore_case: bool,
-    /// Whether follow mode is active
-    pub follow_mode: bool,
-    /// Follow reader for tailing files
-    pub follow_reader: Option<FollowReader>,
-    /// Path to the file being viewed (for follow mode)
-    pub file_path: Option<PathBuf>,
-    /// Line wrapping mode
-    pub wrap_mode: WrapMode,
     /// Max width for truncation mode
     pub max_width: usize,
-    /// Cached wrapped lines (invalidated on resize or wrap mode change)
-    pub wrapped_lines: Option<Vec<WrappedLine>>,
+    /// Fixed column to wrap at instead of the screen width (`--wrap-width`)
+    pub wrap_width: Option<usize>,
+    /// Text width of the reading mode
+    pub reading_width: usize,
+    /// Interval for time jumps
+    pub time_step: Duration,
+    /// Match distribution overlay (shown while set)
+    pub histogram: Option<Histogram>,
+    /// Character breakdown of the top line (diagnostics overlay)
+    pub diagnostics: Option<LineDiagnostics>,
+    /// Most frequent captured values (`:freq` overlay)
+    pub frequencies: Option<Frequencies>,
+    /// The `--grep` pattern, whose captures `:freq` counts when nothing else is given
+    pub grep_pattern: Option<Regex>,
+    /// Columns moved by a single horizontal scroll
+    pub hscroll_step: usize,
+    /// Keybinding profile
+    pub key_profile: KeyProfile,
+    /// Count typed before a command (e.g. the 5 of `5n`)
+    pub count: Option<usize>,
+    /// Prefix key waiting for its second key (e.g. `]` of `]t`)
+    pub pending_key: Option<char>,
+    /// Show a file name header row above the content (`--style header`)
+    pub show_header: bool,
+    /// Draw a bar between line numbers and text (`--style grid`)
+    pub show_grid: bool,
+    /// Show the headings or blocks the top line is in above the content (`--breadcrumbs`)
+    pub show_breadcrumbs: bool,
+    /// One-shot message shown in the status bar until the next key press
+    pub status_message: Option<String>,
+    /// Text waiting to be sent to the terminal clipboard
+    pub pending_clipboard: Option<String>,
+    /// Last yanked `path:line` reference (printed on exit)
+    pub yanked_reference: Option<String>,
+    /// File and stack trace reference waiting to be opened in the editor
+    pub pending_editor: Option<(PathBuf, FileReference)>,
+    /// Path selected with Tab, which Enter opens in a new buffer
+    pub selected_link: Option<PathLink>,
+    /// File and path waiting to be opened in a new buffer
+    pub pending_open: Option<(PathBuf, FileReference)>,
+    /// Whether Enter selects the top line and quits (`--report-action`)
+    pub report_action: bool,
+    /// How the pager was left
+    pub quit_action: QuitAction,
+    /// `path:line:text` of the line selected with Enter
+    pub selected_reference: Option<String>,
+    /// Whether the pager captures the mouse (`--mouse`, `:mouse`)
+    pub mouse: bool,
+    /// Line indices where a mouse drag started and where it is now
+    pub mouse_selection: Option<(usize, usize)>,
+    /// Dictionary for spell checking, loaded when first needed
+    #[cfg(feature = "spellcheck")]
+    pub dictionary: Option<Rc<Dictionary>>,
 }
 
 /// A single display row, which may be part of a wrapped line
@@ -75,6 +179,7 @@ pub struct WrappedLine {
 
 impl App {
     /// Create a new App with the given document
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         document: Document,
         show_line_numbers: bool,
@@ -86,40 +191,166 @@ impl App {
         max_width: usize,
     ) -> Self {
         Self {
-            document,
+            buffer: Buffer::new(document, show_line_numbers, search_state, file_path, wrap_mode),
+            buffers: Vec::new(),
+            buffer_index: 0,
+            global_search: false,
+            search_results: None,
+            toc: None,
+            buffer_list: None,
             original_document: None,
-            scroll_line: 0,
-            scroll_col: 0,
             mode: Mode::Normal,
             should_quit: false,
             terminal_size: (80, 24),
-            show_line_numbers,
-            search_state,
             theme_colors,
             interactive_search: None,
             ignore_case,
-            follow_mode: false,
-            follow_reader: None,
-            file_path,
-            wrap_mode,
             max_width,
-            wrapped_lines: None,
+            wrap_width: None,
+            reading_width: 80,
+            time_step: Duration::minutes(1),
+            histogram: None,
+            diagnostics: None,
+            frequencies: None,
+            grep_pattern: None,
+            hscroll_step: 4,
+            key_profile: KeyProfile::Default,
+            count: None,
+            pending_key: None,
+            show_header: false,
+            show_grid: false,
+            show_breadcrumbs: false,
+            status_message: None,
+            pending_clipboard: None,
+            yanked_reference: None,
+            pending_editor: None,
+            selected_link: None,
+            pending_open: None,
+            report_action: false,
+            quit_action: QuitAction::Quit,
+            selected_reference: None,
+            mouse: false,
+            mouse_selection: None,
+            #[cfg(feature = "spellcheck")]
+            dictionary: None,
+        }
+    }
+
+    /// Make `buffer` the active one, returning the previously active buffer
+    pub fn switch_buffer(&mut self, buffer: Buffer) -> Buffer {
+        let previous = std::mem::replace(&mut self.buffer, buffer);
+        // Overlays and half-typed commands belonged to the previous document
+        self.histogram = None;
+        self.diagnostics = None;
+        self.frequencies = None;
+        self.toc = None;
+        self.buffer_list = None;
+        self.count = None;
+        self.pending_key = None;
+        self.mouse_selection = None;
+        self.selected_link = None;
+        // The terminal may have been resized while the buffer was hidden
+        self.build_wrapped_lines();
+        self.buffer.scroll_line = self.buffer.scroll_line.min(self.max_scroll());
+        previous
+    }
+
+    /// Number of open buffers, the active one included
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len() + 1
+    }
+
+    /// Add `buffer` after the last open buffer, without activating it
+    pub fn open_buffer(&mut self, buffer: Buffer) {
+        self.buffers.push(buffer);
+    }
+
+    /// The open buffer at `index`, in the order they were opened
+    pub fn buffer_at(&self, index: usize) -> &Buffer {
+        match index.cmp(&self.buffer_index) {
+            std::cmp::Ordering::Equal => &self.buffer,
+            std::cmp::Ordering::Less => &self.buffers[index],
+            std::cmp::Ordering::Greater => &self.buffers[index - 1],
+        }
+    }
+
+    /// Make the open buffer at `index` the active one
+    pub fn activate_buffer(&mut self, index: usize) {
+        if index == self.buffer_index || index >= self.buffer_count() {
+            return;
+        }
+        let slot = if index > self.buffer_index { index - 1 } else { index };
+        let target = self.buffers.remove(slot);
+        let previous = self.switch_buffer(target);
+        let position = if index < self.buffer_index { self.buffer_index - 1 } else { self.buffer_index };
+        self.buffers.insert(position, previous);
+        self.buffer_index = index;
+    }
+
+    /// Switch to the next open file (`:n`)
+    pub fn next_file(&mut self) {
+        if self.buffer_index + 1 < self.buffer_count() {
+            self.activate_buffer(self.buffer_index + 1);
+        } else {
+            self.status_message = Some("No next file".to_string());
+        }
+    }
+
+    /// Switch to the previous open file (`:p`)
+    pub fn prev_file(&mut self) {
+        if self.buffer_index > 0 {
+            self.activate_buffer(self.buffer_index - 1);
+        } else {
+            self.status_message = Some("No previous file".to_string());
         }
     }
 
+    /// Show the list of open buffers
+    pub fn show_buffer_list(&mut self) {
+        let entries = (0..self.buffer_count())
+            .map(|index| {
+                let buffer = self.buffer_at(index);
+                BufferEntry {
+                    index,
+                    name: buffer.reference_path(),
+                    lines: buffer.base_document.as_ref().unwrap_or(&buffer.document).line_count(),
+                    followed: buffer.follow_mode,
+                    modified: buffer.modified_on_disk(),
+                }
+            })
+            .collect();
+        self.buffer_list = Some(BufferList::new(entries, self.buffer_index));
+    }
+
+    /// Switch to the selected buffer of the buffer list, closing it
+    pub fn open_buffer_list_entry(&mut self) {
+        let Some(entry) = self.buffer_list.take().and_then(|list| list.selected().cloned()) else {
+            return;
+        };
+        self.activate_buffer(entry.index);
+    }
+
     /// Toggle follow mode
     pub fn toggle_follow(&mut self) {
+        // Streamed stdin is already being read; following only keeps the view at the end
+        if self.buffer.file_path.is_none() && self.buffer.input_stream.is_some() {
+            self.buffer.follow_mode = !self.buffer.follow_mode;
+            if self.buffer.follow_mode {
+                self.go_to_bottom();
+            }
+            return;
+        }
         // Only allow follow mode for files
-        if let Some(ref path) = self.file_path {
-            if self.follow_mode {
+        if let Some(ref path) = self.buffer.file_path {
+            if self.buffer.follow_mode {
                 // Disable follow mode
-                self.follow_mode = false;
-                self.follow_reader = None;
+                self.buffer.follow_mode = false;
+                self.buffer.input_stream = None;
             } else {
                 // Enable follow mode
                 if let Ok(reader) = FollowReader::new(path.clone(), true) {
-                    self.follow_mode = true;
-                    self.follow_reader = Some(reader);
+                    self.buffer.follow_mode = true;
+                    self.buffer.input_stream = Some(InputStream::spawn_follow(reader, FOLLOW_POLL_INTERVAL));
                     // Scroll to bottom when entering follow mode
                     self.go_to_bottom();
                 }
@@ -127,28 +358,736 @@ impl App {
         }
     }
 
-    /// Check for new content in follow mode and append to document
-    pub fn check_follow_updates(&mut self) {
-        if !self.follow_mode {
+    /// Read lines from streamed stdin as they arrive
+    pub fn stream_input(&mut self, stream: InputStream) {
+        self.buffer.input_stream = Some(stream);
+    }
+
+    /// Append lines received from the input stream (follow mode or streamed stdin) to the document
+    ///
+    /// Returns whether the stream had anything to report.
+    pub fn check_input_updates(&mut self) -> bool {
+        let Some(ref stream) = self.buffer.input_stream else {
+            return false;
+        };
+
+        let mut new_lines = Vec::new();
+        let mut appended = false;
+        let mut closed = false;
+        let mut received = false;
+        for event in stream.poll() {
+            received = true;
+            match event {
+                InputEvent::Lines(lines) => new_lines.extend(lines),
+                InputEvent::Rotated => {
+                    // The last lines of the old file go above the marker
+                    self.append_input(std::mem::take(&mut new_lines));
+                    self.mark_rotation();
+                    appended = true;
+                }
+                InputEvent::Error(e) => {
+                    self.status_message = Some(format!("Reading stopped: {}", e));
+                    closed = true;
+                }
+                InputEvent::Closed => closed = true,
+            }
+        }
+        // Lines that arrive with the end of the stream still scroll a followed view
+        let at_end = self.buffer.follow_mode;
+        if closed {
+            self.buffer.follow_mode = false;
+            self.buffer.input_stream = None;
+        }
+        appended |= !new_lines.is_empty();
+        self.append_input(new_lines);
+        if !appended {
+            return received;
+        }
+
+        if self.buffer.base_document.is_some() {
+            self.rebuild_document();
+        }
+        // Wrapped rows of the new lines have to be built too
+        if self.buffer.wrapped_lines.is_some() {
+            self.build_wrapped_lines();
+        }
+        // Auto-scroll to bottom while following
+        if at_end {
+            self.go_to_bottom();
+        }
+        true
+    }
+
+    /// Add lines read from the input at the end of the document, the other search scope's and the panes'
+    ///
+    /// With view transforms active they go into the base document, which the caller rebuilds.
+    fn append_input(&mut self, new_lines: Vec<String>) {
+        if new_lines.is_empty() {
+            return;
+        }
+        if let Some(log) = &mut self.buffer.session_log {
+            log.append(&new_lines);
+        }
+
+        // With a grep filter, lines are numbered after all lines, not the kept ones
+        let all_lines = match (&self.buffer.grep_stream, self.buffer.search_scope) {
+            (Some(_), SearchScope::Filtered) => self.buffer.scope_document.as_ref(),
+            _ => None,
+        }
+        .unwrap_or(self.buffer.base_document.as_ref().unwrap_or(&self.buffer.document));
+        // Separators and markers have no number of their own
+        let start_number = all_lines.lines.iter().rev().find(|l| l.number > 0).map_or(0, |l| l.number) + 1;
+        let mut appended = Vec::new();
+        for (i, text) in new_lines.into_iter().enumerate() {
+            for mut line in Line::chunked(start_number + i, &text) {
+                if let Some(ref mut style) = self.buffer.ansi_style {
+                    line.spans = parse_ansi(&line.text(), style);
+                }
+                appended.push(line);
+            }
+        }
+        for pane in &mut self.buffer.panes {
+            pane.append(&appended, &self.buffer.filters.colors);
+        }
+        // The other search scope's document grows too, to be swapped in by `A`
+        let (shown, other) = match self.buffer.grep_stream.as_mut() {
+            Some(stream) => {
+                let kept = stream.filter(appended.clone());
+                match self.buffer.search_scope {
+                    SearchScope::Filtered => (kept, appended),
+                    SearchScope::All => (appended, kept),
+                }
+            }
+            None => (appended, Vec::new()),
+        };
+        if let Some(document) = self.buffer.scope_document.as_mut() {
+            append_lines(document, other);
+        }
+        let target = self.buffer.base_document.as_mut().unwrap_or(&mut self.buffer.document);
+        append_lines(target, shown);
+    }
+
+    /// Mark where the followed file was replaced (rotated) or truncated, like `tail -F` does
+    fn mark_rotation(&mut self) {
+        let message = format!("{} was rotated", self.buffer.reference_path());
+        let marker = Line::header(&message);
+        if let Some(document) = self.buffer.scope_document.as_mut() {
+            append_lines(document, vec![marker.clone()]);
+        }
+        let target = self.buffer.base_document.as_mut().unwrap_or(&mut self.buffer.document);
+        append_lines(target, vec![marker]);
+        self.status_message = Some(message);
+    }
+
+    /// Show or hide the match distribution overlay
+    pub fn toggle_histogram(&mut self) {
+        if self.histogram.take().is_some() {
+            return;
+        }
+        self.diagnostics = None;
+        self.frequencies = None;
+        let patterns = self.histogram_patterns();
+        let width = (self.terminal_size.0 as usize).saturating_sub(2);
+        self.histogram = Some(Histogram::compute(
+            &self.buffer.document,
+            self.buffer.search_state.as_ref(),
+            &patterns,
+            width,
+        ));
+    }
+
+    /// Show or hide the character breakdown of the top line
+    pub fn toggle_diagnostics(&mut self) {
+        if self.diagnostics.take().is_some() {
+            return;
+        }
+        let anchor = self.anchor_line_idx();
+        let lines = &self.buffer.document.lines;
+        if anchor >= lines.len() {
             return;
         }
+        // A long line split into chunks is inspected whole
+        let start = lines[..=anchor].iter().rposition(|l| !l.continuation).unwrap_or(0);
+        let end = start + 1 + lines[start + 1..].iter().take_while(|l| l.continuation).count();
+        let text: String = lines[start..end].iter().map(|l| l.text()).collect();
+        self.histogram = None;
+        self.frequencies = None;
+        self.diagnostics = Some(LineDiagnostics::inspect(lines[start].number, &text));
+    }
 
-        if let Some(ref mut reader) = self.follow_reader {
-            if let Ok(new_lines) = reader.check_for_new_content() {
-                if !new_lines.is_empty() {
-                    let start_number = self.document.lines.len() + 1;
-                    for (i, text) in new_lines.into_iter().enumerate() {
-                        let line = Line::plain(start_number + i, &text);
-                        let width = line.width();
-                        self.document.lines.push(line);
-                        if width > self.document.max_line_width {
-                            self.document.max_line_width = width;
-                        }
+    /// Show the most frequent values captured by `query`, or by the search or grep pattern
+    pub fn show_frequencies(&mut self, query: Option<&str>) {
+        let pattern = match query {
+            Some(query) => match self.filter_pattern(query) {
+                Some(pattern) => pattern,
+                None => return,
+            },
+            None => {
+                let pattern = self
+                    .buffer
+                    .search_state
+                    .as_ref()
+                    .map(|state| &state.pattern)
+                    .or_else(|| self.buffer.filters.last_grep_pattern())
+                    .or(self.grep_pattern.as_ref());
+                match pattern {
+                    Some(pattern) => pattern.clone(),
+                    None => {
+                        self.status_message = Some("Usage: :freq PATTERN (or search first)".to_string());
+                        return;
                     }
-                    // Auto-scroll to bottom
-                    self.go_to_bottom();
                 }
             }
+        };
+        let frequencies = Frequencies::compute(&self.buffer.document, &pattern);
+        if frequencies.total == 0 {
+            self.status_message = Some(format!("Pattern not found: {}", pattern.as_str()));
+            return;
+        }
+        self.histogram = None;
+        self.diagnostics = None;
+        self.frequencies = Some(frequencies);
+    }
+
+    /// Patterns counted separately in the histogram overlay
+    fn histogram_patterns(&self) -> Vec<Regex> {
+        self.buffer.search_state.iter().map(|s| s.pattern.clone()).collect()
+    }
+
+    /// Toggle collapsing runs of identical lines
+    pub fn toggle_squeeze(&mut self) {
+        self.buffer.squeeze = !self.buffer.squeeze;
+        self.rebuild_document();
+    }
+
+    /// Underline misspelled words, or stop doing so
+    pub fn toggle_spellcheck(&mut self) {
+        if !cfg!(feature = "spellcheck") {
+            self.status_message = Some("Spell checking needs mat built with the spellcheck feature".to_string());
+            return;
+        }
+        if !self.buffer.prose {
+            self.status_message = Some("Spell checking is for markdown and plain text".to_string());
+            return;
+        }
+        #[cfg(feature = "spellcheck")]
+        if self.dictionary.is_none() {
+            match Dictionary::system() {
+                Some(dictionary) => self.dictionary = Some(Rc::new(dictionary)),
+                None => {
+                    self.status_message = Some("No spelling dictionary found (see --dictionary)".to_string());
+                    return;
+                }
+            }
+        }
+        self.buffer.spellcheck = !self.buffer.spellcheck;
+        self.status_message = Some(if self.buffer.spellcheck { "Spell check on" } else { "Spell check off" }.to_string());
+        self.rebuild_document();
+    }
+
+    /// Turn reading mode on or off: prose wrapped at the reading width, centered with margins
+    pub fn toggle_reading_mode(&mut self) {
+        if !self.buffer.prose {
+            self.status_message = Some("Reading mode is for markdown and plain text".to_string());
+            return;
+        }
+        let anchor_number = self.buffer.document.lines.get(self.anchor_line_idx()).map_or(0, |l| l.number);
+        match self.buffer.reading.take() {
+            Some(wrap_mode) => self.buffer.wrap_mode = wrap_mode,
+            None => {
+                self.buffer.reading = Some(self.buffer.wrap_mode);
+                self.buffer.wrap_mode = WrapMode::Wrap;
+                self.buffer.scroll_col = 0;
+            }
+        }
+        self.build_wrapped_lines();
+        self.scroll_to_line_number(anchor_number);
+    }
+
+    /// Show `table` (already rendered as the document) with its header pinned
+    pub fn open_table(&mut self, table: Table) {
+        let rows = self.buffer.document.lines.iter().take_while(|l| l.number == 0).count();
+        self.buffer.sticky_lines = self.buffer.document.lines.drain(..rows).collect();
+        self.buffer.columns = table.column_spans(&self.buffer.table_view);
+        self.buffer.table = Some(table);
+        self.build_wrapped_lines();
+    }
+
+    /// Index of the table column under the column cursor
+    fn current_table_column(&self) -> Option<usize> {
+        let table = self.buffer.table.as_ref()?;
+        table.visible_columns(&self.buffer.table_view).get(self.buffer.table_column).copied()
+    }
+
+    /// Start and end column of the table column under the cursor
+    pub fn current_column_span(&self) -> Option<(usize, usize)> {
+        self.buffer.table.as_ref()?;
+        self.buffer.columns.get(self.buffer.table_column).copied()
+    }
+
+    /// Move the table column cursor one column right or left, scrolling it into view
+    fn move_table_column(&mut self, forward: bool) {
+        let last = self.buffer.columns.len().saturating_sub(1);
+        self.buffer.table_column = if forward {
+            (self.buffer.table_column + 1).min(last)
+        } else {
+            self.buffer.table_column.saturating_sub(1)
+        };
+        let max_scroll = self.buffer.document.max_line_width.saturating_sub(self.content_width());
+        let start = self.buffer.columns.get(self.buffer.table_column).map_or(0, |&(start, _)| start);
+        self.buffer.scroll_col = start.min(max_scroll);
+    }
+
+    /// Sort the table by the column under the cursor: ascending, then descending, then input order
+    pub fn sort_table_column(&mut self) {
+        let (Some(column), Some(table)) = (self.current_table_column(), self.buffer.table.as_ref()) else {
+            self.status_message = Some("Not a table".to_string());
+            return;
+        };
+        let name = table.header[column].clone();
+        let view = &mut self.buffer.table_view;
+        let (sort, message) = match view.sort {
+            Some(sort) if sort.column == column && !sort.descending => (
+                Some(ColumnSort { column, descending: true }),
+                format!("Sorted by {} (descending)", name),
+            ),
+            Some(sort) if sort.column == column => (None, "Rows in input order".to_string()),
+            _ => (
+                Some(ColumnSort { column, descending: false }),
+                format!("Sorted by {} (ascending)", name),
+            ),
+        };
+        view.sort = sort;
+        self.render_table();
+        self.go_to_top();
+        self.status_message = Some(message);
+    }
+
+    /// Hide the table column under the cursor
+    pub fn hide_table_column(&mut self) {
+        let (Some(column), Some(table)) = (self.current_table_column(), self.buffer.table.as_ref()) else {
+            self.status_message = Some("Not a table".to_string());
+            return;
+        };
+        if table.visible_columns(&self.buffer.table_view).len() == 1 {
+            self.status_message = Some("Cannot hide the last column".to_string());
+            return;
+        }
+        let name = table.header[column].clone();
+        let cursor = self.buffer.table_column;
+        self.buffer.table_view.hidden.insert(column);
+        self.buffer.table_column = cursor.min(self.buffer.columns.len() - 2);
+        self.render_table();
+        // Filters match the shown text, so they may have matched only in this column
+        if self.buffer.document.lines.is_empty() {
+            self.buffer.table_view.hidden.remove(&column);
+            self.buffer.table_column = cursor;
+            self.render_table();
+            self.status_message = Some(format!("No line would match the filters without {}", name));
+            return;
+        }
+        self.status_message = Some(format!("Hid column {} (X shows all)", name));
+    }
+
+    /// Show the hidden table columns again
+    pub fn show_table_columns(&mut self) {
+        if self.buffer.table.is_none() {
+            self.status_message = Some("Not a table".to_string());
+            return;
+        }
+        let current = self.current_table_column();
+        let count = std::mem::take(&mut self.buffer.table_view.hidden).len();
+        // The cursor stays on its column, which may have moved right
+        self.buffer.table_column = current.unwrap_or(0);
+        self.render_table();
+        self.status_message = Some(format!("Showing {} hidden columns", count));
+    }
+
+    /// Render the table again after its view changed, keeping the pager filters and scroll position
+    fn render_table(&mut self) {
+        let Some(ref table) = self.buffer.table else {
+            return;
+        };
+        let view = &self.buffer.table_view;
+        let source = &self.buffer.document;
+        let mut document = table.render(view, source.source_name.clone(), source.encoding.clone());
+        let rows = document.lines.iter().take_while(|l| l.number == 0).count();
+        self.buffer.sticky_lines = document.lines.drain(..rows).collect();
+        self.buffer.columns = table.column_spans(view);
+
+        let scroll_line = self.buffer.scroll_line;
+        self.buffer.base_document = Some(self.buffer.filters.reapply(document));
+        self.rebuild_document();
+        self.buffer.scroll_line = scroll_line.min(self.max_scroll());
+        let max_scroll = self.buffer.document.max_line_width.saturating_sub(self.content_width());
+        let start = self.buffer.columns.get(self.buffer.table_column).map_or(0, |&(start, _)| start);
+        self.buffer.scroll_col = self.buffer.scroll_col.min(start).min(max_scroll);
+    }
+
+    /// Close the innermost fold containing the top line, or open it if it is closed
+    pub fn toggle_fold(&mut self) {
+        let Some(anchor) = self.buffer.document.lines.get(self.anchor_line_idx()) else {
+            return;
+        };
+        let anchor_number = anchor.number;
+        if !anchor.continuation && self.buffer.folds.remove(&anchor_number) {
+            self.rebuild_document();
+            return;
+        }
+
+        // Folds are found in the document without them
+        let base = self.buffer.base_document.as_ref().unwrap_or(&self.buffer.document);
+        let header = base
+            .lines
+            .iter()
+            .position(|l| l.number == anchor_number && anchor_number != 0)
+            .and_then(|idx| fold_header(&base.lines, idx))
+            .map(|idx| base.lines[idx].number);
+        match header {
+            Some(number) => {
+                self.buffer.folds.insert(number);
+                self.rebuild_document();
+                self.scroll_to_line_number(number);
+            }
+            None => self.status_message = Some("No fold here".to_string()),
+        }
+    }
+
+    /// Close every fold, leaving an outline of the top-level lines
+    pub fn close_all_folds(&mut self) {
+        let base = self.buffer.base_document.as_ref().unwrap_or(&self.buffer.document);
+        let folds = foldable_lines(&base.lines);
+        if folds.is_empty() {
+            self.status_message = Some("No folds in this document".to_string());
+            return;
+        }
+        self.buffer.folds = folds;
+        self.rebuild_document();
+    }
+
+    /// Open every fold
+    pub fn open_all_folds(&mut self) {
+        if !self.buffer.folds.is_empty() {
+            self.buffer.folds.clear();
+            self.rebuild_document();
+        }
+    }
+
+    /// Cycle the timestamp display: original, relative, local, UTC
+    pub fn cycle_timestamp_mode(&mut self) {
+        self.buffer.timestamp_mode = match self.buffer.timestamp_mode {
+            TimestampMode::Original => TimestampMode::Relative,
+            TimestampMode::Relative => TimestampMode::Local,
+            TimestampMode::Local => TimestampMode::Utc,
+            TimestampMode::Utc => TimestampMode::Original,
+        };
+        self.rebuild_document();
+        if let Some(value) = self.buffer.timestamp_mode.to_possible_value() {
+            self.status_message = Some(format!("Timestamps: {}", value.get_name()));
+        }
+    }
+
+    /// Switch between searching (and showing) the grep-filtered lines and all lines
+    ///
+    /// The active search is re-run on the newly shown document.
+    pub fn toggle_search_scope(&mut self) {
+        if !self.buffer.filters.is_empty() {
+            self.status_message = Some("Remove the pager filters (U) to change the search scope".to_string());
+            return;
+        }
+        let Some(other) = self.buffer.scope_document.take() else {
+            self.status_message = Some("No grep filter: searching all lines".to_string());
+            return;
+        };
+        let current = self.buffer.base_document.take().unwrap_or_else(|| self.buffer.document.clone());
+        self.buffer.scope_document = Some(current);
+        self.buffer.base_document = Some(other);
+        self.buffer.search_scope = match self.buffer.search_scope {
+            SearchScope::Filtered => SearchScope::All,
+            SearchScope::All => SearchScope::Filtered,
+        };
+        self.rebuild_document();
+
+        let message = format!("Searching {}", self.search_scope_label().unwrap_or_default());
+        self.status_message = Some(message);
+    }
+
+    /// Switch between the pretty-printed input and the input as it came
+    pub fn toggle_raw_view(&mut self) {
+        if !self.buffer.filters.is_empty() {
+            self.status_message = Some("Remove the pager filters (U) to switch views".to_string());
+            return;
+        }
+        let Some(other) = self.buffer.raw_document.take() else {
+            self.status_message = Some("The input is shown as it came".to_string());
+            return;
+        };
+        let current = self.buffer.base_document.take().unwrap_or_else(|| self.buffer.document.clone());
+        self.buffer.raw_document = Some(current);
+        self.buffer.base_document = Some(other);
+        self.buffer.raw_view = !self.buffer.raw_view;
+        // Folds are of the other form's lines
+        self.buffer.folds.clear();
+        self.rebuild_document();
+        self.buffer.scroll_col = 0;
+
+        let message = if self.buffer.raw_view { "Showing the input as it came" } else { "Showing the input pretty-printed" };
+        self.status_message = Some(message.to_string());
+    }
+
+    /// Status bar label of the search scope, while a grep filter makes it matter
+    pub fn search_scope_label(&self) -> Option<&'static str> {
+        self.buffer.scope_document.as_ref()?;
+        Some(match self.buffer.search_scope {
+            SearchScope::Filtered => "filtered lines",
+            SearchScope::All => "all lines",
+        })
+    }
+
+    /// Narrow the view to lines matching `query`, on top of any earlier filter
+    pub fn push_grep_filter(&mut self, query: &str) {
+        if let Some(pattern) = self.filter_pattern(query) {
+            let filter = PagerFilter::Grep {
+                query: query.to_string(),
+                pattern,
+            };
+            self.push_filter(filter, format!("Pattern not found: {}", query));
+        }
+    }
+
+    /// Hide lines matching `query`, on top of any earlier filter
+    pub fn push_hide_filter(&mut self, query: &str) {
+        if let Some(pattern) = self.filter_pattern(query) {
+            let filter = PagerFilter::Hide {
+                query: query.to_string(),
+                pattern,
+            };
+            self.push_filter(filter, format!("Every line matches: {}", query));
+        }
+    }
+
+    /// Compile a filter query with the ignore-case setting, reporting invalid ones
+    fn filter_pattern(&mut self, query: &str) -> Option<Regex> {
+        let pattern_str = build_regex_pattern(query, self.ignore_case, false, false, false);
+        let pattern = Regex::new(&pattern_str).ok();
+        if pattern.is_none() {
+            self.status_message = Some(format!("Invalid pattern: {}", query));
+        }
+        pattern
+    }
+
+    /// Apply `filter` to the base document, or show `empty_message` if no line would remain
+    fn push_filter(&mut self, filter: PagerFilter, empty_message: String) {
+        let base = self.buffer.base_document.as_ref().unwrap_or(&self.buffer.document);
+        match self.buffer.filters.push(filter, base) {
+            Some(filtered) => {
+                self.buffer.base_document = Some(filtered);
+                self.rebuild_document();
+            }
+            None => self.status_message = Some(empty_message),
+        }
+    }
+
+    /// Open a pane below the document showing the lines matching `query`, as they arrive
+    pub fn add_pane(&mut self, query: &str) {
+        if let Some(pattern) = self.filter_pattern(query) {
+            let filter = PagerFilter::Grep {
+                query: query.to_string(),
+                pattern,
+            };
+            // Panes see all lines, not just the ones the pager filters leave
+            let source = self.buffer.filters.source();
+            let source = source.or(self.buffer.base_document.as_ref()).unwrap_or(&self.buffer.document);
+            let pane = Pane::new(filter, source, &self.buffer.filters.colors);
+            self.buffer.panes.push(pane);
+        }
+    }
+
+    /// Close the filter panes, giving the document the whole screen again
+    pub fn close_panes(&mut self) {
+        self.buffer.panes.clear();
+    }
+
+    /// Rows of the filter panes: with N panes, N of N+1 equal shares of the screen
+    pub fn panes_height(&self) -> usize {
+        let count = self.buffer.panes.len();
+        let rows = (self.terminal_size.1 as usize).saturating_sub(1 + self.header_rows());
+        rows * count / (count + 1)
+    }
+
+    /// Remove the last pager filter, restoring the lines it hid
+    pub fn pop_filter(&mut self) {
+        match self.buffer.filters.pop() {
+            Some(document) => {
+                self.buffer.base_document = Some(document);
+                self.rebuild_document();
+            }
+            None => self.status_message = Some("No filter to remove".to_string()),
+        }
+    }
+
+    /// Whether any view transform (squeeze, timestamps, search highlighting) is active
+    fn has_view_transforms(&self) -> bool {
+        self.buffer.squeeze
+            || self.buffer.spellcheck
+            || self.buffer.timestamp_mode != TimestampMode::Original
+            || self.buffer.search_state.is_some()
+            || !self.buffer.folds.is_empty()
+    }
+
+    /// Recompute the displayed document from the base document and view transforms
+    pub fn rebuild_document(&mut self) {
+        if !self.has_view_transforms() && self.buffer.base_document.is_none() {
+            return;
+        }
+        let base = self.buffer.base_document.take().unwrap_or_else(|| self.buffer.document.clone());
+        if !self.has_view_transforms() {
+            self.replace_document(base);
+            return;
+        }
+
+        let mut view = if self.buffer.folds.is_empty() {
+            base.clone()
+        } else {
+            apply_folds(&base, &self.buffer.folds)
+        };
+        if self.buffer.squeeze {
+            view = squeeze_repeated(&view);
+        }
+        render_timestamps(&mut view, self.buffer.timestamp_mode, Utc::now());
+        #[cfg(feature = "spellcheck")]
+        if let (true, Some(dictionary)) = (self.buffer.spellcheck, &self.dictionary) {
+            apply_spell_highlight(&mut view, dictionary);
+        }
+        // Highlights are redone from the base so a changed or cleared search leaves none behind
+        if let Some(ref state) = self.buffer.search_state {
+            apply_search_highlight(&mut view, &state.pattern);
+        }
+
+        self.buffer.base_document = Some(base);
+        self.replace_document(view);
+    }
+
+    /// Swap in a transformed document, keeping the anchor line at the top
+    fn replace_document(&mut self, document: Document) {
+        let anchor_number = self.buffer.document.lines.get(self.anchor_line_idx()).map_or(0, |l| l.number);
+        self.buffer.document = document;
+        // The selected path is known by its line index too
+        self.selected_link = None;
+
+        // Match positions are line indices, so they must be recomputed
+        if let Some(ref mut state) = self.buffer.search_state {
+            state.start_search(&self.buffer.document);
+            state.current_match = None;
+        }
+        self.build_wrapped_lines();
+        self.scroll_to_line_number(anchor_number);
+    }
+
+    /// Put the first line numbered `number` or later at the top of the viewport
+    fn scroll_to_line_number(&mut self, number: usize) {
+        let line_idx = self
+            .buffer
+            .document
+            .lines
+            .iter()
+            .position(|l| l.number >= number)
+            .unwrap_or(0);
+        let row = match self.buffer.wrapped_lines {
+            Some(ref wrapped) => wrapped.iter().position(|w| w.line_idx >= line_idx).unwrap_or(0),
+            None => line_idx,
+        };
+        self.buffer.scroll_line = row.min(self.max_scroll());
+    }
+
+    /// Put line `number` at the top of the viewport, or show the end if there are fewer lines
+    ///
+    /// A line the filters hid is passed for the next one shown.
+    pub fn go_to_line(&mut self, number: usize) {
+        let last = self.buffer.document.lines.last().map_or(0, |l| l.number);
+        if number > last {
+            self.go_to_bottom();
+        } else {
+            self.scroll_to_line_number(number);
+        }
+    }
+
+    /// Show line `number` (centered) with `column` revealed, like `file:line:col` references
+    pub fn go_to_location(&mut self, number: usize, column: Option<usize>) {
+        if self.buffer.wrap_mode.wraps() {
+            self.scroll_to_line_number(number);
+            return;
+        }
+        let line_idx = self
+            .buffer
+            .document
+            .lines
+            .iter()
+            .position(|l| l.number >= number)
+            .unwrap_or_else(|| self.buffer.document.line_count().saturating_sub(1));
+        self.scroll_to_line(line_idx);
+
+        if let Some(column) = column {
+            let width = self.content_width();
+            let col = column.saturating_sub(1);
+            if col < self.buffer.scroll_col || col >= self.buffer.scroll_col + width {
+                let max_scroll = self.buffer.document.max_line_width.saturating_sub(width);
+                self.buffer.scroll_col = col.saturating_sub(width / 2).min(max_scroll);
+            }
+        }
+    }
+
+    /// Jump forward or backward by `time_step` from the anchor line's timestamp
+    ///
+    /// Forward goes to the first line at least one step later; backward goes to
+    /// the earliest preceding line within one step before the anchor.
+    pub fn jump_by_time(&mut self, forward: bool) {
+        // Parse the base document: the displayed one may have rewritten timestamps
+        let source = self.buffer.base_document.as_ref().unwrap_or(&self.buffer.document);
+        let anchor_number = self.buffer.document.lines.get(self.anchor_line_idx()).map_or(0, |l| l.number);
+        let start = match source.lines.iter().position(|l| l.number >= anchor_number) {
+            Some(idx) => idx,
+            None => return,
+        };
+        let timestamp = |line: &Line| {
+            if line.continuation {
+                None
+            } else {
+                parse_line_timestamp(&line.text()).map(|t| t.time)
+            }
+        };
+
+        // The anchor's time is that of the closest stamped line at or above it
+        let anchor_time = match source.lines[..=start].iter().rev().find_map(timestamp) {
+            Some(time) => time,
+            None => {
+                self.status_message = Some("No timestamp at this line".to_string());
+                return;
+            }
+        };
+
+        let target = if forward {
+            let limit = anchor_time + self.time_step;
+            source.lines[start + 1..]
+                .iter()
+                .find(|l| timestamp(l).is_some_and(|t| t >= limit))
+                .map(|l| l.number)
+        } else {
+            let limit = anchor_time - self.time_step;
+            let mut target = None;
+            for line in source.lines[..start].iter().rev() {
+                match timestamp(line) {
+                    Some(t) if t < limit => break,
+                    Some(_) => target = Some(line.number),
+                    None => {}
+                }
+            }
+            target
+        };
+
+        match target {
+            Some(number) => self.scroll_to_line_number(number),
+            None => self.status_message = Some("No line within the time step".to_string()),
         }
     }
 
@@ -156,7 +1095,7 @@ impl App {
     /// If `case_insensitive` is true, search will ignore case
     pub fn enter_search_mode(&mut self, case_insensitive: bool) {
         // Save original document for potential cancellation
-        self.original_document = Some(self.document.clone());
+        self.original_document = Some(self.buffer.document.clone());
         self.interactive_search = Some(InteractiveSearch::new(case_insensitive));
         self.mode = Mode::Search {
             query: String::new(),
@@ -197,78 +1136,313 @@ impl App {
     fn apply_incremental_search(&mut self) {
         // Restore original document first
         if let Some(ref original) = self.original_document {
-            self.document = original.clone();
+            self.buffer.document = original.clone();
         }
 
         // Apply highlighting
         if let Some(ref search) = self.interactive_search {
-            search.apply_highlighting(&mut self.document);
+            search.apply_highlighting(&mut self.buffer.document);
         }
     }
 
     /// Confirm the search and exit search mode
     pub fn confirm_search(&mut self) {
-        if let Some(ref search) = self.interactive_search {
-            if !search.is_empty() {
-                // Create a proper SearchState for navigation
-                if let Some(pattern) = search.compile_pattern() {
-                    let mut state = SearchState {
-                        pattern,
-                        matches: Vec::new(),
-                        current_match: None,
-                    };
-                    state.find_matches(&self.document);
-                    self.search_state = Some(state);
-                }
+        let pattern = self
+            .interactive_search
+            .as_ref()
+            .filter(|search| !search.is_empty())
+            .and_then(|search| search.compile_pattern());
+        self.mode = Mode::Normal;
+        self.interactive_search = None;
+        let original = self.original_document.take();
+
+        if let Some(pattern) = pattern {
+            // A search typed in one buffer stays in it
+            self.global_search = false;
+            // Create a proper SearchState for navigation
+            let mut state = SearchState::new(pattern);
+            state.ripgrep_source = self.buffer.ripgrep_source.clone();
+            self.buffer.search_state = Some(state);
+            // The document before the incremental highlights becomes the base of the view
+            if self.buffer.base_document.is_none() {
+                self.buffer.base_document = original;
             }
+            // Also rebuilds the wrap cache, as the annotation column may have appeared
+            self.rebuild_document();
         }
-
-        self.mode = Mode::Normal;
-        self.interactive_search = None;
-        self.original_document = None;
-    }
+    }
 
     /// Cancel the search and restore original document
     pub fn cancel_search(&mut self) {
         // Restore original document
         if let Some(original) = self.original_document.take() {
-            self.document = original;
+            self.buffer.document = original;
         }
 
         self.mode = Mode::Normal;
         self.interactive_search = None;
     }
 
-    /// Navigate to next search match
-    pub fn next_match(&mut self) {
-        if let Some(ref mut state) = self.search_state {
-            if let Some(line_idx) = state.next_match() {
+    /// Navigate `count` search matches forward
+    pub fn next_match(&mut self, count: usize) {
+        if self.crosses_buffers() {
+            for _ in 0..count {
+                self.step_global_match(true);
+            }
+            return;
+        }
+        if let Some(ref mut state) = self.buffer.search_state {
+            let mut target = None;
+            for _ in 0..count {
+                target = state.next_match();
+            }
+            if let Some(line_idx) = target {
+                self.scroll_to_line(line_idx);
+            }
+        }
+    }
+
+    /// Navigate `count` search matches backward
+    pub fn prev_match(&mut self, count: usize) {
+        if self.crosses_buffers() {
+            for _ in 0..count {
+                self.step_global_match(false);
+            }
+            return;
+        }
+        if let Some(ref mut state) = self.buffer.search_state {
+            let mut target = None;
+            for _ in 0..count {
+                target = state.prev_match();
+            }
+            if let Some(line_idx) = target {
                 self.scroll_to_line(line_idx);
             }
         }
     }
 
-    /// Navigate to previous search match
-    pub fn prev_match(&mut self) {
-        if let Some(ref mut state) = self.search_state {
-            if let Some(line_idx) = state.prev_match() {
+    /// Whether match navigation continues into the other buffers
+    fn crosses_buffers(&self) -> bool {
+        self.global_search && self.buffer_count() > 1
+    }
+
+    /// Move one match forward or backward, continuing in the next buffer with matches past either end
+    fn step_global_match(&mut self, forward: bool) {
+        let at_end = match self.buffer.search_state {
+            Some(ref state) => match (state.current_match, state.match_count()) {
+                (_, 0) => true,
+                (Some(i), total) => (forward && i + 1 == total) || (!forward && i == 0),
+                (None, _) => false,
+            },
+            None => true,
+        };
+        if at_end {
+            let count = self.buffer_count();
+            let next = (1..=count)
+                .map(|step| {
+                    if forward {
+                        (self.buffer_index + step) % count
+                    } else {
+                        (self.buffer_index + count - step) % count
+                    }
+                })
+                .find(|&i| self.buffer_at(i).search_state.as_ref().is_some_and(|s| s.match_count() > 0));
+            let Some(index) = next else {
+                self.status_message = Some(format!("Pattern not found in {} files", count));
+                return;
+            };
+            self.activate_buffer(index);
+            if let Some(ref mut state) = self.buffer.search_state {
+                state.current_match = None;
+            }
+        }
+
+        let target = self
+            .buffer
+            .search_state
+            .as_mut()
+            .and_then(|state| if forward { state.next_match() } else { state.prev_match() });
+        if let Some(line_idx) = target {
+            self.scroll_to_line(line_idx);
+        }
+    }
+
+    /// Search every open buffer for `query`, listing the matching lines in the results panel
+    pub fn search_all_buffers(&mut self, query: &str) {
+        let Some(pattern) = self.filter_pattern(query) else {
+            return;
+        };
+        let active = self.buffer_index;
+        let mut files = Vec::with_capacity(self.buffer_count());
+        let mut results = Vec::new();
+        for index in 0..self.buffer_count() {
+            self.activate_buffer(index);
+            let mut state = SearchState::new(pattern.clone());
+            state.ripgrep_source = self.buffer.ripgrep_source.clone();
+            self.buffer.search_state = Some(state);
+            self.rebuild_document();
+
+            files.push(self.buffer.document.source_name.clone());
+            let Some(ref state) = self.buffer.search_state else {
+                continue;
+            };
+            // One result per line, pointing at its first match
+            let mut previous_line = None;
+            for (match_index, position) in state.matches.iter().enumerate() {
+                if previous_line == Some(position.line_idx) {
+                    continue;
+                }
+                previous_line = Some(position.line_idx);
+                let line = &self.buffer.document.lines[position.line_idx];
+                results.push(SearchResult {
+                    buffer: index,
+                    match_index,
+                    line_number: line.number,
+                    text: line.text(),
+                });
+            }
+        }
+        self.activate_buffer(active);
+        self.global_search = true;
+
+        if results.is_empty() {
+            self.status_message = Some(format!("Pattern not found in {} files", files.len()));
+        } else {
+            self.histogram = None;
+            self.diagnostics = None;
+            self.search_results = Some(SearchResults::new(query.to_string(), files, results));
+        }
+    }
+
+    /// Jump to the selected result of the results panel, closing it
+    pub fn open_search_result(&mut self) {
+        let Some(result) = self.search_results.take().and_then(|r| r.selected().cloned()) else {
+            return;
+        };
+        self.activate_buffer(result.buffer);
+        if let Some(ref mut state) = self.buffer.search_state {
+            if let Some(line_idx) = state.select_match(result.match_index) {
                 self.scroll_to_line(line_idx);
             }
         }
     }
 
+    /// Show the table of contents of a markdown document
+    pub fn show_toc(&mut self) {
+        let toc = TableOfContents::new(&self.buffer.document, self.anchor_line_idx());
+        if toc.entries.is_empty() {
+            self.status_message = Some("No headings".to_string());
+            return;
+        }
+        self.toc = Some(toc);
+    }
+
+    /// Scroll to the selected heading of the contents overlay, closing it
+    pub fn open_toc_entry(&mut self) {
+        let Some(entry) = self.toc.take().and_then(|toc| toc.selected().cloned()) else {
+            return;
+        };
+        self.scroll_to_line_idx(entry.line_idx);
+    }
+
+    /// Jump to the `n`th search match (1-indexed)
+    pub fn go_to_match(&mut self, n: usize) {
+        let Some(ref mut state) = self.buffer.search_state else {
+            self.status_message = Some("No active search".to_string());
+            return;
+        };
+        match state.select_match(n.saturating_sub(1)) {
+            Some(line_idx) => self.scroll_to_line(line_idx),
+            None => self.status_message = Some(format!("No match {} ({} matches)", n, state.match_count())),
+        }
+    }
+
+    /// Put the marker line of page `n` (of a converted PDF) at the top of the viewport
+    pub fn go_to_page(&mut self, n: usize) {
+        let pages: Vec<(usize, usize)> = self
+            .buffer
+            .document
+            .lines
+            .iter()
+            .filter_map(|l| parse_page_marker(&l.text()).map(|page| (page, l.number)))
+            .collect();
+        match pages.iter().find(|(page, _)| *page == n) {
+            Some(&(_, number)) => self.scroll_to_line_number(number),
+            None if pages.is_empty() => self.status_message = Some("No pages in this document".to_string()),
+            None => self.status_message = Some(format!("No page {} ({} pages)", n, pages.len())),
+        }
+    }
+
+    /// Drop the active search and its highlights, keeping the scroll position
+    pub fn clear_search(&mut self) {
+        self.global_search = false;
+        if self.buffer.search_state.take().is_some() {
+            self.rebuild_document();
+        }
+    }
+
+    /// Add a digit to the pending count
+    pub fn push_count_digit(&mut self, digit: u32) {
+        let count = self.count.unwrap_or(0);
+        self.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+    }
+
+    /// Open the `:` command prompt
+    pub fn enter_command_mode(&mut self) {
+        self.enter_command_mode_with("");
+    }
+
+    /// Open the `:` command prompt with `input` already typed
+    pub fn enter_command_mode_with(&mut self, input: &str) {
+        self.mode = Mode::Command {
+            input: input.to_string(),
+        };
+    }
+
+    /// Open the keybinding cheat sheet
+    pub fn show_help(&mut self) {
+        self.mode = Mode::Help { scroll: 0 };
+    }
+
+    /// Scroll the cheat sheet by `delta` lines, keeping its last line at the bottom at most
+    pub fn scroll_help(&mut self, delta: isize) {
+        let max_scroll = help_line_count().saturating_sub(help_rows(self.content_height()));
+        if let Mode::Help { ref mut scroll } = self.mode {
+            *scroll = scroll.saturating_add_signed(delta).min(max_scroll);
+        }
+    }
+
+    /// Run the typed command and return to normal mode
+    pub fn confirm_command(&mut self) {
+        if let Mode::Command { input } = std::mem::replace(&mut self.mode, Mode::Normal) {
+            command::execute_command(&input, self);
+        }
+    }
+
     /// Scroll to show a specific line in the viewport
     fn scroll_to_line(&mut self, line_idx: usize) {
         let height = self.content_height();
         // Try to center the line in the viewport
         let target = line_idx.saturating_sub(height / 2);
-        let max_scroll = self.document.line_count().saturating_sub(height);
-        self.scroll_line = target.min(max_scroll);
+        let max_scroll = self.buffer.document.line_count().saturating_sub(height);
+        self.buffer.scroll_line = target.min(max_scroll);
+    }
+
+    /// Scan the next chunk of a search still in progress on a long document
+    pub fn continue_search(&mut self) {
+        if let Some(ref mut state) = self.buffer.search_state {
+            state.continue_search(&self.buffer.document);
+        }
+    }
+
+    /// Whether the search is still finding matches, so the match count is a lower bound
+    pub fn search_in_progress(&self) -> bool {
+        self.buffer.search_state.as_ref().is_some_and(|state| state.is_searching())
     }
 
     /// Get search info for status bar
     pub fn search_info(&self) -> Option<(usize, usize)> {
-        self.search_state.as_ref().and_then(|state| {
+        self.buffer.search_state.as_ref().and_then(|state| {
             let total = state.match_count();
             if total > 0 {
                 let current = state.current_match_display().unwrap_or(0);
@@ -284,33 +1458,62 @@ impl App {
         let old_size = self.terminal_size;
         self.terminal_size = (width, height);
         // Invalidate wrapped lines cache if size changed and we're in wrap mode
-        if old_size != (width, height) && self.wrap_mode != WrapMode::None {
-            self.wrapped_lines = None;
+        if old_size != (width, height) && self.buffer.wrap_mode != WrapMode::None {
+            self.buffer.wrapped_lines = None;
         }
     }
 
     /// Get the content area height (excluding status bar)
     pub fn content_height(&self) -> usize {
-        self.terminal_size.1.saturating_sub(1) as usize
+        (self.terminal_size.1 as usize).saturating_sub(1 + self.header_rows() + self.panes_height())
+    }
+
+    /// Rows above the content: the file name header, pinned lines and the breadcrumb
+    pub fn header_rows(&self) -> usize {
+        usize::from(self.show_header) + self.buffer.sticky_lines.len() + usize::from(self.show_breadcrumbs)
     }
 
     /// Get the content area width
     pub fn content_width(&self) -> usize {
-        let gutter_width = if self.show_line_numbers {
+        let gutter_width = if self.buffer.show_line_numbers {
             self.gutter_width()
         } else {
             0
         };
-        (self.terminal_size.0 as usize).saturating_sub(gutter_width)
+        (self.terminal_size.0 as usize).saturating_sub(gutter_width + self.annotation_width())
+    }
+
+    /// Width wrap mode breaks rows at: `--wrap-width` or the reading width, unless the screen is narrower
+    pub fn wrap_columns(&self) -> usize {
+        let width = self.content_width();
+        let fixed = if self.buffer.reading.is_some() {
+            Some(self.reading_width)
+        } else {
+            self.wrap_width
+        };
+        fixed.map_or(width, |fixed| fixed.min(width))
+    }
+
+    /// Columns left blank on each side of the content in reading mode
+    pub fn reading_margin(&self) -> usize {
+        if self.buffer.reading.is_none() {
+            return 0;
+        }
+        self.content_width().saturating_sub(self.wrap_columns()) / 2
+    }
+
+    /// Get the annotation column width (0 when no annotation source is active)
+    pub fn annotation_width(&self) -> usize {
+        gutter::annotation_width(self)
     }
 
     /// Get the gutter (line number) width
     pub fn gutter_width(&self) -> usize {
-        if !self.show_line_numbers {
+        if !self.buffer.show_line_numbers {
             return 0;
         }
         // Calculate width based on max line number
-        let max_line = self.document.line_count();
+        let max_line = self.buffer.document.line_count();
         if max_line == 0 {
             3 // Minimum " 1 "
         } else {
@@ -319,76 +1522,523 @@ impl App {
         }
     }
 
+    /// Toggle between absolute and relative line numbers
+    ///
+    /// Switching to relative numbers also turns the gutter on.
+    pub fn toggle_relative_numbers(&mut self) {
+        self.buffer.line_number_mode = match self.buffer.line_number_mode {
+            LineNumberMode::Absolute => {
+                self.buffer.show_line_numbers = true;
+                LineNumberMode::Relative
+            }
+            LineNumberMode::Relative => LineNumberMode::Absolute,
+        };
+    }
+
+    /// `path:line` reference for the anchor line
+    pub fn anchor_reference(&self) -> Option<String> {
+        let line = self.buffer.document.lines.get(self.anchor_line_idx())?;
+        Some(format!("{}:{}", self.buffer.reference_path(), line.number))
+    }
+
+    /// Put the next (or previous) stack trace line with a file reference at the top
+    pub fn jump_to_reference(&mut self, forward: bool) {
+        let anchor = self.anchor_line_idx();
+        let references = trace_references(&self.buffer.document);
+        let target = if forward {
+            references.iter().find(|(i, _)| *i > anchor)
+        } else {
+            references.iter().rev().find(|(i, _)| *i < anchor)
+        };
+        match target {
+            Some(&(line_idx, _)) => {
+                let number = self.buffer.document.lines[line_idx].number;
+                self.scroll_to_line_number(number);
+            }
+            None if forward => self.status_message = Some("No file reference below".to_string()),
+            None => self.status_message = Some("No file reference above".to_string()),
+        }
+    }
+
+    /// Put the next (or previous) markdown heading at the top
+    pub fn jump_to_heading(&mut self, forward: bool) {
+        let anchor = self.anchor_line_idx();
+        let lines = &self.buffer.document.lines;
+        let target = if forward {
+            (anchor + 1..lines.len()).find(|&i| lines[i].heading.is_some())
+        } else {
+            (0..anchor).rev().find(|&i| lines[i].heading.is_some())
+        };
+        match target {
+            Some(line_idx) => self.scroll_to_line_idx(line_idx),
+            None if forward => self.status_message = Some("No heading below".to_string()),
+            None => self.status_message = Some("No heading above".to_string()),
+        }
+    }
+
+    /// Heading of the markdown section the top line is in
+    pub fn current_section(&self) -> Option<&Heading> {
+        // Only rendered markdown has headings, which is prose
+        if !self.buffer.prose {
+            return None;
+        }
+        let lines = self.buffer.document.lines.get(..=self.anchor_line_idx())?;
+        lines.iter().rev().find_map(|line| line.heading.as_ref())
+    }
+
+    /// Headings (in prose) or block header lines (in code) that the top line is in and are scrolled off, outermost first
+    pub fn breadcrumbs(&self) -> Vec<String> {
+        let lines = &self.buffer.document.lines;
+        let anchor = self.anchor_line_idx().min(lines.len());
+        if self.buffer.prose {
+            let mut path: Vec<&Heading> = Vec::new();
+            for heading in lines[..anchor].iter().filter_map(|line| line.heading.as_ref()) {
+                path.retain(|outer| outer.level < heading.level);
+                path.push(heading);
+            }
+            return path.into_iter().map(|heading| heading.title.clone()).collect();
+        }
+        enclosing_headers(lines, anchor)
+            .into_iter()
+            .map(|idx| {
+                // `fn main() {` reads as `fn main()`, `"user": {` as `"user"`
+                let text = lines[idx].text();
+                text.trim().trim_end_matches(['{', '[', '(', ':']).trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Show or hide the breadcrumb above the content
+    pub fn toggle_breadcrumbs(&mut self) {
+        self.show_breadcrumbs = !self.show_breadcrumbs;
+        self.status_message = Some(if self.show_breadcrumbs { "Breadcrumbs on" } else { "Breadcrumbs off" }.to_string());
+    }
+
+    /// Open the selected path, or else the first stack trace reference on screen (from the top line on), in the editor
+    pub fn open_reference(&mut self) {
+        let anchor = self.anchor_line_idx();
+        let end = anchor + self.content_height();
+        let selected = self.selected_link.as_ref().map(|link| link.reference.clone());
+        let references = trace_references(&self.buffer.document);
+        let on_screen = references.into_iter().find(|(i, _)| (anchor..end).contains(i)).map(|(_, reference)| reference);
+        let Some(reference) = selected.or(on_screen) else {
+            self.status_message = Some("No file reference on screen".to_string());
+            return;
+        };
+        if !exec_allowed() {
+            self.status_message = Some(format!("Opening an editor is {}", EXEC_DISABLED));
+            return;
+        }
+        match resolve_reference(&reference, self.buffer.file_path.as_deref()) {
+            Some(path) => self.pending_editor = Some((path, reference)),
+            None => self.status_message = Some(format!("No such file: {}", reference.path)),
+        }
+    }
+
+    /// Select the next (or previous) path, starting from the top of the screen
+    pub fn select_link(&mut self, forward: bool) {
+        let links = path_links(&self.buffer.document);
+        let anchor = self.anchor_line_idx();
+        let end = anchor + self.content_height();
+        let target = match self.selected_link {
+            Some(ref current) => {
+                let position = (current.line_idx, current.range.start);
+                if forward {
+                    links.into_iter().find(|l| (l.line_idx, l.range.start) > position)
+                } else {
+                    links.into_iter().rev().find(|l| (l.line_idx, l.range.start) < position)
+                }
+            }
+            None if forward => links.into_iter().find(|l| l.line_idx >= anchor),
+            None => links.into_iter().rev().find(|l| l.line_idx < end),
+        };
+        let Some(link) = target else {
+            self.status_message = Some(if forward { "No path below" } else { "No path above" }.to_string());
+            return;
+        };
+        if !(anchor..end).contains(&link.line_idx) {
+            self.scroll_to_line_idx(link.line_idx);
+        }
+        let text = self.buffer.document.lines[link.line_idx].text();
+        self.status_message = Some(format!("{} (Enter to open, o for the editor)", &text[link.range.clone()]));
+        self.selected_link = Some(link);
+    }
+
+    /// Open the selected path in a buffer of its own, or show it in the buffer it is open in
+    pub fn open_link(&mut self) {
+        let Some(link) = self.selected_link.take() else {
+            return;
+        };
+        let Some(path) = resolve_reference(&link.reference, self.buffer.file_path.as_deref()) else {
+            self.status_message = Some(format!("No such file: {}", link.reference.path));
+            return;
+        };
+        let same_file = |other: &Path| match (path.canonicalize(), other.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => path == other,
+        };
+        let open = (0..self.buffer_count()).find(|&i| self.buffer_at(i).file_path.as_deref().is_some_and(same_file));
+        match open {
+            Some(index) => {
+                self.activate_buffer(index);
+                self.go_to_location(link.reference.line, link.reference.column);
+            }
+            None => self.pending_open = Some((path, link.reference)),
+        }
+    }
+
+    /// Select the anchor line and quit (`--report-action`)
+    pub fn select_line(&mut self) {
+        let number = self.buffer.document.lines.get(self.anchor_line_idx()).map_or(0, |l| l.number);
+        if number == 0 {
+            self.status_message = Some("No line to select".to_string());
+            return;
+        }
+        self.selected_reference = Some(self.buffer.line_reference(number));
+        self.quit_action = QuitAction::Selected;
+        self.should_quit = true;
+    }
+
+    /// Lines to print on exit with `--report-action`: the selected line, then the bookmarks
+    pub fn reported_lines(&self) -> Vec<String> {
+        if self.quit_action == QuitAction::Aborted {
+            return Vec::new();
+        }
+        let mut lines: Vec<String> = self.selected_reference.iter().cloned().collect();
+        for reference in self.bookmark_references() {
+            if !lines.contains(&reference) {
+                lines.push(reference);
+            }
+        }
+        lines
+    }
+
+    /// Bookmark the anchor line, or remove its bookmark
+    pub fn toggle_bookmark(&mut self) {
+        let number = self.buffer.document.lines.get(self.anchor_line_idx()).map_or(0, |l| l.number);
+        if number == 0 {
+            return;
+        }
+        let message = if self.buffer.bookmarks.remove(&number) {
+            format!("Removed bookmark on line {}", number)
+        } else {
+            self.buffer.bookmarks.insert(number);
+            format!("Bookmarked line {}", number)
+        };
+        self.status_message = Some(message);
+        // The annotation column may have appeared or disappeared
+        self.build_wrapped_lines();
+    }
+
+    /// `path:line:text` of the bookmarked lines of every open buffer
+    pub fn bookmark_references(&self) -> Vec<String> {
+        (0..self.buffer_count())
+            .flat_map(|i| self.buffer_at(i).bookmark_references())
+            .collect()
+    }
+
+    /// Yank the anchor line reference to the clipboard
+    pub fn yank_reference(&mut self) {
+        match self.anchor_reference() {
+            Some(reference) => {
+                self.status_message = Some(format!("Yanked {}", reference));
+                self.pending_clipboard = Some(reference.clone());
+                self.yanked_reference = Some(reference);
+            }
+            None => self.status_message = Some("Nothing to yank".to_string()),
+        }
+    }
+
+    /// Capture the mouse, or hand it back to the terminal for its own text selection
+    pub fn toggle_mouse(&mut self) {
+        self.mouse = !self.mouse;
+        self.mouse_selection = None;
+        self.status_message = Some(if self.mouse { "Mouse on" } else { "Mouse off (terminal selection)" }.to_string());
+    }
+
+    /// Index of the document line shown on screen row `row`, if it shows one
+    pub fn line_at_row(&self, row: u16) -> Option<usize> {
+        let offset = (row as usize).checked_sub(self.header_rows()).filter(|&r| r < self.content_height())?;
+        let scroll_row = self.buffer.scroll_line + offset;
+        match (self.buffer.wrap_mode, &self.buffer.wrapped_lines) {
+            (WrapMode::Wrap | WrapMode::Word, Some(wrapped)) => wrapped.get(scroll_row).map(|w| w.line_idx),
+            _ => (scroll_row < self.buffer.document.line_count()).then_some(scroll_row),
+        }
+    }
+
+    /// Jump to the point of the document that column `column` of the status bar stands for
+    pub fn jump_to_column(&mut self, column: u16) {
+        let width = (self.terminal_size.0 as usize).max(1);
+        let row = self.scroll_rows() * column as usize / width;
+        self.buffer.scroll_line = row.min(self.max_scroll());
+    }
+
+    /// Start a mouse selection on the line at `line_idx`
+    pub fn start_selection(&mut self, line_idx: usize) {
+        self.mouse_selection = Some((line_idx, line_idx));
+    }
+
+    /// Extend the mouse selection to the line at `line_idx`
+    pub fn extend_selection(&mut self, line_idx: usize) {
+        if let Some((_, ref mut end)) = self.mouse_selection {
+            *end = line_idx;
+        }
+    }
+
+    /// First and last line index of the mouse selection
+    pub fn selected_range(&self) -> Option<(usize, usize)> {
+        self.mouse_selection.map(|(a, b)| (a.min(b), a.max(b)))
+    }
+
+    /// Finish a mouse selection: a click moves its line to the top, a drag copies the lines
+    pub fn finish_selection(&mut self) {
+        let Some((start, end)) = self.selected_range() else {
+            return;
+        };
+        if start == end {
+            self.mouse_selection = None;
+            self.scroll_to_line_idx(start);
+            return;
+        }
+        let Some(lines) = self.buffer.document.lines.get(start..=end) else {
+            self.mouse_selection = None;
+            return;
+        };
+        let mut text = String::new();
+        for line in lines {
+            if !line.continuation && !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&line.text());
+        }
+        let count = lines.iter().filter(|l| !l.continuation).count();
+        self.status_message = Some(format!("Copied {} lines", count));
+        self.pending_clipboard = Some(text);
+    }
+
+    /// Scroll so that the line at `line_idx` is the anchor line
+    fn scroll_to_line_idx(&mut self, line_idx: usize) {
+        let row = match (self.buffer.wrap_mode, &self.buffer.wrapped_lines) {
+            (WrapMode::Wrap | WrapMode::Word, Some(wrapped)) => {
+                wrapped.iter().position(|w| w.line_idx >= line_idx).unwrap_or(0)
+            }
+            _ => line_idx,
+        };
+        self.buffer.scroll_line = row.min(self.max_scroll());
+    }
+
+    /// Index of the anchor line: the document line shown at the top of the viewport
+    pub fn anchor_line_idx(&self) -> usize {
+        match (self.buffer.wrap_mode, &self.buffer.wrapped_lines) {
+            (WrapMode::Wrap | WrapMode::Word, Some(wrapped)) => wrapped
+                .get(self.buffer.scroll_line)
+                .map(|w| w.line_idx)
+                .unwrap_or(0),
+            _ => self.buffer.scroll_line,
+        }
+    }
+
+    /// Number to show in the gutter for the line at `line_idx`
+    pub fn gutter_number(&self, line_idx: usize, line_number: usize) -> usize {
+        match self.buffer.line_number_mode {
+            LineNumberMode::Absolute => line_number,
+            LineNumberMode::Relative => {
+                let anchor = self.anchor_line_idx();
+                if line_idx == anchor {
+                    line_number
+                } else {
+                    line_idx.abs_diff(anchor)
+                }
+            }
+        }
+    }
+
     /// Get the range of visible lines
     pub fn visible_line_range(&self) -> (usize, usize) {
-        let start = self.scroll_line;
-        let end = (start + self.content_height()).min(self.document.line_count());
+        let start = self.buffer.scroll_line;
+        let end = (start + self.content_height()).min(self.buffer.document.line_count());
         (start, end)
     }
 
     /// Scroll down by n lines
     pub fn scroll_down(&mut self, n: usize) {
         let max_scroll = self.max_scroll();
-        self.scroll_line = (self.scroll_line + n).min(max_scroll);
+        self.buffer.scroll_line = (self.buffer.scroll_line + n).min(max_scroll);
     }
 
     /// Scroll up by n lines
     pub fn scroll_up(&mut self, n: usize) {
-        self.scroll_line = self.scroll_line.saturating_sub(n);
+        self.buffer.scroll_line = self.buffer.scroll_line.saturating_sub(n);
+    }
+
+    /// Scroll left by n columns (disabled in wrap mode)
+    pub fn scroll_left(&mut self, n: usize) {
+        if self.buffer.wrap_mode.wraps() {
+            return; // No horizontal scroll in wrap mode
+        }
+        self.buffer.scroll_col = self.buffer.scroll_col.saturating_sub(n);
+    }
+
+    /// Scroll right by n columns (disabled in wrap mode)
+    pub fn scroll_right(&mut self, n: usize) {
+        if self.buffer.wrap_mode.wraps() {
+            return; // No horizontal scroll in wrap mode
+        }
+        let max_scroll = self.buffer.document.max_line_width.saturating_sub(self.content_width());
+        self.buffer.scroll_col = (self.buffer.scroll_col + n).min(max_scroll);
+    }
+
+    /// Scroll to the start of the current line (disabled in wrap mode)
+    pub fn scroll_to_line_start(&mut self) {
+        if !self.buffer.wrap_mode.wraps() {
+            self.buffer.scroll_col = 0;
+        }
+    }
+
+    /// Scroll to the end of the longest visible line (disabled in wrap mode)
+    pub fn scroll_to_line_end(&mut self) {
+        if !self.buffer.wrap_mode.wraps() {
+            let max_scroll = self.buffer.document.max_line_width.saturating_sub(self.content_width());
+            self.buffer.scroll_col = max_scroll;
+        }
+    }
+
+    /// Scroll left by half the content width (disabled in wrap mode)
+    pub fn scroll_half_screen_left(&mut self) {
+        self.scroll_left(self.content_width() / 2);
+    }
+
+    /// Scroll right by half the content width (disabled in wrap mode)
+    pub fn scroll_half_screen_right(&mut self) {
+        self.scroll_right(self.content_width() / 2);
+    }
+
+    /// Scroll right to the next word start of the longest visible line (disabled in wrap mode)
+    pub fn scroll_word_right(&mut self) {
+        if self.buffer.wrap_mode.wraps() {
+            return;
+        }
+        if self.buffer.table.is_some() {
+            self.move_table_column(true);
+            return;
+        }
+        let max_scroll = self.buffer.document.max_line_width.saturating_sub(self.content_width());
+        let next = self.column_stops().into_iter().find(|&col| col > self.buffer.scroll_col);
+        if let Some(col) = next {
+            self.buffer.scroll_col = col.min(max_scroll);
+        }
+    }
+
+    /// Scroll left to the previous word start of the longest visible line (disabled in wrap mode)
+    pub fn scroll_word_left(&mut self) {
+        if self.buffer.wrap_mode.wraps() {
+            return;
+        }
+        if self.buffer.table.is_some() {
+            self.move_table_column(false);
+            return;
+        }
+        let prev = self.column_stops().into_iter().rev().find(|&col| col < self.buffer.scroll_col);
+        self.buffer.scroll_col = prev.unwrap_or(0);
+    }
+
+    /// Columns `w` / `b` stop at: table columns, or word starts of the widest visible line
+    fn column_stops(&self) -> Vec<usize> {
+        if self.buffer.columns.is_empty() {
+            word_start_columns(&self.longest_visible_line())
+        } else {
+            self.buffer.columns.iter().map(|&(start, _)| start).collect()
+        }
+    }
+
+    /// Text of the widest line in the viewport
+    fn longest_visible_line(&self) -> String {
+        let (start, end) = self.visible_line_range();
+        self.buffer.document.lines[start..end]
+            .iter()
+            .max_by_key(|l| l.width())
+            .map(|l| l.text())
+            .unwrap_or_default()
+    }
+
+    /// Go to the top of the document
+    pub fn go_to_top(&mut self) {
+        self.buffer.scroll_line = 0;
+    }
+
+    /// Go to the bottom of the document (last page, not overscrolled)
+    pub fn go_to_bottom(&mut self) {
+        self.buffer.scroll_line = self.scroll_rows().saturating_sub(self.content_height());
     }
 
-    /// Scroll left by n columns (disabled in wrap mode)
-    pub fn scroll_left(&mut self, n: usize) {
-        if self.wrap_mode == WrapMode::Wrap {
-            return; // No horizontal scroll in wrap mode
-        }
-        self.scroll_col = self.scroll_col.saturating_sub(n);
+    /// Get maximum scroll position: like less, the last line may reach the top
+    fn max_scroll(&self) -> usize {
+        self.scroll_rows().saturating_sub(1)
     }
 
-    /// Scroll right by n columns (disabled in wrap mode)
-    pub fn scroll_right(&mut self, n: usize) {
-        if self.wrap_mode == WrapMode::Wrap {
-            return; // No horizontal scroll in wrap mode
+    /// Number of scrollable rows (document lines, or wrapped rows in wrap mode)
+    fn scroll_rows(&self) -> usize {
+        match self.buffer.wrap_mode {
+            WrapMode::None | WrapMode::Truncate => self.buffer.document.line_count(),
+            WrapMode::Wrap | WrapMode::Word => self.total_wrapped_lines(),
         }
-        let max_scroll = self.document.max_line_width.saturating_sub(self.content_width());
-        self.scroll_col = (self.scroll_col + n).min(max_scroll);
     }
 
-    /// Scroll to the start of the current line (disabled in wrap mode)
-    pub fn scroll_to_line_start(&mut self) {
-        if self.wrap_mode != WrapMode::Wrap {
-            self.scroll_col = 0;
-        }
+    /// Number of viewport rows showing document content (the rest are past EOF)
+    pub fn visible_row_count(&self) -> usize {
+        self.scroll_rows().saturating_sub(self.buffer.scroll_line).min(self.content_height())
     }
 
-    /// Scroll to the end of the longest visible line (disabled in wrap mode)
-    pub fn scroll_to_line_end(&mut self) {
-        if self.wrap_mode != WrapMode::Wrap {
-            let max_scroll = self.document.max_line_width.saturating_sub(self.content_width());
-            self.scroll_col = max_scroll;
-        }
+    /// Scroll down a full page
+    pub fn scroll_page_down(&mut self) {
+        self.scroll_down(self.content_height());
     }
 
-    /// Go to the top of the document
-    pub fn go_to_top(&mut self) {
-        self.scroll_line = 0;
+    /// Scroll up a full page
+    pub fn scroll_page_up(&mut self) {
+        self.scroll_up(self.content_height());
     }
 
-    /// Go to the bottom of the document
-    pub fn go_to_bottom(&mut self) {
-        self.scroll_line = self.max_scroll();
+    /// Toggle case-insensitive search, reporting the new setting in the status bar
+    ///
+    /// An active search is recompiled with the new setting and its matches and
+    /// highlights are refreshed.
+    pub fn toggle_ignore_case(&mut self) {
+        self.ignore_case = !self.ignore_case;
+        let setting = if self.ignore_case { "on" } else { "off" };
+        let mut message = format!("Ignore case in searches: {}", setting);
+
+        if let Some(ref mut state) = self.buffer.search_state {
+            state.set_ignore_case(self.ignore_case);
+            self.rebuild_document();
+            let count = self.buffer.search_state.as_ref().map_or(0, |s| s.match_count());
+            message.push_str(&format!(" ({} matches)", count));
+        }
+        self.status_message = Some(message);
     }
 
-    /// Get maximum scroll position
-    fn max_scroll(&self) -> usize {
-        match self.wrap_mode {
-            WrapMode::None | WrapMode::Truncate => {
-                self.document.line_count().saturating_sub(self.content_height())
-            }
-            WrapMode::Wrap => {
-                self.total_wrapped_lines().saturating_sub(self.content_height())
+    /// Show less-style file information (`=`) in the status bar
+    pub fn show_file_info(&mut self) {
+        let (start, end) = self.visible_line_range();
+        let visible = &self.buffer.document.lines[start..end];
+        let first = visible.iter().find(|l| l.number > 0).map(|l| l.number);
+        let last = visible.iter().rev().find(|l| l.number > 0).map(|l| l.number);
+        let total = self.buffer.document.lines.iter().rev().find(|l| l.number > 0).map_or(0, |l| l.number);
+
+        let mut info = self.buffer.document.source_name.clone();
+        if self.buffer_count() > 1 {
+            info.push_str(&format!(" (file {} of {})", self.buffer_index + 1, self.buffer_count()));
+        }
+        if let (Some(first), Some(last)) = (first, last) {
+            info.push_str(&format!(" lines {}-{}/{}", first, last, total));
+            if let Some(percent) = (last * 100).checked_div(total) {
+                info.push_str(&format!(" {}%", percent));
             }
         }
+        if self.at_bottom() {
+            info.push_str(" (END)");
+        }
+        self.status_message = Some(info);
     }
 
     /// Scroll down half a page
@@ -404,25 +2054,25 @@ impl App {
     }
 
     /// Get current line number for status bar (1-indexed)
+    #[allow(dead_code)]
     pub fn current_line_display(&self) -> usize {
-        self.scroll_line + 1
+        self.buffer.scroll_line + 1
     }
 
     /// Get total line count for status bar
     pub fn total_lines(&self) -> usize {
-        self.document.line_count()
+        self.buffer.document.line_count()
     }
 
     /// Check if we're at the end of the document
-    #[allow(dead_code)]
     pub fn at_bottom(&self) -> bool {
-        match self.wrap_mode {
+        match self.buffer.wrap_mode {
             WrapMode::None | WrapMode::Truncate => {
-                self.scroll_line + self.content_height() >= self.document.line_count()
+                self.buffer.scroll_line + self.content_height() >= self.buffer.document.line_count()
             }
-            WrapMode::Wrap => {
+            WrapMode::Wrap | WrapMode::Word => {
                 let total_wrapped = self.total_wrapped_lines();
-                self.scroll_line + self.content_height() >= total_wrapped
+                self.buffer.scroll_line + self.content_height() >= total_wrapped
             }
         }
     }
@@ -430,20 +2080,27 @@ impl App {
     /// Check if we're in a wrapping mode
     #[allow(dead_code)]
     pub fn is_wrapping(&self) -> bool {
-        self.wrap_mode == WrapMode::Wrap
+        self.buffer.wrap_mode.wraps()
     }
 
     /// Get total number of wrapped lines (for wrap mode)
     pub fn total_wrapped_lines(&self) -> usize {
-        if self.wrap_mode != WrapMode::Wrap {
-            return self.document.line_count();
+        if !self.buffer.wrap_mode.wraps() {
+            return self.buffer.document.line_count();
         }
         // This is a simplified calculation - actual wrapping happens in render
-        let width = self.content_width();
+        let width = self.wrap_columns();
         if width == 0 {
-            return self.document.line_count();
+            return self.buffer.document.line_count();
+        }
+        if self.buffer.wrap_mode == WrapMode::Word {
+            // Rows broken between words can't be counted from the width alone
+            return match &self.buffer.wrapped_lines {
+                Some(wrapped) => wrapped.len(),
+                None => self.buffer.document.lines.iter().map(|line| wrap_rows(&line.text(), width, true).len()).sum(),
+            };
         }
-        self.document
+        self.buffer.document
             .lines
             .iter()
             .map(|line| {
@@ -459,100 +2116,62 @@ impl App {
 
     /// Build wrapped line indices for efficient lookup
     pub fn build_wrapped_lines(&mut self) {
-        if self.wrap_mode != WrapMode::Wrap {
-            self.wrapped_lines = None;
+        if !self.buffer.wrap_mode.wraps() {
+            self.buffer.wrapped_lines = None;
             return;
         }
 
-        let width = self.content_width();
+        let width = self.wrap_columns();
         if width == 0 {
-            self.wrapped_lines = None;
+            self.buffer.wrapped_lines = None;
             return;
         }
 
+        let at_words = self.buffer.wrap_mode == WrapMode::Word;
         let mut wrapped = Vec::new();
 
-        for (line_idx, line) in self.document.lines.iter().enumerate() {
-            let line_text = line.text();
-            let line_width = line.width();
-
-            if line_width == 0 {
-                // Empty line - still takes one row
+        for (line_idx, line) in self.buffer.document.lines.iter().enumerate() {
+            // An empty line still takes one row
+            for (i, row) in wrap_rows(&line.text(), width, at_words).into_iter().enumerate() {
                 wrapped.push(WrappedLine {
                     line_idx,
                     line_number: line.number,
-                    is_first_row: true,
-                    char_offset: 0,
-                    display_width: 0,
+                    is_first_row: i == 0,
+                    char_offset: row.char_offset,
+                    display_width: row.width,
                 });
-            } else {
-                // Break line into wrapped rows
-                let mut current_width = 0;
-                let mut is_first = true;
-                let mut row_start = 0;
-
-                for (char_idx, ch) in line_text.chars().enumerate() {
-                    let ch_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0);
-
-                    if current_width + ch_width > width && current_width > 0 {
-                        // Start a new row
-                        wrapped.push(WrappedLine {
-                            line_idx,
-                            line_number: line.number,
-                            is_first_row: is_first,
-                            char_offset: row_start,
-                            display_width: current_width,
-                        });
-                        is_first = false;
-                        row_start = char_idx;
-                        current_width = ch_width;
-                    } else {
-                        current_width += ch_width;
-                    }
-                }
-
-                // Don't forget the last row
-                if current_width > 0 || is_first {
-                    wrapped.push(WrappedLine {
-                        line_idx,
-                        line_number: line.number,
-                        is_first_row: is_first,
-                        char_offset: row_start,
-                        display_width: current_width,
-                    });
-                }
             }
         }
 
-        self.wrapped_lines = Some(wrapped);
+        self.buffer.wrapped_lines = Some(wrapped);
     }
 
     /// Get wrapped lines, building cache if needed
     #[allow(dead_code)]
     pub fn get_wrapped_lines(&mut self) -> Option<&Vec<WrappedLine>> {
-        if self.wrap_mode != WrapMode::Wrap {
+        if !self.buffer.wrap_mode.wraps() {
             return None;
         }
-        if self.wrapped_lines.is_none() {
+        if self.buffer.wrapped_lines.is_none() {
             self.build_wrapped_lines();
         }
-        self.wrapped_lines.as_ref()
+        self.buffer.wrapped_lines.as_ref()
     }
 
     /// Invalidate wrapped lines cache (call when document changes)
     #[allow(dead_code)]
     pub fn invalidate_wrap_cache(&mut self) {
-        self.wrapped_lines = None;
+        self.buffer.wrapped_lines = None;
     }
 
     /// Get visible wrapped line range for rendering
     #[allow(dead_code)]
     pub fn visible_wrapped_range(&self) -> Option<(usize, usize)> {
-        if self.wrap_mode != WrapMode::Wrap {
+        if !self.buffer.wrap_mode.wraps() {
             return None;
         }
-        if let Some(ref wrapped) = self.wrapped_lines {
-            let start = self.scroll_line;
+        if let Some(ref wrapped) = self.buffer.wrapped_lines {
+            let start = self.buffer.scroll_line;
             let end = (start + self.content_height()).min(wrapped.len());
             Some((start, end))
         } else {
@@ -561,14 +2180,59 @@ impl App {
     }
 }
 
+/// Add `lines` at the end of `document`, widening it as needed
+fn append_lines(document: &mut Document, lines: Vec<Line>) {
+    for line in lines {
+        document.max_line_width = document.max_line_width.max(line.width());
+        document.lines.push(line);
+    }
+}
+
+/// Display columns where words start, like vim's `w` stops
+///
+/// A word is a run of word characters or a run of other non-blank characters.
+fn word_start_columns(text: &str) -> Vec<usize> {
+    #[derive(PartialEq)]
+    enum Class {
+        Blank,
+        Word,
+        Punct,
+    }
+    let class = |c: char| {
+        if c.is_whitespace() {
+            Class::Blank
+        } else if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else {
+            Class::Punct
+        }
+    };
+
+    let mut starts = Vec::new();
+    let mut col = 0;
+    let mut prev = Class::Blank;
+    for c in text.chars() {
+        let current = class(c);
+        if current != Class::Blank && current != prev {
+            starts.push(col);
+        }
+        col += UnicodeWidthChar::width(c).unwrap_or(0);
+        prev = current;
+    }
+    starts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pager::session_log::SessionLog;
+    use crate::display::SpanStyle;
     use crate::theme::Theme;
+    use ratatui::style::Color;
 
     fn create_test_doc(lines: usize) -> Document {
         let text: String = (1..=lines).map(|i| format!("Line {}\n", i)).collect();
-        Document::from_text(&text.trim_end(), "test.txt".to_string(), "UTF-8".to_string())
+        Document::from_text(text.trim_end(), "test.txt".to_string(), "UTF-8".to_string())
     }
 
     fn test_theme_colors() -> ThemeColors {
@@ -581,27 +2245,29 @@ mod tests {
         let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
         app.set_terminal_size(80, 24); // 23 content lines
 
-        assert_eq!(app.scroll_line, 0);
+        assert_eq!(app.buffer.scroll_line, 0);
         app.scroll_down(5);
-        assert_eq!(app.scroll_line, 5);
+        assert_eq!(app.buffer.scroll_line, 5);
 
-        // Can't scroll past the end
+        // Can scroll until the last line is at the top, but not past it
         app.scroll_down(1000);
-        assert_eq!(app.scroll_line, 77); // 100 - 23 = 77
+        assert_eq!(app.buffer.scroll_line, 99);
+        assert_eq!(app.visible_row_count(), 1);
+        assert!(app.at_bottom());
     }
 
     #[test]
     fn test_scroll_up() {
         let doc = create_test_doc(100);
         let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
-        app.scroll_line = 50;
+        app.buffer.scroll_line = 50;
 
         app.scroll_up(10);
-        assert_eq!(app.scroll_line, 40);
+        assert_eq!(app.buffer.scroll_line, 40);
 
         // Can't scroll past the start
         app.scroll_up(1000);
-        assert_eq!(app.scroll_line, 0);
+        assert_eq!(app.buffer.scroll_line, 0);
     }
 
     #[test]
@@ -609,13 +2275,13 @@ mod tests {
         let doc = create_test_doc(100);
         let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
         app.set_terminal_size(80, 24);
-        app.scroll_line = 50;
+        app.buffer.scroll_line = 50;
 
         app.go_to_top();
-        assert_eq!(app.scroll_line, 0);
+        assert_eq!(app.buffer.scroll_line, 0);
 
         app.go_to_bottom();
-        assert_eq!(app.scroll_line, 77);
+        assert_eq!(app.buffer.scroll_line, 77);
     }
 
     #[test]
@@ -633,6 +2299,245 @@ mod tests {
         assert_eq!(app.gutter_width(), 5); // " 999 "
     }
 
+    #[test]
+    fn test_relative_gutter_numbers() {
+        let doc = create_test_doc(100);
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.buffer.scroll_line = 10;
+
+        assert_eq!(app.gutter_number(12, 13), 13);
+
+        app.toggle_relative_numbers();
+        assert!(app.buffer.show_line_numbers);
+        assert_eq!(app.gutter_number(10, 11), 11); // anchor keeps its number
+        assert_eq!(app.gutter_number(12, 13), 2);
+        assert_eq!(app.gutter_number(7, 8), 3);
+
+        app.toggle_relative_numbers();
+        assert_eq!(app.buffer.line_number_mode, LineNumberMode::Absolute);
+    }
+
+    #[test]
+    fn test_yank_reference() {
+        let doc = create_test_doc(100);
+        let path = PathBuf::from("src/main.rs");
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, Some(path), WrapMode::None, 200);
+        app.set_terminal_size(80, 24);
+        app.scroll_down(41);
+
+        app.yank_reference();
+        assert_eq!(app.yanked_reference.as_deref(), Some("src/main.rs:42"));
+        assert_eq!(app.pending_clipboard.as_deref(), Some("src/main.rs:42"));
+        assert!(app.status_message.is_some());
+    }
+
+    #[test]
+    fn test_mouse_selection() {
+        let doc = create_test_doc(100);
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 24);
+        app.scroll_down(10);
+
+        // Dragging from row 2 to row 4 copies three lines
+        app.start_selection(app.line_at_row(2).unwrap());
+        app.extend_selection(app.line_at_row(4).unwrap());
+        assert_eq!(app.selected_range(), Some((12, 14)));
+        app.finish_selection();
+        assert_eq!(app.pending_clipboard.as_deref(), Some("Line 13\nLine 14\nLine 15"));
+
+        // A click moves its line to the top
+        app.start_selection(app.line_at_row(5).unwrap());
+        app.finish_selection();
+        assert_eq!(app.buffer.scroll_line, 15);
+        assert_eq!(app.mouse_selection, None);
+
+        // The status bar row shows no line; clicking its middle jumps halfway
+        assert_eq!(app.line_at_row(23), None);
+        app.jump_to_column(40);
+        assert_eq!(app.buffer.scroll_line, 50);
+    }
+
+    #[test]
+    fn test_stack_trace_references() {
+        let text = "start\nTraceback (most recent call last):\n  File \"app.py\", line 3, in main\n    run()\n  File \"missing.py\", line 9, in run\nValueError: bad\nend";
+        let doc = Document::from_text(text, "err.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 24);
+
+        app.jump_to_reference(true);
+        assert_eq!(app.anchor_line_idx(), 2);
+        app.jump_to_reference(true);
+        assert_eq!(app.anchor_line_idx(), 4);
+        app.jump_to_reference(true);
+        assert_eq!(app.status_message.as_deref(), Some("No file reference below"));
+
+        app.open_reference();
+        assert!(app.pending_editor.is_none());
+        assert_eq!(app.status_message.as_deref(), Some("No such file: missing.py"));
+        app.jump_to_reference(false);
+        assert_eq!(app.anchor_line_idx(), 2);
+    }
+
+    #[test]
+    fn test_jump_between_headings() {
+        let md = "# Guide\n\nIntro.\n\n## Install\n\nText.\n\n## Usage\n\nMore.";
+        let doc = crate::markdown::render_markdown(md, "guide.md".to_string(), &Default::default());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 6);
+        app.buffer.prose = true;
+        // The title box starts the document, so its section is the first one
+        assert_eq!(app.current_section().map(|h| h.title.as_str()), Some("Guide"));
+
+        app.jump_to_heading(true);
+        assert_eq!(app.current_section().map(|h| (h.level, h.title.as_str())), Some((2, "Install")));
+        app.scroll_down(1);
+        // Below a heading the section is still the heading's
+        assert_eq!(app.current_section().map(|h| h.title.as_str()), Some("Install"));
+        app.jump_to_heading(false);
+        assert_eq!(app.current_section().map(|h| h.title.as_str()), Some("Install"));
+        app.jump_to_heading(false);
+        assert_eq!(app.anchor_line_idx(), 0);
+        app.jump_to_heading(false);
+        assert_eq!(app.status_message.as_deref(), Some("No heading above"));
+    }
+
+    #[test]
+    fn test_select_and_open_links() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn main() {}\n").unwrap();
+        let viewed = dir.path().join("notes.txt");
+        let text = "see lib.rs:1 and missing.rs:4\nplain\nalso ./lib.rs";
+        let doc = Document::from_text(text, "notes.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, Some(viewed), WrapMode::None, 200);
+        app.set_terminal_size(80, 24);
+
+        app.select_link(true);
+        assert_eq!(app.selected_link.as_ref().map(|l| l.range.clone()), Some(4..12));
+        assert_eq!(app.status_message.as_deref(), Some("lib.rs:1 (Enter to open, o for the editor)"));
+        app.select_link(true);
+        app.select_link(true);
+        assert_eq!(app.selected_link.as_ref().map(|l| l.line_idx), Some(2));
+        app.select_link(true);
+        assert_eq!(app.status_message.as_deref(), Some("No path below"));
+        app.select_link(false);
+        assert_eq!(app.selected_link.as_ref().map(|l| l.reference.path.as_str()), Some("missing.rs"));
+
+        app.open_link();
+        assert!(app.selected_link.is_none());
+        assert_eq!(app.status_message.as_deref(), Some("No such file: missing.rs"));
+
+        // Paths are looked up next to the viewed file
+        app.select_link(true);
+        app.open_link();
+        let (path, reference) = app.pending_open.take().unwrap();
+        assert_eq!(path, dir.path().join("lib.rs"));
+        assert_eq!(reference.line, 1);
+    }
+
+    #[test]
+    fn test_select_line_reports_bookmarks() {
+        let doc = Document::from_text("a\nb\nc", "list.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 24);
+        app.toggle_bookmark();
+        app.scroll_down(1);
+        app.toggle_bookmark();
+        assert_eq!(app.reported_lines(), vec!["list.txt:1:a", "list.txt:2:b"]);
+
+        app.select_line();
+        assert!(app.should_quit);
+        assert_eq!(app.quit_action, QuitAction::Selected);
+        assert_eq!(app.reported_lines(), vec!["list.txt:2:b", "list.txt:1:a"]);
+
+        app.quit_action = QuitAction::Aborted;
+        assert!(app.reported_lines().is_empty());
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let doc = Document::from_text("first\nsecond\nthird", "notes.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 24);
+        app.scroll_down(2);
+        app.toggle_bookmark();
+        assert_eq!(app.status_message.as_deref(), Some("Bookmarked line 3"));
+        assert_eq!(app.annotation_width(), 1);
+        app.scroll_up(2);
+        app.toggle_bookmark();
+
+        // The text comes from the document before view transforms
+        app.toggle_squeeze();
+        assert_eq!(app.bookmark_references(), vec!["notes.txt:1:first", "notes.txt:3:third"]);
+
+        app.toggle_bookmark();
+        assert_eq!(app.status_message.as_deref(), Some("Removed bookmark on line 1"));
+        assert_eq!(app.bookmark_references(), vec!["notes.txt:3:third"]);
+    }
+
+    #[test]
+    fn test_toggle_squeeze() {
+        let text = "a\nspam\nspam\nspam\nb\nc";
+        let doc = Document::from_text(text, "test.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 3);
+        app.scroll_down(4); // "b" at the top
+
+        app.toggle_squeeze();
+        assert_eq!(app.buffer.document.line_count(), 4);
+        assert_eq!(app.buffer.document.lines[1].text(), "spam (× 3)");
+        assert_eq!(app.buffer.document.lines[app.buffer.scroll_line].text(), "b");
+
+        app.toggle_squeeze();
+        assert_eq!(app.buffer.document.line_count(), 6);
+        assert!(app.buffer.base_document.is_none());
+        assert_eq!(app.buffer.document.lines[app.buffer.scroll_line].text(), "b");
+    }
+
+    #[test]
+    fn test_jump_by_time() {
+        let text = "2024-01-15T10:00:00Z a\n\
+                    2024-01-15T10:00:30Z b\n\
+                    continued\n\
+                    2024-01-15T10:01:10Z c\n\
+                    2024-01-15T10:05:00Z d";
+        let doc = Document::from_text(text, "app.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 2);
+
+        app.jump_by_time(true);
+        assert_eq!(app.buffer.scroll_line, 3); // first line >= 10:01:00
+
+        app.jump_by_time(true);
+        assert_eq!(app.buffer.scroll_line, 4);
+
+        app.jump_by_time(false);
+        assert_eq!(app.buffer.scroll_line, 4); // nothing within a minute before 10:05
+        assert!(app.status_message.is_some());
+
+        app.buffer.scroll_line = 3;
+        app.jump_by_time(false);
+        assert_eq!(app.buffer.scroll_line, 1); // 10:00:30 is the earliest within 10:00:10..
+    }
+
+    #[test]
+    fn test_timestamp_mode_keeps_base_document() {
+        let doc = Document::from_text("1705314225 event", "app.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+
+        app.buffer.timestamp_mode = TimestampMode::Utc;
+        app.rebuild_document();
+        assert_eq!(app.buffer.document.lines[0].text(), "2024-01-15T10:23:45Z event");
+
+        // Jumps still parse the original text
+        app.jump_by_time(true);
+        assert!(app.status_message.is_some());
+
+        app.buffer.timestamp_mode = TimestampMode::Original;
+        app.rebuild_document();
+        assert_eq!(app.buffer.document.lines[0].text(), "1705314225 event");
+        assert!(app.buffer.base_document.is_none());
+    }
+
     #[test]
     fn test_wrap_mode_scroll() {
         // Create a document with lines that will wrap
@@ -649,6 +2554,85 @@ mod tests {
         assert!(total > 3, "Expected wrapping to increase line count, got {}", total);
     }
 
+    #[test]
+    fn test_word_wrap() {
+        let doc = Document::from_text("alpha beta gamma delta\nx", "test.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::Word, 200);
+        app.wrap_width = Some(12);
+        app.set_terminal_size(80, 10);
+        app.build_wrapped_lines();
+
+        let rows: Vec<(usize, usize)> =
+            app.buffer.wrapped_lines.as_ref().unwrap().iter().map(|w| (w.char_offset, w.display_width)).collect();
+        assert_eq!(rows, vec![(0, 11), (11, 11), (0, 1)]);
+        assert_eq!(app.total_wrapped_lines(), 3);
+    }
+
+    #[test]
+    fn test_fixed_wrap_width() {
+        let doc = Document::from_text(&"x".repeat(100), "test.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::Wrap, 200);
+        app.wrap_width = Some(30);
+        app.set_terminal_size(80, 10);
+        app.build_wrapped_lines();
+        assert_eq!(app.total_wrapped_lines(), 4);
+
+        // A screen narrower than the wrap width wraps at the screen
+        app.set_terminal_size(25, 10);
+        app.build_wrapped_lines();
+        assert_eq!(app.total_wrapped_lines(), 4);
+        app.set_terminal_size(20, 10);
+        app.build_wrapped_lines();
+        assert_eq!(app.total_wrapped_lines(), 5);
+    }
+
+    #[test]
+    fn test_toggle_diagnostics() {
+        let doc = Document::from_text("plain\n世界 ok", "test.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.scroll_down(1);
+
+        app.toggle_diagnostics();
+        let diagnostics = app.diagnostics.as_ref().unwrap();
+        assert_eq!(diagnostics.line_number, 2);
+        assert_eq!(diagnostics.width, 7);
+        assert_eq!(diagnostics.segments.len(), 3);
+
+        app.toggle_histogram();
+        assert!(app.diagnostics.is_none());
+        app.toggle_diagnostics();
+        assert!(app.histogram.is_none());
+        app.toggle_diagnostics();
+        assert!(app.diagnostics.is_none());
+    }
+
+    #[test]
+    fn test_reading_mode() {
+        let doc = Document::from_text(&"word ".repeat(60), "notes.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(120, 10);
+        app.reading_width = 60;
+
+        app.toggle_reading_mode();
+        assert!(app.status_message.is_some());
+        assert_eq!(app.buffer.wrap_mode, WrapMode::None);
+
+        app.buffer.prose = true;
+        app.toggle_reading_mode();
+        assert_eq!(app.buffer.wrap_mode, WrapMode::Wrap);
+        assert_eq!(app.wrap_columns(), 60);
+        assert_eq!(app.reading_margin(), 30);
+        assert_eq!(app.total_wrapped_lines(), 5);
+
+        // A narrow screen leaves no margin
+        app.set_terminal_size(50, 10);
+        assert_eq!(app.reading_margin(), 0);
+
+        app.toggle_reading_mode();
+        assert_eq!(app.buffer.wrap_mode, WrapMode::None);
+        assert_eq!(app.reading_margin(), 0);
+    }
+
     #[test]
     fn test_wrap_mode_no_horizontal_scroll() {
         let doc = create_test_doc(10);
@@ -657,9 +2641,613 @@ mod tests {
 
         // Horizontal scroll should be disabled in wrap mode
         app.scroll_right(10);
-        assert_eq!(app.scroll_col, 0);
+        assert_eq!(app.buffer.scroll_col, 0);
 
         app.scroll_left(10);
-        assert_eq!(app.scroll_col, 0);
+        assert_eq!(app.buffer.scroll_col, 0);
+    }
+
+    #[test]
+    fn test_word_start_columns() {
+        assert_eq!(word_start_columns("foo bar.baz  qux"), vec![0, 4, 7, 8, 13]);
+        assert_eq!(word_start_columns("  中文 x"), vec![2, 7]);
+    }
+
+    #[test]
+    fn test_scroll_by_words() {
+        let doc = Document::from_text(
+            "short\nalpha beta gamma delta epsilon zeta eta theta iota kappa",
+            "test.txt".to_string(),
+            "UTF-8".to_string(),
+        );
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(20, 24);
+
+        app.scroll_word_right();
+        assert_eq!(app.buffer.scroll_col, 6);
+        app.scroll_word_right();
+        assert_eq!(app.buffer.scroll_col, 11);
+        app.scroll_word_left();
+        assert_eq!(app.buffer.scroll_col, 6);
+        app.scroll_word_left();
+        assert_eq!(app.buffer.scroll_col, 0);
+
+        // Never past the end of the longest line
+        for _ in 0..20 {
+            app.scroll_word_right();
+        }
+        assert_eq!(app.buffer.scroll_col, app.buffer.document.max_line_width - app.content_width());
+    }
+
+    #[test]
+    fn test_show_file_info() {
+        let doc = create_test_doc(100);
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 11); // 10 content lines
+        app.buffer.scroll_line = 40;
+
+        app.show_file_info();
+        assert_eq!(app.status_message.as_deref(), Some("test.txt lines 41-50/100 50%"));
+
+        // With several files open, it tells which one this is
+        let other = Document::from_text("b", "b.txt".to_string(), "UTF-8".to_string());
+        app.open_buffer(Buffer::new(other, false, None, None, WrapMode::None));
+        app.next_file();
+        app.show_file_info();
+        assert_eq!(app.status_message.as_deref(), Some("b.txt (file 2 of 2) lines 1-1/1 100% (END)"));
+    }
+
+    #[test]
+    fn test_counted_and_numbered_match_jumps() {
+        let doc = create_test_doc(100);
+        let mut state = SearchState::new(Regex::new("0$").unwrap());
+        state.find_matches(&doc);
+        let mut app = App::new(doc, false, Some(state), test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 11);
+
+        app.next_match(3);
+        assert_eq!(app.search_info(), Some((3, 10)));
+        app.prev_match(2);
+        assert_eq!(app.search_info(), Some((1, 10)));
+
+        app.go_to_match(7);
+        assert_eq!(app.search_info(), Some((7, 10)));
+        assert_eq!(app.buffer.scroll_line, 69 - 5);
+
+        app.go_to_match(11);
+        assert_eq!(app.search_info(), Some((7, 10)));
+        assert_eq!(app.status_message.as_deref(), Some("No match 11 (10 matches)"));
+    }
+
+    #[test]
+    fn test_go_to_page() {
+        let page: String = (1..=30).map(|i| format!("text {}\n", i)).collect();
+        let text = format!("─── Page 1 ───\n{}─── Page 2 ───\n{}", page, page);
+        let doc = Document::from_text(&text, "report.pdf".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 11);
+
+        app.go_to_page(2);
+        assert_eq!(app.buffer.scroll_line, 31);
+        app.go_to_page(3);
+        assert_eq!(app.status_message.as_deref(), Some("No page 3 (2 pages)"));
+    }
+
+    #[test]
+    fn test_go_to_location() {
+        let mut text: String = (1..=100).map(|i| format!("Line {}\n", i)).collect();
+        text.push_str(&format!("{}needle{}", " ".repeat(300), "x".repeat(200)));
+        let doc = Document::from_text(&text, "test.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 11);
+
+        app.go_to_location(50, None);
+        assert_eq!(app.buffer.scroll_line, 49 - 5);
+        assert_eq!(app.buffer.scroll_col, 0);
+
+        // A column past the viewport scrolls it into the middle
+        app.go_to_location(101, Some(301));
+        assert_eq!(app.buffer.scroll_col, 300 - 40);
+
+        // Visible columns don't move the view
+        app.go_to_location(3, Some(280));
+        assert_eq!(app.buffer.scroll_col, 260);
+    }
+
+    #[test]
+    fn test_toggle_ignore_case_refreshes_search() {
+        let doc = Document::from_text("Error one\nerror two\nnothing", "test.txt".to_string(), "UTF-8".to_string());
+        let state = SearchState::new(Regex::new("error").unwrap());
+        let mut app = App::new(doc, false, Some(state), test_theme_colors(), false, None, WrapMode::None, 200);
+        app.rebuild_document();
+        let highlighted = |app: &App, idx: usize| app.buffer.document.lines[idx].spans.iter().any(|s| s.style.bg.is_some());
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 1);
+        assert!(!highlighted(&app, 0));
+
+        app.toggle_ignore_case();
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 2);
+        assert!(highlighted(&app, 0) && highlighted(&app, 1));
+        assert_eq!(app.status_message.as_deref(), Some("Ignore case in searches: on (2 matches)"));
+
+        // Turning it off again removes the extra highlight
+        app.toggle_ignore_case();
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 1);
+        assert!(!highlighted(&app, 0) && highlighted(&app, 1));
+    }
+
+    #[test]
+    fn test_clear_search_keeps_position() {
+        let doc = create_test_doc(100);
+        let state = SearchState::new(Regex::new("Line").unwrap());
+        let mut app = App::new(doc, false, Some(state), test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 11);
+        app.rebuild_document();
+        app.scroll_down(30);
+
+        app.clear_search();
+        assert!(app.buffer.search_state.is_none());
+        assert!(app.buffer.base_document.is_none());
+        assert!(app.buffer.document.lines.iter().all(|l| l.spans.iter().all(|s| s.style.bg.is_none())));
+        assert_eq!(app.buffer.scroll_line, 30);
+    }
+
+    #[test]
+    fn test_toggle_search_scope() {
+        let all = Document::from_text("error 1\nok\nerror 2\nok error", "test.txt".to_string(), "UTF-8".to_string());
+        let mut filtered = all.clone();
+        filtered.lines.retain(|l| l.text().starts_with("error"));
+        let state = SearchState::new(Regex::new("error").unwrap());
+        let mut app = App::new(filtered, false, Some(state), test_theme_colors(), false, None, WrapMode::None, 200);
+        app.rebuild_document();
+        assert_eq!(app.search_scope_label(), None);
+
+        app.buffer.scope_document = Some(all);
+        assert_eq!(app.search_scope_label(), Some("filtered lines"));
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 2);
+
+        app.toggle_search_scope();
+        assert_eq!(app.buffer.search_scope, SearchScope::All);
+        assert_eq!(app.buffer.document.lines.len(), 4);
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 3);
+
+        app.toggle_search_scope();
+        assert_eq!(app.search_scope_label(), Some("filtered lines"));
+        assert_eq!(app.buffer.document.lines.len(), 2);
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 2);
+    }
+
+    #[test]
+    fn test_toggle_raw_view() {
+        let pretty = Document::from_text("{\n  \"id\": 7,\n  \"ok\": true\n}", "stdin".to_string(), "UTF-8".to_string());
+        let raw = Document::from_text("{\"id\":7,\"ok\":true}", "stdin".to_string(), "UTF-8".to_string());
+        let state = SearchState::new(Regex::new("ok").unwrap());
+        let mut app = App::new(pretty, false, Some(state), test_theme_colors(), false, None, WrapMode::None, 200);
+        app.rebuild_document();
+        app.toggle_raw_view();
+        assert_eq!(app.status_message.as_deref(), Some("The input is shown as it came"));
+
+        app.buffer.raw_document = Some(raw);
+        app.toggle_raw_view();
+        assert!(app.buffer.raw_view);
+        assert_eq!(app.buffer.document.lines.len(), 1);
+        assert_eq!(app.buffer.search_state.as_ref().unwrap().match_count(), 1);
+
+        app.toggle_raw_view();
+        assert!(!app.buffer.raw_view);
+        assert_eq!(app.buffer.document.lines.len(), 4);
+        assert_eq!(app.buffer.document.lines[2].text(), "  \"ok\": true");
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let code = "impl App {\n    fn scroll(&mut self) {\n        if ready {\n            go();\n        }\n    }\n}";
+        let doc = Document::from_text(code, "app.rs".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        assert!(app.breadcrumbs().is_empty());
+        app.buffer.scroll_line = 3;
+        assert_eq!(app.breadcrumbs(), vec!["impl App", "fn scroll(&mut self)", "if ready"]);
+        app.buffer.scroll_line = 5;
+        assert_eq!(app.breadcrumbs(), vec!["impl App"]);
+
+        // Prose gets the path of its headings instead
+        let md = "# Guide\n\nintro\n\n## Install\n\n### Linux\n\napt\n\n## Usage\n\nrun";
+        let doc = crate::markdown::render_markdown(md, "guide.md".to_string(), &Default::default());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.buffer.prose = true;
+        let line_of = |app: &App, text: &str| app.buffer.document.lines.iter().position(|l| l.text().contains(text)).unwrap();
+        app.buffer.scroll_line = line_of(&app, "apt");
+        assert_eq!(app.breadcrumbs(), vec!["Guide", "Install", "Linux"]);
+        app.buffer.scroll_line = line_of(&app, "run");
+        assert_eq!(app.breadcrumbs(), vec!["Guide", "Usage"]);
+
+        let height = app.content_height();
+        app.toggle_breadcrumbs();
+        assert_eq!(app.content_height(), height - 1);
+    }
+
+    #[test]
+    fn test_grep_filter_stack() {
+        let doc = Document::from_text(
+            "error: disk\ninfo: ok\nerror: timeout\nwarn: slow",
+            "test.log".to_string(),
+            "UTF-8".to_string(),
+        );
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+
+        app.push_grep_filter("error");
+        app.push_grep_filter("timeout");
+        assert_eq!(app.buffer.document.lines.len(), 1);
+        assert_eq!(app.buffer.filters.breadcrumb().as_deref(), Some("error > timeout"));
+
+        app.push_grep_filter("missing");
+        assert_eq!(app.status_message.as_deref(), Some("Pattern not found: missing"));
+        assert_eq!(app.buffer.document.lines.len(), 1);
+
+        app.pop_filter();
+        assert_eq!(app.buffer.document.lines.len(), 3);
+        app.pop_filter();
+        assert_eq!(app.buffer.document.lines.len(), 4);
+        assert!(app.buffer.base_document.is_none());
+
+        app.pop_filter();
+        assert_eq!(app.status_message.as_deref(), Some("No filter to remove"));
+
+        app.push_hide_filter("o");
+        assert_eq!(app.status_message.as_deref(), Some("Every line matches: o"));
+        app.push_hide_filter("^info");
+        assert_eq!(app.buffer.document.lines.len(), 3);
+    }
+
+    #[test]
+    fn test_pinned_table_header() {
+        let table = crate::format::Table::parse(" id | name | city\n----+------+------\n  1 | Ada  | Rome").unwrap();
+        let doc = table.to_document("stdin".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(6, 10);
+        app.open_table(table);
+
+        assert_eq!(app.buffer.sticky_lines.len(), 2);
+        assert_eq!(app.buffer.document.line_count(), 1);
+        assert_eq!(app.content_height(), 7);
+
+        // w / b move by table column
+        app.scroll_word_right();
+        assert_eq!(app.buffer.scroll_col, 5);
+        app.scroll_word_left();
+        assert_eq!(app.buffer.scroll_col, 0);
+    }
+
+    #[test]
+    fn test_sort_and_hide_table_columns() {
+        let table = crate::format::Table::parse_delimited("name,age\nBob,9\nAda,36\nCy,100", ',').unwrap();
+        let doc = table.to_document("people.csv".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(6, 10);
+        app.open_table(table);
+        let numbers = |app: &App| app.buffer.document.lines.iter().map(|l| l.number).collect::<Vec<_>>();
+
+        // w moves the column cursor, which sorting acts on
+        app.scroll_word_right();
+        assert_eq!(app.current_column_span(), Some((7, 10)));
+        app.sort_table_column();
+        assert_eq!(numbers(&app), vec![1, 2, 3]);
+        assert_eq!(app.status_message.as_deref(), Some("Sorted by age (ascending)"));
+        app.sort_table_column();
+        assert_eq!(numbers(&app), vec![3, 2, 1]);
+
+        // Filters are applied to the re-rendered rows
+        app.push_grep_filter("o|y");
+        app.scroll_word_left();
+        app.sort_table_column();
+        assert_eq!(numbers(&app), vec![1, 3]);
+
+        app.hide_table_column();
+        assert_eq!(app.status_message.as_deref(), Some("No line would match the filters without name"));
+        app.pop_filter();
+        app.hide_table_column();
+        assert_eq!(app.buffer.document.lines[0].text(), " 36");
+        assert_eq!(app.current_column_span(), Some((0, 3)));
+        assert_eq!(app.buffer.sticky_lines[0].text(), "age");
+        app.hide_table_column();
+        assert_eq!(app.status_message.as_deref(), Some("Cannot hide the last column"));
+        app.show_table_columns();
+        assert_eq!(app.buffer.sticky_lines[0].text(), "name │ age");
+    }
+
+    #[test]
+    fn test_show_frequencies() {
+        let doc = Document::from_text(
+            "user=ada ok\nuser=bob fail\nuser=ada fail",
+            "auth.log".to_string(),
+            "UTF-8".to_string(),
+        );
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+
+        app.show_frequencies(None);
+        assert!(app.frequencies.is_none());
+        assert!(app.status_message.is_some());
+
+        // The last grep filter is used when no pattern is given
+        app.push_grep_filter("user=(\\w+) fail");
+        app.show_frequencies(None);
+        let values = &app.frequencies.as_ref().unwrap().values;
+        assert_eq!(values, &vec![("ada".to_string(), 1), ("bob".to_string(), 1)]);
+
+        app.show_frequencies(Some("nobody"));
+        assert_eq!(app.status_message.as_deref(), Some("Pattern not found: nobody"));
+    }
+
+    #[test]
+    fn test_toggle_folds() {
+        let text = "<a>\n  <b>\n    <c/>\n  </b>\n  <d/>\n</a>";
+        let doc = Document::from_text(text, "test.xml".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 3); // 2 content lines
+
+        app.close_all_folds();
+        let texts: Vec<String> = app.buffer.document.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["<a> … 4 lines", "</a>"]);
+
+        // Opening the outer fold shows its children, still folded
+        app.toggle_fold();
+        assert_eq!(app.buffer.document.line_count(), 5);
+        assert_eq!(app.buffer.document.lines[1].text(), "  <b> … 1 line");
+
+        // From inside a fold, its header is closed and scrolled to
+        app.open_all_folds();
+        assert!(app.buffer.base_document.is_none());
+        app.scroll_down(2);
+        app.toggle_fold();
+        assert_eq!(app.buffer.document.lines[1].text(), "  <b> … 1 line");
+        assert_eq!(app.buffer.scroll_line, 1);
+    }
+
+    #[test]
+    fn test_switch_buffer_keeps_settings_apart() {
+        let mut app = App::new(create_test_doc(100), false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 11);
+        app.scroll_down(40);
+        app.toggle_squeeze();
+        app.push_grep_filter("Line 1");
+
+        let other = Buffer::new(create_test_doc(10), true, None, None, WrapMode::Wrap);
+        let first = app.switch_buffer(other);
+        assert_eq!(app.buffer.scroll_line, 0);
+        assert!(app.buffer.show_line_numbers && !app.buffer.squeeze);
+        assert!(app.buffer.filters.is_empty());
+        assert!(app.buffer.wrapped_lines.is_some());
+
+        let second = app.switch_buffer(first);
+        assert!(app.buffer.squeeze);
+        assert_eq!(app.buffer.filters.breadcrumb().as_deref(), Some("Line 1"));
+        assert!(!app.buffer.show_line_numbers);
+        assert_eq!(second.wrap_mode, WrapMode::Wrap);
+    }
+
+    #[test]
+    fn test_activate_buffer_keeps_order() {
+        let doc = |name: &str| Document::from_text(name, format!("{}.txt", name), "UTF-8".to_string());
+        let mut app = App::new(doc("a"), false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.open_buffer(Buffer::new(doc("b"), false, None, None, WrapMode::None));
+        app.open_buffer(Buffer::new(doc("c"), false, None, None, WrapMode::None));
+        let names = |app: &App| -> Vec<String> {
+            (0..app.buffer_count()).map(|i| app.buffer_at(i).document.source_name.clone()).collect()
+        };
+
+        app.activate_buffer(2);
+        assert_eq!(app.buffer.document.source_name, "c.txt");
+        app.prev_file();
+        assert_eq!(app.buffer.document.source_name, "b.txt");
+        assert_eq!(names(&app), vec!["a.txt", "b.txt", "c.txt"]);
+        app.activate_buffer(0);
+        app.prev_file();
+        assert_eq!(app.status_message.as_deref(), Some("No previous file"));
+        assert_eq!(names(&app), vec!["a.txt", "b.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn test_search_all_buffers() {
+        let doc = |text: &str, name: &str| Document::from_text(text, name.to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc("error 1\nok", "a.log"), false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.open_buffer(Buffer::new(doc("ok", "b.log"), false, None, None, WrapMode::None));
+        app.open_buffer(Buffer::new(doc("ok\nerror 2 error 3\nerror 4", "c.log"), false, None, None, WrapMode::None));
+        app.set_terminal_size(80, 24);
+
+        app.search_all_buffers("error");
+        let results = app.search_results.clone().unwrap();
+        assert_eq!(app.buffer_index, 0);
+        assert_eq!(results.file_count(), 2);
+        let found: Vec<(usize, usize)> = results.results.iter().map(|r| (r.buffer, r.line_number)).collect();
+        assert_eq!(found, vec![(0, 1), (2, 2), (2, 3)]);
+
+        // The panel opens the selected line's buffer at its match
+        app.search_results.as_mut().unwrap().select_next();
+        app.open_search_result();
+        assert!(app.search_results.is_none());
+        assert_eq!(app.buffer_index, 2);
+        assert_eq!(app.search_info(), Some((1, 3)));
+
+        // n / N step through the matches, skipping the buffer without any and wrapping around
+        app.next_match(2);
+        assert_eq!(app.search_info(), Some((3, 3)));
+        app.next_match(1);
+        assert_eq!((app.buffer_index, app.search_info()), (0, Some((1, 1))));
+        app.prev_match(1);
+        assert_eq!((app.buffer_index, app.search_info()), (2, Some((3, 3))));
+
+        app.search_all_buffers("missing");
+        assert!(app.search_results.is_none());
+        assert_eq!(app.status_message.as_deref(), Some("Pattern not found in 3 files"));
+    }
+
+    #[test]
+    fn test_follow_appends_lines_from_stream() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "first").unwrap();
+        let doc = Document::from_text("first", "test.log".to_string(), "UTF-8".to_string());
+        let path = Some(file.path().to_path_buf());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, path, WrapMode::None, 200);
+        app.toggle_follow();
+        assert!(app.buffer.follow_mode);
+
+        writeln!(file, "second\nthird").unwrap();
+        let start = std::time::Instant::now();
+        while app.buffer.document.lines.len() < 3 && start.elapsed() < std::time::Duration::from_secs(2) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let numbers: Vec<usize> = app.buffer.document.lines.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(app.buffer.document.lines[2].text(), "third");
+
+        // A quiet file reports nothing, so the event loop can back off
+        assert!(!app.check_input_updates());
+    }
+
+    #[test]
+    fn test_follow_marks_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "first\n").unwrap();
+        let doc = Document::from_text("first", "app.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, Some(path.clone()), WrapMode::None, 200);
+        app.toggle_follow();
+
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        std::fs::write(&path, "fresh\n").unwrap();
+        let start = std::time::Instant::now();
+        while app.buffer.document.lines.len() < 3 && start.elapsed() < std::time::Duration::from_secs(3) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let lines: Vec<(usize, String)> = app.buffer.document.lines.iter().map(|l| (l.number, l.text())).collect();
+        let marker = format!("==> {} was rotated <==", path.display());
+        assert_eq!(lines, vec![(1, "first".to_string()), (0, marker), (2, "fresh".to_string())]);
+        assert!(app.status_message.as_deref().is_some_and(|m| m.ends_with("was rotated")));
+    }
+
+    #[test]
+    fn test_streamed_input_goes_through_grep() {
+        use crate::filter::{grep_filter, GrepOptions, GrepStream};
+        use crate::theme::GrepColors;
+
+        let options = || GrepOptions {
+            pattern: regex::Regex::new("ERROR").unwrap(),
+            before: 0,
+            after: 1,
+            invert: false,
+            colors: GrepColors::default(),
+        };
+        let all = Document::from_text("INFO start\nERROR disk full\ndetail", "app.log".to_string(), "UTF-8".to_string());
+        let doc = grep_filter(&all, &options());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.buffer.grep_stream = Some(GrepStream::new(options(), &all));
+        app.buffer.scope_document = Some(all);
+
+        let text = "INFO retry\nINFO again\nERROR disk still full\nafter\nINFO done\n";
+        app.stream_input(InputStream::spawn_reader(std::io::Cursor::new(text.as_bytes().to_vec())));
+        let start = std::time::Instant::now();
+        while app.buffer.input_stream.is_some() && start.elapsed() < std::time::Duration::from_secs(2) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        // The new match and its context, numbered among all lines, below a separator
+        let shown: Vec<(usize, bool)> = app.buffer.document.lines.iter().map(|l| (l.number, l.is_match)).collect();
+        assert_eq!(shown, vec![(2, true), (3, false), (0, false), (6, true), (7, false)]);
+        assert_eq!(app.buffer.scope_document.as_ref().map(|d| d.lines.len()), Some(8));
+
+        // Searching all lines shows them all, and keeps filtering the kept ones
+        app.toggle_search_scope();
+        assert_eq!(app.buffer.document.lines.len(), 8);
+        assert_eq!(app.buffer.scope_document.as_ref().map(|d| d.lines.len()), Some(5));
+    }
+
+    #[test]
+    fn test_panes_follow_streamed_input() {
+        let doc = Document::from_text("INFO start\nERROR disk full", "app.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 13);
+        app.push_grep_filter("INFO");
+        app.add_pane("ERROR");
+        app.add_pane("WARN");
+        // The panes take two of three shares of the rows above the status bar
+        assert_eq!(app.panes_height(), 8);
+        assert_eq!(app.content_height(), 4);
+        // A pane sees the lines the pager filters hide
+        assert_eq!(app.buffer.panes[0].line_count(), 1);
+        app.pop_filter();
+
+        let text = "INFO retry\nERROR disk still full\nWARN slow\n";
+        app.stream_input(InputStream::spawn_reader(std::io::Cursor::new(text.as_bytes().to_vec())));
+        let start = std::time::Instant::now();
+        while app.buffer.input_stream.is_some() && start.elapsed() < std::time::Duration::from_secs(2) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let numbers: Vec<usize> = app.buffer.panes[0].tail(5).iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![2, 4]);
+        assert_eq!(app.buffer.panes[1].line_count(), 1);
+
+        app.close_panes();
+        assert_eq!(app.content_height(), 12);
+    }
+
+    #[test]
+    fn test_streamed_input_keeps_ansi_colors() {
+        let doc = Document::from_text("", "stdin".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.buffer.ansi_style = Some(SpanStyle::default());
+        let text = "\x1b[32m+added\nstill added\x1b[m\n";
+        app.stream_input(InputStream::spawn_reader(std::io::Cursor::new(text.as_bytes().to_vec())));
+
+        let start = std::time::Instant::now();
+        while app.buffer.input_stream.is_some() && start.elapsed() < std::time::Duration::from_secs(2) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let lines = &app.buffer.document.lines;
+        assert_eq!(lines[0].text(), "+added");
+        assert_eq!(lines[1].spans[0].style.fg, Some(Color::Green));
+        assert_eq!(app.buffer.document.max_line_width, 11);
+    }
+
+    #[test]
+    fn test_arriving_lines_are_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("session.log");
+        let doc = Document::from_text("before", "app.log".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.buffer.session_log = Some(SessionLog::open(&log, false).unwrap());
+        app.stream_input(InputStream::spawn_reader(std::io::Cursor::new(b"started\nready\n".to_vec())));
+
+        let start = std::time::Instant::now();
+        while app.buffer.input_stream.is_some() && start.elapsed() < std::time::Duration::from_secs(2) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        // Only what arrived is logged, not what was already there
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "started\nready\n");
+    }
+
+    #[test]
+    fn test_streamed_input_appends_without_scrolling() {
+        let doc = Document::from_text("", "stdin".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, test_theme_colors(), false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 3);
+        let text: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        app.stream_input(InputStream::spawn_reader(std::io::Cursor::new(text.into_bytes())));
+
+        let start = std::time::Instant::now();
+        while app.buffer.input_stream.is_some() && start.elapsed() < std::time::Duration::from_secs(2) {
+            app.check_input_updates();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(app.buffer.input_stream.is_none());
+        assert_eq!(app.buffer.document.lines.len(), 10);
+        assert_eq!(app.buffer.document.lines[9].text(), "line 10");
+        // Unlike follow mode, the view stays where it was
+        assert_eq!(app.buffer.scroll_line, 0);
     }
 }
@@ -0,0 +1,181 @@
+//! Per-document view state
+//!
+//! Everything tied to one open document lives in a [`Buffer`]: its lines and
+//! view transforms, search, filters, scroll position and display settings.
+//! [`App`](super::App) keeps one active buffer plus the state shared by all of
+//! them (mode, terminal size, theme, key handling), so swapping buffers never
+//! carries settings from one document over to another.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::cli::{SearchScope, TimestampMode, WrapMode};
+use crate::display::{Document, Line, SpanStyle};
+use crate::filter::GrepStream;
+use crate::format::{Table, TableView};
+use crate::highlight::SearchState;
+use crate::input::InputStream;
+
+use super::app::{LineNumberMode, WrappedLine};
+use super::filters::FilterStack;
+use super::panes::Pane;
+use super::session_log::SessionLog;
+
+/// View state of one document
+pub struct Buffer {
+    /// The document being viewed
+    pub document: Document,
+    /// Current scroll line (0-indexed, top of viewport)
+    pub scroll_line: usize,
+    /// Current horizontal scroll offset (0-indexed)
+    pub scroll_col: usize,
+    /// Whether to show line numbers
+    pub show_line_numbers: bool,
+    /// Absolute or relative line numbers
+    pub line_number_mode: LineNumberMode,
+    /// Search state (if any)
+    pub search_state: Option<SearchState>,
+    /// Whether follow mode is active
+    pub follow_mode: bool,
+    /// Lines appended to the input (a followed file or streamed stdin), read in the background
+    pub input_stream: Option<InputStream>,
+    /// With `--ansi`, the style the appended lines' escape sequences left active
+    pub ansi_style: Option<SpanStyle>,
+    /// With `--log-output`, where the lines arriving while following are recorded
+    pub session_log: Option<SessionLog>,
+    /// With `--grep`, the filter the appended lines go through
+    pub grep_stream: Option<GrepStream>,
+    /// Path to the file being viewed (for follow mode)
+    pub file_path: Option<PathBuf>,
+    /// Modification time of the file when it was opened
+    pub opened_mtime: Option<SystemTime>,
+    /// Line wrapping mode
+    pub wrap_mode: WrapMode,
+    /// Cached wrapped lines (invalidated on resize or wrap mode change)
+    pub wrapped_lines: Option<Vec<WrappedLine>>,
+    /// File to delegate search match discovery to ripgrep for
+    pub ripgrep_source: Option<PathBuf>,
+    /// Document before view transforms (set while any transform is active)
+    pub base_document: Option<Document>,
+    /// With a grep filter, the document of the other search scope (swapped in by `A`)
+    pub scope_document: Option<Document>,
+    /// Whether searches see the grep-filtered lines or all lines
+    pub search_scope: SearchScope,
+    /// With pretty-printed input, the document of the other form (swapped in by `r`)
+    pub raw_document: Option<Document>,
+    /// Whether the input is shown as it came rather than pretty-printed
+    pub raw_view: bool,
+    /// Checked and total task list items of a markdown document, shown in the status bar
+    pub tasks: Option<(usize, usize)>,
+    /// Filters entered in the pager (`:grep`, `:hide`), popped with `U`
+    pub filters: FilterStack,
+    /// Live filter panes below the document (`:pane`), closed with `:only`
+    pub panes: Vec<Pane>,
+    /// Collapse runs of identical lines (view transform)
+    pub squeeze: bool,
+    /// How line-leading timestamps are displayed (view transform)
+    pub timestamp_mode: TimestampMode,
+    /// Underline misspelled words (view transform, `:spell`)
+    pub spellcheck: bool,
+    /// Line numbers of the closed fold headers (view transform)
+    pub folds: BTreeSet<usize>,
+    /// Line numbers of the bookmarked lines (`m`)
+    pub bookmarks: BTreeSet<usize>,
+    /// Lines pinned above the scrolling content (a table header)
+    pub sticky_lines: Vec<Line>,
+    /// Start and end columns of the table columns, whose starts are the stops of `w` / `b`
+    pub columns: Vec<(usize, usize)>,
+    /// Index of the visible table column that sorting and hiding act on
+    pub table_column: usize,
+    /// Records of a table document, which sorting and hiding columns re-render
+    pub table: Option<Table>,
+    /// Row order and hidden columns of the table
+    pub table_view: TableView,
+    /// Whether the document is prose (markdown or plain text), which reading mode applies to
+    pub prose: bool,
+    /// While reading mode is on, the wrap mode to restore when it is turned off
+    pub reading: Option<WrapMode>,
+}
+
+impl Buffer {
+    /// Create a buffer showing `document` from the top
+    pub fn new(
+        document: Document,
+        show_line_numbers: bool,
+        search_state: Option<SearchState>,
+        file_path: Option<PathBuf>,
+        wrap_mode: WrapMode,
+    ) -> Self {
+        Self {
+            document,
+            scroll_line: 0,
+            scroll_col: 0,
+            show_line_numbers,
+            line_number_mode: LineNumberMode::Absolute,
+            search_state,
+            follow_mode: false,
+            input_stream: None,
+            ansi_style: None,
+            session_log: None,
+            grep_stream: None,
+            opened_mtime: file_path.as_deref().and_then(modified_time),
+            file_path,
+            wrap_mode,
+            wrapped_lines: None,
+            ripgrep_source: None,
+            base_document: None,
+            scope_document: None,
+            search_scope: SearchScope::Filtered,
+            raw_document: None,
+            raw_view: false,
+            tasks: None,
+            filters: FilterStack::default(),
+            panes: Vec::new(),
+            squeeze: false,
+            timestamp_mode: TimestampMode::Original,
+            spellcheck: false,
+            folds: BTreeSet::new(),
+            bookmarks: BTreeSet::new(),
+            sticky_lines: Vec::new(),
+            columns: Vec::new(),
+            table_column: 0,
+            table: None,
+            table_view: TableView::default(),
+            prose: false,
+            reading: None,
+        }
+    }
+
+    /// Whether the file changed on disk since the buffer was opened
+    pub fn modified_on_disk(&self) -> bool {
+        let current = self.file_path.as_deref().and_then(modified_time);
+        current.is_some() && current != self.opened_mtime
+    }
+
+    /// Path shown in `path:line` references: the file, or the source name for stdin
+    pub fn reference_path(&self) -> String {
+        match self.file_path {
+            Some(ref path) => path.display().to_string(),
+            None => self.document.source_name.clone(),
+        }
+    }
+
+    /// `path:line:text` of line `number`
+    pub fn line_reference(&self, number: usize) -> String {
+        // The base document has the text without highlights or rendered timestamps
+        let document = self.base_document.as_ref().unwrap_or(&self.document);
+        let text: String = document.lines.iter().filter(|l| l.number == number).map(|l| l.text()).collect();
+        format!("{}:{}:{}", self.reference_path(), number, text)
+    }
+
+    /// `path:line:text` of every bookmarked line, in line order
+    pub fn bookmark_references(&self) -> Vec<String> {
+        self.bookmarks.iter().map(|&number| self.line_reference(number)).collect()
+    }
+}
+
+/// Modification time of the file at `path`
+fn modified_time(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,222 @@
+//! List of the open buffers (`B`, `:ls`)
+//!
+//! Shows every open document with its line count and whether it is followed
+//! or has changed on disk since it was opened. Typing narrows the list to the
+//! names the typed letters appear in, in order (a fuzzy match), best matches
+//! first; Enter switches to the selected buffer.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// An open buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferEntry {
+    /// Position of the buffer among all open buffers
+    pub index: usize,
+    pub name: String,
+    pub lines: usize,
+    /// Whether follow mode is on
+    pub followed: bool,
+    /// Whether the file changed on disk since it was opened
+    pub modified: bool,
+}
+
+/// The open buffers, narrowed by a query, with a selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferList {
+    pub entries: Vec<BufferEntry>,
+    /// Position of the active buffer
+    pub active: usize,
+    /// Letters typed to narrow the list
+    pub query: String,
+    /// Indices into `entries` of the buffers matching the query, best first
+    pub shown: Vec<usize>,
+    /// Index into `shown` of the selected buffer
+    pub selected: usize,
+}
+
+impl BufferList {
+    /// All of `entries`, with the active buffer selected
+    pub fn new(entries: Vec<BufferEntry>, active: usize) -> Self {
+        let mut list = Self {
+            entries,
+            active,
+            query: String::new(),
+            shown: Vec::new(),
+            selected: 0,
+        };
+        list.refilter();
+        list.selected = list.shown.iter().position(|&i| list.entries[i].index == active).unwrap_or(0);
+        list
+    }
+
+    /// Add a letter to the query
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refilter();
+    }
+
+    /// Remove the last letter of the query
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.refilter();
+    }
+
+    /// Move the selection down one buffer
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.shown.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Move the selection up one buffer
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// The selected buffer
+    pub fn selected(&self) -> Option<&BufferEntry> {
+        self.shown.get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    /// Keep the buffers whose names match the query, best first, selecting the best
+    fn refilter(&mut self) {
+        let mut scored: Vec<(usize, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| fuzzy_score(&self.query, &entry.name).map(|score| (score, i)))
+            .collect();
+        // Stable, so equally good matches stay in the order they were opened
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.shown = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+}
+
+/// How well `name` matches `query`, if all of its letters appear in order (case-insensitive)
+///
+/// Letters that follow each other or start a path component count extra, so
+/// `mr` prefers `src/main.rs` over `src/lib/more.rs`.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut rest = name.char_indices().map(|(i, c)| (i, c.to_lowercase().next().unwrap_or(c)));
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        let (i, _) = rest.by_ref().find(|&(_, c)| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| name[p..i].chars().count() == 1) {
+            score += 2;
+        }
+        if i == 0 || name[..i].ends_with(['/', '.', '_', '-']) {
+            score += 1;
+        }
+        previous = Some(i);
+    }
+    Some(score)
+}
+
+/// Render the buffer list as a centered overlay, with the query in its title
+pub fn render_buffer_list(frame: &mut Frame, list: &BufferList, highlight: Color, area: Rect) {
+    let rows: Vec<(String, String)> = list
+        .shown
+        .iter()
+        .map(|&i| {
+            let entry = &list.entries[i];
+            let marker = if entry.index == list.active { '%' } else { ' ' };
+            let mut state = format!("{} line{}", entry.lines, if entry.lines == 1 { "" } else { "s" });
+            if entry.followed {
+                state.push_str(", followed");
+            }
+            if entry.modified {
+                state.push_str(", modified");
+            }
+            (format!("{}{:>3} {}", marker, entry.index + 1, entry.name), state)
+        })
+        .collect();
+
+    let longest = rows.iter().map(|(name, state)| name.chars().count() + state.chars().count() + 2).max().unwrap_or(0);
+    let width = (longest as u16 + 2).max(32).min(area.width);
+    let height = (rows.len() as u16 + 2).max(3).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let title = if list.query.is_empty() { " Buffers ".to_string() } else { format!(" Buffers: {} ", list.query) };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let visible = inner.height as usize;
+    let first = (list.selected + 1).saturating_sub(visible);
+    let lines: Vec<RatatuiLine> = rows
+        .into_iter()
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(i, (name, state))| {
+            let style = if i == list.selected { Style::default().bg(highlight) } else { Style::default() };
+            let gap = (inner.width as usize).saturating_sub(name.chars().count() + state.chars().count()).max(2);
+            RatatuiLine::from(vec![
+                Span::styled(name, style),
+                Span::styled(" ".repeat(gap), style),
+                Span::styled(state, style.add_modifier(Modifier::DIM)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(index: usize, name: &str) -> BufferEntry {
+        BufferEntry {
+            index,
+            name: name.to_string(),
+            lines: 10,
+            followed: false,
+            modified: false,
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("mr", "src/main.rs").is_some());
+        assert!(fuzzy_score("MAIN", "src/main.rs").is_some());
+        assert!(fuzzy_score("rm", "main.c").is_none());
+        assert!(fuzzy_score("mr", "src/main.rs") > fuzzy_score("mr", "src/lib/more.rs"));
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_buffer_list_filtering() {
+        let entries = vec![entry(0, "app.log"), entry(1, "src/main.rs"), entry(2, "README.md")];
+        let mut list = BufferList::new(entries, 1);
+        assert_eq!(list.selected().map(|e| e.index), Some(1));
+
+        for c in "md".chars() {
+            list.push(c);
+        }
+        assert_eq!(list.selected().map(|e| e.name.as_str()), Some("README.md"));
+        list.select_next();
+        assert_eq!(list.shown.len(), 1);
+
+        list.pop();
+        list.pop();
+        list.push('l');
+        // Only app.log has an l
+        assert_eq!(list.shown.len(), 1);
+        assert_eq!(list.selected().map(|e| e.index), Some(0));
+    }
+}
@@ -0,0 +1,186 @@
+//! `:` commands typed at the pager prompt
+
+use crate::usage;
+
+use super::app::App;
+
+/// A parsed `:` command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `:N` - go to line N
+    Line(usize),
+    /// `:match N` - jump to the Nth search match (1-indexed)
+    Match(usize),
+    /// `:page N` - jump to page N of a converted PDF (1-indexed)
+    Page(usize),
+    /// `:noh` - clear the search highlights
+    NoHighlight,
+    /// `:grep PATTERN` - keep only matching lines, on top of earlier filters
+    Grep(String),
+    /// `:hide PATTERN` - hide matching lines, on top of earlier filters
+    Hide(String),
+    /// `:search PATTERN` - search all open files, listing the matching lines
+    SearchAll(String),
+    /// `:n` - switch to the next open file
+    NextFile,
+    /// `:p` - switch to the previous open file
+    PrevFile,
+    /// `:spell` - turn spell checking on or off
+    Spell,
+    /// `:freq [PATTERN]` - most frequent values captured by PATTERN (or the search / grep pattern)
+    Frequencies(Option<String>),
+    /// `:pane PATTERN` - show the lines matching PATTERN in a live pane below the document
+    Pane(String),
+    /// `:only` - close the filter panes
+    Only,
+    /// `:toc` - show the table of contents of a markdown document
+    Toc,
+    /// `:ls` - list the open buffers
+    Buffers,
+    /// `:help` - show the keybinding cheat sheet
+    Help,
+    /// `:mouse` - capture the mouse, or leave it to the terminal for text selection
+    Mouse,
+}
+
+impl Command {
+    /// Name of the command, without its argument
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Line(_) => ":N",
+            Command::Match(_) => ":match",
+            Command::Page(_) => ":page",
+            Command::NoHighlight => ":noh",
+            Command::Grep(_) => ":grep",
+            Command::Hide(_) => ":hide",
+            Command::SearchAll(_) => ":search",
+            Command::NextFile => ":n",
+            Command::PrevFile => ":p",
+            Command::Spell => ":spell",
+            Command::Frequencies(_) => ":freq",
+            Command::Pane(_) => ":pane",
+            Command::Only => ":only",
+            Command::Toc => ":toc",
+            Command::Buffers => ":ls",
+            Command::Help => ":help",
+            Command::Mouse => ":mouse",
+        }
+    }
+}
+
+/// Parse a command line (without the leading `:`)
+pub fn parse_command(input: &str) -> Result<Command, String> {
+    let mut words = input.split_whitespace();
+    let name = words.next().unwrap_or("");
+    let arg = words.next();
+
+    if let (Ok(number), None) = (name.parse::<usize>(), arg) {
+        return if number > 0 { Ok(Command::Line(number)) } else { Err("Usage: :N (a line number)".to_string()) };
+    }
+    match name {
+        "match" | "m" => match arg.and_then(|n| n.parse::<usize>().ok()) {
+            Some(n) if n > 0 && words.next().is_none() => Ok(Command::Match(n)),
+            _ => Err("Usage: :match N".to_string()),
+        },
+        "page" => match arg.and_then(|n| n.parse::<usize>().ok()) {
+            Some(n) if n > 0 && words.next().is_none() => Ok(Command::Page(n)),
+            _ => Err("Usage: :page N".to_string()),
+        },
+        "noh" | "nohlsearch" => Ok(Command::NoHighlight),
+        "grep" | "g" => rest_of_line(input, name).map(Command::Grep).ok_or("Usage: :grep PATTERN".to_string()),
+        "hide" => rest_of_line(input, name).map(Command::Hide).ok_or("Usage: :hide PATTERN".to_string()),
+        "search" | "s" => rest_of_line(input, name)
+            .map(Command::SearchAll)
+            .ok_or("Usage: :search PATTERN".to_string()),
+        "pane" => rest_of_line(input, name).map(Command::Pane).ok_or("Usage: :pane PATTERN".to_string()),
+        "only" | "on" => Ok(Command::Only),
+        "n" | "next" => Ok(Command::NextFile),
+        "p" | "prev" | "previous" => Ok(Command::PrevFile),
+        "spell" => Ok(Command::Spell),
+        "toc" => Ok(Command::Toc),
+        "ls" | "buffers" => Ok(Command::Buffers),
+        "help" | "h" => Ok(Command::Help),
+        "mouse" => Ok(Command::Mouse),
+        "freq" => Ok(Command::Frequencies(rest_of_line(input, name))),
+        _ => Err(format!("Unknown command: {}", name)),
+    }
+}
+
+/// The argument after the command `name`, spaces included (None if empty)
+fn rest_of_line(input: &str, name: &str) -> Option<String> {
+    let rest = input.trim_start()[name.len()..].trim();
+    (!rest.is_empty()).then(|| rest.to_string())
+}
+
+/// Run a command line, reporting errors in the status bar
+pub fn execute_command(input: &str, app: &mut App) {
+    if input.trim().is_empty() {
+        return;
+    }
+    let command = parse_command(input);
+    if let Ok(command) = &command {
+        usage::record(command.name());
+    }
+    match command {
+        Ok(Command::Line(n)) => app.go_to_line(n),
+        Ok(Command::Match(n)) => app.go_to_match(n),
+        Ok(Command::Page(n)) => app.go_to_page(n),
+        Ok(Command::NoHighlight) => app.clear_search(),
+        Ok(Command::Grep(pattern)) => app.push_grep_filter(&pattern),
+        Ok(Command::Hide(pattern)) => app.push_hide_filter(&pattern),
+        Ok(Command::SearchAll(pattern)) => app.search_all_buffers(&pattern),
+        Ok(Command::Pane(pattern)) => app.add_pane(&pattern),
+        Ok(Command::Only) => app.close_panes(),
+        Ok(Command::NextFile) => app.next_file(),
+        Ok(Command::PrevFile) => app.prev_file(),
+        Ok(Command::Spell) => app.toggle_spellcheck(),
+        Ok(Command::Toc) => app.show_toc(),
+        Ok(Command::Buffers) => app.show_buffer_list(),
+        Ok(Command::Help) => app.show_help(),
+        Ok(Command::Mouse) => app.toggle_mouse(),
+        Ok(Command::Frequencies(pattern)) => app.show_frequencies(pattern.as_deref()),
+        Err(message) => app.status_message = Some(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_match_command() {
+        assert_eq!(parse_command("match 37"), Ok(Command::Match(37)));
+        assert_eq!(parse_command("  m 2 "), Ok(Command::Match(2)));
+        assert_eq!(parse_command("page 3"), Ok(Command::Page(3)));
+        assert_eq!(parse_command("120"), Ok(Command::Line(120)));
+        assert_eq!(parse_command("noh"), Ok(Command::NoHighlight));
+        assert_eq!(parse_command("grep  connection reset "), Ok(Command::Grep("connection reset".to_string())));
+        assert_eq!(parse_command("hide GET /health"), Ok(Command::Hide("GET /health".to_string())));
+        assert_eq!(parse_command("search timed out"), Ok(Command::SearchAll("timed out".to_string())));
+        assert_eq!(parse_command("pane ERROR|WARN"), Ok(Command::Pane("ERROR|WARN".to_string())));
+        assert_eq!(parse_command("only"), Ok(Command::Only));
+        assert_eq!(parse_command("n"), Ok(Command::NextFile));
+        assert_eq!(parse_command("prev"), Ok(Command::PrevFile));
+        assert_eq!(parse_command("spell"), Ok(Command::Spell));
+        assert_eq!(parse_command("mouse"), Ok(Command::Mouse));
+        assert_eq!(parse_command("toc"), Ok(Command::Toc));
+        assert_eq!(parse_command("buffers"), Ok(Command::Buffers));
+        assert_eq!(parse_command("help"), Ok(Command::Help));
+        assert_eq!(parse_command("freq"), Ok(Command::Frequencies(None)));
+        assert_eq!(parse_command("freq status=(\\d+)"), Ok(Command::Frequencies(Some("status=(\\d+)".to_string()))));
+    }
+
+    #[test]
+    fn test_parse_invalid_commands() {
+        assert!(parse_command("match").is_err());
+        assert!(parse_command("match 0").is_err());
+        assert!(parse_command("match x").is_err());
+        assert!(parse_command("page 0").is_err());
+        assert!(parse_command("0").is_err());
+        assert!(parse_command("99999999999999999999999").is_err());
+        assert!(parse_command("grep ").is_err());
+        assert!(parse_command("search").is_err());
+        assert!(parse_command("pane").is_err());
+        assert_eq!(parse_command("quit"), Err("Unknown command: quit".to_string()));
+    }
+}
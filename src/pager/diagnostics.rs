@@ -0,0 +1,204 @@
+//! Character diagnostics overlay
+//!
+//! Breaks the top line down into grapheme clusters with their columns, UTF-8
+//! bytes, code points and display widths, so misaligned text (wide CJK,
+//! combining marks, zero-width joiners, stray control characters) can be
+//! understood and reported. Runs of printable ASCII are summarized as one row.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// One row of the breakdown: a grapheme cluster, or a run of printable ASCII
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Display column the segment starts at
+    pub column: usize,
+    /// The text of the segment
+    pub text: String,
+    /// Display width of the segment
+    pub width: usize,
+    /// Whether the segment is a run of printable ASCII
+    pub ascii: bool,
+}
+
+impl Segment {
+    /// UTF-8 bytes in hex, e.g. `e4 b8 96`
+    pub fn hex_bytes(&self) -> String {
+        self.text.bytes().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Code points, e.g. `U+0065 U+0301`
+    pub fn code_points(&self) -> String {
+        self.text.chars().map(|c| format!("U+{:04X}", c as u32)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Whether the segment is likely to upset alignment: wide, zero-width or several code points
+    pub fn is_unusual(&self) -> bool {
+        !self.ascii && (self.width != 1 || self.text.chars().count() > 1)
+    }
+}
+
+/// Composition of one line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiagnostics {
+    /// Line number the breakdown is for
+    pub line_number: usize,
+    pub bytes: usize,
+    pub chars: usize,
+    pub graphemes: usize,
+    /// Display width in columns
+    pub width: usize,
+    pub segments: Vec<Segment>,
+}
+
+impl LineDiagnostics {
+    /// Break `text` (line `line_number`) down into segments
+    pub fn inspect(line_number: usize, text: &str) -> Self {
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut column = 0;
+        let mut graphemes = 0;
+        for grapheme in text.graphemes(true) {
+            graphemes += 1;
+            let width = UnicodeWidthStr::width(grapheme);
+            let ascii = grapheme.len() == 1 && grapheme.bytes().all(|b| b.is_ascii_graphic() || b == b' ');
+            match segments.last_mut() {
+                Some(last) if ascii && last.ascii => {
+                    last.text.push_str(grapheme);
+                    last.width += width;
+                }
+                _ => segments.push(Segment {
+                    column,
+                    text: grapheme.to_string(),
+                    width,
+                    ascii,
+                }),
+            }
+            column += width;
+        }
+
+        Self {
+            line_number,
+            bytes: text.len(),
+            chars: text.chars().count(),
+            graphemes,
+            width: column,
+            segments,
+        }
+    }
+
+    /// Summary line: sizes of the line in bytes, chars, graphemes and columns
+    pub fn summary(&self) -> String {
+        format!(
+            "Line {}: {} bytes, {} chars, {} graphemes, {} columns",
+            self.line_number, self.bytes, self.chars, self.graphemes, self.width
+        )
+    }
+}
+
+/// Render the breakdown as a centered overlay
+pub fn render_diagnostics(frame: &mut Frame, diagnostics: &LineDiagnostics, area: Rect) {
+    let width = 76.min(area.width);
+    let height = (diagnostics.segments.len() as u16 + 4).max(5).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let block = Block::default().borders(Borders::ALL).title(" Characters ");
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let mut lines = vec![
+        RatatuiLine::from(Span::raw(diagnostics.summary())),
+        RatatuiLine::from(Span::styled(
+            format!("{:>5}  {:>5}  {:<24}  {}", "col", "width", "bytes", "code points"),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let rows = (inner.height as usize).saturating_sub(lines.len());
+    let shown = if diagnostics.segments.len() > rows {
+        rows.saturating_sub(1)
+    } else {
+        rows
+    };
+    for segment in diagnostics.segments.iter().take(shown) {
+        let line = if segment.ascii {
+            RatatuiLine::from(Span::raw(format!(
+                "{:>5}  {:>5}  {:<24}  \"{}\"",
+                segment.column,
+                segment.width,
+                format!("{} ASCII", segment.text.len()),
+                segment.text
+            )))
+        } else {
+            let style = if segment.is_unusual() {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            RatatuiLine::from(Span::styled(
+                format!(
+                    "{:>5}  {:>5}  {:<24}  {}",
+                    segment.column,
+                    segment.width,
+                    segment.hex_bytes(),
+                    segment.code_points()
+                ),
+                style,
+            ))
+        };
+        lines.push(line);
+    }
+    if shown < diagnostics.segments.len() {
+        let more = diagnostics.segments.len() - shown;
+        lines.push(RatatuiLine::from(Span::styled(
+            format!("{} more segments", more),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect_mixed_line() {
+        // "e" with a combining acute accent is one grapheme of two code points
+        let diagnostics = LineDiagnostics::inspect(3, "ab 世e\u{301}\u{200b}!");
+        assert_eq!(diagnostics.summary(), "Line 3: 13 bytes, 8 chars, 7 graphemes, 7 columns");
+
+        let columns: Vec<(usize, &str, usize)> = diagnostics
+            .segments
+            .iter()
+            .map(|s| (s.column, s.text.as_str(), s.width))
+            .collect();
+        assert_eq!(columns, vec![(0, "ab ", 3), (3, "世", 2), (5, "e\u{301}", 1), (6, "\u{200b}", 0), (6, "!", 1)]);
+
+        let wide = &diagnostics.segments[1];
+        assert_eq!(wide.hex_bytes(), "e4 b8 96");
+        assert_eq!(wide.code_points(), "U+4E16");
+        assert!(wide.is_unusual());
+        assert_eq!(diagnostics.segments[2].code_points(), "U+0065 U+0301");
+        assert!(diagnostics.segments[3].is_unusual());
+        assert!(!diagnostics.segments[0].is_unusual());
+    }
+
+    #[test]
+    fn test_inspect_control_characters() {
+        let diagnostics = LineDiagnostics::inspect(1, "a\tb");
+        assert_eq!(diagnostics.segments.len(), 3);
+        assert_eq!(diagnostics.segments[1].hex_bytes(), "09");
+    }
+}
@@ -0,0 +1,77 @@
+//! Opening stack trace references and paths in an editor
+//!
+//! The editor comes from `$VISUAL` or `$EDITOR` (`vi` by default). Most
+//! editors take the line as `+LINE FILE`; those known to expect `FILE:LINE:COL`
+//! get that instead.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::highlight::FileReference;
+
+/// Editors opening `FILE:LINE:COL` (VS Code and VSCodium need `-g` for it)
+const COLON_EDITORS: &[&str] = &["code", "codium", "subl", "hx", "helix", "zed", "micro"];
+
+/// Program and arguments opening `path` at the reference's line
+fn editor_command(editor: Option<&str>, path: &Path, reference: &FileReference) -> (String, Vec<String>) {
+    let mut words: Vec<String> = editor.unwrap_or("").split_whitespace().map(str::to_string).collect();
+    if words.is_empty() {
+        words = vec!["vi".to_string()];
+    }
+    let program = words.remove(0);
+    let name = Path::new(&program).file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    if COLON_EDITORS.contains(&name) {
+        if matches!(name, "code" | "codium") && !words.iter().any(|w| w == "-g" || w == "--goto") {
+            words.push("-g".to_string());
+        }
+        let column = reference.column.map(|c| format!(":{}", c)).unwrap_or_default();
+        words.push(format!("{}:{}{}", path.display(), reference.line, column));
+    } else {
+        words.push(format!("+{}", reference.line));
+        words.push(path.display().to_string());
+    }
+    (program, words)
+}
+
+/// The file a reference points at: as written (`~/` being the home directory), or next to the viewed file
+pub fn resolve_reference(reference: &FileReference, viewed_file: Option<&Path>) -> Option<PathBuf> {
+    if let (Some(rest), Some(home)) = (reference.path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        let path = Path::new(&home).join(rest);
+        return path.exists().then_some(path);
+    }
+    let path = Path::new(&reference.path);
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+    let beside = viewed_file?.parent()?.join(path);
+    beside.exists().then_some(beside)
+}
+
+/// Run the editor on `path` and wait for it to exit
+pub fn open_in_editor(path: &Path, reference: &FileReference) -> io::Result<()> {
+    let editor = std::env::var("VISUAL").ok().filter(|e| !e.trim().is_empty()).or_else(|| std::env::var("EDITOR").ok());
+    let (program, args) = editor_command(editor.as_deref(), path, reference);
+    Command::new(program).args(&args).status().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_command() {
+        let reference = FileReference {
+            path: "src/main.rs".to_string(),
+            line: 42,
+            column: Some(7),
+        };
+        let path = Path::new("src/main.rs");
+        let words = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(editor_command(None, path, &reference), ("vi".to_string(), words(&["+42", "src/main.rs"])));
+        assert_eq!(editor_command(Some("nvim -p"), path, &reference).1, words(&["-p", "+42", "src/main.rs"]));
+        assert_eq!(editor_command(Some("code --wait"), path, &reference).1, words(&["--wait", "-g", "src/main.rs:42:7"]));
+        assert_eq!(editor_command(Some("/usr/bin/hx"), path, &reference).1, words(&["src/main.rs:42:7"]));
+    }
+}
@@ -0,0 +1,118 @@
+//! Paging through an external pager
+//!
+//! When the TUI can't run (`TERM=dumb`, a terminal without raw mode), or with
+//! `--pager-fallback always`, the printed output is piped into `$PAGER` (`less`
+//! by default) instead. If that pager can't be started either (or `--no-exec`
+//! forbids it), the output is printed directly.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+use crate::cli::{Args, PagerFallback};
+use crate::display::{Document, OutputStyle};
+use crate::error::MatError;
+use crate::sandbox::exec_allowed;
+
+use super::{output_width, print_document, write_document};
+
+/// Whether to page through the external pager rather than the TUI
+pub fn use_external_pager(args: &Args) -> bool {
+    if args.render_screen.is_some() {
+        return false;
+    }
+    match args.pager_fallback {
+        PagerFallback::Always => true,
+        PagerFallback::Never => false,
+        PagerFallback::Auto => is_dumb_terminal(std::env::var("TERM").ok().as_deref()) || !supports_raw_mode(),
+    }
+}
+
+/// Whether `$TERM` names a terminal without cursor control
+///
+/// Windows consoles don't set `TERM`, so only an explicit `dumb` counts there.
+fn is_dumb_terminal(term: Option<&str>) -> bool {
+    term.map_or(!cfg!(windows), |term| term.is_empty() || term == "dumb")
+}
+
+/// Whether the terminal can be switched to raw mode (tried and undone at once)
+fn supports_raw_mode() -> bool {
+    enable_raw_mode().and_then(|_| disable_raw_mode()).is_ok()
+}
+
+/// Program and arguments of the pager to use, from `$PAGER`
+///
+/// Falls back to `less` when `$PAGER` is unset, empty or mat itself (which
+/// would loop). `less` gets `-R` so colors come through.
+fn pager_command(pager: Option<&str>) -> (String, Vec<String>) {
+    let mut words: Vec<String> = pager.unwrap_or("").split_whitespace().map(str::to_string).collect();
+    let is_mat = words
+        .first()
+        .is_some_and(|program| Path::new(program).file_stem().is_some_and(|stem| stem == "mat"));
+    if words.is_empty() || is_mat {
+        words = vec!["less".to_string()];
+    }
+
+    let program = words.remove(0);
+    let is_less = Path::new(&program).file_stem().is_some_and(|stem| stem == "less");
+    let raw_flags = ["-R", "-r", "--RAW-CONTROL-CHARS", "--raw-control-chars"];
+    if is_less && !words.iter().any(|w| raw_flags.contains(&w.as_str())) {
+        words.push("-R".to_string());
+    }
+    (program, words)
+}
+
+/// Pipe the printed document into the external pager and wait for it to exit
+pub fn page_externally(document: &Document, style: &OutputStyle) -> Result<(), MatError> {
+    let stdout_error = |source: io::Error| MatError::Io {
+        source,
+        path: "stdout".into(),
+    };
+    // A dumb terminal shouldn't get escape codes
+    let mut style = *style;
+    style.color &= !is_dumb_terminal(std::env::var("TERM").ok().as_deref());
+
+    let (program, args) = pager_command(std::env::var("PAGER").ok().as_deref());
+    let child = exec_allowed().then(|| Command::new(&program).args(&args).stdin(Stdio::piped()).spawn());
+    let Some(Ok(mut child)) = child else {
+        return print_document(document, &style).map_err(stdout_error);
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let written = write_document(&mut stdin, document, &style, output_width()).and_then(|_| stdin.flush());
+        // Quitting the pager early closes the pipe; that's not an error
+        match written {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(stdout_error(e)),
+            _ => {}
+        }
+    }
+    child.wait().map_err(stdout_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), ("less".to_string(), vec!["-R".to_string()]));
+        assert_eq!(pager_command(Some("more")), ("more".to_string(), vec![]));
+        assert_eq!(
+            pager_command(Some("/usr/bin/less -S")),
+            ("/usr/bin/less".to_string(), vec!["-S".to_string(), "-R".to_string()])
+        );
+        assert_eq!(pager_command(Some("less -r")).1, vec!["-r".to_string()]);
+        // mat as $PAGER would start the fallback again
+        assert_eq!(pager_command(Some("mat -P")).0, "less");
+    }
+
+    #[test]
+    fn test_dumb_terminal() {
+        assert!(is_dumb_terminal(Some("dumb")));
+        assert_eq!(is_dumb_terminal(None), !cfg!(windows));
+        assert!(!is_dumb_terminal(Some("xterm-256color")));
+    }
+}
@@ -0,0 +1,180 @@
+//! Grep and hide filters applied from inside the pager
+//!
+//! Filters stack: each one narrows the document left by the previous one and
+//! keeps that document so popping it restores the view exactly.
+
+use regex::Regex;
+
+use crate::display::Document;
+use crate::filter::{apply_grep_highlight, grep_filter, hide_matching, GrepOptions};
+use crate::theme::GrepColors;
+
+/// A filter entered in the pager
+#[derive(Debug, Clone)]
+pub enum PagerFilter {
+    /// Keep only lines matching `pattern`, compiled from the typed `query`
+    Grep { query: String, pattern: Regex },
+    /// Remove lines matching `pattern`
+    Hide { query: String, pattern: Regex },
+}
+
+impl PagerFilter {
+    /// Breadcrumb label of the filter
+    pub fn label(&self) -> String {
+        match self {
+            PagerFilter::Grep { query, .. } => query.clone(),
+            PagerFilter::Hide { query, .. } => format!("!{}", query),
+        }
+    }
+
+    /// Apply the filter to `document`
+    pub(super) fn apply(&self, document: &Document, colors: &GrepColors) -> Document {
+        match self {
+            PagerFilter::Grep { pattern, .. } => {
+                let options = GrepOptions {
+                    pattern: pattern.clone(),
+                    before: 0,
+                    after: 0,
+                    invert: false,
+                    colors: colors.clone(),
+                };
+                let mut filtered = grep_filter(document, &options);
+                apply_grep_highlight(&mut filtered, &options);
+                filtered
+            }
+            PagerFilter::Hide { pattern, .. } => hide_matching(document, pattern),
+        }
+    }
+}
+
+/// Stack of pager filters, each keeping the document it was applied to
+#[derive(Debug, Default)]
+pub struct FilterStack {
+    /// Styles for matches of grep filters
+    pub colors: GrepColors,
+    layers: Vec<(PagerFilter, Document)>,
+}
+
+impl FilterStack {
+    /// Whether no filter is applied
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Apply `filter` on top of `document`, returning the filtered document
+    ///
+    /// Returns `None` (and keeps the stack as is) when no line would remain.
+    pub fn push(&mut self, filter: PagerFilter, document: &Document) -> Option<Document> {
+        let filtered = filter.apply(document, &self.colors);
+        if filtered.lines.is_empty() {
+            return None;
+        }
+        self.layers.push((filter, document.clone()));
+        Some(filtered)
+    }
+
+    /// Remove the last filter, returning the document it was applied to
+    pub fn pop(&mut self) -> Option<Document> {
+        self.layers.pop().map(|(_, document)| document)
+    }
+
+    /// Apply every filter again, from the first, to a new version of the document
+    pub fn reapply(&mut self, document: Document) -> Document {
+        let mut current = document;
+        for (filter, layer) in &mut self.layers {
+            let filtered = filter.apply(&current, &self.colors);
+            *layer = std::mem::replace(&mut current, filtered);
+        }
+        current
+    }
+
+    /// The document the first filter was applied to, i.e. all lines
+    pub fn source(&self) -> Option<&Document> {
+        self.layers.first().map(|(_, document)| document)
+    }
+
+    /// Pattern of the most recent grep filter
+    pub fn last_grep_pattern(&self) -> Option<&Regex> {
+        self.layers.iter().rev().find_map(|(filter, _)| match filter {
+            PagerFilter::Grep { pattern, .. } => Some(pattern),
+            PagerFilter::Hide { .. } => None,
+        })
+    }
+
+    /// Filters from first to last, e.g. `error > !health` (`!` marks hide filters)
+    pub fn breadcrumb(&self) -> Option<String> {
+        if self.layers.is_empty() {
+            return None;
+        }
+        let labels: Vec<String> = self.layers.iter().map(|(filter, _)| filter.label()).collect();
+        Some(labels.join(" > "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grep(query: &str) -> PagerFilter {
+        PagerFilter::Grep {
+            query: query.to_string(),
+            pattern: Regex::new(query).unwrap(),
+        }
+    }
+
+    fn doc(text: &str) -> Document {
+        Document::from_text(text, "test.log".to_string(), "UTF-8".to_string())
+    }
+
+    #[test]
+    fn test_push_and_pop_filters() {
+        let mut stack = FilterStack::default();
+        let original = doc("error: disk\ninfo: ok\nerror: timeout\nwarn: slow");
+
+        // Like -g, a separator (line number 0) marks the gap
+        let errors = stack.push(grep("error"), &original).unwrap();
+        assert_eq!(errors.lines.iter().map(|l| l.number).collect::<Vec<_>>(), vec![1, 0, 3]);
+
+        let timeouts = stack.push(grep("timeout"), &errors).unwrap();
+        assert_eq!(timeouts.lines.len(), 1);
+        assert_eq!(stack.breadcrumb().as_deref(), Some("error > timeout"));
+
+        assert_eq!(stack.pop().unwrap().lines.len(), 3);
+        assert_eq!(stack.pop().unwrap().lines.len(), 4);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_reapply_filters() {
+        let mut stack = FilterStack::default();
+        stack.push(grep("error"), &doc("error: disk\ninfo: ok")).unwrap();
+
+        let filtered = stack.reapply(doc("info: ok\nerror: disk\nerror: timeout"));
+        assert_eq!(filtered.lines.iter().map(|l| l.number).collect::<Vec<_>>(), vec![2, 3]);
+        // Popping restores the new version
+        assert_eq!(stack.pop().unwrap().lines.len(), 3);
+        assert_eq!(stack.breadcrumb(), None);
+    }
+
+    #[test]
+    fn test_hide_filter_composes_with_grep() {
+        let mut stack = FilterStack::default();
+        let original = doc("GET /health\nGET /api error\nGET /health error\nGET /api");
+
+        let errors = stack.push(grep("error"), &original).unwrap();
+        let hide = PagerFilter::Hide {
+            query: "health".to_string(),
+            pattern: Regex::new("health").unwrap(),
+        };
+        let kept = stack.push(hide, &errors).unwrap();
+        assert_eq!(kept.lines.iter().map(|l| l.number).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(stack.breadcrumb().as_deref(), Some("error > !health"));
+    }
+
+    #[test]
+    fn test_filter_without_matches_is_not_pushed() {
+        let mut stack = FilterStack::default();
+        assert!(stack.push(grep("missing"), &doc("a\nb")).is_none());
+        assert!(stack.is_empty());
+    }
+}
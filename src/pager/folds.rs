@@ -0,0 +1,207 @@
+//! Indentation folds
+//!
+//! A line folds the lines after it that are indented deeper: the children of
+//! an element in pretty-printed markup, the body of a block in code. Folds are
+//! kept as the line numbers of their header lines and applied as a view
+//! transform, so they survive searches and the other transforms.
+
+use std::collections::BTreeSet;
+
+use ratatui::style::Color;
+
+use crate::display::glyphs::glyphs;
+use crate::display::{Document, Line, SpanStyle, StyledSpan};
+
+/// Lines looked back over for the blocks a line is in, as this is done on every redraw
+const MAX_ENCLOSING_DISTANCE: usize = 20_000;
+
+/// Indentation of a line in columns, `None` for blank lines
+fn indent(line: &Line) -> Option<usize> {
+    let text = line.text();
+    let trimmed = text.trim_start();
+    (!trimmed.is_empty()).then(|| text.len() - trimmed.len())
+}
+
+/// Index just past the continuation chunks of the line at `idx`
+fn group_end(lines: &[Line], idx: usize) -> usize {
+    let mut end = idx + 1;
+    while end < lines.len() && lines[end].continuation {
+        end += 1;
+    }
+    end
+}
+
+/// Index of the first chunk of the source line containing `idx`
+fn group_start(lines: &[Line], mut idx: usize) -> usize {
+    while idx > 0 && lines[idx].continuation {
+        idx -= 1;
+    }
+    idx
+}
+
+/// End (exclusive) of the lines folded by the header at `idx`, `None` without children
+///
+/// Blank lines inside the fold are folded too, but not the ones after it.
+fn fold_end(lines: &[Line], idx: usize) -> Option<usize> {
+    let header = indent(&lines[idx])?;
+    if lines[idx].number == 0 {
+        return None;
+    }
+    let mut end = None;
+    let mut next = group_end(lines, idx);
+    while next < lines.len() && lines[next].number != 0 {
+        match indent(&lines[next]) {
+            Some(depth) if depth <= header => break,
+            Some(_) => end = Some(group_end(lines, next)),
+            None => {}
+        }
+        next = group_end(lines, next);
+    }
+    end
+}
+
+/// Header line of the innermost fold containing the line at `idx`
+///
+/// A line with deeper-indented lines after it is its own header.
+pub fn fold_header(lines: &[Line], idx: usize) -> Option<usize> {
+    if idx >= lines.len() {
+        return None;
+    }
+    let idx = group_start(lines, idx);
+    if fold_end(lines, idx).is_some() {
+        return Some(idx);
+    }
+    (0..idx)
+        .rev()
+        .filter(|&i| !lines[i].continuation)
+        .find(|&i| fold_end(lines, i).is_some_and(|end| end > idx))
+}
+
+/// Indices of the fold headers above `idx` whose folds contain it, outermost first
+///
+/// These are the lines a block starts on (`impl App {`, `fn scroll(...)`), so they
+/// tell where a line deep in a block is. A blank line belongs to the line after it.
+pub fn enclosing_headers(lines: &[Line], idx: usize) -> Vec<usize> {
+    let mut headers = Vec::new();
+    if idx >= lines.len() {
+        return headers;
+    }
+    let idx = group_start(lines, idx);
+    let Some(mut depth) = lines[idx..].iter().filter(|l| !l.continuation).find_map(indent) else {
+        return headers;
+    };
+    for i in (idx.saturating_sub(MAX_ENCLOSING_DISTANCE)..idx).rev() {
+        let line = &lines[i];
+        // A file separator ends the blocks of the file above it
+        if depth == 0 || line.number == 0 {
+            break;
+        }
+        if line.continuation {
+            continue;
+        }
+        if let Some(header) = indent(line).filter(|&header| header < depth) {
+            headers.push(i);
+            depth = header;
+        }
+    }
+    headers.reverse();
+    headers
+}
+
+/// Line numbers of every line that folds something
+pub fn foldable_lines(lines: &[Line]) -> BTreeSet<usize> {
+    (0..lines.len())
+        .filter(|&i| !lines[i].continuation && fold_end(lines, i).is_some())
+        .map(|i| lines[i].number)
+        .collect()
+}
+
+/// Hide the lines folded by the headers numbered in `folds`
+///
+/// Each folded header gets a dimmed `… N lines` suffix.
+pub fn apply_folds(document: &Document, folds: &BTreeSet<usize>) -> Document {
+    let lines = &document.lines;
+    let mut result = Vec::with_capacity(lines.len());
+
+    let mut i = 0;
+    while i < lines.len() {
+        let header_end = group_end(lines, i);
+        result.extend_from_slice(&lines[i..header_end]);
+        let folded = folds
+            .contains(&lines[i].number)
+            .then(|| fold_end(lines, i))
+            .flatten();
+        match folded {
+            Some(end) => {
+                let hidden = lines[header_end..end].iter().filter(|l| !l.continuation).count();
+                if let Some(last) = result.last_mut() {
+                    last.spans.push(fold_span(hidden));
+                }
+                i = end;
+            }
+            None => i = header_end,
+        }
+    }
+
+    let mut folded = Document {
+        lines: result,
+        max_line_width: 0,
+        source_name: document.source_name.clone(),
+        encoding: document.encoding.clone(),
+    };
+    folded.recalculate_max_width();
+    folded
+}
+
+/// Dimmed suffix telling how many lines a fold hides
+fn fold_span(hidden: usize) -> StyledSpan {
+    let label = if hidden == 1 { "line" } else { "lines" };
+    StyledSpan::new(format!(" {} {} {}", glyphs().ellipsis, hidden, label), SpanStyle::new().fg(Color::DarkGray))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(text: &str) -> Document {
+        Document::from_text(text, "test.xml".to_string(), "UTF-8".to_string())
+    }
+
+    const XML: &str = "<a>\n  <b>\n    <c/>\n\n    <d/>\n  </b>\n\n  <e/>\n</a>";
+
+    #[test]
+    fn test_enclosing_headers() {
+        let doc = doc(XML);
+        assert_eq!(enclosing_headers(&doc.lines, 2), vec![0, 1]);
+        // A blank line is in the block of the line after it
+        assert_eq!(enclosing_headers(&doc.lines, 3), vec![0, 1]);
+        assert_eq!(enclosing_headers(&doc.lines, 6), vec![0]);
+        assert_eq!(enclosing_headers(&doc.lines, 1), vec![0]);
+        assert!(enclosing_headers(&doc.lines, 0).is_empty());
+        assert!(enclosing_headers(&doc.lines, 8).is_empty());
+        assert!(enclosing_headers(&doc.lines, 99).is_empty());
+    }
+
+    #[test]
+    fn test_fold_header() {
+        let doc = doc(XML);
+        assert_eq!(fold_header(&doc.lines, 0), Some(0));
+        assert_eq!(fold_header(&doc.lines, 2), Some(1));
+        assert_eq!(fold_header(&doc.lines, 7), Some(0));
+        assert_eq!(fold_header(&doc.lines, 8), None);
+        assert_eq!(foldable_lines(&doc.lines), BTreeSet::from([1, 2]));
+    }
+
+    #[test]
+    fn test_apply_folds() {
+        let doc = doc(XML);
+        let folded = apply_folds(&doc, &BTreeSet::from([2]));
+        let texts: Vec<String> = folded.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, vec!["<a>", "  <b> … 3 lines", "  </b>", "", "  <e/>", "</a>"]);
+
+        // The outer fold hides the inner one along with everything else
+        let folded = apply_folds(&doc, &BTreeSet::from([1, 2]));
+        assert_eq!(folded.lines.len(), 2);
+        assert_eq!(folded.lines[0].text(), "<a> … 7 lines");
+    }
+}
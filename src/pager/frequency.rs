@@ -0,0 +1,111 @@
+//! Frequency table overlay
+//!
+//! Counts the values a pattern captures across the document and lists the
+//! most frequent ones, like `grep -o | sort | uniq -c | sort -rn` on a log.
+
+use std::collections::HashMap;
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use regex::Regex;
+
+use crate::display::Document;
+
+/// Counts of the values captured by a pattern, most frequent first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frequencies {
+    /// The pattern the values were captured with
+    pub pattern: String,
+    /// Each distinct value and its count, by count then value
+    pub values: Vec<(String, usize)>,
+    /// Number of matches counted
+    pub total: usize,
+}
+
+impl Frequencies {
+    /// Count the first capture group of every match of `pattern` in `document`
+    ///
+    /// Without a capture group (or when none took part in a match) the whole
+    /// match is counted.
+    pub fn compute(document: &Document, pattern: &Regex) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut total = 0;
+        for line in document.lines.iter().filter(|l| l.number != 0) {
+            let text = line.text();
+            for captures in pattern.captures_iter(&text) {
+                let value = captures.iter().skip(1).flatten().next().or_else(|| captures.get(0));
+                if let Some(value) = value {
+                    *counts.entry(value.as_str().to_string()).or_default() += 1;
+                    total += 1;
+                }
+            }
+        }
+
+        let mut values: Vec<(String, usize)> = counts.into_iter().collect();
+        values.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        Self {
+            pattern: pattern.as_str().to_string(),
+            values,
+            total,
+        }
+    }
+}
+
+/// Render the most frequent values as a centered overlay, as many as fit
+pub fn render_frequencies(frame: &mut Frame, frequencies: &Frequencies, color: Color, area: Rect) {
+    let width = 60.min(area.width);
+    let height = (frequencies.values.len() as u16 + 4).max(5).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let title = format!(" Top values of {} ", frequencies.pattern);
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let mut lines = vec![RatatuiLine::from(Span::styled(
+        format!("{} matches, {} distinct values", frequencies.total, frequencies.values.len()),
+        Style::default().fg(Color::DarkGray),
+    ))];
+    let rows = (inner.height as usize).saturating_sub(lines.len());
+    let count_width = frequencies.values.first().map_or(1, |(_, count)| count.to_string().len());
+    for (value, count) in frequencies.values.iter().take(rows) {
+        lines.push(RatatuiLine::from(vec![
+            Span::styled(format!("{:>width$}  ", count, width = count_width), Style::default().fg(color)),
+            Span::raw(value.clone()),
+        ]));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_captured_values() {
+        let text = "GET /a 200\nGET /b 404\nPOST /a 200\nGET /c 500\nGET /a 200";
+        let doc = Document::from_text(text, "access.log".to_string(), "UTF-8".to_string());
+
+        let frequencies = Frequencies::compute(&doc, &Regex::new(r" (\d{3})$").unwrap());
+        assert_eq!(frequencies.total, 5);
+        assert_eq!(
+            frequencies.values,
+            vec![("200".to_string(), 3), ("404".to_string(), 1), ("500".to_string(), 1)]
+        );
+
+        // Without a group the whole match is counted
+        let frequencies = Frequencies::compute(&doc, &Regex::new("GET|POST").unwrap());
+        assert_eq!(frequencies.values[0], ("GET".to_string(), 4));
+    }
+}
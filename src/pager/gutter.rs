@@ -0,0 +1,211 @@
+//! Gutter annotation column
+//!
+//! Features mark lines (search matches, bookmarks, folds) by
+//! implementing [`AnnotationSource`] and registering in [`SOURCES`], instead
+//! of drawing into the line number gutter themselves. Every active source
+//! owns one cell of the annotation column, laid out in [`AnnotationKind`]
+//! order, so glyphs from different features never overwrite each other.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::display::glyphs::glyphs;
+
+use super::app::App;
+
+/// Kind of gutter annotation (also the left-to-right column order)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnnotationKind {
+    /// Line contains a search match
+    Match,
+    /// Line is bookmarked
+    Bookmark,
+    /// Line starts a fold
+    Fold,
+}
+
+/// A single glyph shown next to a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Annotation {
+    pub glyph: char,
+    pub color: Color,
+}
+
+/// A feature contributing glyphs to the annotation column
+pub trait AnnotationSource: Sync {
+    /// Kind of annotation this source produces
+    fn kind(&self) -> AnnotationKind;
+
+    /// Whether the source currently reserves a column
+    fn is_active(&self, app: &App) -> bool;
+
+    /// Annotation for the document line at `line_idx`, if any
+    fn annotate(&self, app: &App, line_idx: usize) -> Option<Annotation>;
+}
+
+/// Marks lines containing search matches
+struct SearchMatches;
+
+impl AnnotationSource for SearchMatches {
+    fn kind(&self) -> AnnotationKind {
+        AnnotationKind::Match
+    }
+
+    fn is_active(&self, app: &App) -> bool {
+        app.buffer.search_state.as_ref().is_some_and(|s| s.match_count() > 0)
+    }
+
+    fn annotate(&self, app: &App, line_idx: usize) -> Option<Annotation> {
+        let state = app.buffer.search_state.as_ref()?;
+        if !state.line_has_match(line_idx) {
+            return None;
+        }
+        let is_current = state
+            .current_match
+            .and_then(|i| state.matches.get(i))
+            .is_some_and(|m| m.line_idx == line_idx);
+        Some(Annotation {
+            glyph: if is_current { glyphs().current_match } else { glyphs().match_mark },
+            color: app.theme_colors.search_bg,
+        })
+    }
+}
+
+/// Marks the headers of closed folds
+struct ClosedFolds;
+
+impl AnnotationSource for ClosedFolds {
+    fn kind(&self) -> AnnotationKind {
+        AnnotationKind::Fold
+    }
+
+    fn is_active(&self, app: &App) -> bool {
+        !app.buffer.folds.is_empty()
+    }
+
+    fn annotate(&self, app: &App, line_idx: usize) -> Option<Annotation> {
+        let line = app.buffer.document.lines.get(line_idx)?;
+        (!line.continuation && app.buffer.folds.contains(&line.number)).then_some(Annotation {
+            glyph: glyphs().closed_fold,
+            color: app.theme_colors.line_number,
+        })
+    }
+}
+
+/// Marks bookmarked lines
+struct Bookmarks;
+
+impl AnnotationSource for Bookmarks {
+    fn kind(&self) -> AnnotationKind {
+        AnnotationKind::Bookmark
+    }
+
+    fn is_active(&self, app: &App) -> bool {
+        !app.buffer.bookmarks.is_empty()
+    }
+
+    fn annotate(&self, app: &App, line_idx: usize) -> Option<Annotation> {
+        let line = app.buffer.document.lines.get(line_idx)?;
+        (!line.continuation && app.buffer.bookmarks.contains(&line.number)).then_some(Annotation {
+            glyph: glyphs().bookmark,
+            color: app.theme_colors.status_bg,
+        })
+    }
+}
+
+/// Registered annotation sources
+static SOURCES: &[&dyn AnnotationSource] = &[&SearchMatches, &Bookmarks, &ClosedFolds];
+
+/// Sources currently reserving a column, in [`AnnotationKind`] order
+fn active_sources(app: &App) -> Vec<&'static dyn AnnotationSource> {
+    let mut sources: Vec<_> = SOURCES.iter().copied().filter(|s| s.is_active(app)).collect();
+    sources.sort_by_key(|s| s.kind());
+    sources
+}
+
+/// Width of the annotation column (one cell per active source)
+pub fn annotation_width(app: &App) -> usize {
+    active_sources(app).len()
+}
+
+/// Annotations for a line, one slot per active source
+pub fn annotations_for(app: &App, line_idx: usize) -> Vec<Option<Annotation>> {
+    active_sources(app).into_iter().map(|s| s.annotate(app, line_idx)).collect()
+}
+
+/// Render the annotation column; `rows` holds the document line index of each
+/// screen row, or `None` for rows that only continue a line
+pub fn render_annotations(frame: &mut Frame, app: &App, rows: &[Option<usize>], area: Rect) {
+    let width = annotation_width(app);
+
+    let lines: Vec<RatatuiLine> = rows
+        .iter()
+        .map(|row| match row {
+            Some(line_idx) => RatatuiLine::from(
+                annotations_for(app, *line_idx)
+                    .into_iter()
+                    .map(|slot| match slot {
+                        Some(a) => Span::styled(a.glyph.to_string(), Style::default().fg(a.color)),
+                        None => Span::raw(" "),
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            None => RatatuiLine::from(" ".repeat(width)),
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::WrapMode;
+    use crate::display::Document;
+    use crate::highlight::SearchState;
+    use crate::theme::{Theme, ThemeColors};
+    use regex::Regex;
+
+    fn create_app(text: &str) -> App {
+        let doc = Document::from_text(text, "test.txt".to_string(), "UTF-8".to_string());
+        App::new(doc, false, None, ThemeColors::for_theme(Theme::Dark), false, None, WrapMode::None, 200)
+    }
+
+    #[test]
+    fn test_no_column_without_sources() {
+        let app = create_app("alpha\nbeta");
+        assert_eq!(annotation_width(&app), 0);
+        assert!(annotations_for(&app, 0).is_empty());
+    }
+
+    #[test]
+    fn test_search_match_annotations() {
+        let mut app = create_app("alpha\nbeta\nalpha again");
+        let mut state = SearchState::new(Regex::new("alpha").unwrap());
+        state.find_matches(&app.buffer.document);
+        state.next_match();
+        app.buffer.search_state = Some(state);
+
+        assert_eq!(annotation_width(&app), 1);
+        assert_eq!(annotations_for(&app, 0)[0].map(|a| a.glyph), Some('▶'));
+        assert_eq!(annotations_for(&app, 1), vec![None]);
+        assert_eq!(annotations_for(&app, 2)[0].map(|a| a.glyph), Some('●'));
+    }
+
+    #[test]
+    fn test_annotation_column_narrows_content() {
+        let mut app = create_app("alpha\nbeta");
+        app.terminal_size = (80, 24);
+        assert_eq!(app.content_width(), 80);
+
+        let mut state = SearchState::new(Regex::new("beta").unwrap());
+        state.find_matches(&app.buffer.document);
+        app.buffer.search_state = Some(state);
+        assert_eq!(app.content_width(), 79);
+    }
+}
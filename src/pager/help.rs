@@ -0,0 +1,153 @@
+//! Keybinding cheat sheet (F1, `:help`)
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// The keys of the pager by topic, each with what it does
+const SECTIONS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Moving",
+        &[
+            ("j / k", "Scroll down / up one line"),
+            ("d / u", "Scroll down / up half a page"),
+            ("g / G", "Go to top / bottom"),
+            ("42g / :42", "Go to line 42"),
+            ("h / l", "Scroll left / right"),
+            ("w / b", "Scroll to the next / previous word (table column)"),
+            ("0 / $", "Scroll to line start / end"),
+            ("zL / zH", "Scroll right / left half a screen"),
+            ("]] / [[", "Next / previous markdown heading"),
+            ("O / :toc", "Table of contents of a markdown document"),
+            ("f", "Follow the end of the file"),
+        ],
+    ),
+    (
+        "Searching",
+        &[
+            ("/", "Search, ignoring case"),
+            ("?", "Search, matching case"),
+            ("n / N", "Next / previous match (5n skips 5)"),
+            ("Ctrl+L", "Clear the search highlights"),
+            ("-i", "Toggle ignore-case"),
+            ("A", "Search all lines or only the grep-filtered ones"),
+            ("H", "Match distribution overlay"),
+        ],
+    ),
+    (
+        "Filtering",
+        &[
+            ("&", "Keep only lines matching a pattern (:grep)"),
+            ("U", "Remove the last :grep / :hide filter"),
+            ("S", "Squeeze repeated lines"),
+            ("za / zM / zR", "Fold the top line / everything / nothing"),
+        ],
+    ),
+    (
+        "Display",
+        &[
+            ("# / -N", "Toggle line numbers"),
+            ("R", "Toggle relative line numbers"),
+            ("T", "Cycle timestamps: original, relative, local, UTC"),
+            ("M", "Toggle reading mode"),
+            ("C", "Toggle the breadcrumb of headings / blocks the top line is in"),
+            ("r", "Switch pretty-printed JSON to the input as it came and back"),
+            ("D", "Character breakdown of the top line"),
+            ("s / x / X", "Sort by / hide a table column, show all columns"),
+        ],
+    ),
+    (
+        "Lines and files",
+        &[
+            ("y", "Copy path:line of the top line"),
+            ("m", "Bookmark the top line"),
+            ("Tab / Shift+Tab", "Select the next / previous path"),
+            ("Enter", "Open the selected path in a new buffer"),
+            ("o", "Open the selected path (or trace reference) in the editor"),
+            ("]r / [r", "Next / previous stack trace reference"),
+            ("]t / [t", "Jump forward / backward by the time step"),
+            ("]f / [f", "Next / previous open file"),
+            ("B / :ls", "List the open files, type to narrow, Enter to switch"),
+        ],
+    ),
+    (
+        "Commands",
+        &[
+            (":grep / :hide PAT", "Keep / hide lines matching PAT"),
+            (":search PAT", "Search all open files"),
+            (":pane PAT / :only", "Live pane of lines matching PAT / close panes"),
+            (":freq [PAT]", "Most frequent values captured by PAT"),
+            (":match N / :page N", "Jump to the Nth match / page"),
+            (":mouse", "Capture the mouse, or leave it to the terminal"),
+            (":help", "This help"),
+        ],
+    ),
+    ("Quitting", &[("q / Esc", "Quit (or close an overlay)"), ("Ctrl+C", "Abort")]),
+];
+
+/// Width of the key column
+const KEY_WIDTH: usize = 20;
+
+/// The cheat sheet, one section heading or key per line
+fn help_lines(color: Color) -> Vec<RatatuiLine<'static>> {
+    let mut lines = Vec::new();
+    for (i, (title, keys)) in SECTIONS.iter().enumerate() {
+        if i > 0 {
+            lines.push(RatatuiLine::default());
+        }
+        lines.push(RatatuiLine::from(Span::styled(*title, Style::default().add_modifier(Modifier::BOLD))));
+        for (key, action) in keys.iter() {
+            lines.push(RatatuiLine::from(vec![
+                Span::styled(format!("  {:<width$}", key, width = KEY_WIDTH), Style::default().fg(color)),
+                Span::raw(*action),
+            ]));
+        }
+    }
+    lines
+}
+
+/// Number of lines of the cheat sheet
+pub fn help_line_count() -> usize {
+    SECTIONS.iter().map(|(_, keys)| keys.len() + 2).sum::<usize>() - 1
+}
+
+/// Rows of the cheat sheet shown at once in an area `height` rows high
+pub fn help_rows(height: usize) -> usize {
+    height.saturating_sub(2).max(1)
+}
+
+/// Render the cheat sheet as a centered overlay, from line `scroll` on
+pub fn render_help(frame: &mut Frame, scroll: usize, color: Color, area: Rect) {
+    let width = 72.min(area.width);
+    let height = (help_line_count() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let more = help_line_count() > help_rows(popup.height as usize);
+    let title = if more { " Keys (j/k to scroll, q to close) " } else { " Keys (q to close) " };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+    frame.render_widget(Paragraph::new(help_lines(color)).scroll((scroll as u16, 0)), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_line_count() {
+        assert_eq!(help_lines(Color::Cyan).len(), help_line_count());
+        // Keys fit their column
+        assert!(SECTIONS.iter().flat_map(|(_, keys)| keys.iter()).all(|(key, _)| key.len() < KEY_WIDTH));
+    }
+}
@@ -0,0 +1,176 @@
+//! Match distribution overlay
+//!
+//! A sparkline of where matches fall across the document, one bar per
+//! bucket of lines, plus a count for each pattern.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Sparkline},
+    Frame,
+};
+use regex::Regex;
+
+use crate::display::Document;
+use crate::highlight::SearchState;
+
+/// Maximum number of buckets (sparkline bars)
+const MAX_BUCKETS: usize = 80;
+
+/// Match counts per bucket of lines and per pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    /// Number of matches in each bucket
+    pub buckets: Vec<u64>,
+    /// Number of document lines covered by each bucket
+    pub lines_per_bucket: usize,
+    /// Label and match count for each pattern
+    pub patterns: Vec<(String, usize)>,
+}
+
+impl Histogram {
+    /// Compute the distribution of search matches (or grep matching lines
+    /// when no search is active), using at most `max_buckets` bars
+    pub fn compute(
+        document: &Document,
+        search_state: Option<&SearchState>,
+        patterns: &[Regex],
+        max_buckets: usize,
+    ) -> Self {
+        let line_count = document.line_count().max(1);
+        let bucket_count = max_buckets.clamp(1, MAX_BUCKETS).min(line_count);
+        let lines_per_bucket = (line_count + bucket_count - 1) / bucket_count;
+        let mut buckets = vec![0u64; (line_count + lines_per_bucket - 1) / lines_per_bucket];
+
+        match search_state {
+            Some(state) => {
+                for m in &state.matches {
+                    buckets[m.line_idx / lines_per_bucket] += 1;
+                }
+            }
+            None => {
+                for (idx, _) in document.lines.iter().enumerate().filter(|(_, l)| l.is_match) {
+                    buckets[idx / lines_per_bucket] += 1;
+                }
+            }
+        }
+
+        let mut counts: Vec<(String, usize)> = patterns.iter().map(|p| (p.as_str().to_string(), 0)).collect();
+        for line in &document.lines {
+            let text = line.text();
+            for (i, pattern) in patterns.iter().enumerate() {
+                counts[i].1 += pattern.find_iter(&text).count();
+            }
+        }
+        if search_state.is_none() {
+            let grep_lines = document.lines.iter().filter(|l| l.is_match).count();
+            if grep_lines > 0 {
+                counts.push(("grep matching lines".to_string(), grep_lines));
+            }
+        }
+
+        Self {
+            buckets,
+            lines_per_bucket,
+            patterns: counts,
+        }
+    }
+
+    /// Index of the busiest bucket, if any bucket has matches
+    pub fn peak_bucket(&self) -> Option<usize> {
+        let (idx, &max) = self.buckets.iter().enumerate().max_by_key(|(i, &c)| (c, std::cmp::Reverse(*i)))?;
+        (max > 0).then_some(idx)
+    }
+}
+
+/// Render the histogram as a centered overlay
+pub fn render_histogram(frame: &mut Frame, histogram: &Histogram, color: Color, area: Rect) {
+    let width = (histogram.buckets.len() as u16 + 2).max(40).min(area.width);
+    let height = (histogram.patterns.len() as u16 + 6).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let block = Block::default().borders(Borders::ALL).title(" Match distribution ");
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+    if inner.height < 3 {
+        return;
+    }
+
+    let spark_area = Rect { height: 3, ..inner };
+    let sparkline = Sparkline::default().data(&histogram.buckets).style(Style::default().fg(color));
+    frame.render_widget(sparkline, spark_area);
+
+    let mut lines = vec![RatatuiLine::from(Span::raw(format!(
+        "{} lines per bar{}",
+        histogram.lines_per_bucket,
+        match histogram.peak_bucket() {
+            Some(peak) => format!(", peak at line {}", peak * histogram.lines_per_bucket + 1),
+            None => String::new(),
+        }
+    )))];
+    for (label, count) in &histogram.patterns {
+        lines.push(RatatuiLine::from(Span::raw(format!("{:>6}  {}", count, label))));
+    }
+
+    let text_area = Rect {
+        y: inner.y + 3,
+        height: inner.height - 3,
+        ..inner
+    };
+    frame.render_widget(Paragraph::new(lines), text_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_doc(lines: &[&str]) -> Document {
+        Document::from_text(&lines.join("\n"), "test.log".to_string(), "UTF-8".to_string())
+    }
+
+    #[test]
+    fn test_histogram_buckets_search_matches() {
+        let mut lines = vec!["ok"; 100];
+        lines[5] = "error";
+        lines[7] = "error error";
+        lines[95] = "error";
+        let doc = create_doc(&lines);
+
+        let pattern = Regex::new("error").unwrap();
+        let mut state = SearchState::new(pattern.clone());
+        state.find_matches(&doc);
+
+        let histogram = Histogram::compute(&doc, Some(&state), &[pattern], 10);
+        assert_eq!(histogram.lines_per_bucket, 10);
+        assert_eq!(histogram.buckets.len(), 10);
+        assert_eq!(histogram.buckets[0], 3);
+        assert_eq!(histogram.buckets[9], 1);
+        assert_eq!(histogram.patterns, vec![("error".to_string(), 4)]);
+        assert_eq!(histogram.peak_bucket(), Some(0));
+    }
+
+    #[test]
+    fn test_histogram_grep_lines_without_search() {
+        let mut doc = create_doc(&["a", "b", "c"]);
+        doc.lines[2].is_match = true;
+
+        let histogram = Histogram::compute(&doc, None, &[], 80);
+        assert_eq!(histogram.buckets, vec![0, 0, 1]);
+        assert_eq!(histogram.patterns, vec![("grep matching lines".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_histogram_no_matches() {
+        let doc = create_doc(&["a", "b"]);
+        let histogram = Histogram::compute(&doc, None, &[], 80);
+        assert_eq!(histogram.peak_bucket(), None);
+        assert!(histogram.patterns.is_empty());
+    }
+}
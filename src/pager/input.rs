@@ -1,37 +1,189 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-use super::app::{App, Mode};
+use crate::config::KeyProfile;
+use crate::usage;
+
+use super::app::{App, Mode, QuitAction};
+use super::keys::key_name;
+
+/// Lines scrolled by one step of the mouse wheel
+const WHEEL_SCROLL_LINES: usize = 3;
 
 /// Handle a key event, returning true if the app should quit
 pub fn handle_key(key: KeyEvent, app: &mut App) -> bool {
     // Check for Ctrl+C first - always quit
     if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        app.quit_action = QuitAction::Aborted;
         app.should_quit = true;
         return true;
     }
 
+    // Any key dismisses the previous status message and mouse selection
+    app.status_message = None;
+    app.mouse_selection = None;
+
     // Handle based on current mode
     match &app.mode {
-        Mode::Normal => handle_normal_mode(key, app),
+        Mode::Normal => {
+            usage::record(&key_name(key));
+            handle_normal_mode(key, app)
+        }
         Mode::Search { .. } => handle_search_mode(key, app),
+        Mode::Command { .. } => handle_command_mode(key, app),
+        Mode::Help { .. } => handle_help_mode(key, app),
+    }
+}
+
+/// Handle a mouse event (only delivered while the mouse is captured)
+pub fn handle_mouse(event: MouseEvent, app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    let status_row = app.terminal_size.1.saturating_sub(1);
+    match event.kind {
+        MouseEventKind::ScrollDown => app.scroll_down(WHEEL_SCROLL_LINES),
+        MouseEventKind::ScrollUp => app.scroll_up(WHEEL_SCROLL_LINES),
+        MouseEventKind::Down(MouseButton::Left) if event.row == status_row => {
+            app.mouse_selection = None;
+            app.jump_to_column(event.column);
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            app.status_message = None;
+            app.mouse_selection = None;
+            if let Some(line_idx) = app.line_at_row(event.row) {
+                app.start_selection(line_idx);
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if let Some(line_idx) = app.line_at_row(event.row) {
+                app.extend_selection(line_idx);
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => app.finish_selection(),
+        _ => {}
     }
 }
 
 /// Handle key events in normal mode
 fn handle_normal_mode(key: KeyEvent, app: &mut App) -> bool {
+    // Second key of a two-key sequence
+    if let Some(prefix) = app.pending_key.take() {
+        return handle_prefixed_key(prefix, key, app);
+    }
+
+    // The results panel of `:search` takes the keys while it is open
+    if let Some(ref mut results) = app.search_results {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => results.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => results.select_prev(),
+            KeyCode::Enter => app.open_search_result(),
+            KeyCode::Char('q') | KeyCode::Esc => app.search_results = None,
+            _ => {}
+        }
+        return false;
+    }
+
+    // The buffer list takes typed letters as its query
+    if let Some(ref mut list) = app.buffer_list {
+        match key.code {
+            KeyCode::Down => list.select_next(),
+            KeyCode::Up => list.select_prev(),
+            KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => list.select_next(),
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => list.select_prev(),
+            KeyCode::Char(c) => list.push(c),
+            KeyCode::Backspace => list.pop(),
+            KeyCode::Enter => app.open_buffer_list_entry(),
+            KeyCode::Esc => app.buffer_list = None,
+            _ => {}
+        }
+        return false;
+    }
+
+    // So does the table of contents
+    if let Some(ref mut toc) = app.toc {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => toc.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => toc.select_prev(),
+            KeyCode::Enter => app.open_toc_entry(),
+            KeyCode::Char('q' | 'O') | KeyCode::Esc => app.toc = None,
+            _ => {}
+        }
+        return false;
+    }
+
+    // Count prefix (0 only continues a count, otherwise it goes to line start)
+    if let KeyCode::Char(c @ '0'..='9') = key.code {
+        if c != '0' || app.count.is_some() {
+            app.push_count_digit(c.to_digit(10).unwrap_or(0));
+            return false;
+        }
+    }
+    let typed_count = app.count.take();
+    let count = typed_count.unwrap_or(1);
+
+    // Enter opens the path selected with Tab, whatever else it does
+    if app.selected_link.is_some() && key.code == KeyCode::Enter {
+        app.open_link();
+        return false;
+    }
+
+    // Selecting a line ends the session when mat runs as a step of a script
+    if app.report_action && key.code == KeyCode::Enter {
+        app.select_line();
+        return app.should_quit;
+    }
+
+    if app.key_profile == KeyProfile::Less && handle_less_key(key, app) {
+        return app.should_quit;
+    }
+
     match key.code {
+        // Close the overlay before anything else
+        KeyCode::Esc if app.histogram.is_some() || app.diagnostics.is_some() || app.frequencies.is_some() => {
+            app.histogram = None;
+            app.diagnostics = None;
+            app.frequencies = None;
+            false
+        }
+        KeyCode::Esc if app.selected_link.is_some() => {
+            app.selected_link = None;
+            false
+        }
+
         // Quit
         KeyCode::Char('q') | KeyCode::Esc => {
             app.should_quit = true;
             true
         }
 
+        // Show the keybinding cheat sheet
+        KeyCode::F(1) => {
+            app.show_help();
+            false
+        }
+
         // Enter search mode (case-insensitive)
         KeyCode::Char('/') => {
             app.enter_search_mode(true);
             false
         }
 
+        // Open the command prompt
+        KeyCode::Char(':') => {
+            app.enter_command_mode();
+            false
+        }
+
+        // Filter to matching lines / remove the last filter
+        KeyCode::Char('&') => {
+            app.enter_command_mode_with("grep ");
+            false
+        }
+        KeyCode::Char('U') => {
+            app.pop_filter();
+            false
+        }
+
         // Enter search mode (case-sensitive)
         KeyCode::Char('?') => {
             app.enter_search_mode(false);
@@ -50,15 +202,41 @@ fn handle_normal_mode(key: KeyEvent, app: &mut App) -> bool {
             false
         }
 
+        // Scroll left / right by half a screen
+        KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            app.scroll_half_screen_left();
+            false
+        }
+        KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            app.scroll_half_screen_right();
+            false
+        }
+
         // Scroll left
         KeyCode::Char('h') | KeyCode::Left => {
-            app.scroll_left(4);
+            app.scroll_left(app.hscroll_step);
+            false
+        }
+
+        // Clear search highlights
+        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.clear_search();
             false
         }
 
         // Scroll right
         KeyCode::Char('l') | KeyCode::Right => {
-            app.scroll_right(4);
+            app.scroll_right(app.hscroll_step);
+            false
+        }
+
+        // Scroll right / left by words
+        KeyCode::Char('w') => {
+            app.scroll_word_right();
+            false
+        }
+        KeyCode::Char('b') => {
+            app.scroll_word_left();
             false
         }
 
@@ -86,27 +264,33 @@ fn handle_normal_mode(key: KeyEvent, app: &mut App) -> bool {
             false
         }
 
-        // Go to top
+        // Go to top, or to line N with a count (42g)
         KeyCode::Char('g') | KeyCode::Home => {
-            app.go_to_top();
+            match typed_count {
+                Some(number) => app.go_to_line(number),
+                None => app.go_to_top(),
+            }
             false
         }
 
-        // Go to bottom
+        // Go to bottom, or to line N with a count (42G)
         KeyCode::Char('G') | KeyCode::End => {
-            app.go_to_bottom();
+            match typed_count {
+                Some(number) => app.go_to_line(number),
+                None => app.go_to_bottom(),
+            }
             false
         }
 
         // Next search match
         KeyCode::Char('n') => {
-            app.next_match();
+            app.next_match(count);
             false
         }
 
         // Previous search match
         KeyCode::Char('N') => {
-            app.prev_match();
+            app.prev_match(count);
             false
         }
 
@@ -118,7 +302,121 @@ fn handle_normal_mode(key: KeyEvent, app: &mut App) -> bool {
 
         // Toggle line numbers
         KeyCode::Char('#') => {
-            app.show_line_numbers = !app.show_line_numbers;
+            app.buffer.show_line_numbers = !app.buffer.show_line_numbers;
+            false
+        }
+
+        // Toggle relative line numbers
+        KeyCode::Char('R') => {
+            app.toggle_relative_numbers();
+            false
+        }
+
+        // Toggle squeezing of repeated lines
+        KeyCode::Char('S') => {
+            app.toggle_squeeze();
+            false
+        }
+
+        // Toggle reading mode (prose centered at the reading width)
+        KeyCode::Char('M') => {
+            app.toggle_reading_mode();
+            false
+        }
+
+        // Toggle match distribution overlay
+        KeyCode::Char('H') => {
+            app.toggle_histogram();
+            false
+        }
+
+        // Toggle the character breakdown of the top line
+        KeyCode::Char('D') => {
+            app.toggle_diagnostics();
+            false
+        }
+
+        // Table columns: sort by the leftmost one, hide it, show the hidden ones again
+        KeyCode::Char('s') => {
+            app.sort_table_column();
+            false
+        }
+        KeyCode::Char('x') => {
+            app.hide_table_column();
+            false
+        }
+        KeyCode::Char('X') => {
+            app.show_table_columns();
+            false
+        }
+
+        // Toggle searching the grep-filtered lines or all lines
+        KeyCode::Char('A') => {
+            app.toggle_search_scope();
+            false
+        }
+
+        // Toggle the breadcrumb of the headings or blocks the top line is in
+        KeyCode::Char('C') => {
+            app.toggle_breadcrumbs();
+            false
+        }
+
+        // Toggle between the pretty-printed input and the input as it came
+        KeyCode::Char('r') => {
+            app.toggle_raw_view();
+            false
+        }
+
+        // Cycle timestamp display mode
+        KeyCode::Char('T') => {
+            app.cycle_timestamp_mode();
+            false
+        }
+
+        // Start a two-key sequence (]t, [t, ]r, [r, zL, zH, za, zM, zR, -i, -N)
+        KeyCode::Char(c @ (']' | '[' | 'z' | '-')) => {
+            app.pending_key = Some(c);
+            false
+        }
+
+        // Yank path:line of the anchor line
+        KeyCode::Char('y') => {
+            app.yank_reference();
+            false
+        }
+
+        // Bookmark the anchor line
+        KeyCode::Char('m') => {
+            app.toggle_bookmark();
+            false
+        }
+
+        // Open the selected path or the stack trace reference on screen in the editor
+        KeyCode::Char('o') => {
+            app.open_reference();
+            false
+        }
+
+        // Table of contents of a markdown document
+        KeyCode::Char('O') => {
+            app.show_toc();
+            false
+        }
+
+        // List of the open buffers
+        KeyCode::Char('B') => {
+            app.show_buffer_list();
+            false
+        }
+
+        // Select the next / previous path
+        KeyCode::Tab => {
+            app.select_link(true);
+            false
+        }
+        KeyCode::BackTab => {
+            app.select_link(false);
             false
         }
 
@@ -126,6 +424,69 @@ fn handle_normal_mode(key: KeyEvent, app: &mut App) -> bool {
     }
 }
 
+/// Keys of the less profile that differ from the defaults, returning true if the key was handled
+fn handle_less_key(key: KeyEvent, app: &mut App) -> bool {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        // Page forward / backward
+        KeyCode::Char(' ') | KeyCode::Char('f') => app.scroll_page_down(),
+        KeyCode::Char('b') => app.scroll_page_up(),
+        KeyCode::Char('v' | 'z') if ctrl => app.scroll_page_down(),
+
+        // Line forward / backward
+        KeyCode::Enter | KeyCode::Char('e') => app.scroll_down(1),
+        KeyCode::Char('y') => app.scroll_up(1),
+
+        // Top / bottom
+        KeyCode::Char('<') => app.go_to_top(),
+        KeyCode::Char('>') => app.go_to_bottom(),
+
+        // Follow mode
+        KeyCode::Char('F') => app.toggle_follow(),
+
+        // Search with the current ignore-case setting
+        KeyCode::Char('/') => app.enter_search_mode(app.ignore_case),
+
+        // File information
+        KeyCode::Char('=') => app.show_file_info(),
+        KeyCode::Char('g') if ctrl => app.show_file_info(),
+
+        _ => return false,
+    }
+    true
+}
+
+/// Handle the second key of a sequence started with `prefix`
+fn handle_prefixed_key(prefix: char, key: KeyEvent, app: &mut App) -> bool {
+    match (prefix, key.code) {
+        // less-style option toggles
+        ('-', KeyCode::Char('i')) => app.toggle_ignore_case(),
+        ('-', KeyCode::Char('N')) => app.buffer.show_line_numbers = !app.buffer.show_line_numbers,
+        // Jump forward / backward by the time step
+        (']', KeyCode::Char('t')) => app.jump_by_time(true),
+        ('[', KeyCode::Char('t')) => app.jump_by_time(false),
+        // Switch to the next / previous open file
+        (']', KeyCode::Char('f')) => app.next_file(),
+        ('[', KeyCode::Char('f')) => app.prev_file(),
+        // Jump between the headings of markdown
+        (']', KeyCode::Char(']')) => app.jump_to_heading(true),
+        ('[', KeyCode::Char('[')) => app.jump_to_heading(false),
+        // Jump between the file references of stack traces
+        (']', KeyCode::Char('r')) => app.jump_to_reference(true),
+        ('[', KeyCode::Char('r')) => app.jump_to_reference(false),
+        // Shift the view by half a screen horizontally, like less
+        ('z', KeyCode::Char('L')) => app.scroll_half_screen_right(),
+        ('z', KeyCode::Char('H')) => app.scroll_half_screen_left(),
+        // Folds by indentation, like vim
+        ('z', KeyCode::Char('a')) => app.toggle_fold(),
+        ('z', KeyCode::Char('M')) => app.close_all_folds(),
+        ('z', KeyCode::Char('R')) => app.open_all_folds(),
+        // Unknown sequence - ignore it
+        _ => {}
+    }
+    false
+}
+
 /// Handle key events in search mode
 fn handle_search_mode(key: KeyEvent, app: &mut App) -> bool {
     match key.code {
@@ -157,12 +518,53 @@ fn handle_search_mode(key: KeyEvent, app: &mut App) -> bool {
     }
 }
 
+/// Handle key events while the keybinding cheat sheet is open
+fn handle_help_mode(key: KeyEvent, app: &mut App) -> bool {
+    let half_page = (app.content_height() / 2).max(1) as isize;
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_help(1),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_help(-1),
+        KeyCode::Char('d') | KeyCode::PageDown | KeyCode::Char(' ') => app.scroll_help(half_page),
+        KeyCode::Char('u') | KeyCode::PageUp => app.scroll_help(-half_page),
+        KeyCode::Char('g') | KeyCode::Home => app.scroll_help(isize::MIN),
+        KeyCode::Char('G') | KeyCode::End => app.scroll_help(isize::MAX),
+        KeyCode::Char('q') | KeyCode::Esc | KeyCode::F(1) | KeyCode::Enter => app.mode = Mode::Normal,
+        _ => {}
+    }
+    false
+}
+
+/// Handle key events at the `:` command prompt
+fn handle_command_mode(key: KeyEvent, app: &mut App) -> bool {
+    match key.code {
+        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Enter => app.confirm_command(),
+        KeyCode::Backspace => {
+            if let Mode::Command { ref mut input } = app.mode {
+                // Deleting past the prompt closes it, like vim
+                if input.pop().is_none() {
+                    app.mode = Mode::Normal;
+                }
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Mode::Command { ref mut input } = app.mode {
+                input.push(c);
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cli::WrapMode;
     use crate::display::Document;
+    use crate::highlight::SearchState;
     use crate::theme::{Theme, ThemeColors};
+    use crate::pager::help;
 
     fn create_test_app() -> App {
         let doc = Document::from_text(
@@ -186,38 +588,166 @@ mod tests {
         assert!(app.should_quit);
     }
 
+    #[test]
+    fn test_help_overlay() {
+        let mut app = create_test_app();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        handle_key(key(KeyCode::F(1)), &mut app);
+        assert_eq!(app.mode, Mode::Help { scroll: 0 });
+        // Scrolling moves the cheat sheet, not the document, and stops at its end
+        handle_key(key(KeyCode::Char('j')), &mut app);
+        assert_eq!(app.mode, Mode::Help { scroll: 1 });
+        handle_key(key(KeyCode::Char('G')), &mut app);
+        assert_eq!(app.mode, Mode::Help { scroll: help::help_line_count() - 1 });
+        assert_eq!(app.buffer.scroll_line, 0);
+
+        // q closes the help rather than quitting
+        assert!(!handle_key(key(KeyCode::Char('q')), &mut app));
+        assert_eq!(app.mode, Mode::Normal);
+    }
+
     #[test]
     fn test_scroll_down() {
         let mut app = create_test_app();
 
         let key = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
         handle_key(key, &mut app);
-        assert_eq!(app.scroll_line, 1);
+        assert_eq!(app.buffer.scroll_line, 1);
     }
 
     #[test]
     fn test_scroll_up() {
         let mut app = create_test_app();
-        app.scroll_line = 2;
+        app.buffer.scroll_line = 2;
 
         let key = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE);
         handle_key(key, &mut app);
-        assert_eq!(app.scroll_line, 1);
+        assert_eq!(app.buffer.scroll_line, 1);
     }
 
     #[test]
     fn test_go_to_top_bottom() {
         let mut app = create_test_app();
-        app.scroll_line = 2;
+        app.buffer.scroll_line = 2;
 
         // Go to top
         let key = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
         handle_key(key, &mut app);
-        assert_eq!(app.scroll_line, 0);
+        assert_eq!(app.buffer.scroll_line, 0);
 
         // Go to bottom
         let key = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE);
         handle_key(key, &mut app);
-        assert_eq!(app.scroll_line, 3); // 5 lines - 2 visible = 3
+        assert_eq!(app.buffer.scroll_line, 3); // 5 lines - 2 visible = 3
+    }
+
+    #[test]
+    fn test_horizontal_scroll_keys() {
+        let doc = Document::from_text(&"x".repeat(200), "wide.txt".to_string(), "UTF-8".to_string());
+        let theme_colors = ThemeColors::for_theme(Theme::Dark);
+        let mut app = App::new(doc, false, None, theme_colors, false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 3);
+        app.hscroll_step = 10;
+
+        handle_key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE), &mut app);
+        assert_eq!(app.buffer.scroll_col, 10);
+
+        handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE), &mut app);
+        handle_key(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT), &mut app);
+        assert_eq!(app.buffer.scroll_col, 50);
+
+        handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT), &mut app);
+        assert_eq!(app.buffer.scroll_col, 10);
+    }
+
+    #[test]
+    fn test_less_profile_keys() {
+        let doc = Document::from_text(
+            &(1..=20).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n"),
+            "test.txt".to_string(),
+            "UTF-8".to_string(),
+        );
+        let theme_colors = ThemeColors::for_theme(Theme::Dark);
+        let mut app = App::new(doc, false, None, theme_colors, false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 6); // 5 content lines
+        app.key_profile = KeyProfile::Less;
+
+        // Space pages forward, b pages back
+        handle_key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE), &mut app);
+        assert_eq!(app.buffer.scroll_line, 5);
+        handle_key(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE), &mut app);
+        assert_eq!(app.buffer.scroll_line, 0);
+
+        // -i toggles ignore case
+        handle_key(KeyEvent::new(KeyCode::Char('-'), KeyModifiers::NONE), &mut app);
+        handle_key(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE), &mut app);
+        assert!(app.ignore_case);
+
+        // Keys not overridden keep their default meaning
+        handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE), &mut app);
+        assert_eq!(app.buffer.scroll_line, 1);
+        assert!(handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE), &mut app));
+    }
+
+    #[test]
+    fn test_go_to_line() {
+        let text: Vec<String> = (1..=20).map(|i| format!("Line {}", i)).collect();
+        let doc = Document::from_text(&text.join("\n"), "test.txt".to_string(), "UTF-8".to_string());
+        let theme_colors = ThemeColors::for_theme(Theme::Dark);
+        let mut app = App::new(doc, false, None, theme_colors, false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 6);
+        let type_keys = |app: &mut App, keys: &str| {
+            for c in keys.chars() {
+                let code = if c == '\n' { KeyCode::Enter } else { KeyCode::Char(c) };
+                handle_key(KeyEvent::new(code, KeyModifiers::NONE), app);
+            }
+        };
+
+        type_keys(&mut app, "7g");
+        assert_eq!(app.buffer.scroll_line, 6);
+        type_keys(&mut app, "g");
+        assert_eq!(app.buffer.scroll_line, 0);
+        type_keys(&mut app, ":12\n");
+        assert_eq!(app.buffer.scroll_line, 11);
+        // Past the end (and near it) the last page is shown
+        type_keys(&mut app, "99G");
+        assert_eq!(app.buffer.scroll_line, 15);
+
+        // With soft wrapping the line's first row goes to the top
+        let long = format!("{}\nshort\ntarget", "x".repeat(200));
+        let doc = Document::from_text(&long, "test.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, ThemeColors::for_theme(Theme::Dark), false, None, WrapMode::Wrap, 200);
+        app.set_terminal_size(80, 3);
+        app.build_wrapped_lines();
+        type_keys(&mut app, "2g");
+        assert_eq!(app.anchor_line_idx(), 1);
+    }
+
+    #[test]
+    fn test_count_prefix_and_match_command() {
+        let doc = Document::from_text(
+            &(1..=20).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n"),
+            "test.txt".to_string(),
+            "UTF-8".to_string(),
+        );
+        let mut state = SearchState::new(regex::Regex::new("Line").unwrap());
+        state.find_matches(&doc);
+        let theme_colors = ThemeColors::for_theme(Theme::Dark);
+        let mut app = App::new(doc, false, Some(state), theme_colors, false, None, WrapMode::None, 200);
+        app.set_terminal_size(80, 6);
+
+        for c in ['1', '2', 'n'] {
+            handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), &mut app);
+        }
+        assert_eq!(app.search_info(), Some((12, 20)));
+        assert_eq!(app.count, None);
+
+        for c in ":match 3".chars() {
+            handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), &mut app);
+        }
+        handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &mut app);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.search_info(), Some((3, 20)));
     }
 }
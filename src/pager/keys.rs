@@ -0,0 +1,155 @@
+//! Scripted key input (`--keys`, `--keys-file`)
+//!
+//! A key sequence is typed into the pager before it takes any input from the
+//! terminal, e.g. `/error<Enter>nn` or `:grep timeout<Enter>zM`. Characters
+//! are keys as they are; names in angle brackets stand for the others:
+//! `<Enter>`, `<Esc>`, `<Tab>`, `<BS>`, `<Space>`, `<Up>` `<Down>` `<Left>`
+//! `<Right>`, `<Home>` `<End>`, `<PageUp>` `<PageDown>`, `<F1>` to `<F12>`,
+//! `<lt>` for `<` itself, and `<C-x>` / `<S-x>` for Ctrl / Shift with a key.
+//! Line breaks count as Enter, so a keys file can hold one command per line.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Parse a key sequence, describing the first key that isn't one
+pub fn parse_keys(spec: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = Vec::new();
+    let mut rest = spec;
+    while let Some(c) = rest.chars().next() {
+        let (key, len) = match c {
+            '<' => {
+                let end = rest.find('>').ok_or_else(|| format!("unclosed '<' in '{}'", rest))?;
+                (named_key(&rest[1..end])?, end + 1)
+            }
+            '\n' => (KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), 1),
+            '\r' => {
+                rest = &rest[1..];
+                continue;
+            }
+            c => (KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), c.len_utf8()),
+        };
+        keys.push(key);
+        rest = &rest[len..];
+    }
+    Ok(keys)
+}
+
+/// How `key` is written in a key sequence, e.g. `j`, `<Enter>` or `<C-l>`
+pub fn key_name(key: KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char('<') => "lt".to_string(),
+        KeyCode::Char(c) if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT => return c.to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "S-Tab".to_string(),
+        KeyCode::Backspace => "BS".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    };
+    let ctrl = if key.modifiers.contains(KeyModifiers::CONTROL) { "C-" } else { "" };
+    let shift = match key.code {
+        KeyCode::Char(_) | KeyCode::BackTab => "",
+        _ if key.modifiers.contains(KeyModifiers::SHIFT) => "S-",
+        _ => "",
+    };
+    format!("<{}{}{}>", ctrl, shift, name)
+}
+
+/// The key written as `<name>`, with `C-` / `S-` modifier prefixes
+fn named_key(name: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut base = name;
+    loop {
+        match base.get(..2).map(str::to_ascii_uppercase).as_deref() {
+            Some("C-") if base.len() > 2 => modifiers |= KeyModifiers::CONTROL,
+            Some("S-") if base.len() > 2 => modifiers |= KeyModifiers::SHIFT,
+            _ => break,
+        }
+        base = &base[2..];
+    }
+
+    let mut chars = base.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match base.to_ascii_lowercase().as_str() {
+            "enter" | "cr" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            // Terminals report Shift+Tab as a key of its own
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            "bs" | "backspace" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            "lt" => KeyCode::Char('<'),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key <{}>", name)),
+            },
+        },
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys("jn q").unwrap(),
+            vec![key(KeyCode::Char('j')), key(KeyCode::Char('n')), key(KeyCode::Char(' ')), key(KeyCode::Char('q'))]
+        );
+        assert_eq!(
+            parse_keys("/a<Enter><lt><F1>\r\n").unwrap(),
+            vec![
+                key(KeyCode::Char('/')),
+                key(KeyCode::Char('a')),
+                key(KeyCode::Enter),
+                key(KeyCode::Char('<')),
+                key(KeyCode::F(1)),
+                key(KeyCode::Enter),
+            ]
+        );
+        assert_eq!(parse_keys("<C-l>").unwrap(), vec![KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL)]);
+        assert_eq!(parse_keys("<s-right>").unwrap(), vec![KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT)]);
+        assert_eq!(parse_keys("<S-Tab>").unwrap(), vec![key(KeyCode::BackTab)]);
+        assert_eq!(parse_keys("<C-->").unwrap(), vec![KeyEvent::new(KeyCode::Char('-'), KeyModifiers::CONTROL)]);
+
+        assert_eq!(parse_keys("<Foo>"), Err("unknown key <Foo>".to_string()));
+        assert!(parse_keys("<F13>").is_err());
+        assert!(parse_keys("ab<Enter").is_err());
+    }
+
+    #[test]
+    fn test_key_name() {
+        for spec in ["j", "G", "<Space>", "<lt>", "<Enter>", "<S-Tab>", "<C-l>", "<S-Right>", "<F1>"] {
+            let keys = parse_keys(spec).unwrap();
+            assert_eq!(key_name(keys[0]), spec);
+        }
+    }
+}
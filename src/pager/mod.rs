@@ -1,26 +1,63 @@
 mod app;
+mod buffer;
+mod buffers;
+mod clipboard;
+mod command;
+mod diagnostics;
+mod editor;
+mod external;
+mod filters;
+mod folds;
+mod frequency;
+mod gutter;
+mod help;
+mod histogram;
 mod input;
+mod keys;
+mod panes;
+mod results;
+mod screen;
 mod search;
+mod session_log;
+mod terminal;
+mod toc;
 mod ui;
 
-use std::io::{self, stdout, Write};
-use std::panic;
+use std::io::{self, stdout, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use crossterm::{
-    event::{self, Event, KeyEventKind},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use crossterm::event::{self, Event, KeyEvent, KeyEventKind};
 
-use crate::cli::Args;
-use crate::display::Document;
+use crate::cli::{Args, Paging, SearchScope};
+use crate::config::Config;
+use crate::display::glyphs::glyphs;
+use crate::display::timestamp::parse_time_step;
+use crate::display::{hexdump, Document, OutputStyle, SpanStyle};
 use crate::error::MatError;
-use crate::highlight::SearchState;
-use crate::theme::{get_theme, ThemeColors};
+use crate::filter::{GrepOptions, GrepStream};
+use crate::format::Table;
+use crate::highlight::{apply_path_highlight, apply_syntax_highlight, linguist_language, SearchState};
+#[cfg(feature = "spellcheck")]
+use crate::highlight::Dictionary;
+use crate::input::{load_content, InputSource, InputStream, Location};
+use crate::theme::{get_theme, GrepColors, ThemeColors};
 
-pub use app::App;
+use buffer::Buffer;
+use session_log::SessionLog;
+use terminal::TerminalGuard;
+
+pub use app::{App, QuitAction};
+pub use external::{page_externally, use_external_pager};
+
+/// How often the event loop collects the lines read in the background
+const INPUT_TICK: Duration = Duration::from_millis(100);
+
+/// How far the input tick backs off while the input stays quiet, as often as `tail -f` looks
+const MAX_INPUT_TICK: Duration = Duration::from_secs(1);
+
+/// How long the event loop waits for a key when there is nothing else to do
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
 
 /// Parse line range from --lines argument
 pub fn parse_line_range(range: &str, total_lines: usize) -> Result<(usize, usize), MatError> {
@@ -84,103 +121,232 @@ pub fn filter_line_range(document: &mut Document, start: usize, end: usize) {
 }
 
 /// Print document directly to stdout (no-pager mode)
-pub fn print_document(document: &Document, show_line_numbers: bool) -> io::Result<()> {
-    let gutter_width = if show_line_numbers {
-        let max_line = document.line_count();
-        if max_line == 0 {
-            3
-        } else {
-            let digits = (max_line as f64).log10().floor() as usize + 1;
-            digits + 2
+pub fn print_document(document: &Document, style: &OutputStyle) -> io::Result<()> {
+    let mut out = stdout().lock();
+    write_document(&mut out, document, style, output_width())?;
+    out.flush()
+}
+
+/// Width used for `--style grid` rules in no-pager mode
+fn output_width() -> usize {
+    crossterm::terminal::size().map(|(w, _)| w as usize).unwrap_or(80)
+}
+
+/// Columns left for text in no-pager mode, after the line numbers and grid
+pub fn print_text_width(document: &Document, style: &OutputStyle) -> usize {
+    let text_offset = print_gutter_width(document, style) + if style.grid && style.numbers { 2 } else { 0 };
+    output_width().saturating_sub(text_offset).max(1)
+}
+
+/// Whether the pager can be used at all, before looking at the content
+pub fn may_page(args: &Args) -> bool {
+    // A screen rendered headlessly needs no terminal
+    if args.render_screen.is_some() {
+        return true;
+    }
+    // The TUI needs a terminal; in a pipeline fall back to printing
+    !args.no_pager && args.paging != Paging::Never && stdout().is_terminal()
+}
+
+/// Decide whether to show the pager (`--paging`, with `-P` meaning never)
+pub fn should_page(args: &Args, document: &Document, style: &OutputStyle) -> bool {
+    if !may_page(args) {
+        return false;
+    }
+    // Following a file needs the pager even while it is short
+    if args.paging == Paging::Always || args.follow || args.render_screen.is_some() || has_scripted_keys(args) {
+        return true;
+    }
+    match crossterm::terminal::size() {
+        Ok((width, height)) => !fits_on_screen(document, style, width as usize, height as usize),
+        Err(_) => true,
+    }
+}
+
+/// Whether keys are typed into the pager, which then opens however short the document is
+fn has_scripted_keys(args: &Args) -> bool {
+    args.keys.is_some() || args.keys_file.is_some()
+}
+
+/// Whether the printed document fits on a `width` x `height` screen, keeping a row for the prompt
+fn fits_on_screen(document: &Document, style: &OutputStyle, width: usize, height: usize) -> bool {
+    let width = width.max(1);
+    let text_offset = print_gutter_width(document, style) + if style.grid && style.numbers { 2 } else { 0 };
+
+    let mut rows = 0;
+    if style.grid {
+        rows += 2;
+    }
+    if style.header {
+        rows += if style.grid { 2 } else { 1 };
+    }
+
+    // Continuation chunks are printed on the same row as their line
+    let mut widths: Vec<usize> = Vec::new();
+    for line in &document.lines {
+        match widths.last_mut() {
+            Some(w) if line.continuation => *w += line.width(),
+            _ => widths.push(text_offset + line.width()),
         }
+    }
+    rows += widths.iter().map(|w| ((w + width - 1) / width).max(1)).sum::<usize>();
+
+    rows < height
+}
+
+/// Width of the line number column in printed output (0 without numbers)
+fn print_gutter_width(document: &Document, style: &OutputStyle) -> usize {
+    if !style.numbers {
+        return 0;
+    }
+    let max_line = document.line_count();
+    if max_line == 0 {
+        3
     } else {
-        0
+        let digits = (max_line as f64).log10().floor() as usize + 1;
+        digits + 2
+    }
+}
+
+/// Write a document with the decorations selected by `style`
+fn write_document<W: Write>(out: &mut W, document: &Document, style: &OutputStyle, width: usize) -> io::Result<()> {
+    let gutter_width = print_gutter_width(document, style);
+    // Columns before the text: "123 " plus "│ " with grid
+    let number_cols = gutter_width.saturating_sub(1);
+    let g = glyphs();
+    let bar = if style.grid && style.numbers { format!("{} ", g.vertical) } else { String::new() };
+
+    // Horizontal rule, joining the number bar with `joint`
+    let rule = |joint: &str| -> String {
+        if number_cols > 0 {
+            format!(
+                "{}{}{}",
+                g.horizontal.repeat(number_cols),
+                joint,
+                g.horizontal.repeat(width.saturating_sub(number_cols + 1))
+            )
+        } else {
+            g.horizontal.repeat(width)
+        }
     };
 
-    for line in &document.lines {
-        if show_line_numbers {
-            print!("{:>width$} ", line.number, width = gutter_width - 2);
+    if style.grid {
+        writeln!(out, "{}", rule(g.down_tee))?;
+    }
+    if style.header {
+        writeln!(out, "{:pad$}{}File: {}", "", bar, document.source_name, pad = number_cols)?;
+        if style.grid {
+            writeln!(out, "{}", rule(g.cross))?;
         }
-        println!("{}", line.text());
     }
 
-    stdout().flush()?;
+    for (i, line) in document.lines.iter().enumerate() {
+        // Continuation chunks are printed on the same output line
+        let text = if style.color { line.ansi_text() } else { line.text() };
+        if line.continuation {
+            write!(out, "{}", text)?;
+        } else {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            if style.numbers {
+                if line.number == 0 {
+                    // Separators and file banners have no line number
+                    write!(out, "{:width$}", "", width = gutter_width - 1)?;
+                } else {
+                    write!(out, "{:>width$} ", line.number, width = gutter_width - 2)?;
+                }
+            }
+            write!(out, "{}{}", bar, text)?;
+        }
+    }
+    if !document.lines.is_empty() {
+        writeln!(out)?;
+    }
+
+    if style.grid {
+        writeln!(out, "{}", rule(g.up_tee))?;
+    }
+
     Ok(())
 }
 
+/// Where the pager content comes from and where to open it
+#[derive(Debug, Default)]
+pub struct PagerSource {
+    /// File being viewed (for follow mode and `y`)
+    pub file_path: Option<PathBuf>,
+    /// File to delegate search match discovery to ripgrep for
+    pub ripgrep_source: Option<PathBuf>,
+    /// Line (and column) to open at, from a `file:line[:col]` argument
+    pub start: Option<Location>,
+    /// The input before grep filtering, for searching all lines
+    pub unfiltered: Option<Document>,
+    /// A database result table or CSV file, whose header is pinned
+    pub table: Option<Table>,
+    /// Whether the input is prose (markdown or plain text), for reading mode
+    pub prose: bool,
+    /// The input as it came, when it was pretty-printed (swapped in by `r`)
+    pub raw: Option<Document>,
+    /// Checked and total task list items of a markdown document, for the status bar
+    pub tasks: Option<(usize, usize)>,
+    /// Further input files, each opened in a buffer of its own
+    pub files: Vec<PagerFile>,
+    /// Stdin that is still open, appended to the document as it arrives
+    pub stream: Option<InputStream>,
+}
+
+/// An input file opened in its own pager buffer
+#[derive(Debug)]
+pub struct PagerFile {
+    pub document: Document,
+    /// File being viewed (for follow mode and `y`)
+    pub file_path: Option<PathBuf>,
+    /// File to delegate search match discovery to ripgrep for
+    pub ripgrep_source: Option<PathBuf>,
+    /// A database result table or CSV file, whose header is pinned
+    pub table: Option<Table>,
+    /// Whether the file is prose (markdown or plain text), for reading mode
+    pub prose: bool,
+    /// Checked and total task list items of a markdown document, for the status bar
+    pub tasks: Option<(usize, usize)>,
+}
+
 /// Run the pager TUI
 pub fn run_pager(
     document: Document,
     args: &Args,
+    config: &Config,
+    style: &OutputStyle,
     search_state: Option<SearchState>,
-    file_path: Option<std::path::PathBuf>,
-) -> Result<(), MatError> {
-    // Set up panic hook to restore terminal on panic
-    let original_hook = panic::take_hook();
-    panic::set_hook(Box::new(move |panic_info| {
-        let _ = disable_raw_mode();
-        let _ = execute!(stdout(), LeaveAlternateScreen);
-        original_hook(panic_info);
-    }));
-
-    // Setup terminal
-    enable_raw_mode().map_err(|e| MatError::Io {
-        source: e,
-        path: std::path::PathBuf::from("terminal"),
-    })?;
-
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen).map_err(|e| MatError::Io {
-        source: e,
-        path: std::path::PathBuf::from("terminal"),
-    })?;
-
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend).map_err(|e| MatError::Io {
-        source: e,
-        path: std::path::PathBuf::from("terminal"),
-    })?;
-
-    // Determine theme and create colors
-    let theme = get_theme(args.theme.as_deref());
-    let theme_colors = ThemeColors::for_theme(theme);
-
-    // Create app with search state and theme
-    let mut app = App::new(
-        document,
-        args.line_numbers,
-        search_state,
-        theme_colors,
-        args.ignore_case,
-        file_path,
-        args.wrap,
-        args.max_width,
-    );
-
-    // Find all matches if search is active
-    if let Some(ref mut state) = app.search_state {
-        state.find_matches(&app.document);
-    }
-
-    // Enable follow mode if requested
-    if args.follow {
-        app.toggle_follow();
-    }
-
-    // Get initial terminal size
-    let size = terminal.size().map_err(|e| MatError::Io {
+    source: PagerSource,
+) -> Result<QuitAction, MatError> {
+    // Set up (and validate the options) before taking over the terminal
+    let size = crossterm::terminal::size().map_err(|e| MatError::Io {
         source: e,
         path: std::path::PathBuf::from("terminal"),
     })?;
-    app.set_terminal_size(size.width, size.height);
-
-    // Build wrapped lines if in wrap mode
-    app.build_wrapped_lines();
+    let mut app = build_app(document, args, config, style, search_state, source, size)?;
+
+    // Restore the terminal on panic (and log it), and on every other exit via the guard
+    terminal::install_panic_hook();
+    let mut guard = TerminalGuard::enter(args.keep_screen)?;
+
+    // Whether the terminal currently reports mouse events to us
+    let mut mouse_captured = false;
+    // Doubled on every tick that brings no input, so an idle followed file barely wakes us
+    let mut input_tick = INPUT_TICK;
+
+    // Main loop (scripted keys may have quit already)
+    while !app.should_quit {
+        if app.mouse != mouse_captured {
+            guard.capture_mouse(app.mouse)?;
+            mouse_captured = app.mouse;
+        }
 
-    // Main loop
-    loop {
         // Render
-        terminal
+        guard
+            .terminal()
             .draw(|frame| {
                 ui::render(frame, &app);
             })
@@ -189,8 +355,16 @@ pub fn run_pager(
                 path: std::path::PathBuf::from("terminal"),
             })?;
 
-        // Handle events
-        if event::poll(Duration::from_millis(100)).map_err(|e| MatError::Io {
+        // Handle events, only checking for them while a search is still scanning, and
+        // waking up for lines read in the background, if any
+        let timeout = if app.search_in_progress() {
+            Duration::ZERO
+        } else if app.buffer.input_stream.is_some() {
+            input_tick
+        } else {
+            IDLE_TIMEOUT
+        };
+        if event::poll(timeout).map_err(|e| MatError::Io {
             source: e,
             path: std::path::PathBuf::from("terminal"),
         })? {
@@ -198,11 +372,10 @@ pub fn run_pager(
                 source: e,
                 path: std::path::PathBuf::from("terminal"),
             })? {
-                Event::Key(key) if key.kind == KeyEventKind::Press => {
-                    if input::handle_key(key, &mut app) {
-                        break;
-                    }
+                Event::Key(key) if key.kind == KeyEventKind::Press && input::handle_key(key, &mut app) => {
+                    break;
                 }
+                Event::Mouse(mouse) => input::handle_mouse(mouse, &mut app),
                 Event::Resize(width, height) => {
                     app.set_terminal_size(width, height);
                     // Rebuild wrapped lines on resize
@@ -212,31 +385,343 @@ pub fn run_pager(
             }
         }
 
-        // Check for follow mode updates
-        app.check_follow_updates();
+        if let Some(text) = app.pending_clipboard.take() {
+            clipboard::copy_to_clipboard(guard.terminal().backend_mut(), &text).map_err(|e| MatError::Io {
+                source: e,
+                path: std::path::PathBuf::from("terminal"),
+            })?;
+        }
 
-        if app.should_quit {
-            break;
+        if let Some((path, reference)) = app.pending_editor.take() {
+            if let Err(e) = guard.suspend(|| editor::open_in_editor(&path, &reference))? {
+                app.status_message = Some(format!("Could not start the editor: {}", e));
+            }
+            // Suspending released the mouse along with the rest of the terminal
+            mouse_captured = false;
         }
+
+        open_pending_file(&mut app, args, config);
+
+        // Collect input read in the background (follow mode, streamed stdin)
+        input_tick = if app.check_input_updates() {
+            INPUT_TICK
+        } else {
+            (input_tick * 2).min(MAX_INPUT_TICK)
+        };
+        app.continue_search();
+    }
+
+    // Restore the terminal before printing anything
+    drop(guard);
+
+    // Leave the last yanked reference on screen, in case the terminal ignores OSC 52
+    if let Some(ref reference) = app.yanked_reference {
+        println!("{}", reference);
+    }
+
+    if let Some(ref path) = args.export_bookmarks {
+        write_references(&app.bookmark_references(), path)?;
     }
+    if args.report_action {
+        write_references(&app.reported_lines(), Path::new("-"))?;
+    }
+
+    Ok(app.quit_action)
+}
 
-    // Cleanup
-    disable_raw_mode().map_err(|e| MatError::Io {
+/// Print the first screen the pager would show on a `size` terminal, without a terminal
+pub fn render_screen(
+    document: Document,
+    args: &Args,
+    config: &Config,
+    style: &OutputStyle,
+    search_state: Option<SearchState>,
+    source: PagerSource,
+    size: (u16, u16),
+) -> Result<(), MatError> {
+    let app = build_app(document, args, config, style, search_state, source, size)?;
+    let mut out = stdout().lock();
+    let written = screen::draw_screen(&app).iter().try_for_each(|line| {
+        let text = if style.color { line.ansi_text() } else { line.text().trim_end().to_string() };
+        writeln!(out, "{}", text)
+    });
+    written.map_err(|e| MatError::Io {
         source: e,
-        path: std::path::PathBuf::from("terminal"),
-    })?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen).map_err(|e| MatError::Io {
+        path: std::path::PathBuf::from("stdout"),
+    })
+}
+
+/// Set up the pager for a `size` terminal as the command line asks, before the first frame
+fn build_app(
+    document: Document,
+    args: &Args,
+    config: &Config,
+    style: &OutputStyle,
+    search_state: Option<SearchState>,
+    source: PagerSource,
+    size: (u16, u16),
+) -> Result<App, MatError> {
+    let time_step = parse_time_step(&args.time_step)?;
+    // Lines kept by an inverted grep have no captures to count
+    let grep_options = GrepOptions::from_args(args)?;
+    let grep_pattern = grep_options.as_ref().filter(|options| !options.invert).map(|options| options.pattern.clone());
+
+    // Determine theme and create colors
+    let theme = get_theme(args.theme.as_deref());
+    let mut theme_colors = ThemeColors::for_theme(theme);
+
+    // As a grep replacement, honor the user's GREP_COLORS line number color
+    if args.grep.is_some() || args.pattern_file.is_some() {
+        if let Some(color) = GrepColors::from_env().line_number {
+            theme_colors.line_number = color;
+        }
+    }
+
+    // Create app with search state and theme
+    let mut app = App::new(
+        document,
+        style.numbers,
+        search_state,
+        theme_colors,
+        args.ignore_case,
+        source.file_path,
+        args.wrap_mode(),
+        args.max_width,
+    );
+    app.set_terminal_size(size.0, size.1);
+
+    app.hscroll_step = config.hscroll_step;
+    app.wrap_width = args.wrap_width;
+    app.reading_width = config.reading_width;
+    app.key_profile = config.keys;
+    app.show_header = style.header;
+    app.show_grid = style.grid;
+    app.time_step = time_step;
+    app.report_action = args.report_action;
+    app.mouse = args.mouse;
+    app.show_breadcrumbs = args.breadcrumbs;
+    app.grep_pattern = grep_pattern;
+    #[cfg(feature = "spellcheck")]
+    {
+        app.dictionary = Dictionary::from_args(args)?.map(std::rc::Rc::new);
+    }
+
+    app.buffer.ripgrep_source = source.ripgrep_source;
+    // Lines appended while following go through the same filter
+    if let (Some(options), Some(unfiltered)) = (grep_options, &source.unfiltered) {
+        app.buffer.grep_stream = Some(GrepStream::new(options, unfiltered));
+    }
+    app.buffer.scope_document = source.unfiltered;
+    if source.raw.is_some() {
+        app.status_message = Some("Pretty-printed JSON (r shows it as it came)".to_string());
+    }
+    app.buffer.raw_document = source.raw;
+    app.buffer.tasks = source.tasks;
+    configure_buffer(&mut app, args, source.table, source.prose);
+    start_at_end(&mut app, args, config);
+
+    // Every further file gets a buffer of its own, set up like the first
+    for file in source.files {
+        let mut search_state = SearchState::from_args(args)?;
+        if let Some(ref mut state) = search_state {
+            state.ripgrep_source = file.ripgrep_source.clone();
+        }
+        let mut buffer = Buffer::new(file.document, style.numbers, search_state, file.file_path, args.wrap_mode());
+        buffer.ripgrep_source = file.ripgrep_source;
+        app.open_buffer(buffer);
+        app.activate_buffer(app.buffer_count() - 1);
+        app.buffer.tasks = file.tasks;
+        configure_buffer(&mut app, args, file.table, file.prose);
+        start_at_end(&mut app, args, config);
+    }
+    app.activate_buffer(0);
+    // With several files, a search from the command line continues across them
+    app.global_search = app.buffer_count() > 1 && app.buffer.search_state.is_some();
+
+    if let Some(stream) = source.stream {
+        app.stream_input(stream);
+    }
+
+    // Enable follow mode if requested
+    if args.follow {
+        app.toggle_follow();
+    }
+    if let Some(path) = &args.log_output {
+        app.buffer.session_log = Some(SessionLog::open(path, args.log_timestamps)?);
+    }
+
+    // Build wrapped lines if in wrap mode
+    app.build_wrapped_lines();
+
+    if let Some(location) = source.start {
+        app.go_to_location(location.line, location.column);
+    }
+
+    for key in scripted_keys(args)? {
+        if input::handle_key(key, &mut app) {
+            break;
+        }
+        open_pending_file(&mut app, args, config);
+    }
+
+    Ok(app)
+}
+
+/// Scroll the active buffer to its end with `--start-at-end`, or for a file type configured to
+///
+/// A location to start at (`+N`, `file:line`) still wins, and `-f` goes to the end anyway.
+fn start_at_end(app: &mut App, args: &Args, config: &Config) {
+    let configured = app.buffer.file_path.as_deref().is_some_and(|path| config.starts_at_end(path));
+    if args.start_at_end || configured {
+        app.build_wrapped_lines();
+        app.go_to_bottom();
+    }
+}
+
+/// Keys to type into the pager from `--keys` or `--keys-file`
+fn scripted_keys(args: &Args) -> Result<Vec<KeyEvent>, MatError> {
+    let spec = match (&args.keys, &args.keys_file) {
+        (Some(keys), _) => keys.clone(),
+        (None, Some(path)) => std::fs::read_to_string(path).map_err(|source| MatError::Io {
+            source,
+            path: path.clone(),
+        })?,
+        (None, None) => return Ok(Vec::new()),
+    };
+    keys::parse_keys(&spec).map_err(|message| MatError::InvalidKeys { message })
+}
+
+/// Write `path:line:text` references one per line to `path`, or to stdout for `-`
+fn write_references(references: &[String], path: &Path) -> Result<(), MatError> {
+    let mut text = String::new();
+    for reference in references {
+        text.push_str(reference);
+        text.push('\n');
+    }
+    let result = if path.as_os_str() == "-" {
+        stdout().lock().write_all(text.as_bytes())
+    } else {
+        std::fs::write(path, text)
+    };
+    result.map_err(|e| MatError::Io {
         source: e,
-        path: std::path::PathBuf::from("terminal"),
-    })?;
+        path: path.to_path_buf(),
+    })
+}
 
-    Ok(())
+/// Open the file of a path selected in the pager in a new buffer, set up like the others
+///
+/// The file is shown as syntax highlighted source, whatever its kind, or as a
+/// hex dump with `--hex`.
+fn open_pending_file(app: &mut App, args: &Args, config: &Config) {
+    let Some((path, reference)) = app.pending_open.take() else {
+        return;
+    };
+    let content = match load_content(InputSource::File(path.clone()), args, &config.preprocessors) {
+        Ok(content) => content,
+        Err(e) => {
+            app.status_message = Some(e.to_string());
+            return;
+        }
+    };
+    let binary = content.binary.is_some();
+    let mut document = match content.binary {
+        Some(bytes) => hexdump(&bytes, content.source_name),
+        None => Document::from_text(&content.text, content.source_name, content.encoding),
+    };
+    if !args.no_highlight && !binary {
+        let language = args.language.clone().or_else(|| linguist_language(&path));
+        apply_syntax_highlight(&mut document, language.as_deref(), get_theme(args.theme.as_deref()));
+        apply_path_highlight(&mut document);
+    }
+
+    let buffer = Buffer::new(document, app.buffer.show_line_numbers, None, Some(path), args.wrap_mode());
+    app.open_buffer(buffer);
+    app.activate_buffer(app.buffer_count() - 1);
+    configure_buffer(app, args, None, false);
+    app.go_to_location(reference.line, reference.column);
+}
+
+/// Apply the command line options to the active buffer
+fn configure_buffer(app: &mut App, args: &Args, table: Option<Table>, prose: bool) {
+    app.buffer.filters.colors = GrepColors::from_env();
+    if let Some(table) = table {
+        app.open_table(table);
+    }
+    if args.search_scope == SearchScope::All {
+        app.toggle_search_scope();
+    }
+    app.buffer.prose = prose;
+    app.buffer.ansi_style = args.ansi.then(SpanStyle::default);
+    for query in &args.panes {
+        app.add_pane(query);
+    }
+
+    if args.relative_numbers {
+        app.toggle_relative_numbers();
+    }
+
+    // View transforms (this also highlights and finds the matches of an active search)
+    app.buffer.squeeze = args.squeeze_repeated;
+    app.buffer.timestamp_mode = args.timestamps;
+    #[cfg(feature = "spellcheck")]
+    {
+        app.buffer.spellcheck = prose && app.dictionary.is_some();
+    }
+    app.rebuild_document();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn render(document: &Document, style: &OutputStyle) -> String {
+        let mut out = Vec::new();
+        write_document(&mut out, document, style, 12).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_fits_on_screen() {
+        let doc = Document::from_text("a\nb\nc", "f.txt".to_string(), "UTF-8".to_string());
+        assert!(fits_on_screen(&doc, &OutputStyle::default(), 80, 4));
+        assert!(!fits_on_screen(&doc, &OutputStyle::default(), 80, 3));
+
+        // Long lines wrap onto several rows
+        let doc = Document::from_text(&"x".repeat(100), "f.txt".to_string(), "UTF-8".to_string());
+        assert!(!fits_on_screen(&doc, &OutputStyle::default(), 40, 3));
+        assert!(fits_on_screen(&doc, &OutputStyle::default(), 40, 4));
+    }
+
+    #[test]
+    fn test_write_document_plain() {
+        let doc = Document::from_text("a\nb", "f.txt".to_string(), "UTF-8".to_string());
+        assert_eq!(render(&doc, &OutputStyle::default()), "a\nb\n");
+    }
+
+    #[test]
+    fn test_write_document_full_style() {
+        let doc = Document::from_text("a\nb", "f.txt".to_string(), "UTF-8".to_string());
+        let style = OutputStyle::parse("full").unwrap();
+        let expected = "\
+──┬─────────
+  │ File: f.txt
+──┼─────────
+1 │ a
+2 │ b
+──┴─────────
+";
+        assert_eq!(render(&doc, &style), expected);
+    }
+
+    #[test]
+    fn test_write_references_to_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let references = vec!["a.log:3:error".to_string(), "b.log:10:retry".to_string()];
+        write_references(&references, file.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(file.path()).unwrap(), "a.log:3:error\nb.log:10:retry\n");
+    }
+
     #[test]
     fn test_parse_line_range_full() {
         assert_eq!(parse_line_range("10:20", 100).unwrap(), (10, 20));
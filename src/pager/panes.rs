@@ -0,0 +1,76 @@
+//! Live filter panes
+//!
+//! `:pane PATTERN` splits the view: below the document, each pane shows the
+//! latest lines matching its pattern and keeps up with a followed file or
+//! streamed input, so errors stay in sight while the full stream scrolls by.
+
+use crate::display::{Document, Line};
+use crate::theme::GrepColors;
+
+use super::filters::PagerFilter;
+
+/// A pane showing the lines of the input that pass `filter`
+#[derive(Debug)]
+pub struct Pane {
+    /// Filter the input is seen through
+    pub filter: PagerFilter,
+    /// The lines that passed, with their matches highlighted
+    pub document: Document,
+}
+
+impl Pane {
+    /// Show the lines of `source` that pass `filter`
+    pub fn new(filter: PagerFilter, source: &Document, colors: &GrepColors) -> Self {
+        let document = filter.apply(source, colors);
+        Self { filter, document }
+    }
+
+    /// Add the lines appended to the input that pass the filter
+    pub fn append(&mut self, lines: &[Line], colors: &GrepColors) {
+        let appended = Document {
+            lines: lines.to_vec(),
+            max_line_width: 0,
+            source_name: self.document.source_name.clone(),
+            encoding: self.document.encoding.clone(),
+        };
+        for line in self.filter.apply(&appended, colors).lines {
+            self.document.max_line_width = self.document.max_line_width.max(line.width());
+            self.document.lines.push(line);
+        }
+    }
+
+    /// Number of input lines shown (chunks of long lines count once)
+    pub fn line_count(&self) -> usize {
+        self.document.lines.iter().filter(|l| l.number != 0 && !l.continuation).count()
+    }
+
+    /// The last `rows` lines, which the pane shows
+    pub fn tail(&self, rows: usize) -> &[Line] {
+        let lines = &self.document.lines;
+        &lines[lines.len().saturating_sub(rows)..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_pane_keeps_up_with_appended_lines() {
+        let source = Document::from_text("INFO start\nERROR disk\nINFO ok", "app.log".to_string(), "UTF-8".to_string());
+        let filter = PagerFilter::Grep {
+            query: "ERROR".to_string(),
+            pattern: Regex::new("ERROR").unwrap(),
+        };
+        let colors = GrepColors::default();
+        let mut pane = Pane::new(filter, &source, &colors);
+        assert_eq!(pane.line_count(), 1);
+
+        pane.append(&[Line::plain(4, "INFO again"), Line::plain(5, "ERROR net")], &colors);
+        assert_eq!(pane.line_count(), 2);
+        let tail: Vec<(usize, String)> = pane.tail(1).iter().map(|l| (l.number, l.text())).collect();
+        assert_eq!(tail, vec![(5, "ERROR net".to_string())]);
+        assert_eq!(pane.tail(10).len(), 2);
+    }
+}
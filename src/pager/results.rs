@@ -0,0 +1,180 @@
+//! Results panel of a search across all open files
+//!
+//! `:search PATTERN` lists every matching line of every buffer, grouped under
+//! the name of its file. One result is selected at a time; Enter jumps to it,
+//! switching buffers if needed.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// A matching line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// Index of the buffer the line is in
+    pub buffer: usize,
+    /// Index of the line's first match in the buffer's search state
+    pub match_index: usize,
+    pub line_number: usize,
+    pub text: String,
+}
+
+/// Matching lines of all buffers, with a selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResults {
+    pub query: String,
+    /// File name of each buffer
+    pub files: Vec<String>,
+    /// Results in buffer order, then line order
+    pub results: Vec<SearchResult>,
+    /// Index of the selected result
+    pub selected: usize,
+}
+
+/// A row of the panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    /// File name and match count above a group
+    File(usize),
+    /// Index of a result
+    Result(usize),
+}
+
+impl SearchResults {
+    pub fn new(query: String, files: Vec<String>, results: Vec<SearchResult>) -> Self {
+        Self {
+            query,
+            files,
+            results,
+            selected: 0,
+        }
+    }
+
+    /// Move the selection down one result
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Move the selection up one result
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// The selected result
+    pub fn selected(&self) -> Option<&SearchResult> {
+        self.results.get(self.selected)
+    }
+
+    /// Number of files with at least one result
+    pub fn file_count(&self) -> usize {
+        self.rows().iter().filter(|row| matches!(row, Row::File(_))).count()
+    }
+
+    /// Panel rows: each file's header followed by its results
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::with_capacity(self.results.len() + self.files.len());
+        for (i, result) in self.results.iter().enumerate() {
+            if i == 0 || self.results[i - 1].buffer != result.buffer {
+                rows.push(Row::File(result.buffer));
+            }
+            rows.push(Row::Result(i));
+        }
+        rows
+    }
+}
+
+/// Render the results as a centered overlay, scrolled to keep the selection visible
+pub fn render_results(frame: &mut Frame, results: &SearchResults, highlight: Color, area: Rect) {
+    let width = area.width.saturating_sub(4).max(area.width.min(20));
+    let rows = results.rows();
+    let height = (rows.len() as u16 + 2).min(area.height.saturating_sub(2).max(3)).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let title = format!(
+        " {}: {} lines in {} files ",
+        results.query,
+        results.results.len(),
+        results.file_count()
+    );
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let visible = inner.height as usize;
+    let selected_row = rows
+        .iter()
+        .position(|row| *row == Row::Result(results.selected))
+        .unwrap_or(0);
+    let first = (selected_row + 1).saturating_sub(visible);
+
+    let count_in = |buffer: usize| results.results.iter().filter(|r| r.buffer == buffer).count();
+    let lines: Vec<RatatuiLine> = rows
+        .iter()
+        .skip(first)
+        .take(visible)
+        .map(|row| match *row {
+            Row::File(buffer) => RatatuiLine::from(Span::styled(
+                format!("{} ({})", results.files[buffer], count_in(buffer)),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Row::Result(i) => {
+                let result = &results.results[i];
+                let style = if i == results.selected {
+                    Style::default().bg(highlight)
+                } else {
+                    Style::default()
+                };
+                RatatuiLine::from(Span::styled(format!("{:>6}: {}", result.line_number, result.text), style))
+            }
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(buffer: usize, line_number: usize) -> SearchResult {
+        SearchResult {
+            buffer,
+            match_index: line_number - 1,
+            line_number,
+            text: format!("line {}", line_number),
+        }
+    }
+
+    #[test]
+    fn test_rows_group_by_file() {
+        let files = vec!["a.log".to_string(), "b.log".to_string(), "c.log".to_string()];
+        let results = SearchResults::new("err".to_string(), files, vec![result(0, 1), result(0, 4), result(2, 2)]);
+
+        assert_eq!(
+            results.rows(),
+            vec![Row::File(0), Row::Result(0), Row::Result(1), Row::File(2), Row::Result(2)]
+        );
+        assert_eq!(results.file_count(), 2);
+    }
+
+    #[test]
+    fn test_selection_stays_in_bounds() {
+        let mut results = SearchResults::new("x".to_string(), vec!["a".to_string()], vec![result(0, 1), result(0, 2)]);
+        results.select_prev();
+        assert_eq!(results.selected().map(|r| r.line_number), Some(1));
+        results.select_next();
+        results.select_next();
+        assert_eq!(results.selected().map(|r| r.line_number), Some(2));
+    }
+}
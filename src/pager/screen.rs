@@ -0,0 +1,114 @@
+//! Headless rendering (`--render-screen WxH`)
+//!
+//! Draws the first frame the pager would show on a terminal of the given size
+//! and prints it, without a terminal: for golden-file tests of the layout and
+//! for scripts capturing the first page of a document.
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer as ScreenBuffer;
+use ratatui::style::{Color, Modifier};
+use ratatui::Terminal;
+use unicode_width::UnicodeWidthStr;
+
+use crate::display::{Line, SpanStyle, StyledSpan};
+
+use super::app::App;
+use super::ui;
+
+/// Draw the pager's screen for `app` at its terminal size, one line per row
+pub fn draw_screen(app: &App) -> Vec<Line> {
+    let (width, height) = app.terminal_size;
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("the test backend can't fail");
+    let frame = terminal.draw(|frame| ui::render(frame, app)).expect("the test backend can't fail");
+    screen_lines(frame.buffer)
+}
+
+/// The rows of a drawn screen as styled lines
+fn screen_lines(screen: &ScreenBuffer) -> Vec<Line> {
+    let width = screen.area.width as usize;
+    screen
+        .content
+        .chunks(width.max(1))
+        .enumerate()
+        .map(|(row, cells)| {
+            let mut spans: Vec<StyledSpan> = Vec::new();
+            // The cells a wide character covers after its own hold only filler
+            let mut covered = 0;
+            for cell in cells {
+                if covered > 0 {
+                    covered -= 1;
+                    continue;
+                }
+                covered = cell.symbol().width().saturating_sub(1);
+                let style = span_style(cell.fg, cell.bg, cell.modifier);
+                match spans.last_mut() {
+                    Some(last) if last.style == style => last.text.push_str(cell.symbol()),
+                    _ => spans.push(StyledSpan::new(cell.symbol(), style)),
+                }
+            }
+            // Trailing blanks carry nothing a reader would miss
+            if let Some(last) = spans.last_mut().filter(|span| span.style.is_plain()) {
+                last.text.truncate(last.text.trim_end().len());
+            }
+            spans.retain(|span| !span.text.is_empty());
+            let mut line = Line::plain(row + 1, "");
+            line.spans = spans;
+            line
+        })
+        .collect()
+}
+
+/// Span style of a screen cell
+///
+/// Reversed cells swap their colors, with the terminal's default colors taken
+/// as black on gray.
+fn span_style(fg: Color, bg: Color, modifier: Modifier) -> SpanStyle {
+    let color = |color: Color| (color != Color::Reset).then_some(color);
+    let (fg, bg) = if modifier.contains(Modifier::REVERSED) {
+        (Some(color(bg).unwrap_or(Color::Black)), Some(color(fg).unwrap_or(Color::Gray)))
+    } else {
+        (color(fg), color(bg))
+    };
+    SpanStyle {
+        fg,
+        bg,
+        bold: modifier.contains(Modifier::BOLD),
+        italic: modifier.contains(Modifier::ITALIC),
+        underline: modifier.contains(Modifier::UNDERLINED),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::WrapMode;
+    use crate::display::Document;
+    use crate::theme::{Theme, ThemeColors};
+
+    #[test]
+    fn test_draw_screen() {
+        let doc = Document::from_text("first\nsecond\nthird", "notes.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, true, None, ThemeColors::for_theme(Theme::Dark), false, None, WrapMode::None, 200);
+        app.set_terminal_size(30, 3);
+
+        let lines = draw_screen(&app);
+        let text: Vec<String> = lines.iter().map(|l| l.text()).collect();
+        assert_eq!(text[0], "1  first");
+        assert_eq!(text[1], "2  second");
+        assert_eq!(lines.len(), 3);
+        // The status bar is reversed
+        assert!(lines[2].spans.iter().any(|span| span.style.bg.is_some()));
+    }
+
+    #[test]
+    fn test_draw_wide_characters() {
+        let doc = Document::from_text("日本語 text
+한국어", "cjk.txt".to_string(), "UTF-8".to_string());
+        let mut app = App::new(doc, false, None, ThemeColors::for_theme(Theme::Dark), false, None, WrapMode::None, 200);
+        app.set_terminal_size(30, 3);
+
+        let lines = draw_screen(&app);
+        assert_eq!(lines[0].text(), "日本語 text");
+        assert_eq!(lines[1].text(), "한국어");
+    }
+}
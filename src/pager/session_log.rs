@@ -0,0 +1,70 @@
+//! Session log of followed output (`--log-output FILE`)
+//!
+//! Every line that arrives while following a file is appended to the log, so
+//! watching a service with `mat -f` leaves a record to go back to later. The
+//! log is only ever appended to; each session adds to what earlier ones wrote.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::Local;
+
+use crate::error::MatError;
+
+/// An open session log
+#[derive(Debug)]
+pub struct SessionLog {
+    file: File,
+    /// Prefix each line with the local time it arrived at
+    timestamps: bool,
+}
+
+impl SessionLog {
+    /// Open (or create) the log at `path` for appending
+    pub fn open(path: &Path, timestamps: bool) -> Result<Self, MatError> {
+        let file = OpenOptions::new().create(true).append(true).open(path).map_err(|source| MatError::Io {
+            source,
+            path: path.to_path_buf(),
+        })?;
+        Ok(Self { file, timestamps })
+    }
+
+    /// Append newly arrived lines
+    ///
+    /// Write errors are ignored: a full disk must not stop the file from being followed.
+    pub fn append(&mut self, lines: &[String]) {
+        let mut text = String::new();
+        let arrived = self.timestamps.then(|| Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string());
+        for line in lines {
+            if let Some(arrived) = &arrived {
+                text.push_str(arrived);
+                text.push(' ');
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+        let _ = self.file.write_all(text.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_log_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.log");
+        std::fs::write(&path, "earlier\n").unwrap();
+
+        SessionLog::open(&path, false).unwrap().append(&["one".to_string(), "two".to_string()]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "earlier\none\ntwo\n");
+
+        SessionLog::open(&path, true).unwrap().append(&["three".to_string()]);
+        let last = std::fs::read_to_string(&path).unwrap().lines().last().unwrap().to_string();
+        let (time, text) = last.split_once(' ').unwrap();
+        assert_eq!(text, "three");
+        assert!(chrono::DateTime::parse_from_rfc3339(time).is_ok(), "{}", time);
+    }
+}
@@ -0,0 +1,221 @@
+//! Terminal setup with guaranteed restoration
+//!
+//! [`TerminalGuard`] puts the terminal in raw mode on the alternate screen
+//! (where the terminal has one, and unless `--keep-screen` asks for the main
+//! screen) and restores it when dropped, so every exit path (normal quit, `?` errors, and
+//! panics via [`install_panic_hook`]) leaves a usable shell behind. Panics are
+//! also appended with a backtrace to `mat/crash.log` under `$XDG_STATE_HOME`
+//! (falling back to `~/.local/state`).
+
+use std::backtrace::Backtrace;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, stdout, Stdout, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crossterm::{
+    cursor::{MoveTo, Show},
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::error::MatError;
+use crate::logging;
+use crate::sandbox::exec_allowed;
+
+/// Terminal types without an alternate screen
+const NO_ALTERNATE_SCREEN: &[&str] = &["linux", "vt100", "vt102", "vt220", "cons25", "ansi", "dumb"];
+
+/// Screen the pager draws on, so restoring (at most once) knows what to undo
+static SCREEN: AtomicU8 = AtomicU8::new(NO_SCREEN);
+const NO_SCREEN: u8 = 0;
+const MAIN_SCREEN: u8 = 1;
+const ALTERNATE_SCREEN: u8 = 2;
+/// The main screen, left as the pager drew it (`--keep-screen`)
+const KEPT_SCREEN: u8 = 3;
+
+/// Raw-mode alternate-screen terminal, restored on drop
+pub struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    /// Whether to draw on the main screen and leave the last page there
+    keep_screen: bool,
+}
+
+impl TerminalGuard {
+    /// Enter raw mode and, unless the terminal lacks one or `keep_screen` is set, the alternate screen
+    pub fn enter(keep_screen: bool) -> Result<Self, MatError> {
+        enable_raw_mode().map_err(terminal_error)?;
+        // From here on a failure must undo what was already set up
+        let guard_on_error = |e: io::Error| {
+            restore();
+            terminal_error(e)
+        };
+        enter_screen(keep_screen).map_err(guard_on_error)?;
+        let terminal = Terminal::new(CrosstermBackend::new(stdout())).map_err(guard_on_error)?;
+        Ok(Self { terminal, keep_screen })
+    }
+
+    /// The ratatui terminal
+    pub fn terminal(&mut self) -> &mut Terminal<CrosstermBackend<Stdout>> {
+        &mut self.terminal
+    }
+
+    /// Hand the terminal to `run` (an editor), then take it back and redraw from scratch
+    pub fn suspend<T>(&mut self, run: impl FnOnce() -> T) -> Result<T, MatError> {
+        restore();
+        let result = run();
+        enable_raw_mode().map_err(terminal_error)?;
+        enter_screen(self.keep_screen).map_err(terminal_error)?;
+        self.terminal.clear().map_err(terminal_error)?;
+        Ok(result)
+    }
+
+    /// Start or stop receiving mouse events (the terminal's own selection works while stopped)
+    pub fn capture_mouse(&mut self, capture: bool) -> Result<(), MatError> {
+        let result = if capture {
+            execute!(stdout(), EnableMouseCapture)
+        } else {
+            execute!(stdout(), DisableMouseCapture)
+        };
+        result.map_err(terminal_error)
+    }
+}
+
+/// Switch to the alternate screen, unless the terminal lacks one or `keep_screen` is set
+fn enter_screen(keep_screen: bool) -> io::Result<()> {
+    logging::hold();
+    if keep_screen {
+        SCREEN.store(KEPT_SCREEN, Ordering::Relaxed);
+        Ok(())
+    } else if has_alternate_screen(std::env::var("TERM").ok().as_deref()) {
+        SCREEN.store(ALTERNATE_SCREEN, Ordering::Relaxed);
+        execute!(stdout(), EnterAlternateScreen)
+    } else {
+        SCREEN.store(MAIN_SCREEN, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leave raw mode, mouse capture and the alternate screen, ignoring errors
+///
+/// Without an alternate screen the pager drew over the shell, so the screen
+/// is cleared instead. A kept screen stays, with the prompt below it. Log
+/// messages held back meanwhile are written after.
+fn restore() {
+    let _ = disable_raw_mode();
+    let _ = match SCREEN.swap(NO_SCREEN, Ordering::Relaxed) {
+        ALTERNATE_SCREEN => execute!(stdout(), DisableMouseCapture, LeaveAlternateScreen, Show),
+        MAIN_SCREEN => execute!(stdout(), DisableMouseCapture, Clear(ClearType::All), MoveTo(0, 0), Show),
+        KEPT_SCREEN => {
+            let bottom = crossterm::terminal::size().map_or(0, |(_, rows)| rows.saturating_sub(1));
+            execute!(stdout(), DisableMouseCapture, MoveTo(0, bottom), Show).and_then(|_| writeln!(stdout()))
+        }
+        _ => execute!(stdout(), Show),
+    };
+    logging::release();
+}
+
+/// Whether a terminal of type `term` has an alternate screen
+fn has_alternate_screen(term: Option<&str>) -> bool {
+    !term.is_some_and(|term| NO_ALTERNATE_SCREEN.contains(&term))
+}
+
+/// Error for a failed terminal operation
+fn terminal_error(source: io::Error) -> MatError {
+    MatError::Io {
+        source,
+        path: PathBuf::from("terminal"),
+    }
+}
+
+/// Restore the terminal and write a crash log before the default panic output
+pub fn install_panic_hook() {
+    let original_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        restore();
+        original_hook(panic_info);
+
+        if !exec_allowed() {
+            return;
+        }
+        let report = format!("{}\n\nbacktrace:\n{}", panic_info, Backtrace::force_capture());
+        let written = crash_log_path(std::env::var_os("XDG_STATE_HOME"), std::env::var_os("HOME"))
+            .and_then(|path| append_crash_report(&path, &report).ok().map(|_| path));
+        if let Some(path) = written {
+            eprintln!("mat: crash report written to {}", path.display());
+        }
+    }));
+}
+
+/// Location of the crash log from `$XDG_STATE_HOME` or `$HOME`
+fn crash_log_path(state_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let state_dir = state_home
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| PathBuf::from(home).join(".local").join("state")))?;
+    Some(state_dir.join("mat").join("crash.log"))
+}
+
+/// Append a timestamped report to the crash log, creating its directory
+fn append_crash_report(path: &Path, report: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(
+        file,
+        "=== mat {} crashed at {} ===\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().to_rfc3339(),
+        report
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_alternate_screen() {
+        assert!(has_alternate_screen(Some("xterm-256color")));
+        assert!(has_alternate_screen(None));
+        assert!(!has_alternate_screen(Some("vt100")));
+        assert!(!has_alternate_screen(Some("linux")));
+    }
+
+    #[test]
+    fn test_crash_log_path() {
+        assert_eq!(
+            crash_log_path(Some("/state".into()), Some("/home/u".into())),
+            Some(PathBuf::from("/state/mat/crash.log"))
+        );
+        assert_eq!(
+            crash_log_path(None, Some("/home/u".into())),
+            Some(PathBuf::from("/home/u/.local/state/mat/crash.log"))
+        );
+        assert_eq!(crash_log_path(None, None), None);
+    }
+
+    #[test]
+    fn test_append_crash_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mat").join("crash.log");
+
+        append_crash_report(&path, "first").unwrap();
+        append_crash_report(&path, "second").unwrap();
+
+        let log = fs::read_to_string(&path).unwrap();
+        assert!(log.contains("first") && log.contains("second"));
+        assert_eq!(log.matches("=== mat").count(), 2);
+    }
+}
@@ -0,0 +1,142 @@
+//! Table of contents of a markdown document (`O`, `:toc`)
+//!
+//! Lists the headings recorded while rendering, indented by level, with the
+//! section the top line is in selected. Enter scrolls the document to the
+//! selected heading.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line as RatatuiLine, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::display::Document;
+
+/// A heading of the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// Index of the heading's line in the document
+    pub line_idx: usize,
+    pub level: u8,
+    pub title: String,
+}
+
+/// The headings of a document, with a selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOfContents {
+    pub entries: Vec<TocEntry>,
+    /// Index of the selected entry
+    pub selected: usize,
+}
+
+impl TableOfContents {
+    /// The headings of `document`, with the last one at or above line `anchor` selected
+    pub fn new(document: &Document, anchor: usize) -> Self {
+        let entries: Vec<TocEntry> = document
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(line_idx, line)| {
+                line.heading.as_ref().map(|heading| TocEntry {
+                    line_idx,
+                    level: heading.level,
+                    title: heading.title.clone(),
+                })
+            })
+            .collect();
+        let selected = entries.iter().rposition(|entry| entry.line_idx <= anchor).unwrap_or(0);
+        Self { entries, selected }
+    }
+
+    /// Move the selection down one heading
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Move the selection up one heading
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// The selected heading
+    pub fn selected(&self) -> Option<&TocEntry> {
+        self.entries.get(self.selected)
+    }
+}
+
+/// Render the table of contents as a centered overlay, scrolled to keep the selection visible
+pub fn render_toc(frame: &mut Frame, toc: &TableOfContents, highlight: Color, area: Rect) {
+    // Top level headings stay flush left however deep the document starts
+    let top = toc.entries.iter().map(|entry| entry.level).min().unwrap_or(1);
+    let rows: Vec<String> = toc
+        .entries
+        .iter()
+        .map(|entry| format!("{}{}", "  ".repeat((entry.level - top) as usize), entry.title))
+        .collect();
+
+    let longest = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0) as u16;
+    let width = (longest + 4).max(24).min(area.width);
+    let height = (rows.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    let block = Block::default().borders(Borders::ALL).title(" Contents ");
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let visible = inner.height as usize;
+    let first = (toc.selected + 1).saturating_sub(visible);
+    let lines: Vec<RatatuiLine> = rows
+        .into_iter()
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(i, row)| {
+            let style = if i == toc.selected { Style::default().bg(highlight) } else { Style::default() };
+            RatatuiLine::from(Span::styled(row, style))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::{render_markdown, MarkdownOptions};
+
+    #[test]
+    fn test_toc_selects_current_section() {
+        let md = "# Guide\n\nIntro.\n\n## Install\n\nText.\n\n### Linux\n\nMore.\n\n## Usage\n";
+        let doc = render_markdown(md, "guide.md".to_string(), &MarkdownOptions::default());
+        let titles = |toc: &TableOfContents| toc.entries.iter().map(|e| (e.level, e.title.clone())).collect::<Vec<_>>();
+
+        let toc = TableOfContents::new(&doc, 0);
+        assert_eq!(
+            titles(&toc),
+            vec![
+                (1, "Guide".to_string()),
+                (2, "Install".to_string()),
+                (3, "Linux".to_string()),
+                (2, "Usage".to_string())
+            ]
+        );
+        assert_eq!(toc.selected, 0);
+
+        // Below "Linux", before "Usage"
+        let linux = toc.entries[2].line_idx;
+        let mut toc = TableOfContents::new(&doc, linux + 2);
+        assert_eq!(toc.selected().map(|e| e.title.as_str()), Some("Linux"));
+        toc.select_next();
+        toc.select_next();
+        assert_eq!(toc.selected().map(|e| e.title.as_str()), Some("Usage"));
+    }
+}
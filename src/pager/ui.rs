@@ -2,82 +2,292 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line as RatatuiLine, Span},
-    widgets::Paragraph,
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
+use std::borrow::Cow;
+
 use unicode_width::UnicodeWidthStr;
 
 use crate::cli::WrapMode;
-use crate::display::Line;
+use crate::display::glyphs::glyphs;
+use crate::display::{Line, SpanStyle};
+use crate::highlight::restyle_range;
 
 use super::app::{App, Mode, WrappedLine};
+use super::buffers::render_buffer_list;
+use super::gutter::render_annotations;
+use super::diagnostics::render_diagnostics;
+use super::frequency::render_frequencies;
+use super::help::render_help;
+use super::histogram::render_histogram;
+use super::results::render_results;
+use super::toc::render_toc;
 
 /// Render the main UI
 pub fn render(frame: &mut Frame, app: &App) {
     let size = frame.area();
 
-    // Layout: content area + status bar
+    // Layout: optional header + pinned lines + breadcrumb + content area + status bar
+    let header_height = if app.show_header { 1 } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(1),    // Content area
-            Constraint::Length(1), // Status bar
+            Constraint::Length(header_height),                         // Header
+            Constraint::Length(app.buffer.sticky_lines.len() as u16), // Pinned lines
+            Constraint::Length(u16::from(app.show_breadcrumbs)),      // Breadcrumb
+            Constraint::Min(1),                                        // Content area
+            Constraint::Length(app.panes_height() as u16),             // Filter panes
+            Constraint::Length(1),                                     // Status bar
         ])
         .split(size);
 
-    render_content(frame, app, chunks[0]);
-    render_status_bar(frame, app, chunks[1]);
+    if app.show_header {
+        render_header(frame, app, chunks[0]);
+    }
+    if !app.buffer.sticky_lines.is_empty() {
+        render_sticky_lines(frame, app, chunks[1]);
+    }
+    if app.show_breadcrumbs {
+        render_breadcrumbs(frame, app, chunks[2]);
+    }
+    render_content(frame, app, chunks[3]);
+    if let Some(range) = app.selected_range() {
+        render_mouse_selection(frame, app, range, chunks[3]);
+    }
+    if !app.buffer.panes.is_empty() {
+        render_panes(frame, app, chunks[4]);
+    }
+    render_status_bar(frame, app, chunks[5]);
+
+    if let Some(ref histogram) = app.histogram {
+        render_histogram(frame, histogram, app.theme_colors.search_bg, chunks[3]);
+    }
+    if let Some(ref diagnostics) = app.diagnostics {
+        render_diagnostics(frame, diagnostics, chunks[3]);
+    }
+    if let Some(ref frequencies) = app.frequencies {
+        render_frequencies(frame, frequencies, app.theme_colors.search_bg, chunks[3]);
+    }
+    if let Some(ref results) = app.search_results {
+        render_results(frame, results, app.theme_colors.match_line_bg, chunks[3]);
+    }
+    if let Some(ref toc) = app.toc {
+        render_toc(frame, toc, app.theme_colors.match_line_bg, chunks[3]);
+    }
+    if let Some(ref list) = app.buffer_list {
+        render_buffer_list(frame, list, app.theme_colors.match_line_bg, chunks[3]);
+    }
+    if let Mode::Help { scroll } = app.mode {
+        render_help(frame, scroll, app.theme_colors.search_bg, chunks[3]);
+    }
+}
+
+/// Render the filter panes, each showing its latest lines under a rule with its pattern
+fn render_panes(frame: &mut Frame, app: &App, area: Rect) {
+    let count = app.buffer.panes.len() as u32;
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, count); count as usize])
+        .split(area);
+    let gutter_width = app.gutter_width();
+    let gutter_style = Style::default().fg(app.theme_colors.line_number);
+
+    for (pane, &area) in app.buffer.panes.iter().zip(areas.iter()) {
+        let title = format!(" {} ({} lines) ", pane.filter.label(), pane.line_count());
+        let block = Block::default().borders(Borders::TOP).border_style(gutter_style).title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let lines = pane.tail(inner.height as usize);
+        let content_width = (inner.width as usize).saturating_sub(gutter_width + app.annotation_width());
+        let (_, gutter_area, text_area) = split_columns(app, inner, gutter_width);
+        if let Some(gutter_area) = gutter_area {
+            // The absolute line numbers, which tell where in the input a line is
+            let numbers: Vec<RatatuiLine> = lines
+                .iter()
+                .map(|line| {
+                    let number = if line.continuation || line.number == 0 { String::new() } else { line.number.to_string() };
+                    let text = format!("{:>width$} {}", number, gutter_edge(app), width = gutter_width - 2);
+                    RatatuiLine::from(Span::styled(text, gutter_style))
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(numbers), gutter_area);
+        }
+        render_lines(frame, app, lines, content_width, text_area);
+    }
+}
+
+/// Reverse the rows of the lines selected by dragging the mouse
+fn render_mouse_selection(frame: &mut Frame, app: &App, (first, last): (usize, usize), area: Rect) {
+    let style = Style::default().add_modifier(Modifier::REVERSED);
+    for row in area.y..area.y + area.height {
+        if app.line_at_row(row).is_some_and(|idx| (first..=last).contains(&idx)) {
+            frame.buffer_mut().set_style(Rect::new(area.x, row, area.width, 1), style);
+        }
+    }
+}
+
+/// Render the file name header (`--style header`)
+fn render_header(frame: &mut Frame, app: &App, area: Rect) {
+    let mut style = Style::default().add_modifier(Modifier::BOLD);
+    if app.show_grid {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    let text = format!("{:width$}", format!(" File: {}", app.buffer.document.source_name), width = area.width as usize);
+    frame.render_widget(Paragraph::new(RatatuiLine::from(Span::styled(text, style))), area);
+}
+
+/// Render the breadcrumb of the headings or blocks the top line is in (`--breadcrumbs`)
+///
+/// When they don't all fit, the outermost ones give way to an ellipsis.
+fn render_breadcrumbs(frame: &mut Frame, app: &App, area: Rect) {
+    let crumbs = app.breadcrumbs();
+    let separator = format!(" {} ", glyphs().breadcrumb);
+    let width = area.width as usize;
+    let mut text = format!(" {}", crumbs.join(&separator));
+    let mut skipped = 0;
+    while text.width() > width && skipped + 1 < crumbs.len() {
+        skipped += 1;
+        text = format!(" {}{}{}", glyphs().ellipsis, separator, crumbs[skipped..].join(&separator));
+    }
+    let style = Style::default().fg(app.theme_colors.line_number).add_modifier(Modifier::BOLD);
+    frame.render_widget(Paragraph::new(RatatuiLine::from(Span::styled(text, style))), area);
+}
+
+/// Render the pinned lines (a table header), scrolled horizontally with the content
+fn render_sticky_lines(frame: &mut Frame, app: &App, area: Rect) {
+    let gutter_width = app.gutter_width();
+    let content_width = (area.width as usize).saturating_sub(gutter_width + app.annotation_width());
+    let (_, _, text_area) = split_columns(app, area, gutter_width);
+    render_lines(frame, app, &app.buffer.sticky_lines, content_width, text_area);
+
+    // The column that sorting and hiding act on is marked in the header
+    if let Some((start, end)) = app.current_column_span() {
+        let scroll_col = app.buffer.scroll_col;
+        let left = start.saturating_sub(scroll_col).min(text_area.width as usize) as u16;
+        let right = end.saturating_sub(scroll_col).min(text_area.width as usize) as u16;
+        let cells = Rect::new(text_area.x + left, text_area.y, right - left, 1);
+        frame.buffer_mut().set_style(cells, Style::default().add_modifier(Modifier::REVERSED));
+    }
+}
+
+/// Last gutter column: a bar with `--style grid`, blank otherwise
+fn gutter_edge(app: &App) -> &'static str {
+    if app.show_grid {
+        glyphs().vertical
+    } else {
+        " "
+    }
 }
 
 /// Render the content area (line numbers + text)
 fn render_content(frame: &mut Frame, app: &App, area: Rect) {
+    // Reading mode centers the text between blank margins
+    let margin = app.reading_margin() as u16;
+    let area = Rect {
+        x: area.x + margin,
+        width: area.width.saturating_sub(2 * margin),
+        ..area
+    };
     let gutter_width = app.gutter_width();
-    let content_width = area.width as usize - gutter_width;
+    let content_width = (area.width as usize).saturating_sub(gutter_width + app.annotation_width());
 
-    match app.wrap_mode {
+    match app.buffer.wrap_mode {
         WrapMode::None => {
             render_content_normal(frame, app, area, gutter_width, content_width);
         }
-        WrapMode::Wrap => {
+        WrapMode::Wrap | WrapMode::Word => {
             render_content_wrapped(frame, app, area, gutter_width, content_width);
         }
         WrapMode::Truncate => {
             render_content_truncated(frame, app, area, gutter_width, content_width);
         }
     }
+
+    render_eof_markers(frame, app, area);
+}
+
+/// Mark rows past the end of the document with `~`, like less
+fn render_eof_markers(frame: &mut Frame, app: &App, area: Rect) {
+    let shown = app.visible_row_count() as u16;
+    if shown >= area.height {
+        return;
+    }
+    let marker_area = Rect {
+        y: area.y + shown,
+        height: area.height - shown,
+        ..area
+    };
+    let style = Style::default().fg(app.theme_colors.line_number);
+    let markers: Vec<RatatuiLine> = (0..marker_area.height)
+        .map(|_| RatatuiLine::from(Span::styled("~", style)))
+        .collect();
+    frame.render_widget(Paragraph::new(markers), marker_area);
+}
+
+/// Split the content area into annotation column, line number gutter and text
+fn split_columns(app: &App, area: Rect, gutter_width: usize) -> (Option<Rect>, Option<Rect>, Rect) {
+    let annotation_width = app.annotation_width();
+    let gutter_width = if app.buffer.show_line_numbers { gutter_width } else { 0 };
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(annotation_width as u16),
+            Constraint::Length(gutter_width as u16),
+            Constraint::Min(1),
+        ])
+        .split(area);
+
+    let annotations = (annotation_width > 0).then_some(chunks[0]);
+    let gutter = (gutter_width > 0).then_some(chunks[1]);
+    (annotations, gutter, chunks[2])
+}
+
+/// Document line index of each row for the annotation column (`None` for continuation rows)
+fn annotation_rows(first_idx: usize, lines: &[Line]) -> Vec<Option<usize>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| (!line.continuation).then_some(first_idx + i))
+        .collect()
 }
 
 /// Render content in normal mode (horizontal scrolling)
 fn render_content_normal(frame: &mut Frame, app: &App, area: Rect, gutter_width: usize, content_width: usize) {
     let (start, end) = app.visible_line_range();
-    let visible_lines = &app.document.lines[start..end];
-
-    // Split area for gutter and content
-    if app.show_line_numbers && gutter_width > 0 {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(gutter_width as u16),
-                Constraint::Min(1),
-            ])
-            .split(area);
-
-        // Render line number gutter
-        render_gutter(frame, visible_lines, gutter_width, chunks[0], app.theme_colors.line_number);
-
-        // Render content
-        render_lines(frame, app, visible_lines, content_width, chunks[1]);
-    } else {
-        // Render content without gutter
-        render_lines(frame, app, visible_lines, content_width, area);
+    let visible_lines = &app.buffer.document.lines[start..end];
+
+    let (annotations_area, gutter_area, text_area) = split_columns(app, area, gutter_width);
+    if let Some(annotations_area) = annotations_area {
+        render_annotations(frame, app, &annotation_rows(start, visible_lines), annotations_area);
+    }
+    if let Some(gutter_area) = gutter_area {
+        render_gutter(frame, app, start, visible_lines, gutter_width, gutter_area);
     }
+    render_lines(frame, app, &with_selected_link(app, start, visible_lines), content_width, text_area);
+}
+
+/// `lines` (from document index `first_idx` on) with the path selected with Tab marked
+fn with_selected_link<'a>(app: &App, first_idx: usize, lines: &'a [Line]) -> Cow<'a, [Line]> {
+    let Some(link) = app.selected_link.as_ref() else {
+        return Cow::Borrowed(lines);
+    };
+    let Some(offset) = link.line_idx.checked_sub(first_idx).filter(|&i| i < lines.len()) else {
+        return Cow::Borrowed(lines);
+    };
+    let mut lines = lines.to_vec();
+    let style = SpanStyle::new().fg(Color::Black).bg(Color::Cyan).underline();
+    lines[offset].spans = restyle_range(&lines[offset].spans, &link.range, |_| style.clone());
+    Cow::Owned(lines)
 }
 
 /// Render content in wrap mode (soft wrapping)
 fn render_content_wrapped(frame: &mut Frame, app: &App, area: Rect, gutter_width: usize, content_width: usize) {
     // Get visible wrapped lines
-    let (start, end) = if let Some(ref wrapped) = app.wrapped_lines {
-        let start = app.scroll_line;
+    let (start, end) = if let Some(ref wrapped) = app.buffer.wrapped_lines {
+        let start = app.buffer.scroll_line;
         let end = (start + app.content_height()).min(wrapped.len());
         (start, end)
     } else {
@@ -86,64 +296,54 @@ fn render_content_wrapped(frame: &mut Frame, app: &App, area: Rect, gutter_width
         return;
     };
 
-    let wrapped_lines = app.wrapped_lines.as_ref().unwrap();
+    let wrapped_lines = app.buffer.wrapped_lines.as_ref().unwrap();
     let visible_wrapped = &wrapped_lines[start..end];
 
-    // Split area for gutter and content
-    if app.show_line_numbers && gutter_width > 0 {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(gutter_width as u16),
-                Constraint::Min(1),
-            ])
-            .split(area);
-
-        // Render line number gutter for wrapped lines
-        render_gutter_wrapped(frame, visible_wrapped, gutter_width, chunks[0], app.theme_colors.line_number);
-
-        // Render wrapped content
-        render_wrapped_lines(frame, app, visible_wrapped, content_width, chunks[1]);
-    } else {
-        // Render wrapped content without gutter
-        render_wrapped_lines(frame, app, visible_wrapped, content_width, area);
+    let (annotations_area, gutter_area, text_area) = split_columns(app, area, gutter_width);
+    if let Some(annotations_area) = annotations_area {
+        // Only the first row of a source line carries its annotations
+        let rows: Vec<Option<usize>> = visible_wrapped
+            .iter()
+            .map(|w| (w.is_first_row && !app.buffer.document.lines[w.line_idx].continuation).then_some(w.line_idx))
+            .collect();
+        render_annotations(frame, app, &rows, annotations_area);
     }
+    if let Some(gutter_area) = gutter_area {
+        render_gutter_wrapped(frame, app, visible_wrapped, gutter_width, gutter_area);
+    }
+    render_wrapped_lines(frame, app, visible_wrapped, content_width, text_area);
 }
 
 /// Render content in truncate mode (hard truncation)
 fn render_content_truncated(frame: &mut Frame, app: &App, area: Rect, gutter_width: usize, content_width: usize) {
     let (start, end) = app.visible_line_range();
-    let visible_lines = &app.document.lines[start..end];
-
-    // Split area for gutter and content
-    if app.show_line_numbers && gutter_width > 0 {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(gutter_width as u16),
-                Constraint::Min(1),
-            ])
-            .split(area);
-
-        // Render line number gutter
-        render_gutter(frame, visible_lines, gutter_width, chunks[0], app.theme_colors.line_number);
-
-        // Render truncated content
-        render_lines_truncated(frame, app, visible_lines, content_width, chunks[1]);
-    } else {
-        // Render truncated content without gutter
-        render_lines_truncated(frame, app, visible_lines, content_width, area);
+    let visible_lines = &app.buffer.document.lines[start..end];
+
+    let (annotations_area, gutter_area, text_area) = split_columns(app, area, gutter_width);
+    if let Some(annotations_area) = annotations_area {
+        render_annotations(frame, app, &annotation_rows(start, visible_lines), annotations_area);
     }
+    if let Some(gutter_area) = gutter_area {
+        render_gutter(frame, app, start, visible_lines, gutter_width, gutter_area);
+    }
+    render_lines_truncated(frame, app, &with_selected_link(app, start, visible_lines), content_width, text_area);
 }
 
-/// Render the line number gutter
-fn render_gutter(frame: &mut Frame, lines: &[Line], gutter_width: usize, area: Rect, line_number_color: Color) {
-    let gutter_style = Style::default().fg(line_number_color);
+/// Render the line number gutter (`first_idx` is the document index of `lines[0]`)
+fn render_gutter(frame: &mut Frame, app: &App, first_idx: usize, lines: &[Line], gutter_width: usize, area: Rect) {
+    let gutter_style = Style::default().fg(app.theme_colors.line_number);
 
     let gutter_lines: Vec<RatatuiLine> = lines
         .iter()
-        .map(|line| {
-            let num_str = format!("{:>width$} ", line.number, width = gutter_width - 2);
+        .enumerate()
+        .map(|(i, line)| {
+            if line.continuation || line.number == 0 {
+                // Chunk of a very long line, separator or banner - show empty gutter
+                let empty_str = format!("{}{}", " ".repeat(gutter_width - 1), gutter_edge(app));
+                return RatatuiLine::from(Span::styled(empty_str, gutter_style));
+            }
+            let number = app.gutter_number(first_idx + i, line.number);
+            let num_str = format!("{:>width$} {}", number, gutter_edge(app), width = gutter_width - 2);
             RatatuiLine::from(Span::styled(num_str, gutter_style))
         })
         .collect();
@@ -153,18 +353,20 @@ fn render_gutter(frame: &mut Frame, lines: &[Line], gutter_width: usize, area: R
 }
 
 /// Render the line number gutter for wrapped lines (only show number for first row)
-fn render_gutter_wrapped(frame: &mut Frame, wrapped_lines: &[WrappedLine], gutter_width: usize, area: Rect, line_number_color: Color) {
-    let gutter_style = Style::default().fg(line_number_color);
+fn render_gutter_wrapped(frame: &mut Frame, app: &App, wrapped_lines: &[WrappedLine], gutter_width: usize, area: Rect) {
+    let gutter_style = Style::default().fg(app.theme_colors.line_number);
 
     let gutter_lines: Vec<RatatuiLine> = wrapped_lines
         .iter()
         .map(|wrapped| {
-            if wrapped.is_first_row {
-                let num_str = format!("{:>width$} ", wrapped.line_number, width = gutter_width - 2);
+            let line = &app.buffer.document.lines[wrapped.line_idx];
+            if wrapped.is_first_row && !line.continuation && line.number != 0 {
+                let number = app.gutter_number(wrapped.line_idx, wrapped.line_number);
+                let num_str = format!("{:>width$} {}", number, gutter_edge(app), width = gutter_width - 2);
                 RatatuiLine::from(Span::styled(num_str, gutter_style))
             } else {
                 // Continuation line - show empty gutter
-                let empty_str = " ".repeat(gutter_width);
+                let empty_str = format!("{}{}", " ".repeat(gutter_width - 1), gutter_edge(app));
                 RatatuiLine::from(Span::styled(empty_str, gutter_style))
             }
         })
@@ -179,16 +381,19 @@ fn render_wrapped_lines(frame: &mut Frame, app: &App, wrapped_lines: &[WrappedLi
     let display_lines: Vec<RatatuiLine> = wrapped_lines
         .iter()
         .map(|wrapped| {
-            let line = &app.document.lines[wrapped.line_idx];
+            let lines = std::slice::from_ref(&app.buffer.document.lines[wrapped.line_idx]);
+            let marked = with_selected_link(app, wrapped.line_idx, lines);
+            let line = &marked[0];
             let text = line.text();
 
-            // Get the substring for this wrapped row
+            // Get the substring for this wrapped row (rows broken between words end early)
+            let row_width = wrapped.display_width.min(width);
             let chars: Vec<char> = text.chars().collect();
             let row_text: String = chars
                 .iter()
                 .copied()
                 .skip(wrapped.char_offset)
-                .take_until_width(width)
+                .take_until_width(row_width)
                 .collect();
 
             if line.spans.is_empty() || line.spans.len() == 1 && line.spans[0].style.is_plain() {
@@ -197,7 +402,7 @@ fn render_wrapped_lines(frame: &mut Frame, app: &App, wrapped_lines: &[WrappedLi
                 RatatuiLine::from(Span::raw(padded))
             } else {
                 // Styled text - need to extract the right portion of spans
-                let ratatui_spans = extract_wrapped_spans(&line.spans, wrapped.char_offset, width);
+                let ratatui_spans = extract_wrapped_spans(&line.spans, wrapped.char_offset, row_width, width);
                 RatatuiLine::from(ratatui_spans)
             }
         })
@@ -209,7 +414,7 @@ fn render_wrapped_lines(frame: &mut Frame, app: &App, wrapped_lines: &[WrappedLi
 
 /// Render lines with hard truncation
 fn render_lines_truncated(frame: &mut Frame, app: &App, lines: &[Line], width: usize, area: Rect) {
-    let scroll_col = app.scroll_col;
+    let scroll_col = app.buffer.scroll_col;
     let truncate_width = app.max_width.min(width);
 
     let display_lines: Vec<RatatuiLine> = lines
@@ -264,10 +469,11 @@ impl<I: Iterator<Item = char>> Iterator for TakeUntilWidthIter<I> {
     }
 }
 
-/// Extract wrapped portion of styled spans
+/// Extract wrapped portion of styled spans: `row_width` columns from `char_offset`, padded to `width`
 fn extract_wrapped_spans(
     spans: &[crate::display::StyledSpan],
     char_offset: usize,
+    row_width: usize,
     width: usize,
 ) -> Vec<Span<'static>> {
     let mut result = Vec::new();
@@ -275,7 +481,7 @@ fn extract_wrapped_spans(
     let mut chars_taken = 0;
 
     for span in spans {
-        if chars_taken >= width {
+        if chars_taken >= row_width {
             break;
         }
 
@@ -287,7 +493,7 @@ fn extract_wrapped_spans(
 
             if current_char >= char_offset {
                 // We're at or past the offset, start adding characters
-                if chars_taken + ch_width <= width {
+                if chars_taken + ch_width <= row_width {
                     span_text.push(ch);
                     chars_taken += ch_width;
                 } else {
@@ -310,17 +516,42 @@ fn extract_wrapped_spans(
     result
 }
 
+/// Build the `…+N` indicator for a line with columns hidden past the right edge
+///
+/// Returns the indicator and the width left for text, or `None` if everything
+/// right of `scroll_col` fits within `max_width`.
+fn hidden_columns_indicator(line_width: usize, scroll_col: usize, max_width: usize) -> Option<(String, usize)> {
+    let mut reserved = 0;
+    loop {
+        let effective_width = max_width.saturating_sub(reserved);
+        let hidden = line_width.saturating_sub(scroll_col + effective_width);
+        if hidden == 0 {
+            return None;
+        }
+
+        let indicator = format!("{}+{}", glyphs().truncated, hidden);
+        let indicator_width = UnicodeWidthStr::width(indicator.as_str());
+        if indicator_width > max_width {
+            // No room for the count - fall back to the bare ellipsis
+            let truncated = glyphs().truncated;
+            return Some((truncated.to_string(), max_width.saturating_sub(UnicodeWidthStr::width(truncated))));
+        }
+        if indicator_width <= reserved {
+            return Some((indicator, effective_width));
+        }
+        // Reserving room for the indicator hides more text, so recount
+        reserved = indicator_width;
+    }
+}
+
 /// Truncate text with an indicator when content is cut off
 fn truncate_with_indicator(text: &str, scroll_col: usize, max_width: usize, display_width: usize) -> String {
     let line_width = UnicodeWidthStr::width(text);
 
-    // If the line fits within max_width, use normal truncation
-    if line_width <= max_width {
+    // If the rest of the line fits within max_width, use normal truncation
+    let Some((indicator, effective_width)) = hidden_columns_indicator(line_width, scroll_col, max_width) else {
         return truncate_with_scroll(text, scroll_col, display_width);
-    }
-
-    // Line exceeds max_width - show truncation indicator
-    let effective_width = max_width.saturating_sub(1); // Reserve space for indicator
+    };
 
     let mut result = String::new();
     let mut current_col = 0;
@@ -350,8 +581,8 @@ fn truncate_with_indicator(text: &str, scroll_col: usize, max_width: usize, disp
     }
 
     // Add truncation indicator
-    result.push('…');
-    chars_taken += 1;
+    result.push_str(&indicator);
+    chars_taken += UnicodeWidthStr::width(indicator.as_str());
 
     // Pad to display width
     while chars_taken < display_width {
@@ -372,13 +603,10 @@ fn truncate_spans_with_indicator(
     // Calculate total line width
     let line_width: usize = spans.iter().map(|s| s.width()).sum();
 
-    // If the line fits within max_width, use normal truncation
-    if line_width <= max_width {
+    // If the rest of the line fits within max_width, use normal truncation
+    let Some((indicator, effective_width)) = hidden_columns_indicator(line_width, scroll_col, max_width) else {
         return truncate_spans_with_scroll(spans, scroll_col, display_width);
-    }
-
-    // Line exceeds max_width - show truncation indicator
-    let effective_width = max_width.saturating_sub(1);
+    };
 
     let mut result = Vec::new();
     let mut current_col = 0;
@@ -421,8 +649,8 @@ fn truncate_spans_with_indicator(
     }
 
     // Add truncation indicator
-    result.push(Span::styled("…", Style::default().fg(Color::DarkGray)));
-    chars_taken += 1;
+    chars_taken += UnicodeWidthStr::width(indicator.as_str());
+    result.push(Span::styled(indicator, Style::default().fg(Color::DarkGray)));
 
     // Pad to display width
     if chars_taken < display_width {
@@ -434,7 +662,7 @@ fn truncate_spans_with_indicator(
 
 /// Render the text lines
 fn render_lines(frame: &mut Frame, app: &App, lines: &[Line], width: usize, area: Rect) {
-    let scroll_col = app.scroll_col;
+    let scroll_col = app.buffer.scroll_col;
 
     let display_lines: Vec<RatatuiLine> = lines
         .iter()
@@ -564,7 +792,7 @@ fn scroll_fraction(app: &App) -> f64 {
         if max_scroll == 0 {
             1.0
         } else {
-            (app.scroll_line as f64) / (max_scroll as f64)
+            ((app.buffer.scroll_line as f64) / (max_scroll as f64)).min(1.0)
         }
     }
 }
@@ -578,7 +806,7 @@ fn progress_bar(fraction: f64, width: usize) -> String {
     let filled = ((fraction * width as f64).round() as usize).min(width);
     let empty = width.saturating_sub(filled);
 
-    format!("{}{}", "█".repeat(filled), "░".repeat(empty))
+    format!("{}{}", glyphs().bar_full.repeat(filled), glyphs().bar_empty.repeat(empty))
 }
 
 /// Render the status bar
@@ -587,11 +815,15 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         .bg(app.theme_colors.status_bg)
         .fg(app.theme_colors.status_fg);
 
-    // Left: file name (and total lines if line numbers are shown)
-    let position_text = if app.show_line_numbers {
-        format!(" {} ({} lines) ", app.document.source_name, app.total_lines())
+    // Left: file name, its place among the open files, and total lines if line numbers are shown
+    let mut name = app.buffer.document.source_name.clone();
+    if app.buffer_count() > 1 {
+        name.push_str(&format!(" [{}/{}]", app.buffer_index + 1, app.buffer_count()));
+    }
+    let position_text = if app.buffer.show_line_numbers {
+        format!(" {} ({} lines) ", name, app.total_lines())
     } else {
-        format!(" {} ", app.document.source_name)
+        format!(" {} ", name)
     };
 
     // Center: mode indicator and search info
@@ -600,20 +832,57 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             let mut indicators = Vec::new();
 
             // Show wrap mode indicator
-            match app.wrap_mode {
+            match app.buffer.wrap_mode {
+                WrapMode::Wrap if app.buffer.reading.is_some() => indicators.push("[READING]".to_string()),
                 WrapMode::Wrap => indicators.push("[WRAP]".to_string()),
+                WrapMode::Word => indicators.push("[WORD WRAP]".to_string()),
                 WrapMode::Truncate => indicators.push("[TRUNC]".to_string()),
                 WrapMode::None => {}
             }
 
-            // Show follow mode indicator
-            if app.follow_mode {
+            // Show follow mode indicator, or that stdin is still being read
+            if app.buffer.follow_mode {
                 indicators.push("[FOLLOW]".to_string());
+            } else if app.buffer.input_stream.is_some() {
+                indicators.push("[STREAMING]".to_string());
+            }
+
+            // Markdown section the top line is in
+            if let Some(heading) = app.current_section() {
+                indicators.push(format!("{} {}", "#".repeat(heading.level as usize), heading.title));
+            }
+
+            // Progress through the task list of a markdown document
+            if let Some((done, total)) = app.buffer.tasks {
+                indicators.push(format!("{}/{} tasks done", done, total));
+            }
+
+            // Breadcrumb of the pager filters
+            if let Some(breadcrumb) = app.buffer.filters.breadcrumb() {
+                indicators.push(format!("Filter: {}", breadcrumb));
+            }
+
+            // Show which lines searches cover while a grep filter is active
+            if let Some(label) = app.search_scope_label() {
+                indicators.push(format!("[{}]", label.to_uppercase()));
             }
 
             // Show search match info if available
-            if let Some((current, total)) = app.search_info() {
-                indicators.push(format!("Match {}/{}", current, total));
+            match app.search_info() {
+                Some((current, total)) if app.search_in_progress() => {
+                    indicators.push(format!("Match {}/≥{} (searching…)", current, total))
+                }
+                Some((current, total)) => indicators.push(format!("Match {}/{}", current, total)),
+                None if app.search_in_progress() => indicators.push("Searching…".to_string()),
+                None => {}
+            }
+
+            if app.at_bottom() {
+                indicators.push("(END)".to_string());
+            }
+
+            if let Some(ref message) = app.status_message {
+                indicators.push(message.clone());
             }
 
             if indicators.is_empty() {
@@ -622,30 +891,35 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 format!(" {} ", indicators.join(" | "))
             }
         }
-        Mode::Search { query } => format!(" [SEARCH: {}] ", query),
+        Mode::Search { query } => match app.search_scope_label() {
+            Some(label) => format!(" [SEARCH {}: {}] ", label, query),
+            None => format!(" [SEARCH: {}] ", query),
+        },
+        Mode::Command { input } => format!(" :{} ", input),
+        Mode::Help { .. } => " [HELP] ".to_string(),
     };
 
     // Right: column info and encoding (only show column info when not in wrap mode)
-    let right = match app.wrap_mode {
-        WrapMode::Wrap => {
+    let right = match app.buffer.wrap_mode {
+        WrapMode::Wrap | WrapMode::Word => {
             // No column info in wrap mode
-            if app.document.encoding != "UTF-8" {
-                format!("{} ", app.document.encoding)
+            if app.buffer.document.encoding != "UTF-8" {
+                format!("{} ", app.buffer.document.encoding)
             } else {
                 String::new()
             }
         }
         _ => {
             // Show column info in normal and truncate modes
-            if app.document.encoding != "UTF-8" {
+            if app.buffer.document.encoding != "UTF-8" {
                 format!(
                     "Col {}/{} | {} ",
-                    app.scroll_col + 1,
-                    app.document.max_line_width,
-                    app.document.encoding
+                    app.buffer.scroll_col + 1,
+                    app.buffer.document.max_line_width,
+                    app.buffer.document.encoding
                 )
             } else {
-                format!("Col {}/{} ", app.scroll_col + 1, app.document.max_line_width)
+                format!("Col {}/{} ", app.buffer.scroll_col + 1, app.buffer.document.max_line_width)
             }
         }
     };
@@ -731,6 +1005,27 @@ mod tests {
         assert!(result.contains('…'));
     }
 
+    #[test]
+    fn test_truncate_indicator_shows_hidden_columns() {
+        // 24 columns, 10 visible: 6 of text + "…+18"
+        let result = truncate_with_indicator("Hello World This Is Long", 0, 10, 15);
+        assert_eq!(result.trim_end(), "Hello …+18");
+
+        // Scrolled so the rest fits - no indicator
+        let result = truncate_with_indicator("Hello World This Is Long", 16, 10, 15);
+        assert!(!result.contains('…'));
+    }
+
+    #[test]
+    fn test_hidden_columns_indicator() {
+        assert_eq!(hidden_columns_indicator(10, 0, 10), None);
+        assert_eq!(hidden_columns_indicator(250, 0, 14), Some(("…+241".to_string(), 9)));
+        // Count needs an extra digit once the indicator itself hides text
+        assert_eq!(hidden_columns_indicator(107, 0, 10), Some(("…+102".to_string(), 5)));
+        // Too narrow for the count
+        assert_eq!(hidden_columns_indicator(1000, 0, 3), Some(("…".to_string(), 2)));
+    }
+
     #[test]
     fn test_take_until_width_iterator() {
         let chars: Vec<char> = "Hello World".chars().collect();
@@ -0,0 +1,109 @@
+//! Platform quirks
+//!
+//! Windows consoles only render escape codes with virtual terminal processing
+//! turned on, reserve device names (`NUL`, `CON`, `COM1`, ...) in every
+//! directory, and canonical paths come back in the verbatim `\\?\` form. The
+//! name checks are plain string logic, so they are tested on every platform.
+
+use std::path::Path;
+
+/// Reserved device names of Windows, usable with any extension (`nul.txt`)
+#[cfg_attr(not(windows), allow(dead_code))]
+const DEVICE_NAMES: &[&str] = &["CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$"];
+
+/// A Windows device a path refers to
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    /// `NUL`: always empty
+    Null,
+    /// `CON` / `CONIN$`: the console, read like stdin
+    Console,
+    /// Any other device (printers, serial ports), which can't be viewed
+    Other,
+}
+
+/// Turn on escape code processing, returning whether escape codes are rendered
+///
+/// Called before anything is printed; later calls just return the result.
+pub fn console_supports_ansi() -> bool {
+    #[cfg(windows)]
+    {
+        crossterm::ansi_support::supports_ansi()
+    }
+    #[cfg(not(windows))]
+    {
+        true
+    }
+}
+
+/// The device `path` names on Windows, if any (case-insensitive, any directory)
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn device_name(path: &Path) -> Option<Device> {
+    let name = path.file_name()?.to_str()?;
+    // The extension and trailing spaces or colons don't matter: `nul.txt`, `CON:`
+    let stem = name.split('.').next()?.trim_end_matches([' ', ':']).to_ascii_uppercase();
+    let is_port = |prefix: &str| {
+        stem.strip_prefix(prefix)
+            .is_some_and(|n| n.len() == 1 && n.as_bytes()[0].is_ascii_digit() && n != "0")
+    };
+    match stem.as_str() {
+        "NUL" => Some(Device::Null),
+        "CON" | "CONIN$" => Some(Device::Console),
+        _ if DEVICE_NAMES.contains(&stem.as_str()) || is_port("COM") || is_port("LPT") => Some(Device::Other),
+        _ => None,
+    }
+}
+
+/// What tells a file apart from another under the same name: device and inode
+///
+/// None where there is no such number (Windows), so only truncation shows
+/// that a followed file was replaced.
+pub fn file_identity(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// `path` for display, without the `\\?\` prefix of verbatim Windows paths
+pub fn display_path(path: &Path) -> String {
+    let text = path.display().to_string();
+    if let Some(unc) = text.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else if let Some(local) = text.strip_prefix(r"\\?\") {
+        local.to_string()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_name() {
+        assert_eq!(device_name(Path::new("NUL")), Some(Device::Null));
+        assert_eq!(device_name(Path::new("dir/nul.txt")), Some(Device::Null));
+        assert_eq!(device_name(Path::new("con:")), Some(Device::Console));
+        assert_eq!(device_name(Path::new("COM3")), Some(Device::Other));
+        assert_eq!(device_name(Path::new("LPT1.log")), Some(Device::Other));
+        assert_eq!(device_name(Path::new("COM0")), None);
+        assert_eq!(device_name(Path::new("console.log")), None);
+        assert_eq!(device_name(Path::new("null")), None);
+    }
+
+    #[test]
+    fn test_display_path() {
+        assert_eq!(display_path(Path::new(r"\\?\C:\logs\app.log")), r"C:\logs\app.log");
+        assert_eq!(display_path(Path::new(r"\\?\UNC\server\share\a.txt")), r"\\server\share\a.txt");
+        assert_eq!(display_path(Path::new("src/main.rs")), "src/main.rs");
+    }
+}
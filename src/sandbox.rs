@@ -0,0 +1,26 @@
+//! Read-only mode (`--no-exec`)
+//!
+//! For inspecting untrusted files, `--no-exec` turns off everything that
+//! starts another program or writes a file: opening an editor, the external
+//! pager, preprocessor commands, the `xz` / `zstd` / `bzip2` decompressors,
+//! ripgrep, the crash log and syntax caches, and usage statistics (`--tee`,
+//! `--log-output` and `--export-bookmarks` are refused outright). Copying goes through the
+//! terminal (OSC 52) and keeps working. The mode is set once at startup with
+//! [`forbid_exec`]; the places that spawn or write check [`exec_allowed`].
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NO_EXEC: AtomicBool = AtomicBool::new(false);
+
+/// Status of a feature turned off by `--no-exec`
+pub const EXEC_DISABLED: &str = "disabled by --no-exec";
+
+/// Forbid (or allow) starting programs and writing files from now on
+pub fn forbid_exec(no_exec: bool) {
+    NO_EXEC.store(no_exec, Ordering::Relaxed);
+}
+
+/// Whether mat may start other programs and write files
+pub fn exec_allowed() -> bool {
+    !NO_EXEC.load(Ordering::Relaxed)
+}
@@ -0,0 +1,79 @@
+//! `--stats`: line count, word count and reading time of each input
+//!
+//! Markdown is counted after parsing, so markup and code blocks do not add
+//! words; other text counts every word as it is.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::cli::Args;
+use crate::config::Config;
+use crate::error::MatError;
+use crate::input::{load_content, InputSource};
+use crate::markdown::{markdown_word_count, reading_minutes};
+
+/// Counts of one input
+#[derive(Debug, PartialEq)]
+pub struct TextStats {
+    /// File name
+    pub name: String,
+    /// Number of lines of the text
+    pub lines: usize,
+    /// Number of words of prose
+    pub words: usize,
+}
+
+impl TextStats {
+    /// Count the lines and words of a text
+    pub fn count(name: String, text: &str, is_markdown: bool) -> Self {
+        let words = if is_markdown {
+            markdown_word_count(text)
+        } else {
+            text.unicode_words().count()
+        };
+        Self {
+            name,
+            lines: text.lines().count(),
+            words,
+        }
+    }
+}
+
+impl std::fmt::Display for TextStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} lines, {} words, {} min read",
+            self.name,
+            self.lines,
+            self.words,
+            reading_minutes(self.words)
+        )
+    }
+}
+
+/// Print the counts of every input, one line each
+pub fn print_stats(sources: &[InputSource], args: &Args, config: &Config) -> Result<(), MatError> {
+    for source in sources {
+        let content = load_content(source.clone(), args, &config.preprocessors)?;
+        let is_markdown = !args.no_markdown && (args.markdown || content.is_markdown);
+        println!("{}", TextStats::count(content.source_name, &content.text, is_markdown));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_markdown_and_text() {
+        let text = "# Notes\n\nSome *emphasized* words.\n\n```\nfn skipped() {}\n```\n";
+        let stats = TextStats::count("notes.md".to_string(), text, true);
+        assert_eq!(stats.lines, 7);
+        assert_eq!(stats.words, 4);
+        assert_eq!(stats.to_string(), "notes.md: 7 lines, 4 words, 1 min read");
+
+        let stats = TextStats::count("notes.txt".to_string(), text, false);
+        assert_eq!(stats.words, 6);
+    }
+}
@@ -1,14 +1,24 @@
 use once_cell::sync::Lazy;
 use ratatui::style::Color;
 
+use crate::highlight::find_theme;
+
 /// Detected or configured theme
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Theme {
     Light,
+    #[default]
     Dark,
+    /// A syntect theme chosen by name (`--theme Nord`), on a light or dark background
+    Named { name: &'static str, light: bool },
 }
 
 impl Theme {
+    /// Whether the theme is meant for a light background
+    pub fn is_light(self) -> bool {
+        matches!(self, Theme::Light | Theme::Named { light: true, .. })
+    }
+
     /// Parse theme from string
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
@@ -19,12 +29,6 @@ impl Theme {
     }
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Theme::Dark
-    }
-}
-
 /// Lazily detected theme
 static DETECTED_THEME: Lazy<Theme> = Lazy::new(detect_terminal_theme);
 
@@ -76,9 +80,12 @@ pub fn detected_theme() -> Theme {
 }
 
 /// Get theme from CLI arg or auto-detect
+///
+/// Besides `light` and `dark`, the argument may name any syntect theme,
+/// bundled or in the user's theme directory.
 pub fn get_theme(theme_arg: Option<&str>) -> Theme {
     match theme_arg {
-        Some(s) => Theme::from_str(s).unwrap_or_else(detected_theme),
+        Some(s) => Theme::from_str(s).or_else(|| find_theme(s)).unwrap_or_else(detected_theme),
         None => detected_theme(),
     }
 }
@@ -111,8 +118,8 @@ impl ThemeColors {
     /// Get colors for the given theme
     pub fn for_theme(theme: Theme) -> Self {
         match theme {
-            Theme::Light => Self::light(),
-            Theme::Dark => Self::dark(),
+            Theme::Light | Theme::Named { light: true, .. } => Self::light(),
+            Theme::Dark | Theme::Named { light: false, .. } => Self::dark(),
         }
     }
 
@@ -0,0 +1,132 @@
+//! grep-compatible color configuration
+//!
+//! Reads `GREP_COLORS` (and the deprecated `GREP_COLOR`) so users who tuned
+//! grep's output get the same colors when using mat as a grep replacement.
+//! Supported capabilities are `ms`/`mt` (matches), `cx` (context lines),
+//! `se` (separators) and `ln` (line numbers); the rest are ignored.
+
+use ratatui::style::Color;
+
+use crate::display::{apply_sgr, Line, SpanStyle, StyledSpan};
+
+/// Styles used for grep output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepColors {
+    /// Matched text on matching lines
+    pub matched: SpanStyle,
+    /// Context lines around matches
+    pub context: SpanStyle,
+    /// `--` separators between groups
+    pub separator: SpanStyle,
+    /// Line number color, when configured (otherwise the theme decides)
+    pub line_number: Option<Color>,
+}
+
+impl Default for GrepColors {
+    fn default() -> Self {
+        Self {
+            matched: SpanStyle::new().fg(Color::Black).bg(Color::Cyan),
+            context: SpanStyle::new().fg(Color::DarkGray),
+            separator: SpanStyle::new().fg(Color::DarkGray),
+            line_number: None,
+        }
+    }
+}
+
+impl GrepColors {
+    /// Read colors from the `GREP_COLORS` and `GREP_COLOR` environment variables
+    pub fn from_env() -> Self {
+        Self::parse(
+            std::env::var("GREP_COLORS").ok().as_deref(),
+            std::env::var("GREP_COLOR").ok().as_deref(),
+        )
+    }
+
+    /// Build colors from `GREP_COLORS` and `GREP_COLOR` values
+    ///
+    /// Like grep, `GREP_COLOR` only sets the match color and is overridden by
+    /// `ms`/`mt` in `GREP_COLORS`. Entries with invalid SGR sequences are ignored.
+    pub fn parse(grep_colors: Option<&str>, grep_color: Option<&str>) -> Self {
+        let mut colors = Self::default();
+
+        if let Some(style) = grep_color.filter(|s| !s.is_empty()).and_then(parse_sgr) {
+            colors.matched = style;
+        }
+
+        for entry in grep_colors.unwrap_or("").split(':') {
+            let Some((cap, value)) = entry.split_once('=') else {
+                continue;
+            };
+            let Some(style) = parse_sgr(value) else {
+                continue;
+            };
+            match cap {
+                "ms" | "mt" => colors.matched = style,
+                "cx" => colors.context = style,
+                "se" => colors.separator = style,
+                "ln" => colors.line_number = style.fg,
+                _ => {}
+            }
+        }
+
+        colors
+    }
+
+    /// Separator line between grep groups
+    pub fn separator_line(&self) -> Line {
+        let mut line = Line::separator();
+        line.spans = vec![StyledSpan::new("--", self.separator.clone())];
+        line
+    }
+}
+
+/// Parse an SGR parameter list such as `01;31` into a span style
+///
+/// An empty value means no styling, as in grep.
+fn parse_sgr(value: &str) -> Option<SpanStyle> {
+    let mut style = SpanStyle::new();
+    apply_sgr(value, &mut style)?;
+    Some(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grep_colors() {
+        let colors = GrepColors::parse(Some("ms=01;31:mc=01;31:sl=:cx=2;37:fn=35:ln=32:bn=32:se=36"), None);
+        assert_eq!(colors.matched, SpanStyle::new().fg(Color::Red).bold());
+        assert_eq!(colors.context, SpanStyle::new().fg(Color::Gray));
+        assert_eq!(colors.separator, SpanStyle::new().fg(Color::Cyan));
+        assert_eq!(colors.line_number, Some(Color::Green));
+    }
+
+    #[test]
+    fn test_grep_color_fallback() {
+        let colors = GrepColors::parse(None, Some("1;32"));
+        assert_eq!(colors.matched, SpanStyle::new().fg(Color::Green).bold());
+
+        // GREP_COLORS wins over the deprecated GREP_COLOR
+        let colors = GrepColors::parse(Some("mt=4"), Some("1;32"));
+        assert_eq!(colors.matched, SpanStyle::new().underline());
+    }
+
+    #[test]
+    fn test_parse_extended_colors() {
+        let colors = GrepColors::parse(Some("ms=38;5;208;48;2;10;20;30"), None);
+        assert_eq!(colors.matched, SpanStyle::new().fg(Color::Indexed(208)).bg(Color::Rgb(10, 20, 30)));
+    }
+
+    #[test]
+    fn test_invalid_entries_keep_defaults() {
+        let colors = GrepColors::parse(Some("ms=red:cx=38;5:rv:ne"), Some(""));
+        assert_eq!(colors, GrepColors::default());
+    }
+
+    #[test]
+    fn test_empty_value_disables_color() {
+        let colors = GrepColors::parse(Some("cx="), None);
+        assert!(colors.context.is_plain());
+    }
+}
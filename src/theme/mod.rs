@@ -1,4 +1,6 @@
 mod detect;
+mod grep_colors;
 
 #[allow(unused_imports)]
 pub use detect::{detected_theme, get_theme, Theme, ThemeColors};
+pub use grep_colors::GrepColors;
@@ -0,0 +1,143 @@
+//! Opt-in usage statistics (`usage_stats = true`, `mat --usage`)
+//!
+//! With the user's consent in the configuration file, mat counts the flags
+//! given on the command line and the keys and `:` commands used in the pager,
+//! and adds them to `usage.txt` in its state directory when it exits. Only
+//! names are counted, never patterns, file names or text, and the file is
+//! never sent anywhere: `mat --usage` shows what it holds. Nothing is counted
+//! without consent or under `--no-exec`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+
+use crate::config::state_dir;
+use crate::error::MatError;
+use crate::sandbox::exec_allowed;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Counts of this run, not yet saved
+static COUNTS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Count usage from now on if the user consented (and files may be written)
+pub fn enable(consent: bool) {
+    ENABLED.store(consent && exec_allowed(), Ordering::Relaxed);
+}
+
+/// Count one use of `feature` (a flag, key or command name)
+pub fn record(feature: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(mut counts) = COUNTS.lock() {
+        *counts.entry(feature.to_string()).or_insert(0) += 1;
+    }
+}
+
+/// Count the long names of the flags given on the command line
+pub fn record_flags(command: &clap::Command, matches: &ArgMatches) {
+    for arg in command.get_arguments() {
+        let given = matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine);
+        if let Some(long) = arg.get_long().filter(|_| given) {
+            record(&format!("--{}", long));
+        }
+    }
+}
+
+/// Add the counts of this run to the statistics file
+///
+/// Failing to write statistics never fails mat, so errors are ignored.
+pub fn save() {
+    let Some(path) = usage_path().filter(|_| ENABLED.load(Ordering::Relaxed)) else {
+        return;
+    };
+    let Ok(counts) = COUNTS.lock() else {
+        return;
+    };
+    if counts.is_empty() {
+        return;
+    }
+    let mut total = std::fs::read_to_string(&path).map(|text| parse_counts(&text)).unwrap_or_default();
+    for (feature, count) in counts.iter() {
+        *total.entry(feature.clone()).or_insert(0) += count;
+    }
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    // Written next to the file and renamed, so concurrent runs never leave it half written
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    if std::fs::write(&temp, format_counts(&total)).is_ok() {
+        let _ = std::fs::rename(&temp, &path);
+    }
+}
+
+/// Print the usage statistics, most used first
+pub fn print_usage() -> Result<(), MatError> {
+    let Some(path) = usage_path() else {
+        println!("No usage statistics: no home directory to keep them in.");
+        return Ok(());
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(text) => {
+            println!("Usage statistics from {}:", path.display());
+            print!("{}", format_counts(&parse_counts(&text)));
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("No usage statistics yet. Add `usage_stats = true` to the config file to count");
+            println!("the flags, keys and commands you use; the counts stay in {}.", path.display());
+            Ok(())
+        }
+        Err(source) => Err(MatError::Io { source, path }),
+    }
+}
+
+/// Location of the statistics file
+fn usage_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("usage.txt"))
+}
+
+/// Parse `COUNT FEATURE` lines, skipping any that aren't
+fn parse_counts(text: &str) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for line in text.lines() {
+        if let Some((count, feature)) = line.trim_start().split_once(' ') {
+            if let Ok(count) = count.parse::<u64>() {
+                *counts.entry(feature.trim().to_string()).or_insert(0) += count;
+            }
+        }
+    }
+    counts
+}
+
+/// `COUNT FEATURE` lines, most used first
+fn format_counts(counts: &BTreeMap<String, u64>) -> String {
+    let mut sorted: Vec<(&String, &u64)> = counts.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let width = sorted.first().map_or(1, |(_, count)| count.to_string().len());
+    sorted
+        .iter()
+        .map(|(feature, count)| format!("{:>width$} {}\n", count, feature, width = width))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_counts_round_trip() {
+        let counts = parse_counts("  3 j\n12 --follow\nnot a count\n1 :grep\n");
+        assert_eq!(counts.get("--follow"), Some(&12));
+        assert_eq!(counts.len(), 3);
+
+        let text = format_counts(&counts);
+        assert_eq!(text, "12 --follow\n 3 j\n 1 :grep\n");
+        assert_eq!(parse_counts(&text), counts);
+    }
+}
@@ -21,6 +21,7 @@ fn run_mat(args: &[&str]) -> (String, String, i32) {
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .env("TERM", "dumb")
+        .env_remove("LESS")
         .output()
         .expect("Failed to execute mat");
 
@@ -42,6 +43,7 @@ fn run_mat_with_stdin(args: &[&str], stdin: &str) -> (String, String, i32) {
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .env("TERM", "dumb")
+        .env_remove("LESS")
         .spawn()
         .expect("Failed to execute mat");
 
@@ -120,6 +122,623 @@ fn test_stdin_input() {
     assert!(stdout.contains("Line 2"));
 }
 
+#[test]
+fn test_psql_table_is_realigned() {
+    let psql = " id | name\n----+------\n  1 | Ada\n 12 | Bob\n(2 rows)\n\n";
+    let (stdout, _, code) = run_mat_with_stdin(&["-P"], psql);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "id │ name\n───┼─────\n 1 │ Ada\n12 │ Bob\n(2 rows)\n");
+}
+
+#[test]
+fn test_ascii_decorations() {
+    let psql = " id | name\n----+------\n  1 | Ada\n(1 row)\n";
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--ascii", "--style", "grid,numbers"], psql);
+    assert_eq!(code, 0);
+    assert!(stdout.is_ascii());
+    assert!(stdout.contains("  | id | name\n"));
+    assert!(stdout.contains("1 |  1 | Ada\n"));
+    assert!(stdout.contains("--+-"));
+}
+
+#[test]
+fn test_wrap_width() {
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--wrap-width", "4", "--style", "numbers"], "abcdefghij
+xy
+");
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "1 abcd
+  efgh
+  ij
+2 xy
+");
+}
+
+#[test]
+fn test_wrap_to_terminal_width() {
+    let long_line = "word ".repeat(200);
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--wrap", "wrap"], &format!("{}\n", long_line));
+    assert_eq!(code, 0);
+    // Without --wrap-width the line breaks at the terminal width (80 without one)
+    assert!(stdout.lines().count() > 1);
+    assert_eq!(stdout.replace('\n', ""), long_line);
+}
+
+#[test]
+fn test_wrap_at_words() {
+    let text = "the quick brown fox jumps\n";
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--wrap", "word", "--wrap-width", "10"], text);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "the quick \nbrown fox \njumps\n");
+
+    let (stdout, _, _) = run_mat_with_stdin(&["--render-screen", "16x4", "--wrap", "word", "-n"], text);
+    assert_eq!(stdout.lines().take(3).collect::<Vec<_>>(), ["1  the quick", "   brown fox", "   jumps"]);
+}
+
+#[test]
+fn test_file_line_reference() {
+    let mut temp = NamedTempFile::new().unwrap();
+    writeln!(temp, "one\ntwo").unwrap();
+
+    // `file:line:col` resolves to the file itself
+    let reference = format!("{}:2:1", temp.path().display());
+    let (stdout, _, code) = run_mat(&["-P", &reference]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "one\ntwo\n");
+}
+
+#[test]
+fn test_multiple_files_concatenated() {
+    let mut first = NamedTempFile::new().unwrap();
+    writeln!(first, "first a").unwrap();
+    writeln!(first, "first b").unwrap();
+    let mut second = NamedTempFile::new().unwrap();
+    writeln!(second, "second a").unwrap();
+
+    let (stdout, _, code) = run_mat(&[
+        "-P",
+        "-n",
+        first.path().to_str().unwrap(),
+        second.path().to_str().unwrap(),
+    ]);
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["1 first a", "2 first b", "3 second a"]);
+}
+
+#[test]
+fn test_multiple_files_headers_per_file_numbers() {
+    let mut first = NamedTempFile::new().unwrap();
+    writeln!(first, "first a").unwrap();
+    let mut second = NamedTempFile::new().unwrap();
+    writeln!(second, "second a").unwrap();
+    let second_path = second.path().to_str().unwrap();
+
+    let (stdout, _, code) = run_mat(&[
+        "-P",
+        "-n",
+        "--file-headers",
+        "--numbering",
+        "per-file",
+        first.path().to_str().unwrap(),
+        second_path,
+    ]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains(&format!("==> {} <==", second_path)));
+    assert_eq!(stdout.lines().filter(|l| l.trim_start().starts_with("1 ")).count(), 2);
+}
+
+#[test]
+fn test_follow_rejects_multiple_files() {
+    let first = NamedTempFile::new().unwrap();
+    let second = NamedTempFile::new().unwrap();
+
+    let (_, stderr, code) = run_mat(&[
+        "-f",
+        first.path().to_str().unwrap(),
+        second.path().to_str().unwrap(),
+    ]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("multiple files"));
+}
+
+#[test]
+fn test_style_header_grid() {
+    let mut temp = NamedTempFile::new().unwrap();
+    writeln!(temp, "Line one").unwrap();
+
+    let (stdout, _, code) = run_mat(&["-P", "--style", "header,grid,numbers", temp.path().to_str().unwrap()]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("│ File: "));
+    assert!(stdout.contains("1 │ Line one"));
+    assert!(stdout.lines().last().unwrap().starts_with("──┴"));
+}
+
+#[test]
+fn test_style_invalid_component() {
+    let (_, stderr, code) = run_mat_with_stdin(&["-P", "--style", "border"], "x\n");
+    assert_eq!(code, 2);
+    assert!(stderr.contains("border"));
+}
+
+#[test]
+fn test_paging_auto_prints_when_piped() {
+    // stdout is a pipe, so the default --paging auto prints without the TUI
+    let (stdout, _, code) = run_mat_with_stdin(&[], "hello\nworld\n");
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "hello\nworld\n");
+
+    let (stdout, _, code) = run_mat_with_stdin(&["--paging", "never", "-n"], "hello\n");
+    assert_eq!(code, 0);
+    assert!(stdout.contains("1 hello"));
+}
+
+#[test]
+fn test_closed_pipe_exits_quietly() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    // `mat file | head -1`: the reader leaves before mat is done writing
+    let mut temp = NamedTempFile::new().unwrap();
+    for i in 0..200_000 {
+        writeln!(temp, "line {}", i).unwrap();
+    }
+
+    let mut child = Command::new(mat_binary())
+        .args(["-P", temp.path().to_str().unwrap()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("TERM", "dumb")
+        .spawn()
+        .expect("Failed to execute mat");
+
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap()).read_line(&mut first).unwrap();
+    assert_eq!(first, "line 0\n");
+
+    let output = child.wait_with_output().expect("Failed to wait on mat");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+}
+
+#[test]
+fn test_color_output() {
+    // Piped output is plain by default, colored on request
+    let (stdout, _, _) = run_mat_with_stdin(&["-g", "err"], "ok\nerr here\n");
+    assert!(!stdout.contains('\x1b'));
+
+    let (stdout, _, code) = run_mat_with_stdin(&["-g", "err", "--color", "always"], "ok\nerr here\n");
+    assert_eq!(code, 0);
+    assert!(stdout.contains("\x1b[30;46merr\x1b[0m here"));
+
+    // Forcing the pager without a terminal falls back to printing
+    let (stdout, _, code) = run_mat_with_stdin(&["--paging", "always"], "hello\n");
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "hello\n");
+}
+
+#[test]
+fn test_ansi_input_colors() {
+    // The colors of the input are kept, its escapes are not matched by grep
+    let diff = "\x1b[1mdiff\x1b[m\n\x1b[31m-old\x1b[m\n\x1b[32m+new\x1b[m\n";
+    let (stdout, _, code) = run_mat_with_stdin(&["--ansi", "--color", "never", "-g", "31|new"], diff);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "+new\n");
+
+    let (stdout, _, code) = run_mat_with_stdin(&["--ansi", "--color", "always"], diff);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("\x1b[31m-old\x1b[0m"));
+
+    // Printed to a pipe they stay colors too, for `less -R`
+    let (stdout, _, code) = run_mat_with_stdin(&["--ansi"], diff);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("\x1b[31m-old\x1b[0m"), "{:?}", stdout);
+}
+
+#[test]
+fn test_render_screen() {
+    let text: String = (1..=30).map(|i| format!("line {}\n", i)).collect();
+    let (stdout, _, code) = run_mat_with_stdin(&["--render-screen", "30x4", "-n", "-s", "line 2"], &text);
+    assert_eq!(code, 0);
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert_eq!(rows.len(), 4);
+    // The annotation column marks the lines with a match
+    assert_eq!(&rows[..3], &["  1  line 1", "● 2  line 2", "  3  line 3"]);
+    assert!(rows[3].contains("stdin"));
+
+    let (_, stderr, code) = run_mat_with_stdin(&["--render-screen", "80"], &text);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("not a screen size"));
+}
+
+#[test]
+fn test_keys() {
+    let text: String = (1..=30).map(|i| format!("line {}\n", i)).collect();
+    let (stdout, _, code) = run_mat_with_stdin(&["--render-screen", "30x4", "--keys", "/line 2<Enter>nn"], &text);
+    assert_eq!(code, 0);
+    // The second match, line 20, is in view below a line of context
+    assert!(stdout.lines().nth(1).unwrap().ends_with("line 20"));
+    assert!(stdout.contains("Match 2/11"));
+
+    let (stdout, _, code) = run_mat_with_stdin(&["--render-screen", "30x4", "--keys", "<F1>"], &text);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Keys"));
+
+    let (_, stderr, code) = run_mat_with_stdin(&["--render-screen", "30x4", "--keys", "<Nope>"], &text);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("unknown key <Nope>"));
+}
+
+#[test]
+fn test_stack_trace_styling() {
+    let log = "start\nthread 'main' panicked at src/main.rs:10:5:\nboom\n";
+    let (stdout, _, code) = run_mat_with_stdin(&["--color", "always"], log);
+    assert_eq!(code, 0);
+    assert!(stdout.starts_with("start\n"));
+    assert!(stdout.contains("\x1b[4;36msrc/main.rs:10:5\x1b[0m"));
+}
+
+#[test]
+fn test_heading_navigation() {
+    let mut file = tempfile::Builder::new().suffix(".md").tempfile().unwrap();
+    write!(file, "# Guide\n\nIntro.\n\n## Install\n\nText.\n\n## Usage\n\nMore.\n").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    // The title box is the first heading, so one jump reaches the next
+    let (stdout, _, code) = run_mat(&["--render-screen", "60x4", "--keys", "]]", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.lines().next().unwrap().contains("Install"));
+    assert!(stdout.contains("## Install"));
+
+    // Jumping back to a boxed heading shows its top border too
+    let (stdout, _, _) = run_mat(&["--render-screen", "60x4", "--keys", "]][[", path]);
+    assert!(stdout.lines().next().unwrap().starts_with('╔'), "{}", stdout);
+}
+
+#[test]
+fn test_start_at_end() {
+    let mut file = tempfile::Builder::new().suffix(".log").tempfile().unwrap();
+    let body: String = (1..=50).map(|i| format!("entry {}\n", i)).collect();
+    write!(file, "{}", body).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let (stdout, _, code) = run_mat(&["--render-screen", "40x6", "--start-at-end", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("entry 50"));
+    assert!(!stdout.contains("entry 1\n"));
+
+    // Configured for .log files
+    let mut config = NamedTempFile::new().unwrap();
+    writeln!(config, "start_at_end = [\"log\"]").unwrap();
+    let output = Command::new(mat_binary())
+        .args(["--render-screen", "40x6", path])
+        .env("MAT_CONFIG", config.path())
+        .output()
+        .expect("Failed to execute mat");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("entry 50"));
+
+    // A line to start at wins
+    let location = format!("{}:10", path);
+    let (stdout, _, _) = run_mat(&["--render-screen", "40x6", "--start-at-end", &location]);
+    assert!(stdout.contains("entry 10"));
+    assert!(!stdout.contains("entry 50"));
+}
+
+#[test]
+fn test_less_env_flags_when_paging_stdin() {
+    use std::process::Stdio;
+
+    let run = |args: &[&str]| {
+        let mut child = Command::new(mat_binary())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .env("LESS", "-x4 -NF")
+            .spawn()
+            .expect("Failed to execute mat");
+        // Unread when a file is viewed instead
+        let _ = child.stdin.take().unwrap().write_all(b"alpha\nbeta\n");
+        String::from_utf8_lossy(&child.wait_with_output().unwrap().stdout).to_string()
+    };
+
+    // Piped in like PAGER, -N shows line numbers
+    let stdout = run(&["--render-screen", "30x4"]);
+    assert!(stdout.contains("2  beta"), "{}", stdout);
+
+    // Viewing a file, LESS is left alone
+    let mut file = NamedTempFile::new().unwrap();
+    write!(file, "alpha\nbeta\n").unwrap();
+    let stdout = run(&["--render-screen", "30x4", file.path().to_str().unwrap()]);
+    assert!(stdout.contains("beta") && !stdout.contains("2  beta"), "{}", stdout);
+}
+
+#[test]
+fn test_search_progress_in_long_document() {
+    let mut file = NamedTempFile::new().unwrap();
+    let body: String = (1..=50_000).map(|i| format!("entry {}\n", i)).collect();
+    write!(file, "{}", body).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    // Scripted keys run before the search gets further than its first chunk
+    let (stdout, _, code) = run_mat(&["--render-screen", "80x4", "--keys", "/entry 9<Enter>nn", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Match 2/≥"));
+    assert!(stdout.contains("(searching…)"));
+    assert!(stdout.contains("entry 90"));
+}
+
+#[test]
+fn test_buffer_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<String> = [("one.txt", "alpha\n"), ("two.log", "beta\nbeta again\n"), ("three.md", "gamma\n")]
+        .iter()
+        .map(|(name, text)| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text).unwrap();
+            path.to_str().unwrap().to_string()
+        })
+        .collect();
+    let args = |keys: &'static str| {
+        let mut args = vec!["--render-screen", "80x10", "--keys", keys];
+        args.extend(paths.iter().map(String::as_str));
+        args
+    };
+
+    let (stdout, _, code) = run_mat(&args("B"));
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Buffers"));
+    assert!(stdout.contains("two.log") && stdout.contains("2 lines"));
+
+    // Typing narrows the list, Enter switches to the selected file
+    let (stdout, _, _) = run_mat(&args("Blog<Enter>"));
+    assert!(!stdout.contains("Buffers"));
+    assert!(stdout.contains("beta again"));
+    assert!(stdout.contains("[2/3]"));
+}
+
+#[test]
+fn test_table_of_contents() {
+    let mut file = tempfile::Builder::new().suffix(".md").tempfile().unwrap();
+    let body: String = (1..=20).map(|i| format!("Paragraph {}.\n\n", i)).collect();
+    write!(file, "# Guide\n\n{}## Install\n\n{}## Usage\n\nMore.\n", body, body).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let (stdout, _, code) = run_mat(&["--render-screen", "60x12", "--keys", "O", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Contents"));
+    assert!(stdout.contains("  Install"));
+
+    // Selecting the last heading scrolls the document to it
+    let (stdout, _, _) = run_mat(&["--render-screen", "60x12", "--keys", "Ojj<Enter>", path]);
+    assert!(!stdout.contains("Contents"));
+    assert!(stdout.lines().next().unwrap().contains("Usage"));
+}
+
+#[test]
+fn test_open_path_in_buffer() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("lib.rs"), "// first\nfn second() {}\n").unwrap();
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "failed at lib.rs:2\n").unwrap();
+    let notes = notes.to_str().unwrap();
+
+    let (stdout, _, code) = run_mat(&["--render-screen", "40x4", "--keys", "<Tab><Enter>", notes]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("fn second() {}"));
+    assert!(!stdout.contains("failed at"));
+
+    let (stdout, _, _) = run_mat(&["--render-screen", "80x4", "--keys", "<Tab>", notes]);
+    assert!(stdout.contains("lib.rs:2 (Enter to open"));
+}
+
+#[test]
+fn test_error_format_json() {
+    let (_, stderr, code) = run_mat(&["--error-format", "json", "/nonexistent/file.txt"]);
+    assert_eq!(code, 1);
+    let json: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+    assert_eq!(json["kind"], "io");
+    assert_eq!(json["path"], "/nonexistent/file.txt");
+    assert_eq!(json["exit_code"], 1);
+}
+
+#[test]
+fn test_invalid_config_file() {
+    let mut config = NamedTempFile::new().unwrap();
+    writeln!(config, "hscroll_step = \"wide\"").unwrap();
+    let mut temp = NamedTempFile::new().unwrap();
+    writeln!(temp, "x").unwrap();
+
+    let output = Command::new(mat_binary())
+        .args(["-P", temp.path().to_str().unwrap()])
+        .env("MAT_CONFIG", config.path())
+        .output()
+        .expect("Failed to execute mat");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid config file"));
+}
+
+#[test]
+fn test_preprocessor_command() {
+    let mut config = NamedTempFile::new().unwrap();
+    writeln!(config, "[[preprocessor]]\nextensions = [\"unsorted\"]\ncommand = \"sort {{}}\"").unwrap();
+    let mut temp = tempfile::Builder::new().suffix(".unsorted").tempfile().unwrap();
+    writeln!(temp, "pear\napple\nfig").unwrap();
+    let cache = tempfile::tempdir().unwrap();
+
+    for _ in 0..2 {
+        let output = Command::new(mat_binary())
+            .args(["-P", temp.path().to_str().unwrap()])
+            .env("MAT_CONFIG", config.path())
+            .env("XDG_CACHE_HOME", cache.path())
+            .output()
+            .expect("Failed to execute mat");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "apple\nfig\npear\n");
+    }
+    // The second run was served from the cache
+    assert_eq!(std::fs::read_dir(cache.path().join("mat").join("preprocess")).unwrap().count(), 1);
+}
+
+#[test]
+fn test_no_exec() {
+    let mut config = NamedTempFile::new().unwrap();
+    writeln!(config, "[[preprocessor]]\nextensions = [\"unsorted\"]\ncommand = \"sort {{}}\"").unwrap();
+    let mut temp = tempfile::Builder::new().suffix(".unsorted").tempfile().unwrap();
+    writeln!(temp, "pear\napple").unwrap();
+    let cache = tempfile::tempdir().unwrap();
+
+    // The file is shown as it is, without running the preprocessor or caching anything
+    let output = Command::new(mat_binary())
+        .args(["-P", "--no-exec", temp.path().to_str().unwrap()])
+        .env("MAT_CONFIG", config.path())
+        .env("XDG_CACHE_HOME", cache.path())
+        .output()
+        .expect("Failed to execute mat");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "pear\napple\n");
+    assert!(!cache.path().join("mat").exists());
+
+    let (_, stderr, code) = run_mat(&["--no-exec", "--export-bookmarks", "marks.txt", "Cargo.toml"]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_usage_stats() {
+    let mut config = NamedTempFile::new().unwrap();
+    writeln!(config, "usage_stats = true").unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let mat = |args: &[&str]| {
+        let output = Command::new(mat_binary())
+            .args(args)
+            .env("MAT_CONFIG", config.path())
+            .env("XDG_STATE_HOME", state.path())
+            .output()
+            .expect("Failed to execute mat");
+        assert_eq!(output.status.code(), Some(0));
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(mat(&["--usage"]).contains("No usage statistics yet"));
+    mat(&["-P", "-n", "Cargo.toml"]);
+    mat(&["-P", "--line-numbers", "Cargo.toml"]);
+    mat(&["--render-screen", "40x5", "--keys", "jj:grep secret<Enter>", "Cargo.toml"]);
+    // Read-only runs count nothing
+    mat(&["-P", "-n", "--no-exec", "Cargo.toml"]);
+
+    let usage = mat(&["--usage"]);
+    assert!(usage.contains("2 --line-numbers\n"), "{}", usage);
+    assert!(usage.contains("2 j\n"));
+    assert!(usage.contains("1 :grep\n"));
+    assert!(!usage.contains("secret"));
+}
+
+#[test]
+fn test_tee() {
+    let dir = tempfile::tempdir().unwrap();
+    let raw = dir.path().join("raw.log");
+    let plain = dir.path().join("plain.log");
+    let input = "ok\n\x1b[31mfailed\x1b[0m\n";
+
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--tee", raw.to_str().unwrap()], input);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "ok\nfailed\n");
+    assert_eq!(std::fs::read_to_string(&raw).unwrap(), input);
+
+    let (_, _, code) = run_mat_with_stdin(&["-P", "--tee", plain.to_str().unwrap(), "--tee-strip-ansi"], input);
+    assert_eq!(code, 0);
+    assert_eq!(std::fs::read_to_string(&plain).unwrap(), "ok\nfailed\n");
+
+    let (_, stderr, code) = run_mat(&["--tee", raw.to_str().unwrap(), "Cargo.toml"]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("--tee saves piped input"));
+}
+
+#[test]
+fn test_squeeze_repeated() {
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--squeeze-repeated"], "start\nspam\nspam\nspam\nend\n");
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["start", "spam (× 3)", "end"]);
+}
+
+#[test]
+fn test_timestamps_utc() {
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--timestamps", "utc"], "1705314225 started\nno time\n");
+    assert_eq!(code, 0);
+    assert!(stdout.contains("2024-01-15T10:23:45Z started"));
+    assert!(stdout.contains("no time"));
+}
+
+#[test]
+fn test_extract_named_groups() {
+    let input = "GET /a 200\nnoise\nPOST /b 500\n";
+    let (stdout, _, code) = run_mat_with_stdin(
+        &["-P", "-g", r"(?P<method>[A-Z]+) (?P<path>\S+) (?P<status>\d+)", "--extract"],
+        input,
+    );
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["method  path  status", "GET     /a    200", "POST    /b    500"]);
+}
+
+#[test]
+fn test_extract_without_named_groups() {
+    let (_, stderr, code) = run_mat_with_stdin(&["-P", "-g", "GET", "--extract"], "GET /\n");
+    assert_eq!(code, 2);
+    assert!(stderr.contains("named groups"));
+}
+
+#[test]
+fn test_search_match_summary() {
+    let input = "error: one\nok\nerror again, error\n";
+    let (stdout, stderr, code) = run_mat_with_stdin(&["-P", "-s", "error"], input);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("ok"));
+    assert_eq!(stderr, "3 matches on 2 lines\n");
+
+    // Printing for any other reason ends with the summary too
+    let (_, stderr, code) = run_mat_with_stdin(&["--paging", "never", "-s", "error"], input);
+    assert_eq!(code, 0);
+    assert_eq!(stderr, "3 matches on 2 lines\n");
+    let (_, stderr, _) = run_mat_with_stdin(&["-s", "error"], input);
+    assert_eq!(stderr, "3 matches on 2 lines\n");
+
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "-s", "error", "--count-matches"], input);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "3\n");
+}
+
+#[test]
+fn test_count_matches_without_search() {
+    let (_, stderr, code) = run_mat_with_stdin(&["-P", "--count-matches"], "x\n");
+    assert_eq!(code, 2);
+    assert!(stderr.contains("--search"));
+}
+
+#[test]
+fn test_stats() {
+    let (stdout, _, code) = run_mat_with_stdin(&["--stats"], "one two three\nfour\n");
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "stdin: 2 lines, 4 words, 1 min read\n");
+}
+
+#[test]
+fn test_pattern_file_filters() {
+    let mut patterns = NamedTempFile::new().unwrap();
+    writeln!(patterns, "timeout").unwrap();
+    writeln!(patterns, "refused").unwrap();
+
+    let (stdout, _, code) = run_mat_with_stdin(
+        &["-P", "--pattern-file", patterns.path().to_str().unwrap()],
+        "ok\nread timeout\nall good\nconnection refused\n",
+    );
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["read timeout", "--", "connection refused"]);
+}
+
 // ============ Line Range Tests ============
 
 #[test]
@@ -177,6 +796,66 @@ fn test_line_range_invalid() {
     assert!(stderr.contains("Invalid") || stderr.contains("invalid"));
 }
 
+#[test]
+fn test_max_memory_refuses_whole_file() {
+    let mut temp = NamedTempFile::new().unwrap();
+    for i in 1..=100 {
+        writeln!(temp, "line {}", i).unwrap();
+    }
+
+    let (_, stderr, code) = run_mat(&["-P", "--max-memory", "100", temp.path().to_str().unwrap()]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("--max-memory limit of 100 bytes"));
+    assert!(stderr.contains("--lines or --grep"));
+}
+
+#[test]
+fn test_max_memory_loads_lines_and_matches() {
+    let mut temp = NamedTempFile::new().unwrap();
+    for i in 1..=100 {
+        writeln!(temp, "line {}", i).unwrap();
+    }
+    let path = temp.path().to_str().unwrap();
+
+    let (stdout, _, code) = run_mat(&["-P", "--max-memory", "100", "-L", "41:43", path]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "line 41\nline 42\nline 43\n");
+
+    let (stdout, _, code) = run_mat(&["-P", "--max-memory", "100", "-g", "^line 9[89]$", "-n", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("98 line 98") && stdout.contains("99 line 99"));
+    assert!(!stdout.contains("line 97"));
+
+    // Counting search matches needs no lines in memory
+    let (stdout, _, code) = run_mat(&["--max-memory", "100", "-s", "line 1", "--count-matches", path]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "12\n");
+
+    let (_, stderr, code) = run_mat(&["-P", "--max-memory", "lots", path]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("is not a size"));
+}
+
+#[test]
+fn test_compare_marks_new_lines() {
+    let mut yesterday = NamedTempFile::new().unwrap();
+    writeln!(yesterday, "2024-05-01T10:00:00Z started\n2024-05-01T10:00:01Z ready").unwrap();
+    let mut today = NamedTempFile::new().unwrap();
+    writeln!(today, "2024-05-02T09:00:00Z started\n2024-05-02T09:00:05Z disk full\n2024-05-02T09:00:06Z ready").unwrap();
+
+    let (stdout, _, code) = run_mat(&[
+        "-P",
+        "--compare",
+        yesterday.path().to_str().unwrap(),
+        today.path().to_str().unwrap(),
+    ]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "  2024-05-02T09:00:00Z started\n+ 2024-05-02T09:00:05Z disk full\n  2024-05-02T09:00:06Z ready\n"
+    );
+}
+
 // ============ Grep Tests ============
 
 #[test]
@@ -195,6 +874,52 @@ fn test_grep_basic() {
     assert!(!stdout.contains("cherry"));
 }
 
+#[test]
+fn test_grep_invert_match() {
+    let mut temp = NamedTempFile::new().unwrap();
+    writeln!(temp, "DEBUG connecting").unwrap();
+    writeln!(temp, "ERROR refused").unwrap();
+    writeln!(temp, "DEBUG retrying").unwrap();
+
+    let (stdout, _, code) = run_mat(&["-P", "-g", "DEBUG", "-v", temp.path().to_str().unwrap()]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "ERROR refused\n");
+}
+
+#[test]
+fn test_hide_with_grep() {
+    let mut temp = NamedTempFile::new().unwrap();
+    writeln!(temp, "GET /health 200").unwrap();
+    writeln!(temp, "GET /api 500").unwrap();
+    writeln!(temp, "GET /health 500").unwrap();
+    writeln!(temp, "GET /debug 200").unwrap();
+
+    let path = temp.path().to_str().unwrap();
+    let (stdout, _, code) = run_mat(&["-P", "--hide", "/health", "--hide", "/debug", path]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout.trim(), "GET /api 500");
+
+    // Hidden lines are gone before grep filtering
+    let (stdout, _, code) = run_mat(&["-P", "--hide", "/health", "-g", "500", path]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout.trim(), "GET /api 500");
+}
+
+#[test]
+fn test_pretty_xml() {
+    let mut temp = tempfile::Builder::new().suffix(".xml").tempfile().unwrap();
+    write!(temp, "<a><b x=\"1\">text</b><c/></a>").unwrap();
+
+    let path = temp.path().to_str().unwrap();
+    let (stdout, _, code) = run_mat(&["-P", "-N", "--pretty", path]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "<a>\n  <b x=\"1\">text</b>\n  <c/>\n</a>\n");
+
+    // Without --pretty the markup is shown as is
+    let (stdout, _, _) = run_mat(&["-P", "-N", path]);
+    assert_eq!(stdout.trim(), "<a><b x=\"1\">text</b><c/></a>");
+}
+
 #[test]
 fn test_grep_case_insensitive() {
     let mut temp = NamedTempFile::new().unwrap();
@@ -262,6 +987,18 @@ fn test_binary_file_detection() {
     assert!(stderr.contains("Binary") || stderr.contains("binary"));
 }
 
+#[test]
+fn test_gzip_input_is_decompressed() {
+    let mut temp = tempfile::Builder::new().suffix(".log.gz").tempfile().unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"first\nsecond\n").unwrap();
+    temp.write_all(&encoder.finish().unwrap()).unwrap();
+
+    let (stdout, _, code) = run_mat(&["-P", temp.path().to_str().unwrap()]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "first\nsecond\n");
+}
+
 #[test]
 fn test_force_binary() {
     let mut temp = NamedTempFile::new().unwrap();
@@ -269,7 +1006,241 @@ fn test_force_binary() {
 
     let (stdout, _, code) = run_mat(&["-P", "--force-binary", temp.path().to_str().unwrap()]);
     assert_eq!(code, 0);
-    assert!(stdout.contains("Hello"));
+    assert_eq!(stdout, "00000000  48 65 6c 6c 6f 00 57 6f  72 6c 64                 |Hello.World|\n");
+}
+
+#[test]
+fn test_hex_dump() {
+    let mut temp = NamedTempFile::new().unwrap();
+    temp.write_all(b"plain text\n").unwrap();
+    let path = temp.path().to_str().unwrap();
+
+    // Text files too, on request
+    let (stdout, _, code) = run_mat(&["-P", "--hex", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.starts_with("00000000  70 6c 61 69 6e 20 74 65  78 74 0a"));
+    assert!(stdout.ends_with("|plain text.|\n"));
+
+    // Search finds bytes in the hex column, and text in the ASCII one
+    for pattern in ["6c 61 69", "in t"] {
+        let (stdout, _, code) = run_mat(&["--render-screen", "80x4", "--hex", "-s", pattern, path]);
+        assert_eq!(code, 0);
+        assert!(stdout.starts_with("●00000000"), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_verbose_logging() {
+    let mut file = tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+    writeln!(file, "fn main() {{}}").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let (_, stderr, code) = run_mat(&["-P", path]);
+    assert_eq!(code, 0);
+    assert_eq!(stderr, "");
+
+    let (stdout, stderr, code) = run_mat(&["-P", "--verbose", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("fn main"));
+    assert!(stderr.contains("bytes decoded as UTF-8"), "{}", stderr);
+    assert!(stderr.contains("highlighted as Rust (detected from its name)"), "{}", stderr);
+    // Debug messages need a second --verbose
+    assert!(!stderr.contains("[debug"));
+
+    let log = NamedTempFile::new().unwrap();
+    let (_, stderr, _) = run_mat(&["-P", "--verbose", "--no-highlight", "--log-file", log.path().to_str().unwrap(), path]);
+    assert_eq!(stderr, "");
+    let logged = std::fs::read_to_string(log.path()).unwrap();
+    assert!(logged.contains("not syntax highlighted: --no-highlight"), "{}", logged);
+
+    let (_, _, code) = run_mat(&["-q", "--verbose", path]);
+    assert_eq!(code, 2);
+}
+
+#[test]
+fn test_list_themes_and_user_themes() {
+    let config = tempfile::tempdir().unwrap();
+    let themes = config.path().join("mat/themes");
+    std::fs::create_dir_all(&themes).unwrap();
+    let plist = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><plist version="1.0"><dict>"#,
+        r#"<key>settings</key><array><dict><key>settings</key><dict>"#,
+        r#"<key>background</key><string>#101010</string><key>foreground</key><string>#0A0B0C</string>"#,
+        r#"</dict></dict></array></dict></plist>"#
+    );
+    std::fs::write(themes.join("Midnight.tmTheme"), plist).unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(mat_binary())
+            .args(args)
+            .env("XDG_CONFIG_HOME", config.path())
+            .output()
+            .expect("Failed to execute mat");
+        (String::from_utf8_lossy(&output.stdout).to_string(), String::from_utf8_lossy(&output.stderr).to_string())
+    };
+
+    let (stdout, _) = run(&["--list-themes"]);
+    assert!(stdout.contains("Solarized (dark) (dark)\n"), "{}", stdout);
+    assert!(stdout.contains("Midnight (dark)\n"), "{}", stdout);
+
+    let mut file = tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+    writeln!(file, "fn main() {{}}").unwrap();
+    let path = file.path().to_str().unwrap();
+    // Any spelling of the name, in the theme's colors
+    let (stdout, stderr) = run(&["-P", "--color", "always", "--theme", "midnight", path]);
+    assert!(stdout.contains("38;2;10;11;12m"), "{}", stdout);
+    assert_eq!(stderr, "");
+
+    let (_, stderr) = run(&["-P", "--theme", "nonesuch", path]);
+    assert!(stderr.contains("unknown theme nonesuch"), "{}", stderr);
+}
+
+#[test]
+fn test_word_diff_in_piped_diff() {
+    let diff = "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-let t = from_secs(30);\n+let t = from_millis(300);\n";
+    let (stdout, _, code) = run_mat_with_stdin(&["-P", "--color", "always"], diff);
+    assert_eq!(code, 0);
+    // Only the changed words get the stronger background
+    assert!(stdout.contains(";48;2;30;100;30mfrom_millis\x1b[0m"), "{:?}", stdout);
+    assert!(stdout.contains(";48;2;120;30;30mfrom_secs\x1b[0m"), "{:?}", stdout);
+    assert!(!stdout.contains("48;2;30;100;30mlet"), "{:?}", stdout);
+
+    let (stdout, _, _) = run_mat_with_stdin(&["-P", "--color", "always", "--no-highlight"], diff);
+    assert!(!stdout.contains("48;2;"), "{:?}", stdout);
+}
+
+#[test]
+fn test_list_languages() {
+    let (stdout, _, code) = run_mat(&["--list-languages"]);
+    assert_eq!(code, 0);
+    let rust = stdout.lines().find(|line| line.starts_with("Rust ")).expect("Rust is listed");
+    assert!(rust.ends_with("rs"), "{}", rust);
+    assert!(stdout.lines().any(|line| line.starts_with("Diff ") && line.contains("patch")));
+}
+
+#[test]
+fn test_single_line_json_is_pretty_printed() {
+    let json = r#"{"user":{"id":7,"roles":["admin","dev"]},"active":true}"#;
+    let (stdout, _, code) = run_mat_with_stdin(&["--render-screen", "40x10"], json);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("    \"roles\": ["), "{}", stdout);
+    assert!(stdout.contains("  \"active\": true"), "{}", stdout);
+
+    // r shows it as it came
+    let (stdout, _, _) = run_mat_with_stdin(&["--render-screen", "80x10", "--keys", "r"], json);
+    assert!(stdout.contains(json), "{}", stdout);
+
+    // Printed to another program it stays as it is
+    let (stdout, _, _) = run_mat_with_stdin(&["--no-highlight"], json);
+    assert_eq!(stdout.trim_end(), json);
+}
+
+#[test]
+fn test_breadcrumbs() {
+    let mut temp = tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+    writeln!(temp, "impl App {{\n    fn scroll(&mut self) {{\n        a();\n        b();\n        c();\n    }}\n}}").unwrap();
+    let path = temp.path().to_str().unwrap();
+    let (stdout, _, code) = run_mat(&["--render-screen", "40x4", "--breadcrumbs", "--keys", "jj", path]);
+    assert_eq!(code, 0);
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert_eq!(rows[0].trim_end(), " impl App › fn scroll(&mut self)");
+    assert_eq!(rows[1].trim(), "a();");
+
+    // C turns it off
+    let (stdout, _, _) = run_mat(&["--render-screen", "40x4", "--breadcrumbs", "--keys", "jjC", path]);
+    assert_eq!(stdout.lines().next().unwrap().trim(), "a();");
+}
+
+#[test]
+fn test_task_progress_in_status_bar() {
+    let mut temp = tempfile::Builder::new().suffix(".md").tempfile().unwrap();
+    writeln!(temp, "- [x] ship\n- [ ] announce").unwrap();
+    let path = temp.path().to_str().unwrap();
+
+    // Printed, the document starts with its first line, numbered 1
+    let (stdout, _, code) = run_mat(&["-P", "-n", "--color", "never", path]);
+    assert_eq!(code, 0);
+    assert!(stdout.lines().next().unwrap().trim_start().starts_with("1"), "{}", stdout);
+    assert!(stdout.contains("[x] ship") && !stdout.contains("tasks done"), "{}", stdout);
+
+    let (stdout, _, _) = run_mat(&["--render-screen", "60x4", path]);
+    assert!(stdout.lines().last().unwrap().contains("1/2 tasks done"), "{}", stdout);
+}
+
+/// Send SIGINT to a running mat, as Ctrl+C would
+#[cfg(unix)]
+fn interrupt(child: &std::process::Child) {
+    let status = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(status.success());
+}
+
+#[test]
+#[cfg(unix)]
+fn test_load_after_interrupt_reads_whole_input() {
+    use std::process::Stdio;
+    use std::time::Duration;
+
+    let mut temp = NamedTempFile::new().unwrap();
+    writeln!(temp, "whole file").unwrap();
+
+    // Ctrl+C while stdin is loading keeps what was read, and the file after it loads in full
+    let mut child = Command::new(mat_binary())
+        .args(["-P", "--open-partial", "-", temp.path().to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("TERM", "dumb")
+        .spawn()
+        .expect("Failed to execute mat");
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"first\n").unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    interrupt(&child);
+    std::thread::sleep(Duration::from_millis(200));
+    let _ = stdin.write_all(b"second\n");
+    drop(stdin);
+
+    let output = child.wait_with_output().expect("Failed to wait on mat");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("first"), "{}", stdout);
+    assert!(stdout.contains("whole file"), "{}", stdout);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_interrupt_after_loading_stops_mat() {
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let mut temp = NamedTempFile::new().unwrap();
+    for i in 0..200_000 {
+        writeln!(temp, "line {}", i).unwrap();
+    }
+
+    // Nobody reads stdout, so mat is stuck printing when Ctrl+C comes
+    let mut child = Command::new(mat_binary())
+        .args(["-P", temp.path().to_str().unwrap()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .env("TERM", "dumb")
+        .spawn()
+        .expect("Failed to execute mat");
+    std::thread::sleep(Duration::from_millis(500));
+    interrupt(&child);
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if started.elapsed() > Duration::from_secs(5) {
+            child.kill().unwrap();
+            panic!("mat ignored Ctrl+C after loading");
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    assert_eq!(status.code(), Some(130));
 }
 
 // ============ Empty File Tests ============
@@ -324,3 +1295,21 @@ fn test_tab_expansion() {
     assert!(stdout.contains("a") && stdout.contains("b"));
     assert!(!stdout.contains('\t'));
 }
+
+// ============ Long Line Tests ============
+
+#[test]
+fn test_very_long_line_printed_intact() {
+    let mut temp = NamedTempFile::new().unwrap();
+    let long = "abcdefgh".repeat(10_000);
+    writeln!(temp, "first").unwrap();
+    writeln!(temp, "{}", long).unwrap();
+    writeln!(temp, "last").unwrap();
+
+    let (stdout, _, code) = run_mat(&["-P", "-n", temp.path().to_str().unwrap()]);
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].ends_with(&long));
+    assert!(lines[2].contains("last"));
+}